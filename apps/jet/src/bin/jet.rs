@@ -353,6 +353,7 @@ async fn run_jet(
         identity_updater: gateway_identity_updater,
         driver_tx_sink: gateway_tx_sink,
         driver_join_handle: gateway_join_handle,
+        active_connections: _,
     } = quic_gateway_spawner.spawn(
         initial_identity.insecure_clone(),
         config.quic.tpu_sender.clone(),
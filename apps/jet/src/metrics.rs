@@ -293,6 +293,7 @@ pub mod jet {
             register!(NEW_SLOT_ARRIVAL_INTERVAL);
 
             yellowstone_jet_tpu_client::prom::register_metrics(&REGISTRY);
+            yellowstone_jet_tpu_client::metrics::register_metrics(&REGISTRY);
             grpc_lewis::prom::register_metrics(&REGISTRY);
         });
     }
@@ -0,0 +1,58 @@
+//!
+//! Benchmarks of leader schedule window lookups, comparing per-slot lookups against a single batched lookup.
+//!
+//! Run with `cargo bench -p yellowstone-jet-tpu-client --features test-utils --bench leader_schedule`.
+//!
+use {
+    criterion::{BenchmarkId, Criterion, criterion_group, criterion_main},
+    solana_clock::DEFAULT_SLOTS_PER_EPOCH,
+    solana_pubkey::Pubkey,
+    std::hint::black_box,
+    yellowstone_jet_tpu_client::rpc::schedule::{CompactSortedSchedule, ManagedLeaderSchedule},
+};
+
+const SLOTS_PER_LEADER: u64 = 4;
+
+fn unique_leaders() -> Vec<Pubkey> {
+    (0..DEFAULT_SLOTS_PER_EPOCH / SLOTS_PER_LEADER)
+        .map(|_| Pubkey::new_unique())
+        .collect()
+}
+
+fn bench_get_leaders_for_range(c: &mut Criterion) {
+    let schedule = ManagedLeaderSchedule::from_schedules(
+        CompactSortedSchedule::new(0, unique_leaders()),
+        CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, unique_leaders()),
+    );
+    let mut group = c.benchmark_group("get_leaders_for_range");
+    for n in [16, 32, 64] {
+        let mut start = 0;
+        group.bench_with_input(BenchmarkId::new("per_slot", n), &n, |b, &n| {
+            b.iter(|| {
+                start = (start + SLOTS_PER_LEADER) % DEFAULT_SLOTS_PER_EPOCH;
+                let leaders = (0..n as u64)
+                    .map(|i| {
+                        schedule
+                            .get_leader(start + i * SLOTS_PER_LEADER)
+                            .expect("get_leader")
+                    })
+                    .collect::<Vec<_>>();
+                black_box(leaders)
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("batched", n), &n, |b, &n| {
+            b.iter(|| {
+                start = (start + SLOTS_PER_LEADER) % DEFAULT_SLOTS_PER_EPOCH;
+                black_box(
+                    schedule
+                        .get_leaders_for_range(start, n, SLOTS_PER_LEADER)
+                        .expect("get_leaders_for_range"),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_get_leaders_for_range);
+criterion_main!(benches);
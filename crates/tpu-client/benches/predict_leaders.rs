@@ -0,0 +1,51 @@
+//!
+//! Benchmarks of the leader prediction hot path, called by connection managers on every slot.
+//!
+//! Run with `cargo bench -p yellowstone-jet-tpu-client --features test-utils --bench predict_leaders`.
+//!
+use {
+    criterion::{BenchmarkId, Criterion, criterion_group, criterion_main},
+    solana_clock::DEFAULT_SLOTS_PER_EPOCH,
+    solana_pubkey::Pubkey,
+    std::{hint::black_box, sync::Arc},
+    yellowstone_jet_tpu_client::{
+        core::UpcomingLeaderPredictor,
+        rpc::schedule::{CompactSortedSchedule, ManagedLeaderSchedule},
+        slot::AtomicSlotTracker,
+        yellowstone_grpc::schedule::YellowstoneUpcomingLeader,
+    },
+};
+
+fn unique_leaders() -> Vec<Pubkey> {
+    (0..DEFAULT_SLOTS_PER_EPOCH / 4)
+        .map(|_| Pubkey::new_unique())
+        .collect()
+}
+
+fn predictor(dedup: bool) -> YellowstoneUpcomingLeader {
+    let schedule = ManagedLeaderSchedule::from_schedules(
+        CompactSortedSchedule::new(0, unique_leaders()),
+        CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, unique_leaders()),
+    );
+    YellowstoneUpcomingLeader::builder(Arc::new(AtomicSlotTracker::new(1_000)), schedule)
+        .dedup(dedup)
+        .max_slot_age(None)
+        .build()
+        .expect("valid predictor")
+}
+
+fn bench_predict_leaders(c: &mut Criterion) {
+    let mut group = c.benchmark_group("try_predict_next_n_leaders");
+    for (name, dedup) in [("window", false), ("distinct", true)] {
+        let predictor = predictor(dedup);
+        for n in [16, 32, 64] {
+            group.bench_with_input(BenchmarkId::new(name, n), &n, |b, &n| {
+                b.iter(|| black_box(predictor.try_predict_next_n_leaders(black_box(n))))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_predict_leaders);
+criterion_main!(benches);
@@ -115,20 +115,24 @@ async fn main() {
     let YellowstoneSlotTrackerOk {
         atomic_slot_tracker,
         join_handle: mut slot_tracker_jh,
+        ..
     } = yellowstone_jet_tpu_client::yellowstone_grpc::slot_tracker::atomic_slot_tracker(
         geyser_client,
     )
     .await
-    .expect("atomic_slot_tracker")
-    .expect("some");
+    .expect("atomic_slot_tracker");
+    atomic_slot_tracker
+        .wait_until_ready(std::time::Duration::from_secs(30))
+        .await
+        .expect("first slot");
 
     let mut ctrlc = tokio::spawn(tokio::signal::ctrl_c());
 
     let mut interval = tokio::time::interval(std::time::Duration::from_millis(400));
-    let leader_predictor = YellowstoneUpcomingLeader {
-        managed_schedule: managed_leader_schedule.clone(),
-        slot_tracker: Arc::clone(&atomic_slot_tracker),
-    };
+    let leader_predictor = YellowstoneUpcomingLeader::new(
+        Arc::clone(&atomic_slot_tracker),
+        managed_leader_schedule.clone(),
+    );
     loop {
         tokio::select! {
             _ = interval.tick() => {},
@@ -147,7 +151,7 @@ async fn main() {
 
         let leader = managed_leader_schedule
             .get_leader(current_slot)
-            .expect("poisoned")
+            .expect("leader schedule lookup")
             .expect("unknow slot");
 
         let upcoming_leaders = leader_predictor.try_predict_next_n_leaders(3);
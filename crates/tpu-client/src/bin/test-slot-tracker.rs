@@ -80,12 +80,16 @@ async fn main() {
     let YellowstoneSlotTrackerOk {
         atomic_slot_tracker,
         mut join_handle,
+        ..
     } = yellowstone_jet_tpu_client::yellowstone_grpc::slot_tracker::atomic_slot_tracker(
         geyser_client,
     )
     .await
-    .expect("atomic_slot_tracker")
-    .expect("Some");
+    .expect("atomic_slot_tracker");
+    atomic_slot_tracker
+        .wait_until_ready(std::time::Duration::from_secs(30))
+        .await
+        .expect("first slot");
     let mut ctrlc = tokio::spawn(tokio::signal::ctrl_c());
 
     let mut interval = tokio::time::interval(std::time::Duration::from_millis(400));
@@ -98,7 +102,7 @@ async fn main() {
                 break;
             }
         }
-        let slot = atomic_slot_tracker.load().expect("load");
+        let slot = atomic_slot_tracker.load();
         writeln!(&mut out, "Current Yellowstone slot: {slot}").expect("write");
         tracing::info!("Current Yellowstone slot: {}", slot);
     }
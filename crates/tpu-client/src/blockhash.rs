@@ -0,0 +1,316 @@
+//!
+//! Recent blockhash tracking.
+//!
+//! A transaction can only land while its recent blockhash is at most [`MAX_PROCESSING_AGE`] slots old.
+//! Forwarding an expired transaction wastes the leader resources and our connection slots.
+//!
+//! [`RecentBlockhashes`] tracks the most recent blockhashes and the slot of their block, so senders can drop
+//! expired transactions and clients can get a recent blockhash without a separate RPC round-trip.
+//!
+//! When using [`crate::yellowstone_grpc`], it is fed from the gRPC block-meta stream, see
+//! [`crate::yellowstone_grpc::slot_tracker::YellowstoneSlotTrackerOk::recent_blockhashes`].
+//!
+use {
+    solana_clock::{MAX_PROCESSING_AGE, MAX_RECENT_BLOCKHASHES, Slot},
+    solana_hash::{HASH_BYTES, Hash},
+    solana_signature::{SIGNATURE_BYTES, Signature},
+    std::{
+        collections::{BTreeSet, HashMap},
+        sync::Mutex,
+    },
+};
+
+///
+/// Number of slots after which a blockhash is considered expired.
+///
+pub const BLOCKHASH_EXPIRY_SLOTS: u64 = MAX_PROCESSING_AGE as u64;
+
+///
+/// Number of slots a blockhash is remembered for, past this window an expired blockhash becomes unknown.
+///
+/// Expired blockhashes are kept for a while so they can be told apart from blockhashes not observed yet.
+///
+pub const BLOCKHASH_RETENTION_SLOTS: u64 = MAX_RECENT_BLOCKHASHES as u64;
+
+#[derive(Debug, Default)]
+struct RecentBlockhashesInner {
+    slots: HashMap<Hash, Slot>,
+    /// Tracked blockhashes sorted by slot, block meta can arrive out of slot order.
+    by_slot: BTreeSet<(Slot, Hash)>,
+    latest: Option<(Hash, Slot)>,
+}
+
+impl RecentBlockhashesInner {
+    fn latest_slot(&self) -> Slot {
+        self.latest.map(|(_, slot)| slot).unwrap_or_default()
+    }
+}
+
+///
+/// Tracks the most recent blockhashes and their slot.
+///
+/// Blockhashes older than [`BLOCKHASH_RETENTION_SLOTS`] relative to the latest recorded slot are evicted.
+///
+/// # Safety
+///
+/// This struct is thread-safe. Share it using an atomic reference-counter.
+///
+#[derive(Debug, Default)]
+pub struct RecentBlockhashes {
+    inner: Mutex<RecentBlockhashesInner>,
+}
+
+impl RecentBlockhashes {
+    ///
+    /// Records the blockhash of the block at `slot`.
+    ///
+    pub fn record(&self, blockhash: Hash, slot: Slot) {
+        let mut inner = self.inner.lock().expect("recent blockhashes lock");
+        if slot >= inner.latest_slot() {
+            inner.latest = Some((blockhash, slot));
+        }
+        if let Some(previous_slot) = inner.slots.insert(blockhash, slot) {
+            inner.by_slot.remove(&(previous_slot, blockhash));
+        }
+        inner.by_slot.insert((slot, blockhash));
+        while let Some((oldest_slot, oldest_blockhash)) = inner.by_slot.first().copied() {
+            if inner.latest_slot().saturating_sub(oldest_slot) <= BLOCKHASH_RETENTION_SLOTS {
+                break;
+            }
+            inner.by_slot.pop_first();
+            inner.slots.remove(&oldest_blockhash);
+        }
+    }
+
+    ///
+    /// Returns `true` if `blockhash` is known and older than [`BLOCKHASH_EXPIRY_SLOTS`] relative to `current_slot`.
+    ///
+    /// Unknown blockhashes are not reported as expired: they may belong to a block not observed yet.
+    ///
+    pub fn is_expired(&self, blockhash: &Hash, current_slot: Slot) -> bool {
+        self.slot_of(blockhash)
+            .is_some_and(|slot| current_slot.saturating_sub(slot) > BLOCKHASH_EXPIRY_SLOTS)
+    }
+
+    ///
+    /// Returns `true` if `blockhash` is known and within [`BLOCKHASH_EXPIRY_SLOTS`] of the latest recorded slot.
+    ///
+    pub fn is_valid(&self, blockhash: &Hash) -> bool {
+        let inner = self.inner.lock().expect("recent blockhashes lock");
+        let latest_slot = inner.latest_slot();
+        inner
+            .slots
+            .get(blockhash)
+            .is_some_and(|slot| latest_slot.saturating_sub(*slot) <= BLOCKHASH_EXPIRY_SLOTS)
+    }
+
+    ///
+    /// Returns the slot of the block `blockhash` belongs to, if known.
+    ///
+    pub fn slot_of(&self, blockhash: &Hash) -> Option<Slot> {
+        self.inner
+            .lock()
+            .expect("recent blockhashes lock")
+            .slots
+            .get(blockhash)
+            .copied()
+    }
+
+    ///
+    /// Returns the most recent blockhash and its slot, `None` until a blockhash is recorded.
+    ///
+    pub fn latest(&self) -> Option<(Hash, Slot)> {
+        self.inner.lock().expect("recent blockhashes lock").latest
+    }
+
+    ///
+    /// Number of blockhashes currently tracked.
+    ///
+    pub fn len(&self) -> usize {
+        self.inner
+            .lock()
+            .expect("recent blockhashes lock")
+            .slots
+            .len()
+    }
+
+    ///
+    /// Returns `true` if no blockhash is tracked.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+///
+/// Decodes a compact-u16 length prefix, returns the value and the number of bytes read.
+///
+fn decode_short_u16(bytes: &[u8]) -> Option<(usize, usize)> {
+    let mut value = 0usize;
+    for (i, byte) in bytes.iter().take(3).enumerate() {
+        value |= ((byte & 0x7f) as usize) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+///
+/// Extracts the recent blockhash from a bincoded legacy or versioned transaction.
+///
+/// Returns `None` if the transaction is malformed.
+///
+pub(crate) fn recent_blockhash_of(wire_txn: &[u8]) -> Option<Hash> {
+    const MESSAGE_HEADER_BYTES: usize = 3;
+    const VERSION_PREFIX_MASK: u8 = 0x80;
+
+    let (num_signatures, read) = decode_short_u16(wire_txn)?;
+    let mut message = wire_txn.get(read + num_signatures * SIGNATURE_BYTES..)?;
+    if *message.first()? & VERSION_PREFIX_MASK != 0 {
+        message = &message[1..];
+    }
+    let message = message.get(MESSAGE_HEADER_BYTES..)?;
+    let (num_account_keys, read) = decode_short_u16(message)?;
+    let offset = read + num_account_keys * solana_pubkey::PUBKEY_BYTES;
+    let blockhash: [u8; HASH_BYTES] = message.get(offset..offset + HASH_BYTES)?.try_into().ok()?;
+    Some(Hash::new_from_array(blockhash))
+}
+
+///
+/// Extracts the first signature, which identifies the transaction, from a bincoded transaction.
+///
+/// Returns `None` if the transaction is malformed or unsigned.
+///
+pub(crate) fn signature_of(wire_txn: &[u8]) -> Option<Signature> {
+    let (num_signatures, read) = decode_short_u16(wire_txn)?;
+    if num_signatures == 0 {
+        return None;
+    }
+    let signature: [u8; SIGNATURE_BYTES] = wire_txn
+        .get(read..read + SIGNATURE_BYTES)?
+        .try_into()
+        .ok()?;
+    Some(Signature::from(signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fabricate_wire_txn(blockhash: Hash, versioned: bool) -> Vec<u8> {
+        let mut wire = vec![1];
+        wire.extend_from_slice(&[7; 64]);
+        if versioned {
+            wire.push(0x80);
+        }
+        // Message header
+        wire.extend_from_slice(&[1, 0, 1]);
+        // Two account keys
+        wire.push(2);
+        wire.extend_from_slice(&[3; 64]);
+        wire.extend_from_slice(blockhash.as_ref());
+        // No instructions
+        wire.push(0);
+        if versioned {
+            // No address table lookups
+            wire.push(0);
+        }
+        wire
+    }
+
+    #[test]
+    fn it_should_extract_recent_blockhash() {
+        let blockhash = Hash::new_unique();
+        assert_eq!(
+            recent_blockhash_of(&fabricate_wire_txn(blockhash, false)),
+            Some(blockhash)
+        );
+        assert_eq!(
+            recent_blockhash_of(&fabricate_wire_txn(blockhash, true)),
+            Some(blockhash)
+        );
+        assert_eq!(recent_blockhash_of(&[1, 2, 3]), None);
+        assert_eq!(recent_blockhash_of(&[]), None);
+
+        let wire_txn = fabricate_wire_txn(blockhash, false);
+        assert_eq!(signature_of(&wire_txn), Some(Signature::from([7; 64])));
+        assert_eq!(signature_of(&[0]), None);
+        assert_eq!(signature_of(&[1, 2, 3]), None);
+    }
+
+    #[test]
+    fn it_should_detect_expired_blockhash() {
+        let recent_blockhashes = RecentBlockhashes::default();
+        let old_blockhash = Hash::new_unique();
+        let fresh_blockhash = Hash::new_unique();
+        recent_blockhashes.record(old_blockhash, 100);
+        recent_blockhashes.record(fresh_blockhash, 200);
+
+        let current_slot = 100 + BLOCKHASH_EXPIRY_SLOTS + 1;
+        let wire_txn = fabricate_wire_txn(old_blockhash, false);
+        let blockhash = recent_blockhash_of(&wire_txn).expect("blockhash");
+        assert!(recent_blockhashes.is_expired(&blockhash, current_slot));
+        assert!(!recent_blockhashes.is_expired(&old_blockhash, current_slot - 1));
+        assert!(!recent_blockhashes.is_expired(&fresh_blockhash, current_slot));
+        // Unknown blockhashes are let through
+        assert!(!recent_blockhashes.is_expired(&Hash::new_unique(), current_slot));
+    }
+
+    #[test]
+    fn it_should_forget_blockhashes_past_retention() {
+        let recent_blockhashes = RecentBlockhashes::default();
+        let old_blockhash = Hash::new_unique();
+        recent_blockhashes.record(old_blockhash, 100);
+        recent_blockhashes.record(Hash::new_unique(), 100 + BLOCKHASH_RETENTION_SLOTS + 1);
+
+        assert_eq!(recent_blockhashes.slot_of(&old_blockhash), None);
+        assert_eq!(recent_blockhashes.len(), 1);
+    }
+
+    #[test]
+    fn it_should_evict_the_lowest_slots_when_recorded_out_of_order() {
+        let recent_blockhashes = RecentBlockhashes::default();
+        let recent_blockhash = Hash::new_unique();
+        let old_blockhash = Hash::new_unique();
+        // The old block meta arrives after a more recent one.
+        recent_blockhashes.record(recent_blockhash, 200);
+        recent_blockhashes.record(old_blockhash, 100);
+        assert_eq!(recent_blockhashes.len(), 2);
+
+        let latest_blockhash = Hash::new_unique();
+        recent_blockhashes.record(latest_blockhash, 100 + BLOCKHASH_RETENTION_SLOTS + 1);
+        assert_eq!(recent_blockhashes.slot_of(&old_blockhash), None);
+        assert_eq!(recent_blockhashes.slot_of(&recent_blockhash), Some(200));
+        assert_eq!(
+            recent_blockhashes.slot_of(&latest_blockhash),
+            Some(100 + BLOCKHASH_RETENTION_SLOTS + 1)
+        );
+        assert_eq!(recent_blockhashes.len(), 2);
+    }
+
+    #[test]
+    fn it_should_track_latest_and_valid_blockhashes() {
+        let recent_blockhashes = RecentBlockhashes::default();
+        assert_eq!(recent_blockhashes.latest(), None);
+
+        let blockhash1 = Hash::new_unique();
+        let blockhash2 = Hash::new_unique();
+        let blockhash3 = Hash::new_unique();
+        recent_blockhashes.record(blockhash1, 10);
+        recent_blockhashes.record(blockhash3, 30);
+        // Late block meta does not regress the latest blockhash
+        recent_blockhashes.record(blockhash2, 20);
+        assert_eq!(recent_blockhashes.latest(), Some((blockhash3, 30)));
+        assert_eq!(recent_blockhashes.slot_of(&blockhash2), Some(20));
+        assert!(recent_blockhashes.is_valid(&blockhash1));
+
+        let blockhash4 = Hash::new_unique();
+        recent_blockhashes.record(blockhash4, 10 + BLOCKHASH_EXPIRY_SLOTS + 1);
+        assert!(!recent_blockhashes.is_valid(&blockhash1));
+        assert!(recent_blockhashes.is_valid(&blockhash2));
+        assert!(recent_blockhashes.is_valid(&blockhash4));
+        assert!(!recent_blockhashes.is_valid(&Hash::new_unique()));
+        assert_eq!(recent_blockhashes.len(), 4);
+    }
+}
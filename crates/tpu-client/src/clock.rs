@@ -0,0 +1,95 @@
+//!
+//! Pluggable monotonic clock.
+//!
+//! Slot staleness, slot interpolation and the warm connection timers all measure elapsed time. Components reading
+//! the time take a [`Clock`], [`SystemClock`] by default, so tests can drive them deterministically with a
+//! `MockClock` instead of sleeping. `MockClock` is available in tests and with the `test-utils` feature.
+//!
+//! See [`crate::slot::AtomicSlotTracker::with_clock`] and
+//! [`crate::warm_connection::WarmConnectionManagerBuilder::with_clock`].
+//!
+use std::time::Instant;
+#[cfg(any(test, feature = "test-utils"))]
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+///
+/// Source of monotonic time.
+///
+pub trait Clock {
+    ///
+    /// Returns the current instant, never earlier than a previously returned one.
+    ///
+    fn now(&self) -> Instant;
+}
+
+///
+/// [`Clock`] reading the system monotonic clock, see [`Instant::now`].
+///
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+///
+/// [`Clock`] that only moves forward when told to, see [`MockClock::advance`].
+///
+/// # Safety
+///
+/// This struct is thread-safe. Share it using an atomic reference-counter.
+///
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Debug)]
+pub struct MockClock {
+    origin: Instant,
+    /// Nanoseconds elapsed since `origin`.
+    elapsed_nanos: AtomicU64,
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl MockClock {
+    ///
+    /// Creates a clock frozen at the current instant.
+    ///
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            elapsed_nanos: AtomicU64::new(0),
+        }
+    }
+
+    ///
+    /// Moves the clock forward by `duration`.
+    ///
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed_nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    ///
+    /// Time elapsed since the clock was created.
+    ///
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.elapsed_nanos.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+}
@@ -1,9 +1,10 @@
 use {
-    serde::{Deserialize, Deserializer, de},
+    quinn::congestion::{BbrConfig, ControllerFactory, CubicConfig},
+    serde::{Deserialize, Deserializer, Serializer, de},
     solana_net_utils::{PortRange, VALIDATOR_PORT_RANGE},
     solana_pubkey::Pubkey,
     solana_quic_definitions::QUIC_MAX_TIMEOUT,
-    std::{net::SocketAddr, num::NonZeroUsize, ops::Range, time::Duration},
+    std::{net::SocketAddr, num::NonZeroUsize, ops::Range, sync::Arc, time::Duration},
 };
 
 ///
@@ -23,6 +24,112 @@ pub enum TpuPortKind {
     Forwards,
 }
 
+///
+/// Address families of the TPU addresses QUIC connections are allowed to.
+///
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressFamily {
+    ///
+    /// Connect to IPv4 and IPv6 TPU addresses.
+    ///
+    #[default]
+    Any,
+    ///
+    /// Only connect to IPv4 TPU addresses, leaders advertising an IPv6 address are skipped.
+    ///
+    V4Only,
+    ///
+    /// Only connect to IPv6 TPU addresses, leaders advertising an IPv4 address are skipped.
+    ///
+    V6Only,
+}
+
+impl AddressFamily {
+    ///
+    /// Returns `true` if IPv4 addresses are allowed.
+    ///
+    pub const fn allows_v4(self) -> bool {
+        matches!(self, Self::Any | Self::V4Only)
+    }
+
+    ///
+    /// Returns `true` if IPv6 addresses are allowed.
+    ///
+    pub const fn allows_v6(self) -> bool {
+        matches!(self, Self::Any | Self::V6Only)
+    }
+
+    ///
+    /// Returns `true` if `addr` belongs to an allowed family.
+    ///
+    /// IPv4-mapped IPv6 addresses are IPv6 addresses.
+    ///
+    pub const fn allows(self, addr: &SocketAddr) -> bool {
+        match addr {
+            SocketAddr::V4(_) => self.allows_v4(),
+            SocketAddr::V6(_) => self.allows_v6(),
+        }
+    }
+}
+
+///
+/// Congestion controller of the QUIC connections to the TPU ports.
+///
+/// Cubic is loss-based: it backs off on packet loss, which keeps it fair with other flows on the link but
+/// shrinks the congestion window on lossy or high-latency paths, delaying transactions queued behind it.
+/// BBR models the bottleneck bandwidth and RTT instead of reacting to loss, so it keeps sending on those paths,
+/// often landing more transactions to distant leaders, at the cost of fairness with loss-based flows sharing the link.
+/// quinn's BBR implementation is experimental.
+///
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CongestionControl {
+    ///
+    /// CUBIC, quinn's default.
+    ///
+    #[default]
+    Cubic,
+    ///
+    /// BBR, see [`BbrConfig`].
+    ///
+    Bbr,
+}
+
+impl CongestionControl {
+    ///
+    /// Returns the quinn factory building the controller of each connection.
+    ///
+    pub fn controller_factory(self) -> Arc<dyn ControllerFactory + Send + Sync + 'static> {
+        match self {
+            Self::Cubic => Arc::new(CubicConfig::default()),
+            Self::Bbr => Arc::new(BbrConfig::default()),
+        }
+    }
+}
+
+///
+/// How transactions are transmitted over the QUIC connections.
+///
+/// Solana TPUs read transactions from unidirectional streams. Datagrams save the stream setup of tiny payloads and
+/// are meant for experimentation: they are unreliable, a lost datagram is not retransmitted, and the payload must fit
+/// in a single packet. A transaction is sent over a stream instead if the peer did not negotiate datagram support or
+/// the transaction exceeds the maximum datagram size.
+///
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransmissionMode {
+    ///
+    /// One unidirectional stream per transaction.
+    ///
+    #[default]
+    Stream,
+    ///
+    /// One datagram per transaction, falling back to a stream when datagrams can't be used.
+    ///
+    Datagram,
+}
+
 ///
 /// Specifies how to rewrite TPU addresses for QUIC connections for a specific remote peer.
 ///
@@ -52,7 +159,14 @@ where
         .map_err(de::Error::custom)
 }
 
-fn deserialize_port_range<'de, D>(deserializer: D) -> Result<PortRange, D::Error>
+pub(crate) fn serialize_pubkey<S>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_str(pubkey)
+}
+
+pub(crate) fn deserialize_port_range<'de, D>(deserializer: D) -> Result<PortRange, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -191,6 +305,26 @@ pub struct TpuSenderConfig {
     ///
     #[serde(default)]
     pub tpu_info_override: Vec<TpuOverrideInfo>,
+
+    ///
+    /// Address families of the TPU addresses to connect to, default is "any".
+    ///
+    /// A local endpoint is bound per allowed family. Leaders whose address is in an excluded family are skipped.
+    ///
+    #[serde(default)]
+    pub address_family_preference: AddressFamily,
+
+    ///
+    /// Congestion controller of the QUIC connections, default is "cubic".
+    ///
+    #[serde(default)]
+    pub congestion_controller: CongestionControl,
+
+    ///
+    /// How transactions are transmitted to the remote peers, default is "stream".
+    ///
+    #[serde(default)]
+    pub transmission_mode: TransmissionMode,
 }
 
 impl TpuSenderConfig {
@@ -287,6 +421,9 @@ impl Default for TpuSenderConfig {
             send_timeout: DEFAULT_TX_SEND_TIMEOUT,
             leader_prediction_lookahead: Some(DEFAULT_LEADER_PREDICTION_LOOKAHEAD),
             tpu_info_override: Vec::new(),
+            address_family_preference: AddressFamily::default(),
+            congestion_controller: CongestionControl::default(),
+            transmission_mode: TransmissionMode::default(),
         }
     }
 }
@@ -294,8 +431,9 @@ impl Default for TpuSenderConfig {
 #[cfg(test)]
 pub mod test {
     use {
-        crate::config::{TpuPortKind, TpuSenderConfig},
-        std::num::NonZeroUsize,
+        crate::config::{CongestionControl, TpuPortKind, TpuSenderConfig, TransmissionMode},
+        quinn::congestion::{Bbr, Cubic},
+        std::{num::NonZeroUsize, time::Instant},
     };
 
     #[test]
@@ -333,4 +471,33 @@ pub mod test {
         let config: super::TpuSenderConfig = serde_yaml::from_str(yaml).unwrap();
         assert_eq!(config, expected);
     }
+
+    #[test]
+    fn it_should_deser_congestion_controller() {
+        let config: TpuSenderConfig = serde_yaml::from_str("congestion_controller: bbr").unwrap();
+        assert_eq!(config.congestion_controller, CongestionControl::Bbr);
+        let config: TpuSenderConfig = serde_yaml::from_str("tpu_port: normal").unwrap();
+        assert_eq!(config.congestion_controller, CongestionControl::Cubic);
+    }
+
+    #[test]
+    fn it_should_deser_transmission_mode() {
+        let config: TpuSenderConfig = serde_yaml::from_str("transmission_mode: datagram").unwrap();
+        assert_eq!(config.transmission_mode, TransmissionMode::Datagram);
+        let config: TpuSenderConfig = serde_yaml::from_str("tpu_port: normal").unwrap();
+        assert_eq!(config.transmission_mode, TransmissionMode::Stream);
+    }
+
+    #[test]
+    fn it_should_build_the_selected_congestion_controller() {
+        let controller = CongestionControl::Cubic
+            .controller_factory()
+            .build(Instant::now(), 1200);
+        assert!(controller.into_any().downcast::<Cubic>().is_ok());
+
+        let controller = CongestionControl::Bbr
+            .controller_factory()
+            .build(Instant::now(), 1200);
+        assert!(controller.into_any().downcast::<Bbr>().is_ok());
+    }
 }
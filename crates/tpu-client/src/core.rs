@@ -40,17 +40,26 @@
 #[cfg(feature = "prometheus")]
 use crate::prom;
 use {
-    crate::config::{TpuOverrideInfo, TpuPortKind, TpuSenderConfig},
+    crate::{
+        config::{
+            AddressFamily, CongestionControl, TpuOverrideInfo, TpuPortKind, TpuSenderConfig,
+            TransmissionMode,
+        },
+        task::{
+            REMOTE_PEER_ADDR_WATCHER_TASK, TPU_SENDER_DRIVER_TASK, spawn_named, spawn_named_on,
+        },
+    },
     bytes::Bytes,
     derive_more::Display,
     futures::task::AtomicWaker,
     quinn::{
-        ClientConfig, Connection, ConnectionError, Endpoint, IdleTimeout, TransportConfig, VarInt,
-        WriteError, crypto::rustls::QuicClientConfig,
+        ClientConfig, Connection, ConnectionError, Endpoint, IdleTimeout, SendDatagramError,
+        TransportConfig, VarInt, WriteError, crypto::rustls::QuicClientConfig,
     },
     rustls::{NamedGroup, crypto::CryptoProvider},
-    solana_clock::{DEFAULT_MS_PER_SLOT, NUM_CONSECUTIVE_LEADER_SLOTS},
+    solana_clock::{DEFAULT_MS_PER_SLOT, NUM_CONSECUTIVE_LEADER_SLOTS, Slot},
     solana_keypair::Keypair,
+    solana_net_utils::PortRange,
     solana_pubkey::Pubkey,
     solana_quic_definitions::{QUIC_KEEP_ALIVE, QUIC_SEND_FAIRNESS},
     solana_signature::Signature,
@@ -59,9 +68,13 @@ use {
     solana_tls_utils::{QuicClientCertificate, SkipServerVerification, new_dummy_x509_certificate},
     std::{
         collections::{BTreeMap, HashMap, HashSet, VecDeque},
-        net::{IpAddr, Ipv4Addr, SocketAddr},
+        net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
         num::NonZeroUsize,
-        sync::{Arc, Mutex as StdMutex, atomic::AtomicBool},
+        ops::Range,
+        sync::{
+            Arc, Mutex as StdMutex,
+            atomic::{AtomicBool, AtomicU64, AtomicUsize},
+        },
         task::Poll,
         time::{Duration, Instant},
     },
@@ -90,6 +103,8 @@ pub(crate) enum ConnectingError {
     ConnectionError(#[from] quinn::ConnectionError),
     #[error("Connection to remote peer not in leader schedule")]
     PeerNotInLeaderSchedule,
+    #[error("No local QUIC endpoint bound for the address family of {0}")]
+    NoEndpointForAddressFamily(SocketAddr),
 }
 
 pub(crate) struct SentOk {
@@ -157,6 +172,18 @@ struct EndpointUsage {
     connecting_remote_peers: HashSet<Pubkey>,
 }
 
+///
+/// A leader predicted by an [`UpcomingLeaderPredictor`], along with the slots it is scheduled for when known.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PredictedLeader {
+    pub leader: Pubkey,
+    ///
+    /// Slots of the rotation, `None` if the predictor does not know them.
+    ///
+    pub slots: Option<Range<Slot>>,
+}
+
 ///
 /// Base trait for predicting upcoming leaders in the Solana cluster.
 ///
@@ -165,6 +192,85 @@ pub trait UpcomingLeaderPredictor {
     /// Tries to predict the next `n` leaders based on the current leader.
     ///
     fn try_predict_next_n_leaders(&self, n: usize) -> Vec<Pubkey>;
+
+    ///
+    /// Tries to predict the current leader followed by the next `n - 1` leaders.
+    ///
+    /// Unlike [`UpcomingLeaderPredictor::try_predict_next_n_leaders`], whose window may start with previous leaders
+    /// to keep their connections warm, see [`crate::yellowstone_grpc::schedule::YellowstoneUpcomingLeader::look_behind`],
+    /// the first leader is the one producing blocks right now: use it to pick the destination of a send.
+    ///
+    /// The default implementation assumes the predictor has no look-behind and returns
+    /// `try_predict_next_n_leaders(n)`.
+    ///
+    fn try_predict_upcoming_leaders(&self, n: usize) -> Vec<Pubkey> {
+        self.try_predict_next_n_leaders(n)
+    }
+
+    ///
+    /// Like [`UpcomingLeaderPredictor::try_predict_next_n_leaders`], along with the slots of each predicted leader.
+    ///
+    /// The default implementation does not know the slots.
+    ///
+    fn try_predict_leader_window(&self, n: usize) -> Vec<PredictedLeader> {
+        self.try_predict_next_n_leaders(n)
+            .into_iter()
+            .map(|leader| PredictedLeader {
+                leader,
+                slots: None,
+            })
+            .collect()
+    }
+
+    ///
+    /// Predicts every leader scheduled up to `end_slot` included, along with its slots, e.g. to cover all the leaders
+    /// before a blockhash expires.
+    ///
+    /// The prediction starts like [`UpcomingLeaderPredictor::try_predict_leader_window`], and a leader holding
+    /// consecutive rotations is returned once with the slots of its run, up to the rotation holding `end_slot`.
+    ///
+    /// The default implementation grows the leader window until it reaches `end_slot`, then keeps the rotations
+    /// starting up to `end_slot`. It predicts nothing if the predictor does not know the slots.
+    ///
+    fn predict_until_slot(&self, end_slot: Slot) -> Vec<(Pubkey, Range<Slot>)> {
+        let mut n = 1usize;
+        loop {
+            let window = self.try_predict_leader_window(n);
+            let reached_end_slot = window
+                .last()
+                .is_none_or(|last| last.slots.as_ref().is_none_or(|slots| slots.end > end_slot));
+            // A shorter window than requested is the end of the known schedule.
+            if reached_end_slot || window.len() < n {
+                return collapse_leader_runs(
+                    window
+                        .into_iter()
+                        .map_while(|predicted| Some((predicted.leader, predicted.slots?)))
+                        .take_while(|(_, slots)| slots.start <= end_slot),
+                );
+            }
+            n = n.saturating_mul(2);
+        }
+    }
+}
+
+///
+/// Collapses the consecutive rotations of a same leader into a single slot range.
+///
+pub(crate) fn collapse_leader_runs(
+    rotations: impl IntoIterator<Item = (Pubkey, Range<Slot>)>,
+) -> Vec<(Pubkey, Range<Slot>)> {
+    let mut runs: Vec<(Pubkey, Range<Slot>)> = Vec::new();
+    for (leader, slots) in rotations {
+        match runs.last_mut() {
+            Some((last_leader, last_slots))
+                if *last_leader == leader && last_slots.end == slots.start =>
+            {
+                last_slots.end = slots.end;
+            }
+            _ => runs.push((leader, slots)),
+        }
+    }
+    runs
 }
 
 ///
@@ -179,6 +285,202 @@ impl UpcomingLeaderPredictor for IgnorantLeaderPredictor {
     }
 }
 
+///
+/// A deterministic upcoming leader predictor over a static, in-memory, leader cycle.
+///
+/// The leader of slot `s` is `leaders[(s / slots_per_leader) % leaders.len()]`.
+///
+/// Meant for testing code built on [`UpcomingLeaderPredictor`] without a live slot feed:
+/// the current slot only moves when [`StaticLeaderPredictor::set_slot`] is called.
+///
+/// # Prediction window
+///
+/// Like [`crate::yellowstone_grpc::schedule::YellowstoneUpcomingLeader`], for `n` requested leaders the prediction
+/// starts at the previous leader: n-1, n, n+1, ..., n+(n-2).
+/// Near slot zero, where there is no previous leader, the window starts at the current leader.
+/// [`UpcomingLeaderPredictor::try_predict_upcoming_leaders`] always starts at the current leader.
+///
+#[derive(Debug)]
+pub struct StaticLeaderPredictor {
+    leaders: Vec<Pubkey>,
+    slots_per_leader: u64,
+    slot: AtomicU64,
+}
+
+impl StaticLeaderPredictor {
+    ///
+    /// Creates a predictor cycling through `leaders`, each holding [`NUM_CONSECUTIVE_LEADER_SLOTS`] slots.
+    ///
+    pub fn new(leaders: Vec<Pubkey>, slot: Slot) -> Self {
+        Self::with_slots_per_leader(leaders, slot, NUM_CONSECUTIVE_LEADER_SLOTS)
+    }
+
+    ///
+    /// Creates a predictor cycling through `leaders`, each holding `slots_per_leader` slots.
+    ///
+    /// A value of `0` for `slots_per_leader`, or an empty `leaders`, disables prediction.
+    ///
+    pub fn with_slots_per_leader(leaders: Vec<Pubkey>, slot: Slot, slots_per_leader: u64) -> Self {
+        Self {
+            leaders,
+            slots_per_leader,
+            slot: AtomicU64::new(slot),
+        }
+    }
+
+    ///
+    /// Sets the current slot, moving the prediction window.
+    ///
+    pub fn set_slot(&self, slot: Slot) {
+        self.slot.store(slot, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    ///
+    /// Returns the current slot.
+    ///
+    pub fn slot(&self) -> Slot {
+        self.slot.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    ///
+    /// Returns the leader scheduled at `slot`, `None` if prediction is disabled.
+    ///
+    pub fn leader_at_slot(&self, slot: Slot) -> Option<Pubkey> {
+        if self.leaders.is_empty() || self.slots_per_leader == 0 {
+            return None;
+        }
+        let rotation = slot / self.slots_per_leader;
+        Some(self.leaders[(rotation % self.leaders.len() as u64) as usize])
+    }
+}
+
+impl UpcomingLeaderPredictor for StaticLeaderPredictor {
+    fn try_predict_next_n_leaders(&self, n: usize) -> Vec<Pubkey> {
+        if self.leaders.is_empty() || self.slots_per_leader == 0 {
+            return Vec::new();
+        }
+        let slot = self.slot();
+        let current_leader_boundary = slot - slot % self.slots_per_leader;
+        let start_boundary = current_leader_boundary.saturating_sub(self.slots_per_leader);
+        (0..n as u64)
+            .filter_map(|i| self.leader_at_slot(start_boundary + i * self.slots_per_leader))
+            .collect()
+    }
+
+    fn try_predict_upcoming_leaders(&self, n: usize) -> Vec<Pubkey> {
+        if self.leaders.is_empty() || self.slots_per_leader == 0 {
+            return Vec::new();
+        }
+        let slot = self.slot();
+        let current_leader_boundary = slot - slot % self.slots_per_leader;
+        (0..n as u64)
+            .filter_map(|i| {
+                self.leader_at_slot(current_leader_boundary + i * self.slots_per_leader)
+            })
+            .collect()
+    }
+
+    fn try_predict_leader_window(&self, n: usize) -> Vec<PredictedLeader> {
+        if self.leaders.is_empty() || self.slots_per_leader == 0 {
+            return Vec::new();
+        }
+        let slot = self.slot();
+        let current_leader_boundary = slot - slot % self.slots_per_leader;
+        let start_boundary = current_leader_boundary.saturating_sub(self.slots_per_leader);
+        (0..n as u64)
+            .filter_map(|i| {
+                let first_slot = start_boundary + i * self.slots_per_leader;
+                self.leader_at_slot(first_slot)
+                    .map(|leader| PredictedLeader {
+                        leader,
+                        slots: Some(first_slot..first_slot + self.slots_per_leader),
+                    })
+            })
+            .collect()
+    }
+}
+
+///
+/// An upcoming leader predictor combining two sources.
+///
+/// The `primary` predictions are returned as-is when they fill the requested window.
+/// When the primary returns fewer than `n` leaders, e.g. because its slot feed is stale,
+/// the `secondary` predictions are merged in after the primary ones, skipping leaders already predicted.
+///
+#[derive(Debug, Clone)]
+pub struct FallbackPredictor<P, S> {
+    pub primary: P,
+    pub secondary: S,
+}
+
+impl<P, S> FallbackPredictor<P, S> {
+    pub const fn new(primary: P, secondary: S) -> Self {
+        Self { primary, secondary }
+    }
+}
+
+impl<P, S> UpcomingLeaderPredictor for FallbackPredictor<P, S>
+where
+    P: UpcomingLeaderPredictor,
+    S: UpcomingLeaderPredictor,
+{
+    fn try_predict_next_n_leaders(&self, n: usize) -> Vec<Pubkey> {
+        let leaders = self.primary.try_predict_next_n_leaders(n);
+        if leaders.len() >= n {
+            return leaders;
+        }
+        merge_fallback_leaders(leaders, self.secondary.try_predict_next_n_leaders(n), n)
+    }
+
+    fn try_predict_upcoming_leaders(&self, n: usize) -> Vec<Pubkey> {
+        let leaders = self.primary.try_predict_upcoming_leaders(n);
+        if leaders.len() >= n {
+            return leaders;
+        }
+        merge_fallback_leaders(leaders, self.secondary.try_predict_upcoming_leaders(n), n)
+    }
+
+    fn try_predict_leader_window(&self, n: usize) -> Vec<PredictedLeader> {
+        let mut leaders = self.primary.try_predict_leader_window(n);
+        if leaders.len() >= n {
+            return leaders;
+        }
+        let mut seen = leaders
+            .iter()
+            .map(|predicted| predicted.leader)
+            .collect::<HashSet<_>>();
+        for predicted in self.secondary.try_predict_leader_window(n) {
+            if leaders.len() == n {
+                break;
+            }
+            if seen.insert(predicted.leader) {
+                leaders.push(predicted);
+            }
+        }
+        leaders
+    }
+}
+
+///
+/// Appends the `secondary` leaders not already in `leaders`, up to `n` leaders.
+///
+fn merge_fallback_leaders(
+    mut leaders: Vec<Pubkey>,
+    secondary: Vec<Pubkey>,
+    n: usize,
+) -> Vec<Pubkey> {
+    let mut seen = leaders.iter().copied().collect::<HashSet<_>>();
+    for leader in secondary {
+        if leaders.len() == n {
+            break;
+        }
+        if seen.insert(leader) {
+            leaders.push(leader);
+        }
+    }
+    leaders
+}
+
 pub trait ValidatorStakeInfoService {
     ///
     /// Gets the stake info for a given validator pubkey.
@@ -227,7 +529,7 @@ pub(crate) struct TpuSenderDriver<CB> {
     ///
     tx_queues: HashMap<Pubkey, VecDeque<(TpuSenderTxn, usize)>>,
 
-    endpoints: Vec<Endpoint>,
+    endpoints: Vec<ClientEndpoints>,
     endpoints_usage: Vec<EndpointUsage>,
 
     ///
@@ -305,6 +607,11 @@ pub(crate) struct TpuSenderDriver<CB> {
     /// Next leader prediction deadline.
     ///
     next_leader_prediction_deadline: Instant,
+
+    ///
+    /// Number of active remote peer connections, shared with [`ActiveConnectionCount`] handles.
+    ///
+    active_connection_cnt: Arc<AtomicUsize>,
 }
 
 pub trait LeaderTpuInfoService {
@@ -349,6 +656,162 @@ where
             .or_else(|| self.other.get_quic_tpu_fwd_socket_addr(leader_pubkey))
     }
 }
+///
+/// A service that hides the TPU addresses outside of the preferred address families.
+///
+/// A leader whose address is in an excluded family resolves to `None`, as if it advertised no address.
+///
+pub struct AddressFamilyTpuInfoService<I> {
+    pub preference: AddressFamily,
+    pub other: I,
+}
+
+impl<I> AddressFamilyTpuInfoService<I> {
+    fn filter(
+        &self,
+        leader_pubkey: &Pubkey,
+        addr: Option<SocketAddr>,
+        port: &str,
+    ) -> Option<SocketAddr> {
+        addr.filter(|addr| {
+            let allowed = self.preference.allows(addr);
+            if !allowed {
+                tracing::debug!(
+                    "Skipping {port} address {addr} of leader {leader_pubkey}, excluded by address family preference {:?}",
+                    self.preference
+                );
+            }
+            allowed
+        })
+    }
+}
+
+impl<I> LeaderTpuInfoService for AddressFamilyTpuInfoService<I>
+where
+    I: LeaderTpuInfoService,
+{
+    fn get_quic_tpu_socket_addr(&self, leader_pubkey: &Pubkey) -> Option<SocketAddr> {
+        let addr = self.other.get_quic_tpu_socket_addr(leader_pubkey);
+        self.filter(leader_pubkey, addr, "TPU")
+    }
+    fn get_quic_tpu_fwd_socket_addr(&self, leader_pubkey: &Pubkey) -> Option<SocketAddr> {
+        let addr = self.other.get_quic_tpu_fwd_socket_addr(leader_pubkey);
+        self.filter(leader_pubkey, addr, "TPU forwards")
+    }
+}
+
+impl<T> LeaderTpuInfoService for Arc<T>
+where
+    T: LeaderTpuInfoService + ?Sized,
+{
+    fn get_quic_tpu_socket_addr(&self, leader_pubkey: &Pubkey) -> Option<SocketAddr> {
+        (**self).get_quic_tpu_socket_addr(leader_pubkey)
+    }
+    fn get_quic_tpu_fwd_socket_addr(&self, leader_pubkey: &Pubkey) -> Option<SocketAddr> {
+        (**self).get_quic_tpu_fwd_socket_addr(leader_pubkey)
+    }
+}
+
+///
+/// TPU QUIC addresses of a leader, see [`AddressResolver`].
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct TpuAddrs {
+    pub tpu_quic: Option<SocketAddr>,
+    pub tpu_forwards_quic: Option<SocketAddr>,
+}
+
+impl TpuAddrs {
+    ///
+    /// Returns the address of the `tpu_port_kind` port.
+    ///
+    pub const fn get(&self, tpu_port_kind: TpuPortKind) -> Option<SocketAddr> {
+        match tpu_port_kind {
+            TpuPortKind::Normal => self.tpu_quic,
+            TpuPortKind::Forwards => self.tpu_forwards_quic,
+        }
+    }
+}
+
+///
+/// Resolves the TPU addresses of leaders.
+///
+/// Addresses are resolved from gossip by default, see [`crate::rpc::tpu_info::RpcClusterTpuQuicInfoService`].
+/// Implement this trait to resolve them from another source instead, e.g. a custom registry or a static map
+/// like [`StaticAddressResolver`].
+///
+/// [`ResolverTpuInfoService`] adapts a resolver into a [`LeaderTpuInfoService`].
+///
+pub trait AddressResolver {
+    ///
+    /// Returns the TPU addresses of `leader`, `None` if the leader is unknown.
+    ///
+    fn resolve(&self, leader: &Pubkey) -> Option<TpuAddrs>;
+}
+
+impl<T> AddressResolver for Arc<T>
+where
+    T: AddressResolver + ?Sized,
+{
+    fn resolve(&self, leader: &Pubkey) -> Option<TpuAddrs> {
+        (**self).resolve(leader)
+    }
+}
+
+///
+/// A [`LeaderTpuInfoService`] backed by an [`AddressResolver`].
+///
+pub struct ResolverTpuInfoService<R> {
+    pub resolver: R,
+}
+
+impl<R> LeaderTpuInfoService for ResolverTpuInfoService<R>
+where
+    R: AddressResolver,
+{
+    fn get_quic_tpu_socket_addr(&self, leader_pubkey: &Pubkey) -> Option<SocketAddr> {
+        self.resolver
+            .resolve(leader_pubkey)
+            .and_then(|addrs| addrs.tpu_quic)
+    }
+    fn get_quic_tpu_fwd_socket_addr(&self, leader_pubkey: &Pubkey) -> Option<SocketAddr> {
+        self.resolver
+            .resolve(leader_pubkey)
+            .and_then(|addrs| addrs.tpu_forwards_quic)
+    }
+}
+
+///
+/// An [`AddressResolver`] over a static, in-memory, map of leader addresses.
+///
+#[derive(Debug, Clone, Default)]
+pub struct StaticAddressResolver {
+    addrs: HashMap<Pubkey, TpuAddrs>,
+}
+
+impl StaticAddressResolver {
+    ///
+    /// Sets the addresses of `leader`, returning its previous addresses if any.
+    ///
+    pub fn insert(&mut self, leader: Pubkey, addrs: TpuAddrs) -> Option<TpuAddrs> {
+        self.addrs.insert(leader, addrs)
+    }
+}
+
+impl FromIterator<(Pubkey, TpuAddrs)> for StaticAddressResolver {
+    fn from_iter<T: IntoIterator<Item = (Pubkey, TpuAddrs)>>(iter: T) -> Self {
+        Self {
+            addrs: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl AddressResolver for StaticAddressResolver {
+    fn resolve(&self, leader: &Pubkey) -> Option<TpuAddrs> {
+        self.addrs.get(leader).copied()
+    }
+}
+
 ///
 /// A transaction with destination details to be sent to a remote peer.
 ///
@@ -529,10 +992,11 @@ struct ConnectingTask {
     remote_peer_identity: Pubkey,
     cert: Arc<QuicClientCertificate>,
     max_idle_timeout: Duration,
+    congestion_controller: CongestionControl,
     connection_timeout: Duration,
     tpu_port_kind: TpuPortKind,
     wait_for_eviction: Option<Arc<Notify>>,
-    endpoint: Endpoint,
+    endpoints: ClientEndpoints,
 }
 
 /// Translate a SocketAddr into a valid SNI for the purposes of QUIC connection
@@ -555,6 +1019,138 @@ pub fn crypto_provider() -> CryptoProvider {
     provider
 }
 
+///
+/// Builds the QUIC client configuration used to connect to TPU ports.
+///
+pub(crate) fn tpu_client_config(
+    cert: &QuicClientCertificate,
+    max_idle_timeout: Duration,
+    keep_alive_interval: Duration,
+    congestion_controller: CongestionControl,
+) -> ClientConfig {
+    let mut crypto = rustls::ClientConfig::builder_with_provider(Arc::new(crypto_provider()))
+        .with_safe_default_protocol_versions()
+        .expect("Failed to set QUIC client protocol versions")
+        .dangerous()
+        .with_custom_certificate_verifier(SkipServerVerification::new())
+        .with_client_auth_cert(vec![cert.certificate.clone()], cert.key.clone_key())
+        .expect("Failed to set QUIC client certificates");
+    crypto.enable_early_data = true;
+    crypto.alpn_protocols = vec![ALPN_TPU_PROTOCOL_ID.to_vec()];
+
+    let mut config = ClientConfig::new(Arc::new(QuicClientConfig::try_from(crypto).unwrap()));
+    config.transport_config(Arc::new(tpu_transport_config(
+        max_idle_timeout,
+        keep_alive_interval,
+        congestion_controller,
+    )));
+    config
+}
+
+///
+/// QUIC transport configuration of TPU connections, see [`tpu_client_config`].
+///
+pub(crate) fn tpu_transport_config(
+    max_idle_timeout: Duration,
+    keep_alive_interval: Duration,
+    congestion_controller: CongestionControl,
+) -> TransportConfig {
+    let mut res = TransportConfig::default();
+
+    let max_idle_timeout =
+        IdleTimeout::try_from(max_idle_timeout).expect("Failed to set QUIC max idle timeout");
+    res.max_idle_timeout(Some(max_idle_timeout));
+    res.keep_alive_interval(Some(keep_alive_interval));
+    // We don't want fairness : https://github.com/quinn-rs/quinn/pull/2002
+    // Fairness use round-robin scheduling to write stream data into the next frame.
+    // Disabling fairness makes that once a stream starts to write it won't be interrupted by round-robin.
+    // This reduce the time the receive the (fin) "end" of a transaction, thus reducing latency.
+    res.send_fairness(QUIC_SEND_FAIRNESS);
+    res.congestion_controller_factory(congestion_controller.controller_factory());
+    res
+}
+
+///
+/// Binds a client-only QUIC endpoint to a local port in `port_range`.
+///
+/// Returns `None` if no port could be bound after `max_attempts` attempts.
+///
+pub(crate) fn bind_client_endpoint(
+    bind_ip: IpAddr,
+    port_range: PortRange,
+    max_attempts: usize,
+) -> Option<Endpoint> {
+    (0..max_attempts).find_map(|_| {
+        let (_, client_socket) = solana_net_utils::bind_in_range(bind_ip, port_range).ok()?;
+        Endpoint::new(
+            quinn::EndpointConfig::default(),
+            None,
+            client_socket,
+            Arc::new(quinn::TokioRuntime),
+        )
+        .ok()
+    })
+}
+
+///
+/// Client QUIC endpoints, one per address family allowed by the [`AddressFamily`] preference.
+///
+/// A socket bound to an IPv4 address cannot reach IPv6 peers and vice versa, connections use the endpoint matching
+/// the family of the remote address, see [`ClientEndpoints::for_addr`].
+///
+#[derive(Clone)]
+pub(crate) struct ClientEndpoints {
+    v4: Option<Endpoint>,
+    v6: Option<Endpoint>,
+}
+
+impl ClientEndpoints {
+    ///
+    /// Binds an endpoint per allowed family, returns `None` if none could be bound.
+    ///
+    /// With [`AddressFamily::Any`], a family whose endpoint cannot be bound, e.g. IPv6 disabled on the host,
+    /// is unreachable but does not prevent the other one.
+    ///
+    pub(crate) fn bind(
+        preference: AddressFamily,
+        port_range: PortRange,
+        max_attempts: usize,
+    ) -> Option<Self> {
+        let bind = |allowed: bool, bind_ip: IpAddr| {
+            if !allowed {
+                return None;
+            }
+            let endpoint = bind_client_endpoint(bind_ip, port_range, max_attempts);
+            if endpoint.is_none() {
+                tracing::warn!(
+                    "Failed to bind a local QUIC endpoint on {bind_ip} in port range {port_range:?}"
+                );
+            }
+            endpoint
+        };
+        let v4 = bind(preference.allows_v4(), IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        let v6 = bind(preference.allows_v6(), IpAddr::V6(Ipv6Addr::UNSPECIFIED));
+        (v4.is_some() || v6.is_some()).then_some(Self { v4, v6 })
+    }
+
+    ///
+    /// Returns the endpoint able to reach `addr`.
+    ///
+    pub(crate) const fn for_addr(&self, addr: &SocketAddr) -> Option<&Endpoint> {
+        match addr {
+            SocketAddr::V4(_) => self.v4.as_ref(),
+            SocketAddr::V6(_) => self.v6.as_ref(),
+        }
+    }
+
+    ///
+    /// Returns the bound endpoints, one UDP socket each.
+    ///
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Endpoint> {
+        self.v4.iter().chain(self.v6.iter())
+    }
+}
+
 impl ConnectingTask {
     async fn run(self) -> Result<Connection, ConnectingError> {
         if let Some(signal) = &self.wait_for_eviction {
@@ -569,40 +1165,18 @@ impl ConnectingTask {
             .service
             .get_quic_dest_addr(&self.remote_peer_identity, self.tpu_port_kind);
         let remote_peer_addr = remote_peer_addr.ok_or(ConnectingError::PeerNotInLeaderSchedule)?;
-        let mut crypto = rustls::ClientConfig::builder_with_provider(Arc::new(crypto_provider()))
-            .with_safe_default_protocol_versions()
-            .expect("Failed to set QUIC client protocol versions")
-            .dangerous()
-            .with_custom_certificate_verifier(SkipServerVerification::new())
-            .with_client_auth_cert(
-                vec![self.cert.certificate.clone()],
-                self.cert.key.clone_key(),
-            )
-            .expect("Failed to set QUIC client certificates");
-        crypto.enable_early_data = true;
-        crypto.alpn_protocols = vec![ALPN_TPU_PROTOCOL_ID.to_vec()];
-
-        let transport_config = {
-            let mut res = TransportConfig::default();
-
-            let max_idle_timeout = IdleTimeout::try_from(self.max_idle_timeout)
-                .expect("Failed to set QUIC max idle timeout");
-            res.max_idle_timeout(Some(max_idle_timeout));
-            res.keep_alive_interval(Some(QUIC_KEEP_ALIVE));
-            // We don't want fairness : https://github.com/quinn-rs/quinn/pull/2002
-            // Fairness use round-robin scheduling to write stream data into the next frame.
-            // Disabling fairness makes that once a stream starts to write it won't be interrupted by round-robin.
-            // This reduce the time the receive the (fin) "end" of a transaction, thus reducing latency.
-            res.send_fairness(QUIC_SEND_FAIRNESS);
-            res
-        };
-
-        let mut config = ClientConfig::new(Arc::new(QuicClientConfig::try_from(crypto).unwrap()));
-        config.transport_config(Arc::new(transport_config));
+        let endpoint = self.endpoints.for_addr(&remote_peer_addr).ok_or(
+            ConnectingError::NoEndpointForAddressFamily(remote_peer_addr),
+        )?;
+        let config = tpu_client_config(
+            &self.cert,
+            self.max_idle_timeout,
+            QUIC_KEEP_ALIVE,
+            self.congestion_controller,
+        );
 
         let server_name = socket_addr_to_quic_server_name(remote_peer_addr);
-        let connecting = self
-            .endpoint
+        let connecting = endpoint
             .connect_with(config, remote_peer_addr, server_name.as_str())
             .map_err(ConnectingError::ConnectError)?;
 
@@ -619,6 +1193,41 @@ impl ConnectingTask {
     }
 }
 
+///
+/// Sends `tx` over `connection` with the requested transmission mode, see [`TransmissionMode`].
+///
+/// A datagram falls back to a unidirectional stream if the peer did not negotiate datagram support, or if `tx`
+/// exceeds the maximum datagram size of the path. Returns the transmission mode actually used.
+///
+pub(crate) async fn send_with_transmission_mode(
+    connection: &Connection,
+    tx: &[u8],
+    mode: TransmissionMode,
+) -> Result<TransmissionMode, SendTxError> {
+    if mode == TransmissionMode::Datagram {
+        match connection.send_datagram(Bytes::copy_from_slice(tx)) {
+            Ok(()) => return Ok(TransmissionMode::Datagram),
+            Err(SendDatagramError::ConnectionLost(connection_error)) => {
+                return Err(SendTxError::ConnectionError(connection_error));
+            }
+            Err(e) => tracing::debug!(
+                "Sending to {} over a stream, datagram not sent: {e}",
+                connection.remote_address()
+            ),
+        }
+    }
+    let mut uni = connection.open_uni().await?;
+    uni.write_all(tx).await.map_err(|e| match e {
+        WriteError::Stopped(var_int) => SendTxError::StreamStopped(var_int),
+        WriteError::ConnectionLost(connection_error) => {
+            SendTxError::ConnectionError(connection_error)
+        }
+        WriteError::ClosedStream => SendTxError::StreamClosed,
+        WriteError::ZeroRttRejected => SendTxError::ZeroRttRejected,
+    })?;
+    Ok(TransmissionMode::Stream)
+}
+
 /// A transaction sender worker tied to a specific remote peer via a single connection.
 ///
 /// To optimize performance for a [`quinn::Connection`], adhere to the following guidelines:
@@ -643,6 +1252,9 @@ struct QuicTxSenderWorker<CB> {
     tx_queue: VecDeque<(TpuSenderTxn, usize)>,
     cancel_notify: Arc<Notify>,
     max_tx_attempt: NonZeroUsize,
+    transmission_mode: TransmissionMode,
+    /// Set once a datagram fell back to a stream, the fallback is only logged once per connection.
+    datagram_fallback_logged: bool,
     // TODO: Check if this is necessary, since there is already flow control in QUIC
     // Moreover, most QUIC API are instantaneous and do not block
     // and if a connection is idle for 2s it will be closed anyway, moreover remote validator could technically decide to kick us off
@@ -672,15 +1284,15 @@ where
 {
     async fn send_tx(&mut self, tx: &[u8]) -> Result<SentOk, SendTxError> {
         let t = Instant::now();
-        let mut uni = self.connection.open_uni().await?;
-        uni.write_all(tx).await.map_err(|e| match e {
-            WriteError::Stopped(var_int) => SendTxError::StreamStopped(var_int),
-            WriteError::ConnectionLost(connection_error) => {
-                SendTxError::ConnectionError(connection_error)
-            }
-            WriteError::ClosedStream => SendTxError::StreamClosed,
-            WriteError::ZeroRttRejected => SendTxError::ZeroRttRejected,
-        })?;
+        let used =
+            send_with_transmission_mode(&self.connection, tx, self.transmission_mode).await?;
+        if used != self.transmission_mode && !self.datagram_fallback_logged {
+            self.datagram_fallback_logged = true;
+            tracing::warn!(
+                "Datagrams can't be used with remote peer: {}, falling back to streams",
+                self.remote_peer
+            );
+        }
         self.txn_sent = self.txn_sent.saturating_add(1);
         let e2e_time = t.elapsed();
         let ok = SentOk { e2e_time };
@@ -1131,10 +1743,11 @@ where
             remote_peer_identity,
             cert,
             max_idle_timeout,
+            congestion_controller: self.config.congestion_controller,
             connection_timeout: self.config.connecting_timeout,
             tpu_port_kind: self.config.tpu_port,
             wait_for_eviction: maybe_wait_for_eviction,
-            endpoint: self.endpoints[endpoint_idx].clone(),
+            endpoints: self.endpoints[endpoint_idx].clone(),
         }
         .run();
         let meta = ConnectingMeta {
@@ -1279,6 +1892,8 @@ where
                 .unwrap_or_default(),
             cancel_notify: Arc::clone(&cancel_notify),
             max_tx_attempt: self.config.max_send_attempt,
+            transmission_mode: self.config.transmission_mode,
+            datagram_fallback_logged: false,
             tx_send_timeout: self.config.send_timeout,
             txn_sent: 0,
         };
@@ -1854,6 +2469,7 @@ where
         prom::set_quic_gw_connecting_cnt(num_connecting_tasks);
         prom::set_quic_gw_ongoing_evictions_cnt(self.being_evicted_peers.len());
         prom::set_quic_gw_tx_blocked_by_connecting_cnt(num_queued_tx);
+        prom::set_tpu_sender_queue_depth(self.tx_inlet.len());
     }
 
     fn handle_remote_peer_addr_change(&mut self, remote_peers_changed: HashSet<Pubkey>) {
@@ -1966,6 +2582,10 @@ where
                 }
             }
             self.try_predict_upcoming_leaders_if_necessary();
+            self.active_connection_cnt.store(
+                self.tx_worker_handle_map.len(),
+                std::sync::atomic::Ordering::Relaxed,
+            );
 
             tokio::select! {
                 maybe = self.tx_inlet.recv() => {
@@ -2002,6 +2622,8 @@ where
         }
 
         self.schedule_graceful_drop_all_worker();
+        self.active_connection_cnt
+            .store(0, std::sync::atomic::Ordering::Relaxed);
         while let Some(result) = self.tasklet.join_next_with_id().await {
             self.handle_tasklet_result(result);
         }
@@ -2035,7 +2657,7 @@ impl RemotePeerAddrWatcher {
             notify: Arc::clone(&notify),
         };
 
-        tokio::spawn(async move {
+        spawn_named(REMOTE_PEER_ADDR_WATCHER_TASK, async move {
             ev_loop.run().await;
         });
 
@@ -2195,6 +2817,28 @@ pub struct TpuSenderSessionContext {
     /// Dropping this handle does not interrupt the driver runtime.
     ///
     pub driver_join_handle: JoinHandle<()>,
+
+    ///
+    /// Number of active remote peer connections of the driver.
+    ///
+    pub active_connections: ActiveConnectionCount,
+}
+
+///
+/// Cheaply cloneable handle reading the number of active remote peer connections of a TPU sender driver.
+///
+/// The count is refreshed on every iteration of the driver event-loop and drops to zero once the driver stops.
+///
+#[derive(Clone, Debug, Default)]
+pub struct ActiveConnectionCount(Arc<AtomicUsize>);
+
+impl ActiveConnectionCount {
+    ///
+    /// Returns the number of active remote peer connections.
+    ///
+    pub fn get(&self) -> usize {
+        self.0.load(std::sync::atomic::Ordering::Relaxed)
+    }
 }
 
 ///
@@ -2312,33 +2956,29 @@ impl TpuSenderDriverSpawner {
         let mut endpoints = vec![];
         let mut endpoints_usage = vec![];
         for _ in 0..config.num_endpoints.get() {
-            let endpoint = (0..config.max_local_port_binding_attempts)
-                .find_map(|_| {
-                    let (_, client_socket) = solana_net_utils::bind_in_range(
-                        IpAddr::V4(Ipv4Addr::UNSPECIFIED),
-                        config.endpoint_port_range,
-                    )
-                    .ok()?;
-                    Endpoint::new(
-                        quinn::EndpointConfig::default(),
-                        None,
-                        client_socket,
-                        Arc::new(quinn::TokioRuntime),
-                    )
-                    .ok()
-                })
-                .expect("Failed to create QUIC endpoint");
+            let endpoint = ClientEndpoints::bind(
+                config.address_family_preference,
+                config.endpoint_port_range,
+                config.max_local_port_binding_attempts,
+            )
+            .expect("Failed to create QUIC endpoint");
 
             endpoints.push(endpoint);
             endpoints_usage.push(EndpointUsage::default());
         }
 
+        let leader_tpu_info_service: Arc<dyn LeaderTpuInfoService + Send + Sync + 'static> =
+            Arc::new(AddressFamilyTpuInfoService {
+                preference: config.address_family_preference,
+                other: Arc::clone(&self.leader_tpu_info_service),
+            });
         let remote_peer_addr_watcher = RemotePeerAddrWatcher::new(
             config.remote_peer_addr_watch_interval,
             config.tpu_port,
-            Arc::clone(&self.leader_tpu_info_service),
+            Arc::clone(&leader_tpu_info_service),
         );
 
+        let active_connections = ActiveConnectionCount::default();
         let driver = TpuSenderDriver {
             stake_info_map: Arc::clone(&self.stake_info_map),
             tx_worker_handle_map: Default::default(),
@@ -2350,7 +2990,7 @@ impl TpuSenderDriverSpawner {
             connecting_tasks: JoinSet::new(),
             connecting_meta: Default::default(),
             connecting_remote_peers: Default::default(),
-            leader_tpu_info_service: Arc::clone(&self.leader_tpu_info_service),
+            leader_tpu_info_service,
             config,
             client_certificate: cert,
             tx_inlet: tx_outlet,
@@ -2367,9 +3007,10 @@ impl TpuSenderDriverSpawner {
             remote_peer_addr_watcher,
             leader_predictor,
             next_leader_prediction_deadline: Instant::now(),
+            active_connection_cnt: Arc::clone(&active_connections.0),
         };
 
-        let jh = driver_rt.spawn(driver.run());
+        let jh = spawn_named_on(TPU_SENDER_DRIVER_TASK, driver.run(), &driver_rt);
 
         TpuSenderSessionContext {
             driver_tx_sink: tx_inlet,
@@ -2377,6 +3018,7 @@ impl TpuSenderDriverSpawner {
                 cnc_tx: driver_cnc_tx,
             },
             driver_join_handle: jh,
+            active_connections,
         }
     }
 }
@@ -2388,7 +3030,7 @@ pub struct TpuSenderIdentityUpdater {
     ///
     /// Command-and-control channel to send command to the QUIC driver
     ///  
-    cnc_tx: mpsc::Sender<DriverCommand>,
+    pub(crate) cnc_tx: mpsc::Sender<DriverCommand>,
 }
 
 ///
@@ -2681,8 +3323,9 @@ mod stake_based_eviction_strategy_test {
 mod leader_tpu_info_service_test {
     use {
         crate::{
-            config::{TpuOverrideInfo, TpuPortKind},
-            core::{LeaderTpuInfoService, OverrideTpuInfoService},
+            config::{AddressFamily, TpuOverrideInfo, TpuPortKind},
+            core::{AddressFamilyTpuInfoService, LeaderTpuInfoService, OverrideTpuInfoService},
+            rpc::{contact::ContactInfoTable, tpu_info::RpcTpuQuicContactInfo},
         },
         solana_pubkey::Pubkey,
         std::{
@@ -2785,4 +3428,335 @@ mod leader_tpu_info_service_test {
         assert_eq!(actual_normal, Some("127.0.0.1:8000".parse().unwrap()));
         assert_eq!(actual_fwd, Some("127.0.0.1:8001".parse().unwrap()));
     }
+
+    #[test]
+    fn test_address_family_preference_should_skip_excluded_families() {
+        let v4_addr: SocketAddr = "127.0.0.1:8000".parse().unwrap();
+        let v6_addr: SocketAddr = "[2001:db8::1]:8000".parse().unwrap();
+        let v4_leader = Pubkey::new_unique();
+        let v6_leader = Pubkey::new_unique();
+        // Advertises its TPU over IPv4 and its TPU forwards over IPv6.
+        let mixed_leader = Pubkey::new_unique();
+        let table = ContactInfoTable::from_iter([
+            (
+                v4_leader,
+                RpcTpuQuicContactInfo {
+                    tpu_quic: Some(v4_addr),
+                    tpu_forwards_quic: Some(v4_addr),
+                },
+            ),
+            (
+                v6_leader,
+                RpcTpuQuicContactInfo {
+                    tpu_quic: Some(v6_addr),
+                    tpu_forwards_quic: Some(v6_addr),
+                },
+            ),
+            (
+                mixed_leader,
+                RpcTpuQuicContactInfo {
+                    tpu_quic: Some(v4_addr),
+                    tpu_forwards_quic: Some(v6_addr),
+                },
+            ),
+        ]);
+
+        let cases = [
+            (
+                AddressFamily::Any,
+                [Some(v4_addr), Some(v6_addr), Some(v4_addr), Some(v6_addr)],
+            ),
+            (
+                AddressFamily::V4Only,
+                [Some(v4_addr), None, Some(v4_addr), None],
+            ),
+            (
+                AddressFamily::V6Only,
+                [None, Some(v6_addr), None, Some(v6_addr)],
+            ),
+        ];
+        for (preference, [v4, v6, mixed_normal, mixed_fwd]) in cases {
+            let service = AddressFamilyTpuInfoService {
+                preference,
+                other: table.clone(),
+            };
+            let resolve = |leader, kind| service.get_quic_dest_addr(leader, kind);
+            assert_eq!(
+                resolve(&v4_leader, TpuPortKind::Forwards),
+                v4,
+                "{preference:?}"
+            );
+            assert_eq!(
+                resolve(&v6_leader, TpuPortKind::Forwards),
+                v6,
+                "{preference:?}"
+            );
+            assert_eq!(
+                resolve(&mixed_leader, TpuPortKind::Normal),
+                mixed_normal,
+                "{preference:?}"
+            );
+            assert_eq!(
+                resolve(&mixed_leader, TpuPortKind::Forwards),
+                mixed_fwd,
+                "{preference:?}"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod static_leader_predictor_test {
+    use {
+        super::{StaticLeaderPredictor, UpcomingLeaderPredictor},
+        solana_clock::NUM_CONSECUTIVE_LEADER_SLOTS,
+        solana_pubkey::Pubkey,
+    };
+
+    fn leaders(n: usize) -> Vec<Pubkey> {
+        (0..n).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    #[test]
+    fn it_should_predict_from_previous_to_future_leaders() {
+        let leaders = leaders(10);
+        // Slot 13 is held by leader 3
+        let predictor = StaticLeaderPredictor::new(leaders.clone(), 13);
+
+        // n-1, n, n+1, ..., n+(n-2)
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(5),
+            leaders[2..7].to_vec()
+        );
+        assert_eq!(predictor.try_predict_next_n_leaders(1), vec![leaders[2]]);
+        assert!(predictor.try_predict_next_n_leaders(0).is_empty());
+    }
+
+    #[test]
+    fn it_should_predict_upcoming_leaders_from_the_current_leader() {
+        let leaders = leaders(10);
+        // Slot 13 is held by leader 3
+        let predictor = StaticLeaderPredictor::new(leaders.clone(), 13);
+        assert_eq!(
+            predictor.try_predict_upcoming_leaders(3),
+            leaders[3..6].to_vec()
+        );
+        assert_eq!(predictor.try_predict_upcoming_leaders(1), vec![leaders[3]]);
+        assert!(predictor.try_predict_upcoming_leaders(0).is_empty());
+
+        predictor.set_slot(0);
+        assert_eq!(
+            predictor.try_predict_upcoming_leaders(2),
+            predictor.try_predict_next_n_leaders(2)
+        );
+    }
+
+    #[test]
+    fn it_should_move_window_with_set_slot() {
+        let leaders = leaders(3);
+        let predictor = StaticLeaderPredictor::new(leaders.clone(), 0);
+        // No previous leader at genesis, the window starts at the current leader
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(3),
+            vec![leaders[0], leaders[1], leaders[2]]
+        );
+
+        predictor.set_slot(2 * NUM_CONSECUTIVE_LEADER_SLOTS + 1);
+        assert_eq!(predictor.slot(), 2 * NUM_CONSECUTIVE_LEADER_SLOTS + 1);
+        // The cycle wraps around
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(4),
+            vec![leaders[1], leaders[2], leaders[0], leaders[1]]
+        );
+    }
+
+    #[test]
+    fn it_should_predict_until_slot_collapsing_leader_runs() {
+        let leaders = leaders(2);
+        // Leader 0 holds two consecutive rotations of each cycle.
+        let cycle = vec![leaders[0], leaders[0], leaders[1]];
+        let predictor = StaticLeaderPredictor::with_slots_per_leader(cycle, 5, 4);
+
+        assert_eq!(
+            predictor.predict_until_slot(13),
+            vec![
+                (leaders[0], 0..8),
+                (leaders[1], 8..12),
+                (leaders[0], 12..16)
+            ]
+        );
+        assert_eq!(
+            predictor.predict_until_slot(12),
+            predictor.predict_until_slot(13)
+        );
+        assert_eq!(
+            predictor.predict_until_slot(11),
+            vec![(leaders[0], 0..8), (leaders[1], 8..12)]
+        );
+        assert!(IgnorantLeaderPredictor.predict_until_slot(100).is_empty());
+    }
+
+    #[test]
+    fn it_should_honor_custom_rotation_width() {
+        let leaders = leaders(4);
+        let predictor = StaticLeaderPredictor::with_slots_per_leader(leaders.clone(), 5, 2);
+        assert_eq!(predictor.leader_at_slot(5), Some(leaders[2]));
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(3),
+            vec![leaders[1], leaders[2], leaders[3]]
+        );
+
+        let disabled = StaticLeaderPredictor::with_slots_per_leader(leaders, 5, 0);
+        assert!(disabled.try_predict_next_n_leaders(3).is_empty());
+        assert!(
+            StaticLeaderPredictor::new(Vec::new(), 5)
+                .try_predict_next_n_leaders(3)
+                .is_empty()
+        );
+    }
+}
+
+#[cfg(test)]
+mod fallback_predictor_test {
+    use {
+        super::{
+            FallbackPredictor, IgnorantLeaderPredictor, StaticLeaderPredictor,
+            UpcomingLeaderPredictor,
+        },
+        solana_pubkey::Pubkey,
+    };
+
+    struct FixedPredictor(Vec<Pubkey>);
+
+    impl UpcomingLeaderPredictor for FixedPredictor {
+        fn try_predict_next_n_leaders(&self, n: usize) -> Vec<Pubkey> {
+            self.0.iter().take(n).copied().collect()
+        }
+    }
+
+    #[test]
+    fn it_should_fill_window_from_secondary_when_primary_is_empty() {
+        let leaders = (0..8).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let secondary = StaticLeaderPredictor::new(leaders.clone(), 13);
+        let predictor = FallbackPredictor::new(IgnorantLeaderPredictor, secondary);
+
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(5),
+            leaders[2..7].to_vec()
+        );
+        // The secondary upcoming leaders start at its current leader.
+        assert_eq!(
+            predictor.try_predict_upcoming_leaders(5),
+            leaders[3..8].to_vec()
+        );
+    }
+
+    #[test]
+    fn it_should_prefer_primary_and_dedup_secondary() {
+        let [a, b, c, d] = std::array::from_fn(|_| Pubkey::new_unique());
+
+        // Primary fills the window, the secondary is not used
+        let predictor =
+            FallbackPredictor::new(FixedPredictor(vec![a, b, c]), FixedPredictor(vec![d]));
+        assert_eq!(predictor.try_predict_next_n_leaders(3), vec![a, b, c]);
+
+        // Primary is short, the secondary leaders are appended without duplicates
+        let predictor =
+            FallbackPredictor::new(FixedPredictor(vec![b, a]), FixedPredictor(vec![a, c, b, d]));
+        assert_eq!(predictor.try_predict_next_n_leaders(3), vec![b, a, c]);
+        assert_eq!(predictor.try_predict_next_n_leaders(10), vec![b, a, c, d]);
+    }
+}
+
+#[cfg(test)]
+mod transmission_mode_test {
+    use {
+        super::{
+            QuicClientCertificate, TransmissionMode, send_with_transmission_mode,
+            socket_addr_to_quic_server_name, tpu_client_config,
+        },
+        crate::{
+            config::CongestionControl, warm_connection::tests::spawn_test_server_with_transport,
+        },
+        quinn::{Connection, Endpoint, TransportConfig},
+        solana_keypair::Keypair,
+        solana_quic_definitions::QUIC_KEEP_ALIVE,
+        solana_tls_utils::new_dummy_x509_certificate,
+        std::{net::SocketAddr, time::Duration},
+    };
+
+    ///
+    /// Connects a client to a local server built with `transport_config`, returns the client and server sides.
+    ///
+    async fn connect(transport_config: TransportConfig) -> (Connection, Connection) {
+        let (server_addr, mut server_connections) =
+            spawn_test_server_with_transport(transport_config);
+        let (certificate, key) = new_dummy_x509_certificate(&Keypair::new());
+        let client_config = tpu_client_config(
+            &QuicClientCertificate { certificate, key },
+            Duration::from_secs(10),
+            QUIC_KEEP_ALIVE,
+            CongestionControl::default(),
+        );
+        let endpoint = Endpoint::client(SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+        let conn = endpoint
+            .connect_with(
+                client_config,
+                server_addr,
+                &socket_addr_to_quic_server_name(server_addr),
+            )
+            .unwrap()
+            .await
+            .expect("connect");
+        let server_conn = server_connections.recv().await.expect("server connection");
+        (conn, server_conn)
+    }
+
+    #[tokio::test]
+    async fn it_should_send_datagrams_when_supported() {
+        let (conn, server_conn) = connect(TransportConfig::default()).await;
+        let used = send_with_transmission_mode(&conn, b"txn", TransmissionMode::Datagram)
+            .await
+            .expect("send");
+        assert_eq!(used, TransmissionMode::Datagram);
+        assert_eq!(
+            server_conn
+                .read_datagram()
+                .await
+                .expect("datagram")
+                .as_ref(),
+            b"txn"
+        );
+
+        // Too large for a datagram.
+        let max_datagram_size = conn.max_datagram_size().expect("datagrams supported");
+        let large = vec![1u8; max_datagram_size + 1];
+        let used = send_with_transmission_mode(&conn, &large, TransmissionMode::Datagram)
+            .await
+            .expect("send");
+        assert_eq!(used, TransmissionMode::Stream);
+        let mut stream = server_conn.accept_uni().await.expect("stream");
+        assert_eq!(stream.read_to_end(usize::MAX).await.expect("read"), large);
+    }
+
+    #[tokio::test]
+    async fn it_should_fall_back_to_a_stream_when_datagrams_are_unsupported() {
+        let mut transport_config = TransportConfig::default();
+        transport_config.datagram_receive_buffer_size(None);
+        let (conn, server_conn) = connect(transport_config).await;
+        assert_eq!(conn.max_datagram_size(), None);
+
+        let used = send_with_transmission_mode(&conn, b"txn", TransmissionMode::Datagram)
+            .await
+            .expect("send");
+        assert_eq!(used, TransmissionMode::Stream);
+        let mut stream = server_conn.accept_uni().await.expect("stream");
+        assert_eq!(stream.read_to_end(1024).await.expect("read"), b"txn");
+
+        // Streams are used as is.
+        let used = send_with_transmission_mode(&conn, b"txn", TransmissionMode::Stream)
+            .await
+            .expect("send");
+        assert_eq!(used, TransmissionMode::Stream);
+    }
 }
@@ -0,0 +1,209 @@
+//!
+//! Transaction deduplication cache.
+//!
+//! Multiple callers can submit the same signed transaction (e.g. RPC client retries), which ends up
+//! opening redundant streams to the leader.
+//! [`DedupCache`] remembers the signatures it has seen and the slot they were first seen at, so the send path
+//! can skip signatures already sent within the TTL window.
+//!
+//! # Expiry
+//!
+//! The TTL is expressed in slots and defaults to one blockhash lifetime ([`DEFAULT_DEDUP_TTL_SLOTS`]).
+//! Once a signature is older than the TTL its blockhash has expired, the entry is dropped and the signature can be sent again.
+//!
+#[cfg(feature = "prometheus")]
+use crate::prom;
+use {
+    solana_clock::{MAX_PROCESSING_AGE, Slot},
+    solana_signature::Signature,
+    std::{
+        collections::{HashMap, VecDeque},
+        sync::{Arc, Mutex},
+    },
+};
+
+///
+/// Default TTL of a [`DedupCache`] entry: one blockhash lifetime.
+///
+pub const DEFAULT_DEDUP_TTL_SLOTS: u64 = MAX_PROCESSING_AGE as u64;
+
+#[derive(Debug, Default)]
+struct DedupCacheInner {
+    /// Slot at which each signature was first seen.
+    seen: HashMap<Signature, Slot>,
+    /// Signatures in insertion order, used to expire entries without scanning `seen`.
+    insertion_order: VecDeque<(Slot, Signature)>,
+    /// Highest slot observed by the cache.
+    latest_slot: Slot,
+}
+
+impl DedupCacheInner {
+    fn is_live(&self, seen_at: Slot, ttl_slots: u64) -> bool {
+        self.latest_slot.saturating_sub(seen_at) < ttl_slots
+    }
+
+    fn expire(&mut self, ttl_slots: u64) {
+        while let Some((seen_at, sig)) = self.insertion_order.front().copied() {
+            if self.is_live(seen_at, ttl_slots) {
+                break;
+            }
+            self.insertion_order.pop_front();
+            // The signature may have been re-inserted after a previous expiry, only drop the matching entry.
+            if self.seen.get(&sig) == Some(&seen_at) {
+                self.seen.remove(&sig);
+            }
+        }
+    }
+}
+
+///
+/// Signature-keyed cache used to suppress re-sending identical transactions.
+///
+/// Cloning a [`DedupCache`] shares the underlying entries.
+///
+#[derive(Debug, Clone)]
+pub struct DedupCache {
+    inner: Arc<Mutex<DedupCacheInner>>,
+    ttl_slots: u64,
+}
+
+impl Default for DedupCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEDUP_TTL_SLOTS)
+    }
+}
+
+impl DedupCache {
+    ///
+    /// Creates an empty cache whose entries live for `ttl_slots` slots.
+    ///
+    pub fn new(ttl_slots: u64) -> Self {
+        Self {
+            inner: Default::default(),
+            ttl_slots,
+        }
+    }
+
+    ///
+    /// Returns the TTL of the cache entries, in slots.
+    ///
+    pub const fn ttl_slots(&self) -> u64 {
+        self.ttl_slots
+    }
+
+    ///
+    /// Records `sig` as seen at `slot`.
+    ///
+    /// Returns `true` if `sig` was already seen within the TTL window, in which case the send should be skipped.
+    /// An expired signature is recorded again and `false` is returned.
+    ///
+    pub fn check_and_insert(&self, sig: Signature, slot: Slot) -> bool {
+        let mut inner = self.inner.lock().expect("dedup cache lock");
+        inner.latest_slot = inner.latest_slot.max(slot);
+        inner.expire(self.ttl_slots);
+
+        if inner.seen.contains_key(&sig) {
+            #[cfg(feature = "prometheus")]
+            {
+                prom::incr_dedup_cache_hit_cnt();
+            }
+            return true;
+        }
+        inner.seen.insert(sig, slot);
+        inner.insertion_order.push_back((slot, sig));
+        false
+    }
+
+    ///
+    /// Forgets `sig`, e.g. when its send failed after [`DedupCache::check_and_insert`], so it can be sent again.
+    ///
+    /// Returns `true` if `sig` was tracked.
+    ///
+    pub fn remove(&self, sig: &Signature) -> bool {
+        // The stale `insertion_order` entry is skipped once it expires, see `DedupCacheInner::expire`.
+        self.inner
+            .lock()
+            .expect("dedup cache lock")
+            .seen
+            .remove(sig)
+            .is_some()
+    }
+
+    ///
+    /// Returns `true` if `sig` was seen within the TTL window, relative to the highest slot observed by the cache.
+    ///
+    pub fn contains(&self, sig: &Signature) -> bool {
+        let inner = self.inner.lock().expect("dedup cache lock");
+        inner
+            .seen
+            .get(sig)
+            .is_some_and(|seen_at| inner.is_live(*seen_at, self.ttl_slots))
+    }
+
+    ///
+    /// Returns the number of signatures currently tracked.
+    ///
+    pub fn len(&self) -> usize {
+        self.inner.lock().expect("dedup cache lock").seen.len()
+    }
+
+    ///
+    /// Returns `true` if no signature is currently tracked.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_suppress_duplicates_within_ttl() {
+        let cache = DedupCache::new(10);
+        let sig = Signature::new_unique();
+
+        assert!(!cache.check_and_insert(sig, 100));
+        assert!(cache.contains(&sig));
+        // Second send within the TTL is suppressed
+        assert!(cache.check_and_insert(sig, 109));
+
+        // Once the blockhash lifetime is over, the signature can be sent again
+        assert!(!cache.check_and_insert(sig, 110));
+        assert!(cache.contains(&sig));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn it_should_expire_old_signatures() {
+        let cache = DedupCache::new(10);
+        let sig1 = Signature::new_unique();
+        let sig2 = Signature::new_unique();
+
+        assert!(!cache.check_and_insert(sig1, 100));
+        assert!(!cache.check_and_insert(sig2, 105));
+        assert_eq!(cache.len(), 2);
+
+        assert!(!cache.check_and_insert(Signature::new_unique(), 112));
+        assert!(!cache.contains(&sig1));
+        assert!(cache.contains(&sig2));
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn it_should_send_again_a_removed_signature() {
+        let cache = DedupCache::new(10);
+        let sig = Signature::new_unique();
+
+        assert!(!cache.check_and_insert(sig, 100));
+        assert!(cache.remove(&sig));
+        assert!(!cache.remove(&sig));
+        assert!(!cache.contains(&sig));
+        assert!(!cache.check_and_insert(sig, 101));
+
+        // The entry recorded again is not expired by the removed one.
+        assert!(cache.check_and_insert(sig, 110));
+        assert!(!cache.check_and_insert(sig, 111));
+    }
+}
@@ -0,0 +1,1358 @@
+//!
+//! Forwards transactions to the current leader over warm connections.
+//!
+//! # Retries
+//!
+//! A single QUIC send can fail transiently, for instance when a connection is reset during a leader transition.
+//! [`LeaderForwarder::forward`] retries failed sends according to a [`RetryPolicy`]: up to
+//! [`RetryPolicy::max_retries`] retries with an exponential backoff starting at [`RetryPolicy::base_delay`],
+//! capped at [`RetryPolicy::max_delay`] and randomized by a jitter.
+//!
+//! The current leader is re-resolved before each attempt, since the leader may have rotated in between.
+//!
+//! # Blockhash expiry
+//!
+//! A transaction can never land once its blockhash has expired. When the caller provides the last slot at which
+//! the blockhash is still valid, retries stop as soon as the [`AtomicSlotTracker`] goes past it.
+//!
+//! # Timeouts and cancellation
+//!
+//! An unresponsive leader may never grant stream credit or acknowledge the written data, which would tie up the task
+//! and the stream indefinitely. Each attempt, from opening the stream to writing the transaction, is bounded by
+//! [`LeaderForwarder::with_send_timeout`] (default [`DEFAULT_FORWARD_SEND_TIMEOUT`]): an attempt past it fails with
+//! [`ForwardAttemptError::Timeout`], and is retried like any other failure.
+//!
+//! [`LeaderForwarder::forward_with_cancellation`] also honors a caller-supplied [`CancellationToken`], failing with
+//! [`ForwardError::Cancelled`] without further retries.
+//!
+//! A stream interrupted by a timeout or a cancellation is reset, so the leader drops the partial transaction and
+//! the stream resources are freed right away.
+//!
+//! # Fan-out
+//!
+//! [`LeaderForwarder::send_to_upcoming_leaders`] sends a transaction to the current and next leaders at once
+//! to maximize its landing probability. It returns as soon as one send succeeds, while the other sends keep
+//! running in the background, see [`UpcomingLeadersSend`].
+//!
+//! # Send outcomes
+//!
+//! [`LeaderForwarder::submit`] starts the same fan-out in the background and returns right away a receiver of the
+//! [`SendOutcome`] of the transaction, keyed by its signature: the result of the send to each leader, reported once
+//! all of them completed. The outcome is reported even if every send failed, so callers can decide upstream whether
+//! to retry.
+//!
+//! # Send history
+//!
+//! [`LeaderForwarder::with_send_history`] retains the recent sends, their targets and outcomes, in a bounded
+//! [`SendHistory`], see [`LeaderForwarder::recent_sends`].
+//!
+//! # Tracing
+//!
+//! Each forwarded transaction is covered by a `forward` span carrying the transaction signature and the optional
+//! caller-supplied request id, see [`LeaderForwarder::forward_with_request_id`].
+//! Each attempt opens a `forward_attempt` span, nesting a `predict_leader` span and a `send` span for the target leader,
+//! itself nesting the `open_stream` span. [`LeaderForwarder::send_to_upcoming_leaders`] opens a `send` span per leader
+//! under its own `send_to_upcoming_leaders` span.
+//!
+//! These spans can be exported by any `tracing` layer, e.g. an OpenTelemetry one.
+//!
+#[cfg(feature = "prometheus")]
+use crate::prom;
+use {
+    crate::{
+        blockhash::signature_of,
+        core::UpcomingLeaderPredictor,
+        send_history::{SendHistory, SendRecord, SendRecorder},
+        slot::AtomicSlotTracker,
+        warm_connection::{OpenStreamError, SendError, WarmConnectionManager},
+    },
+    bytes::Bytes,
+    rand::Rng,
+    serde::Deserialize,
+    solana_clock::Slot,
+    solana_pubkey::Pubkey,
+    solana_signature::Signature,
+    std::{collections::HashSet, future::Future, sync::Arc, time::Duration},
+    tokio::{sync::oneshot, task::JoinSet, time::timeout_at},
+    tokio_util::sync::CancellationToken,
+    tracing::{Instrument, field},
+};
+
+pub const DEFAULT_FORWARD_MAX_RETRIES: usize = 3;
+pub const DEFAULT_FORWARD_RETRY_BASE_DELAY: Duration = Duration::from_millis(50);
+pub const DEFAULT_FORWARD_RETRY_MAX_DELAY: Duration = Duration::from_secs(1);
+pub const DEFAULT_FORWARD_SEND_TIMEOUT: Duration = Duration::from_secs(2);
+
+///
+/// Retry policy of [`LeaderForwarder::forward`].
+///
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct RetryPolicy {
+    ///
+    /// Maximum number of retries after the first attempt.
+    ///
+    #[serde(default = "RetryPolicy::default_max_retries")]
+    pub max_retries: usize,
+
+    ///
+    /// Delay before the first retry, doubled on each subsequent retry.
+    ///
+    #[serde(default = "RetryPolicy::default_base_delay", with = "humantime_serde")]
+    pub base_delay: Duration,
+
+    ///
+    /// Upper bound of the delay between two attempts.
+    ///
+    #[serde(default = "RetryPolicy::default_max_delay", with = "humantime_serde")]
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub const fn default_max_retries() -> usize {
+        DEFAULT_FORWARD_MAX_RETRIES
+    }
+
+    pub const fn default_base_delay() -> Duration {
+        DEFAULT_FORWARD_RETRY_BASE_DELAY
+    }
+
+    pub const fn default_max_delay() -> Duration {
+        DEFAULT_FORWARD_RETRY_MAX_DELAY
+    }
+
+    ///
+    /// Delay before the `retry`-th retry, starting at 1.
+    ///
+    /// The exponential delay is randomized between half and all of its value, so concurrent senders
+    /// failing at the same time do not retry in lockstep.
+    ///
+    pub fn backoff(&self, retry: usize) -> Duration {
+        jittered_exponential_backoff(self.base_delay, self.max_delay, retry)
+    }
+}
+
+///
+/// Exponential backoff delay before the `retry`-th retry, starting at 1, randomized between half and all of its value.
+///
+pub(crate) fn jittered_exponential_backoff(
+    base_delay: Duration,
+    max_delay: Duration,
+    retry: usize,
+) -> Duration {
+    let exp = retry.saturating_sub(1).min(31) as u32;
+    let delay = base_delay.saturating_mul(1 << exp).min(max_delay);
+    let half = delay / 2;
+    let jitter_nanos = rand::rng().random_range(0..=half.as_nanos() as u64);
+    half + Duration::from_nanos(jitter_nanos)
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: Self::default_max_retries(),
+            base_delay: Self::default_base_delay(),
+            max_delay: Self::default_max_delay(),
+        }
+    }
+}
+
+///
+/// Error of a single forwarding attempt.
+///
+#[derive(Debug, thiserror::Error)]
+pub enum ForwardAttemptError {
+    ///
+    /// No leader is known for the current slot.
+    ///
+    #[error("unknown current leader")]
+    UnknownLeader,
+    ///
+    /// No stream could be opened to the leader.
+    ///
+    #[error(transparent)]
+    OpenStream(#[from] OpenStreamError),
+    ///
+    /// Too many sends are already queued to the leader, see
+    /// [`crate::warm_connection::WarmConnectionManagerConfig::max_queued_sends_per_leader`].
+    ///
+    #[error("too many queued sends to leader {0}")]
+    LeaderBusy(Pubkey),
+    ///
+    /// Writing the transaction to the stream failed.
+    ///
+    #[error(transparent)]
+    Write(#[from] quinn::WriteError),
+    ///
+    /// The stream was closed before it could be finished.
+    ///
+    #[error(transparent)]
+    ClosedStream(#[from] quinn::ClosedStream),
+    ///
+    /// The attempt did not complete within the send timeout, the stream was reset.
+    ///
+    #[error("send timed out after {0:?}")]
+    Timeout(Duration),
+    ///
+    /// The caller cancelled the send, the stream was reset.
+    ///
+    #[error("send cancelled")]
+    Cancelled,
+}
+
+impl From<SendError> for ForwardAttemptError {
+    fn from(e: SendError) -> Self {
+        match e {
+            SendError::LeaderBusy(leader) => Self::LeaderBusy(leader),
+            SendError::OpenStream(e) => Self::OpenStream(e),
+            SendError::Write(e) => Self::Write(e),
+            SendError::Finish(e) => Self::ClosedStream(e),
+        }
+    }
+}
+
+///
+/// Error returned by [`LeaderForwarder::forward`].
+///
+#[derive(Debug, thiserror::Error)]
+pub enum ForwardError {
+    ///
+    /// The transaction blockhash expired, retrying is pointless.
+    ///
+    #[error("blockhash expired after {attempts} attempt(s)")]
+    BlockhashExpired { attempts: usize },
+    ///
+    /// Every attempt failed.
+    ///
+    #[error("forwarding failed after {attempts} attempt(s): {last_error}")]
+    RetriesExhausted {
+        attempts: usize,
+        last_error: ForwardAttemptError,
+    },
+    ///
+    /// The caller cancelled the forwarding, see [`LeaderForwarder::forward_with_cancellation`].
+    ///
+    #[error("forwarding cancelled after {attempts} attempt(s)")]
+    Cancelled { attempts: usize },
+}
+
+///
+/// Error returned by [`LeaderForwarder::submit`].
+///
+#[derive(Debug, thiserror::Error)]
+pub enum SubmitError {
+    ///
+    /// The transaction is malformed or unsigned, its outcome could not be keyed by signature.
+    ///
+    #[error("missing transaction signature")]
+    MissingSignature,
+}
+
+///
+/// Successful outcome of [`LeaderForwarder::forward`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Forwarded {
+    ///
+    /// Leader the transaction was sent to.
+    ///
+    pub leader: Pubkey,
+    ///
+    /// Number of attempts, including the successful one.
+    ///
+    pub attempts: usize,
+}
+
+///
+/// Outcome of sending a transaction to a single leader.
+///
+pub type LeaderSendResult = (Pubkey, Result<(), ForwardAttemptError>);
+
+///
+/// Sends started by [`LeaderForwarder::send_to_upcoming_leaders`].
+///
+/// Dropping this object does not cancel the pending sends, they complete in the background.
+///
+pub struct UpcomingLeadersSend {
+    ///
+    /// Results of the sends completed so far, in completion order.
+    ///
+    pub completed: Vec<LeaderSendResult>,
+    pending: JoinSet<LeaderSendResult>,
+}
+
+impl UpcomingLeadersSend {
+    ///
+    /// Returns the first leader the transaction was successfully sent to, if any.
+    ///
+    pub fn first_success(&self) -> Option<Pubkey> {
+        self.completed
+            .iter()
+            .find(|(_, result)| result.is_ok())
+            .map(|(leader, _)| *leader)
+    }
+
+    ///
+    /// Number of sends still in flight.
+    ///
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    ///
+    /// Waits for every pending send and returns the results of all leaders.
+    ///
+    pub async fn wait_all(mut self) -> Vec<LeaderSendResult> {
+        let mut completed = std::mem::take(&mut self.completed);
+        while let Some(result) = self.pending.join_next().await {
+            match result {
+                Ok(leader_result) => completed.push(leader_result),
+                Err(e) => tracing::error!("upcoming leader send task failed: {e}"),
+            }
+        }
+        completed
+    }
+}
+
+///
+/// Outcome of a transaction submitted through [`LeaderForwarder::submit`].
+///
+#[derive(Debug)]
+pub struct SendOutcome {
+    ///
+    /// Signature of the transaction.
+    ///
+    pub signature: Signature,
+    ///
+    /// Result of the send to each leader, in completion order. Empty if no leader was predicted.
+    ///
+    pub per_leader: Vec<LeaderSendResult>,
+}
+
+impl SendOutcome {
+    ///
+    /// Returns the first leader the transaction was successfully sent to, if any.
+    ///
+    pub fn first_success(&self) -> Option<Pubkey> {
+        self.per_leader
+            .iter()
+            .find(|(_, result)| result.is_ok())
+            .map(|(leader, _)| *leader)
+    }
+}
+
+impl Drop for UpcomingLeadersSend {
+    fn drop(&mut self) {
+        self.pending.detach_all();
+    }
+}
+
+///
+/// Removes duplicated leaders, keeping the order of their first occurrence.
+///
+/// The same validator can hold consecutive leader slots, a transaction only needs to be sent to it once.
+///
+fn distinct_leaders(leaders: Vec<Pubkey>) -> Vec<Pubkey> {
+    let mut seen = HashSet::with_capacity(leaders.len());
+    leaders
+        .into_iter()
+        .filter(|leader| seen.insert(*leader))
+        .collect()
+}
+
+///
+/// Sends `txn` on a new stream to `leader`, within `send_timeout` unless `cancellation` is cancelled first.
+///
+/// Waiting for a send slot to the leader, see [`WarmConnectionManager::acquire_send_permit`], counts against the
+/// timeout. The stream is reset if the write times out or is cancelled.
+///
+async fn send_to(
+    warm_connections: &WarmConnectionManager,
+    leader: &Pubkey,
+    txn: &[u8],
+    send_timeout: Duration,
+    cancellation: &CancellationToken,
+) -> Result<(), ForwardAttemptError> {
+    async {
+        let deadline = tokio::time::Instant::now() + send_timeout;
+        let open_stream = async {
+            let permit = warm_connections.acquire_send_permit(leader).await?;
+            let stream = warm_connections.open_uni(leader).await?;
+            Ok::<_, ForwardAttemptError>((permit, stream))
+        }
+        .instrument(tracing::debug_span!("open_stream"));
+        let (_permit, mut stream) = tokio::select! {
+            result = timeout_at(deadline, open_stream) => match result {
+                Ok(result) => result?,
+                Err(_) => return Err(send_timed_out(leader, send_timeout)),
+            },
+            _ = cancellation.cancelled() => return Err(ForwardAttemptError::Cancelled),
+        };
+        let written = tokio::select! {
+            result = timeout_at(deadline, stream.write_all(txn)) => match result {
+                Ok(result) => result.map_err(ForwardAttemptError::from),
+                Err(_) => Err(send_timed_out(leader, send_timeout)),
+            },
+            _ = cancellation.cancelled() => Err(ForwardAttemptError::Cancelled),
+        };
+        if let Err(e) = written {
+            if matches!(
+                e,
+                ForwardAttemptError::Timeout(_) | ForwardAttemptError::Cancelled
+            ) {
+                // Dropping the stream would finish it with a truncated transaction.
+                let _ = stream.reset(0u32.into());
+            }
+            return Err(e);
+        }
+        stream.finish()?;
+        Ok(())
+    }
+    .instrument(tracing::debug_span!("send", leader = %leader))
+    .await
+}
+
+fn send_timed_out(leader: &Pubkey, send_timeout: Duration) -> ForwardAttemptError {
+    tracing::debug!("send to leader {leader} timed out after {send_timeout:?}");
+    #[cfg(feature = "prometheus")]
+    {
+        prom::incr_forward_send_timeout_cnt();
+    }
+    ForwardAttemptError::Timeout(send_timeout)
+}
+
+///
+/// Runs `attempt_fn` until it succeeds, `is_expired` returns true, `cancellation` is cancelled or the retries of
+/// `policy` are exhausted.
+///
+/// `attempt_fn` receives the attempt number, starting at 1.
+///
+pub(crate) async fn retry_with_backoff<T, F, Fut>(
+    policy: &RetryPolicy,
+    is_expired: impl Fn() -> bool,
+    cancellation: &CancellationToken,
+    mut attempt_fn: F,
+) -> Result<(T, usize), ForwardError>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<T, ForwardAttemptError>>,
+{
+    let cancelled = |attempts| {
+        #[cfg(feature = "prometheus")]
+        {
+            prom::incr_forward_txn_outcome("cancelled");
+        }
+        ForwardError::Cancelled { attempts }
+    };
+    let mut attempt = 0;
+    loop {
+        if cancellation.is_cancelled() {
+            return Err(cancelled(attempt));
+        }
+        if is_expired() {
+            #[cfg(feature = "prometheus")]
+            {
+                prom::incr_forward_txn_outcome("expired");
+            }
+            return Err(ForwardError::BlockhashExpired { attempts: attempt });
+        }
+        attempt += 1;
+        match attempt_fn(attempt).await {
+            Ok(value) => {
+                #[cfg(feature = "prometheus")]
+                {
+                    prom::incr_forward_txn_outcome("landed");
+                }
+                return Ok((value, attempt));
+            }
+            Err(ForwardAttemptError::Cancelled) => return Err(cancelled(attempt)),
+            Err(e) if attempt > policy.max_retries => {
+                #[cfg(feature = "prometheus")]
+                {
+                    prom::incr_forward_txn_outcome("failed");
+                }
+                return Err(ForwardError::RetriesExhausted {
+                    attempts: attempt,
+                    last_error: e,
+                });
+            }
+            Err(e) => {
+                let delay = policy.backoff(attempt);
+                tracing::debug!("forwarding attempt {attempt} failed: {e}, retrying in {delay:?}");
+                #[cfg(feature = "prometheus")]
+                {
+                    prom::incr_forward_txn_outcome("retried");
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = cancellation.cancelled() => return Err(cancelled(attempt)),
+                }
+            }
+        }
+    }
+}
+
+///
+/// Sends transactions directly to the current leader using a [`WarmConnectionManager`].
+///
+/// # Clone
+///
+/// Cheap to clone, clones share the same warm connections.
+///
+#[derive(Clone)]
+pub struct LeaderForwarder {
+    warm_connections: WarmConnectionManager,
+    leader_predictor: Arc<dyn UpcomingLeaderPredictor + Send + Sync + 'static>,
+    slot_tracker: Arc<AtomicSlotTracker>,
+    retry_policy: RetryPolicy,
+    send_timeout: Duration,
+    send_history: Option<Arc<SendHistory>>,
+}
+
+impl LeaderForwarder {
+    ///
+    /// Creates a new forwarder.
+    ///
+    /// `leader_predictor` is used to resolve the current leader, it should be the same predictor the
+    /// `warm_connections` manager was spawned with.
+    ///
+    pub fn new(
+        warm_connections: WarmConnectionManager,
+        leader_predictor: Arc<dyn UpcomingLeaderPredictor + Send + Sync + 'static>,
+        slot_tracker: Arc<AtomicSlotTracker>,
+        retry_policy: RetryPolicy,
+    ) -> Self {
+        Self {
+            warm_connections,
+            leader_predictor,
+            slot_tracker,
+            retry_policy,
+            send_timeout: DEFAULT_FORWARD_SEND_TIMEOUT,
+            send_history: None,
+        }
+    }
+
+    ///
+    /// Sets the timeout of each send attempt, defaults to [`DEFAULT_FORWARD_SEND_TIMEOUT`].
+    ///
+    /// See the [module documentation](crate::forwarder#timeouts-and-cancellation).
+    ///
+    pub fn with_send_timeout(mut self, send_timeout: Duration) -> Self {
+        self.send_timeout = send_timeout;
+        self
+    }
+
+    ///
+    /// Records every send in `send_history`, which may be shared with other forwarders.
+    ///
+    /// See the [module documentation](crate::forwarder#send-history).
+    ///
+    pub fn with_send_history(mut self, send_history: Arc<SendHistory>) -> Self {
+        self.send_history = Some(send_history);
+        self
+    }
+
+    ///
+    /// Returns up to `limit` of the sends retained by the send history, most recent first.
+    ///
+    /// Always empty without a send history, see [`LeaderForwarder::with_send_history`].
+    ///
+    pub fn recent_sends(&self, limit: usize) -> Vec<SendRecord> {
+        self.send_history
+            .as_ref()
+            .map(|send_history| send_history.recent(limit))
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Sends `txn` to the current leader, retrying on failure.
+    ///
+    /// # Arguments
+    ///
+    /// * `txn` - The bincoded transaction.
+    /// * `last_valid_slot` - (Optional) last slot at which the transaction blockhash is valid, retries stop once the
+    ///   current slot goes past it. When `None`, retries only stop when exhausted.
+    ///
+    pub async fn forward(
+        &self,
+        txn: &[u8],
+        last_valid_slot: Option<Slot>,
+    ) -> Result<Forwarded, ForwardError> {
+        self.forward_with_request_id(txn, last_valid_slot, None)
+            .await
+    }
+
+    ///
+    /// Same as [`LeaderForwarder::forward`], `request_id` is recorded on the `forward` span to correlate
+    /// the send with the caller request.
+    ///
+    pub async fn forward_with_request_id(
+        &self,
+        txn: &[u8],
+        last_valid_slot: Option<Slot>,
+        request_id: Option<&str>,
+    ) -> Result<Forwarded, ForwardError> {
+        self.forward_with_cancellation(txn, last_valid_slot, request_id, &CancellationToken::new())
+            .await
+    }
+
+    ///
+    /// Same as [`LeaderForwarder::forward_with_request_id`], stopping with [`ForwardError::Cancelled`] as soon as
+    /// `cancellation` is cancelled.
+    ///
+    /// An in-flight stream is reset on cancellation.
+    ///
+    pub async fn forward_with_cancellation(
+        &self,
+        txn: &[u8],
+        last_valid_slot: Option<Slot>,
+        request_id: Option<&str>,
+        cancellation: &CancellationToken,
+    ) -> Result<Forwarded, ForwardError> {
+        let span = tracing::info_span!(
+            "forward",
+            signature = field::Empty,
+            request_id,
+            last_valid_slot
+        );
+        let signature = signature_of(txn);
+        if let Some(signature) = signature {
+            span.record("signature", field::display(signature));
+        }
+        let recorder = self
+            .send_history
+            .as_ref()
+            .map(|send_history| send_history.start(signature, &[]));
+        let is_expired = || {
+            last_valid_slot
+                .is_some_and(|last_valid_slot| self.slot_tracker.load() > last_valid_slot)
+        };
+        async {
+            let (leader, attempts) =
+                retry_with_backoff(&self.retry_policy, is_expired, cancellation, |attempt| {
+                    self.send_once(txn, cancellation, recorder.as_ref())
+                        .instrument(tracing::debug_span!("forward_attempt", attempt))
+                })
+                .await?;
+            Ok(Forwarded { leader, attempts })
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn send_once(
+        &self,
+        txn: &[u8],
+        cancellation: &CancellationToken,
+        recorder: Option<&SendRecorder>,
+    ) -> Result<Pubkey, ForwardAttemptError> {
+        let leader = tracing::debug_span!(
+            "predict_leader",
+            slot = self.slot_tracker.load(),
+            leader = field::Empty
+        )
+        .in_scope(|| {
+            let leader = self
+                .leader_predictor
+                .try_predict_upcoming_leaders(1)
+                .first()
+                .copied();
+            if let Some(leader) = leader {
+                tracing::Span::current().record("leader", field::display(leader));
+            }
+            leader
+        })
+        .ok_or(ForwardAttemptError::UnknownLeader)?;
+        let result = send_to(
+            &self.warm_connections,
+            &leader,
+            txn,
+            self.send_timeout,
+            cancellation,
+        )
+        .await;
+        if let Some(recorder) = recorder {
+            recorder.record(leader, &result);
+        }
+        result.map(|_| leader)
+    }
+
+    ///
+    /// Sends `txn` concurrently to the current leader and the next `k - 1` predicted leaders, see
+    /// [`UpcomingLeaderPredictor::try_predict_upcoming_leaders`]. Leaders that already rotated out are not sent to.
+    ///
+    /// Duplicated leaders are only sent to once, so fewer than `k` sends may be started.
+    /// Leaders without a warm connection fail with [`OpenStreamError::NotConnected`]. Each send is bounded by
+    /// [`LeaderForwarder::with_send_timeout`].
+    ///
+    /// Returns as soon as one send succeeds or all of them failed, the remaining sends keep running in the background.
+    /// Use [`UpcomingLeadersSend::wait_all`] to await all of them.
+    ///
+    pub async fn send_to_upcoming_leaders(&self, txn: &[u8], k: usize) -> UpcomingLeadersSend {
+        let (_, mut pending) = self.spawn_upcoming_leader_sends(txn, k);
+        let mut completed = Vec::with_capacity(pending.len());
+        while let Some(result) = pending.join_next().await {
+            match result {
+                Ok(leader_result) => {
+                    let is_success = leader_result.1.is_ok();
+                    completed.push(leader_result);
+                    if is_success {
+                        break;
+                    }
+                }
+                Err(e) => tracing::error!("upcoming leader send task failed: {e}"),
+            }
+        }
+        UpcomingLeadersSend { completed, pending }
+    }
+
+    ///
+    /// Sends `txn` concurrently to the `k` upcoming leaders in the background, like
+    /// [`LeaderForwarder::send_to_upcoming_leaders`].
+    ///
+    /// Returns right away a receiver of the [`SendOutcome`] of the transaction, reported once every send completed,
+    /// including when all of them failed or no leader is predicted.
+    ///
+    /// # Errors
+    ///
+    /// [`SubmitError::MissingSignature`] if `txn` is malformed or unsigned, nothing is sent.
+    ///
+    pub fn submit(
+        &self,
+        txn: &[u8],
+        k: usize,
+    ) -> Result<oneshot::Receiver<SendOutcome>, SubmitError> {
+        let signature = signature_of(txn).ok_or(SubmitError::MissingSignature)?;
+        let (span, mut pending) = self.spawn_upcoming_leader_sends(txn, k);
+        let (outcome_tx, outcome_rx) = oneshot::channel();
+        tokio::spawn(
+            async move {
+                let mut per_leader = Vec::with_capacity(pending.len());
+                while let Some(result) = pending.join_next().await {
+                    match result {
+                        Ok(leader_result) => per_leader.push(leader_result),
+                        Err(e) => tracing::error!("upcoming leader send task failed: {e}"),
+                    }
+                }
+                // The caller may have dropped the receiver, no longer interested in the outcome.
+                let _ = outcome_tx.send(SendOutcome {
+                    signature,
+                    per_leader,
+                });
+            }
+            .instrument(span),
+        );
+        Ok(outcome_rx)
+    }
+
+    ///
+    /// Spawns a send of `txn` to each of the `k` distinct upcoming leaders, the current one first, under a new
+    /// `send_to_upcoming_leaders` span.
+    ///
+    fn spawn_upcoming_leader_sends(
+        &self,
+        txn: &[u8],
+        k: usize,
+    ) -> (tracing::Span, JoinSet<LeaderSendResult>) {
+        let span = tracing::info_span!("send_to_upcoming_leaders", signature = field::Empty, k);
+        let signature = signature_of(txn);
+        if let Some(signature) = signature {
+            span.record("signature", field::display(signature));
+        }
+        let leaders = span
+            .in_scope(|| distinct_leaders(self.leader_predictor.try_predict_upcoming_leaders(k)));
+        let recorder = self
+            .send_history
+            .as_ref()
+            .map(|send_history| send_history.start(signature, &leaders));
+        let wire_txn = Bytes::copy_from_slice(txn);
+        let mut pending = JoinSet::new();
+        for leader in leaders {
+            let warm_connections = self.warm_connections.clone();
+            let wire_txn = wire_txn.clone();
+            let send_timeout = self.send_timeout;
+            let recorder = recorder.clone();
+            pending.spawn(
+                async move {
+                    let result = send_to(
+                        &warm_connections,
+                        &leader,
+                        &wire_txn,
+                        send_timeout,
+                        &CancellationToken::new(),
+                    )
+                    .await;
+                    if let Some(recorder) = recorder {
+                        recorder.record(leader, &result);
+                    }
+                    (leader, result)
+                }
+                .instrument(span.clone()),
+            );
+        }
+        (span, pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            core::StaticLeaderPredictor,
+            rpc::{contact::ContactInfoTable, tpu_info::RpcTpuQuicContactInfo},
+            warm_connection::{
+                WarmConnectionManagerConfig, WarmConnectionState, spawn_warm_connection_manager,
+                tests::{spawn_test_server_with_transport, wait_for_state},
+            },
+        },
+        quinn::{Connection, TransportConfig},
+        solana_clock::NUM_CONSECUTIVE_LEADER_SLOTS,
+        std::{cell::Cell, collections::HashMap, sync::Mutex, time::Instant},
+        tokio::sync::watch,
+        tracing_subscriber::{
+            Layer,
+            layer::{Context, SubscriberExt},
+            registry::LookupSpan,
+        },
+    };
+
+    fn fast_policy(max_retries: usize) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        }
+    }
+
+    fn transient_error() -> ForwardAttemptError {
+        ForwardAttemptError::OpenStream(OpenStreamError::NotConnected(Pubkey::new_unique()))
+    }
+
+    #[tokio::test]
+    async fn it_should_succeed_after_transient_failures() {
+        const TRANSIENT_FAILURES: usize = 3;
+        let policy = fast_policy(TRANSIENT_FAILURES);
+
+        let (attempt, attempts) = retry_with_backoff(
+            &policy,
+            || false,
+            &CancellationToken::new(),
+            |attempt| async move {
+                if attempt <= TRANSIENT_FAILURES {
+                    Err(transient_error())
+                } else {
+                    Ok(attempt)
+                }
+            },
+        )
+        .await
+        .expect("retry_with_backoff");
+
+        assert_eq!(attempt, TRANSIENT_FAILURES + 1);
+        assert_eq!(attempts, TRANSIENT_FAILURES + 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_give_up_when_retries_are_exhausted() {
+        let policy = fast_policy(2);
+
+        let result = retry_with_backoff(
+            &policy,
+            || false,
+            &CancellationToken::new(),
+            |_| async { Err::<(), _>(transient_error()) },
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(ForwardError::RetriesExhausted { attempts: 3, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_should_stop_retrying_once_blockhash_expired() {
+        let policy = fast_policy(10);
+        let attempts_made = Cell::new(0);
+
+        let result = retry_with_backoff(
+            &policy,
+            || attempts_made.get() >= 2,
+            &CancellationToken::new(),
+            |_| {
+                attempts_made.set(attempts_made.get() + 1);
+                async { Err::<(), _>(transient_error()) }
+            },
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(ForwardError::BlockhashExpired { attempts: 2 })
+        ));
+    }
+
+    #[test]
+    fn backoff_should_grow_exponentially_and_be_capped() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(350),
+        };
+        for _ in 0..100 {
+            let first = policy.backoff(1);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let third = policy.backoff(3);
+            assert!(third >= Duration::from_millis(175) && third <= Duration::from_millis(350));
+        }
+    }
+
+    #[test]
+    fn distinct_leaders_should_keep_first_occurrences() {
+        let leader1 = Pubkey::new_unique();
+        let leader2 = Pubkey::new_unique();
+        let leader3 = Pubkey::new_unique();
+
+        assert_eq!(
+            distinct_leaders(vec![leader1, leader1, leader2, leader1, leader3, leader3]),
+            vec![leader1, leader2, leader3]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_to_upcoming_leaders_should_return_one_result_per_distinct_leader() {
+        let leader1 = Pubkey::new_unique();
+        let leader2 = Pubkey::new_unique();
+        let predictor = Arc::new(StaticLeaderPredictor::new(
+            vec![leader1, leader1, leader2, leader2],
+            0,
+        ));
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        let (warm_connections, _jh) = spawn_warm_connection_manager(
+            WarmConnectionManagerConfig::default(),
+            slot_rx,
+            Arc::clone(&predictor) as _,
+            Arc::new(ContactInfoTable::default()),
+        )
+        .expect("spawn");
+        let forwarder = LeaderForwarder::new(
+            warm_connections,
+            predictor,
+            Arc::new(AtomicSlotTracker::new(0)),
+            RetryPolicy::default(),
+        );
+
+        let fanout = forwarder.send_to_upcoming_leaders(b"txn", 4).await;
+        // No send can succeed without a warm connection, so the call waited for all of them
+        assert_eq!(fanout.first_success(), None);
+        assert_eq!(fanout.pending_count(), 0);
+
+        let results = fanout.wait_all().await;
+        assert_eq!(results.len(), 2);
+        for (leader, result) in results {
+            assert!(leader == leader1 || leader == leader2);
+            assert!(matches!(
+                result,
+                Err(ForwardAttemptError::OpenStream(
+                    OpenStreamError::NotConnected(_)
+                ))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn send_to_upcoming_leaders_should_start_at_the_current_leader() {
+        let [previous, current, next, after_next] = std::array::from_fn(|_| Pubkey::new_unique());
+        let predictor = Arc::new(StaticLeaderPredictor::new(
+            vec![previous, current, next, after_next],
+            NUM_CONSECUTIVE_LEADER_SLOTS,
+        ));
+        let (_slot_tx, slot_rx) = watch::channel(NUM_CONSECUTIVE_LEADER_SLOTS);
+        let (warm_connections, _jh) = spawn_warm_connection_manager(
+            WarmConnectionManagerConfig::default(),
+            slot_rx,
+            Arc::clone(&predictor) as _,
+            Arc::new(ContactInfoTable::default()),
+        )
+        .expect("spawn");
+        let forwarder = LeaderForwarder::new(
+            warm_connections,
+            predictor,
+            Arc::new(AtomicSlotTracker::new(NUM_CONSECUTIVE_LEADER_SLOTS)),
+            RetryPolicy::default(),
+        );
+
+        let results = forwarder
+            .send_to_upcoming_leaders(b"txn", 2)
+            .await
+            .wait_all()
+            .await;
+        let mut sent_to = results
+            .into_iter()
+            .map(|(leader, _)| leader)
+            .collect::<Vec<_>>();
+        sent_to.sort();
+        let mut expected = vec![current, next];
+        expected.sort();
+        assert_eq!(sent_to, expected);
+    }
+
+    fn signed_txn(signature: Signature) -> Vec<u8> {
+        let mut txn = vec![1];
+        txn.extend_from_slice(signature.as_ref());
+        txn
+    }
+
+    #[tokio::test]
+    async fn submit_should_report_the_outcome_of_each_leader() {
+        let (server_addr, mut server_connections) =
+            spawn_test_server_with_transport(TransportConfig::default());
+        let [reachable, unreachable] = std::array::from_fn(|_| Pubkey::new_unique());
+        let predictor = Arc::new(StaticLeaderPredictor::new(vec![reachable, unreachable], 0));
+        // `unreachable` has no known TPU address, it is never connected.
+        let tpu_info = ContactInfoTable::from_iter([(
+            reachable,
+            RpcTpuQuicContactInfo {
+                tpu_quic: Some(server_addr),
+                tpu_forwards_quic: Some(server_addr),
+            },
+        )]);
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        let (warm_connections, _jh) = spawn_warm_connection_manager(
+            WarmConnectionManagerConfig::default(),
+            slot_rx,
+            Arc::clone(&predictor) as _,
+            Arc::new(tpu_info),
+        )
+        .expect("spawn");
+        wait_for_state(&warm_connections, &reachable, WarmConnectionState::Ready).await;
+        let _server_conn = server_connections.recv().await.expect("server connection");
+        let send_history = Arc::new(SendHistory::default());
+        let forwarder = LeaderForwarder::new(
+            warm_connections.clone(),
+            predictor,
+            Arc::new(AtomicSlotTracker::new(0)),
+            RetryPolicy::default(),
+        )
+        .with_send_history(Arc::clone(&send_history));
+
+        assert!(matches!(
+            forwarder.submit(b"txn", 2),
+            Err(SubmitError::MissingSignature)
+        ));
+
+        let signature = Signature::from([7; 64]);
+        let outcome = forwarder
+            .submit(&signed_txn(signature), 2)
+            .expect("submit")
+            .await
+            .expect("outcome");
+        assert_eq!(outcome.signature, signature);
+        assert_eq!(outcome.first_success(), Some(reachable));
+        let per_leader = outcome.per_leader.into_iter().collect::<HashMap<_, _>>();
+        assert_eq!(per_leader.len(), 2);
+        assert!(per_leader[&reachable].is_ok());
+        assert!(matches!(
+            per_leader[&unreachable],
+            Err(ForwardAttemptError::OpenStream(
+                OpenStreamError::NotConnected(leader)
+            )) if leader == unreachable
+        ));
+
+        // Reported even if every send failed.
+        let unreachable_forwarder = LeaderForwarder::new(
+            warm_connections,
+            Arc::new(StaticLeaderPredictor::new(vec![unreachable], 0)),
+            Arc::new(AtomicSlotTracker::new(0)),
+            RetryPolicy::default(),
+        )
+        .with_send_history(send_history);
+        let outcome = unreachable_forwarder
+            .submit(&signed_txn(signature), 2)
+            .expect("submit")
+            .await
+            .expect("outcome");
+        assert_eq!(outcome.first_success(), None);
+        assert_eq!(outcome.per_leader.len(), 1);
+
+        let recent = forwarder.recent_sends(10);
+        assert_eq!(recent.len(), 2);
+        assert!(
+            recent
+                .iter()
+                .all(|record| record.signature == Some(signature))
+        );
+        assert_eq!(recent[0].targets.len(), 1);
+        assert!(matches!(recent[0].targets[0].outcome, Some(Err(_))));
+        let targets = recent[1]
+            .targets
+            .iter()
+            .map(|target| (target.leader, target.outcome.clone()))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(targets[&reachable], Some(Ok(())));
+        assert!(matches!(targets[&unreachable], Some(Err(_))));
+    }
+
+    ///
+    /// Spawns a leader with the server `transport_config` and a forwarder holding a warm connection to it.
+    ///
+    async fn forwarder_to_leader(
+        transport_config: TransportConfig,
+        send_timeout: Duration,
+    ) -> (LeaderForwarder, Connection) {
+        let (server_addr, mut server_connections) =
+            spawn_test_server_with_transport(transport_config);
+        let leader = Pubkey::new_unique();
+        let predictor = Arc::new(StaticLeaderPredictor::new(vec![leader], 0));
+        let tpu_info = ContactInfoTable::from_iter([(
+            leader,
+            RpcTpuQuicContactInfo {
+                tpu_quic: Some(server_addr),
+                tpu_forwards_quic: Some(server_addr),
+            },
+        )]);
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        let (warm_connections, _jh) = spawn_warm_connection_manager(
+            WarmConnectionManagerConfig::default(),
+            slot_rx,
+            Arc::clone(&predictor) as _,
+            Arc::new(tpu_info),
+        )
+        .expect("spawn");
+        wait_for_state(&warm_connections, &leader, WarmConnectionState::Ready).await;
+        let server_conn = server_connections.recv().await.expect("server connection");
+        let forwarder = LeaderForwarder::new(
+            warm_connections,
+            predictor,
+            Arc::new(AtomicSlotTracker::new(0)),
+            fast_policy(0),
+        )
+        .with_send_timeout(send_timeout);
+        (forwarder, server_conn)
+    }
+
+    #[tokio::test]
+    async fn forward_should_send_to_the_current_leader() {
+        let (server_addr, mut server_connections) =
+            spawn_test_server_with_transport(TransportConfig::default());
+        let [previous, current] = std::array::from_fn(|_| Pubkey::new_unique());
+        // The prediction window starts at `previous`, which has no known TPU address.
+        let predictor = Arc::new(StaticLeaderPredictor::new(
+            vec![previous, current],
+            NUM_CONSECUTIVE_LEADER_SLOTS,
+        ));
+        assert_eq!(predictor.try_predict_next_n_leaders(1), vec![previous]);
+        let tpu_info = ContactInfoTable::from_iter([(
+            current,
+            RpcTpuQuicContactInfo {
+                tpu_quic: Some(server_addr),
+                tpu_forwards_quic: Some(server_addr),
+            },
+        )]);
+        let (_slot_tx, slot_rx) = watch::channel(NUM_CONSECUTIVE_LEADER_SLOTS);
+        let (warm_connections, _jh) = spawn_warm_connection_manager(
+            WarmConnectionManagerConfig::default(),
+            slot_rx,
+            Arc::clone(&predictor) as _,
+            Arc::new(tpu_info),
+        )
+        .expect("spawn");
+        wait_for_state(&warm_connections, &current, WarmConnectionState::Ready).await;
+        let _server_conn = server_connections.recv().await.expect("server connection");
+        let forwarder = LeaderForwarder::new(
+            warm_connections,
+            predictor,
+            Arc::new(AtomicSlotTracker::new(NUM_CONSECUTIVE_LEADER_SLOTS)),
+            fast_policy(0),
+        );
+
+        let forwarded = forwarder.forward(b"txn", None).await.expect("forward");
+        assert_eq!(forwarded.leader, current);
+        assert_eq!(forwarded.attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn forward_should_time_out_and_reset_the_stream_when_the_leader_never_acks() {
+        const SEND_TIMEOUT: Duration = Duration::from_millis(200);
+        // The leader never reads, so it never extends the stream credit past 16 bytes.
+        let mut transport_config = TransportConfig::default();
+        transport_config.stream_receive_window(16u32.into());
+        let (forwarder, server_conn) = forwarder_to_leader(transport_config, SEND_TIMEOUT).await;
+
+        let started = Instant::now();
+        let result = forwarder.forward(&[1u8; 1232], None).await;
+        let elapsed = started.elapsed();
+
+        assert!(
+            matches!(
+                result,
+                Err(ForwardError::RetriesExhausted {
+                    attempts: 1,
+                    last_error: ForwardAttemptError::Timeout(timeout),
+                }) if timeout == SEND_TIMEOUT
+            ),
+            "{result:?}"
+        );
+        assert!(
+            elapsed >= SEND_TIMEOUT && elapsed < SEND_TIMEOUT * 5,
+            "{elapsed:?}"
+        );
+        // The partial transaction is reset rather than finished.
+        let mut recv = server_conn.accept_uni().await.expect("accept stream");
+        assert!(matches!(
+            recv.read_to_end(2048).await,
+            Err(quinn::ReadToEndError::Read(quinn::ReadError::Reset(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn forward_should_stop_when_cancelled() {
+        // The leader never grants stream credit, opening a stream hangs.
+        let mut transport_config = TransportConfig::default();
+        transport_config.max_concurrent_uni_streams(0u32.into());
+        let (forwarder, _server_conn) =
+            forwarder_to_leader(transport_config, Duration::from_secs(60)).await;
+        let cancellation = CancellationToken::new();
+        let cancel = cancellation.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            cancel.cancel();
+        });
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            forwarder.forward_with_cancellation(&[1u8; 64], None, None, &cancellation),
+        )
+        .await
+        .expect("cancelled in time");
+
+        assert!(
+            matches!(result, Err(ForwardError::Cancelled { attempts: 1 })),
+            "{result:?}"
+        );
+    }
+
+    ///
+    /// Layer recording every span with its parent and fields.
+    ///
+    #[derive(Clone, Default)]
+    struct SpanRecorder {
+        spans: Arc<Mutex<HashMap<u64, RecordedSpan>>>,
+    }
+
+    #[derive(Debug, Clone)]
+    struct RecordedSpan {
+        name: &'static str,
+        parent: Option<&'static str>,
+        fields: HashMap<&'static str, String>,
+    }
+
+    struct FieldVisitor<'a>(&'a mut HashMap<&'static str, String>);
+
+    impl tracing::field::Visit for FieldVisitor<'_> {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.insert(field.name(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name(), format!("{value:?}"));
+        }
+    }
+
+    impl<S> Layer<S> for SpanRecorder
+    where
+        S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: Context<'_, S>,
+        ) {
+            let span = ctx.span(id).expect("span");
+            let mut fields = HashMap::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            self.spans.lock().unwrap().insert(
+                id.into_u64(),
+                RecordedSpan {
+                    name: span.name(),
+                    parent: span.parent().map(|parent| parent.name()),
+                    fields,
+                },
+            );
+        }
+
+        fn on_record(
+            &self,
+            id: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            _ctx: Context<'_, S>,
+        ) {
+            if let Some(span) = self.spans.lock().unwrap().get_mut(&id.into_u64()) {
+                values.record(&mut FieldVisitor(&mut span.fields));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn forward_should_emit_send_path_spans() {
+        let recorder = SpanRecorder::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+
+        let leader = Pubkey::new_unique();
+        let predictor = Arc::new(StaticLeaderPredictor::new(vec![leader], 42));
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        let (warm_connections, _jh) = spawn_warm_connection_manager(
+            WarmConnectionManagerConfig::default(),
+            slot_rx,
+            Arc::clone(&predictor) as _,
+            Arc::new(ContactInfoTable::default()),
+        )
+        .expect("spawn");
+        let forwarder = LeaderForwarder::new(
+            warm_connections,
+            predictor,
+            Arc::new(AtomicSlotTracker::new(42)),
+            fast_policy(0),
+        );
+
+        let signature = Signature::from([7; 64]);
+        let txn = signed_txn(signature);
+        let result = forwarder
+            .forward_with_request_id(&txn, None, Some("req-1"))
+            .await;
+        assert!(matches!(
+            result,
+            Err(ForwardError::RetriesExhausted { attempts: 1, .. })
+        ));
+
+        let spans = recorder
+            .spans
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        let find = |name: &str| {
+            spans
+                .iter()
+                .find(|span| span.name == name)
+                .unwrap_or_else(|| panic!("missing span {name}: {spans:?}"))
+        };
+
+        let forward = find("forward");
+        assert_eq!(forward.parent, None);
+        assert_eq!(forward.fields["request_id"], "req-1");
+        assert_eq!(forward.fields["signature"], signature.to_string());
+        assert_eq!(find("forward_attempt").parent, Some("forward"));
+        let predict = find("predict_leader");
+        assert_eq!(predict.parent, Some("forward_attempt"));
+        assert_eq!(predict.fields["leader"], leader.to_string());
+        assert_eq!(predict.fields["slot"], "42");
+        let send = find("send");
+        assert_eq!(send.parent, Some("forward_attempt"));
+        assert_eq!(send.fields["leader"], leader.to_string());
+        assert_eq!(find("open_stream").parent, Some("send"));
+    }
+}
@@ -0,0 +1,263 @@
+//!
+//! Health checks summarizing the liveness of the TPU client subsystems.
+//!
+//! [`check_health`] aggregates the state of the slot feed, the leader schedule and the TPU connections into a
+//! [`HealthStatus`], listing every failed check so readiness/liveness probes can report specifics.
+//! [`check_feed_drift`] reports the `feed_drift` flag of a [`SlotDriftMonitor`], when one is running, it requires
+//! the `rpc` feature.
+//!
+#[cfg(feature = "rpc")]
+use crate::slot_drift::SlotDriftMonitor;
+use {
+    crate::{
+        rpc::schedule::{LeaderLookup, ManagedLeaderSchedule},
+        slot::AtomicSlotTracker,
+    },
+    solana_clock::Slot,
+    std::{fmt, time::Duration},
+};
+
+///
+/// Default maximum age of the last slot update before the slot feed is reported unhealthy.
+///
+pub const DEFAULT_HEALTH_MAX_SLOT_AGE: Duration = Duration::from_secs(10);
+
+///
+/// A failed health check.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthCheckFailure {
+    ///
+    /// The slot feed is disconnected, or the background task updating the slot tracker died.
+    ///
+    SlotFeedDisconnected,
+    ///
+    /// The last slot update is older than the configured threshold.
+    ///
+    SlotTooOld { age: Duration, max_age: Duration },
+    ///
+    /// No leader schedule is loaded for the epoch of the current slot.
+    ///
+    LeaderScheduleNotLoaded { slot: Slot },
+    ///
+    /// No TPU connection is ready.
+    ///
+    NoConnectionReady,
+    ///
+    /// The slot feed drifted from the reference RPC beyond the threshold for a sustained period.
+    ///
+    FeedDrift { drift: i64, threshold: u64 },
+}
+
+impl fmt::Display for HealthCheckFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SlotFeedDisconnected => write!(f, "slot feed disconnected"),
+            Self::SlotTooOld { age, max_age } => {
+                write!(f, "last slot update {age:?} ago (max age {max_age:?})")
+            }
+            Self::LeaderScheduleNotLoaded { slot } => {
+                write!(f, "no leader schedule loaded for slot {slot}")
+            }
+            Self::NoConnectionReady => write!(f, "no TPU connection ready"),
+            Self::FeedDrift { drift, threshold } => {
+                write!(
+                    f,
+                    "slot feed drifted {drift} slot(s) from the reference RPC (threshold {threshold})"
+                )
+            }
+        }
+    }
+}
+
+///
+/// Result of a health check, see [`check_health`].
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HealthStatus {
+    ///
+    /// Failed checks, empty when healthy.
+    ///
+    pub failures: Vec<HealthCheckFailure>,
+}
+
+impl HealthStatus {
+    ///
+    /// Returns `true` if every check passed.
+    ///
+    pub fn is_healthy(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for HealthStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_healthy() {
+            return write!(f, "healthy");
+        }
+        write!(f, "unhealthy: ")?;
+        for (i, failure) in self.failures.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{failure}")?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// Checks the health of the TPU client subsystems:
+///
+/// - the slot feed is connected and its background task is alive,
+/// - the last slot update is at most `max_slot_age` old,
+/// - a leader schedule is loaded for the epoch of the current slot,
+/// - at least one TPU connection is ready, according to `active_connections`.
+///
+pub fn check_health(
+    slot_tracker: &AtomicSlotTracker,
+    leader_schedule: &ManagedLeaderSchedule,
+    active_connections: usize,
+    max_slot_age: Duration,
+) -> HealthStatus {
+    let mut failures = Vec::new();
+    if slot_tracker.is_closed() {
+        failures.push(HealthCheckFailure::SlotFeedDisconnected);
+    } else {
+        let slot = slot_tracker.load();
+        if slot_tracker.is_stale() {
+            failures.push(HealthCheckFailure::SlotFeedDisconnected);
+        }
+        let age = slot_tracker.age();
+        if age > max_slot_age {
+            failures.push(HealthCheckFailure::SlotTooOld {
+                age,
+                max_age: max_slot_age,
+            });
+        }
+        let schedule_loaded = leader_schedule
+            .lookup_leader(slot)
+            .is_ok_and(|lookup| matches!(lookup, LeaderLookup::Found(_) | LeaderLookup::Gap));
+        if !schedule_loaded {
+            failures.push(HealthCheckFailure::LeaderScheduleNotLoaded { slot });
+        }
+    }
+    if active_connections == 0 {
+        failures.push(HealthCheckFailure::NoConnectionReady);
+    }
+    HealthStatus { failures }
+}
+
+///
+/// Checks the `feed_drift` flag of `monitor`, see [`SlotDriftMonitor::is_drifting`].
+///
+/// Returns [`HealthCheckFailure::FeedDrift`] while the flag is raised, to extend [`HealthStatus::failures`] with.
+///
+#[cfg(feature = "rpc")]
+pub fn check_feed_drift(monitor: &SlotDriftMonitor) -> Option<HealthCheckFailure> {
+    if !monitor.is_drifting() {
+        return None;
+    }
+    Some(HealthCheckFailure::FeedDrift {
+        drift: monitor.drift().unwrap_or_default(),
+        threshold: monitor.threshold(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{clock::MockClock, rpc::schedule::CompactSortedSchedule},
+        solana_clock::DEFAULT_SLOTS_PER_EPOCH,
+        solana_pubkey::Pubkey,
+        std::sync::Arc,
+    };
+
+    fn unique_leaders(n: u64) -> Vec<Pubkey> {
+        (0..n).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    fn schedule() -> ManagedLeaderSchedule {
+        ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4)),
+            CompactSortedSchedule::new(
+                DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+        )
+    }
+
+    #[test]
+    fn it_should_be_healthy_when_every_check_passes() {
+        let slot_tracker = AtomicSlotTracker::new(100);
+        let status = check_health(&slot_tracker, &schedule(), 1, DEFAULT_HEALTH_MAX_SLOT_AGE);
+        assert!(status.is_healthy(), "{status}");
+    }
+
+    #[test]
+    fn it_should_be_unhealthy_when_slot_tracker_is_stale() {
+        let clock = Arc::new(MockClock::new());
+        let slot_tracker = AtomicSlotTracker::new(100).with_clock(Arc::clone(&clock) as _);
+        clock.advance(Duration::from_millis(10));
+        let status = check_health(&slot_tracker, &schedule(), 1, Duration::from_millis(10));
+        assert!(status.is_healthy(), "{status}");
+        clock.advance(Duration::from_millis(1));
+        let status = check_health(&slot_tracker, &schedule(), 1, Duration::from_millis(10));
+        assert!(!status.is_healthy());
+        assert!(matches!(
+            status.failures.as_slice(),
+            [HealthCheckFailure::SlotTooOld { .. }]
+        ));
+
+        slot_tracker.set_stale(true);
+        let status = check_health(&slot_tracker, &schedule(), 1, DEFAULT_HEALTH_MAX_SLOT_AGE);
+        assert_eq!(
+            status.failures,
+            vec![HealthCheckFailure::SlotFeedDisconnected]
+        );
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn it_should_report_sustained_feed_drift() {
+        let monitor = SlotDriftMonitor::new(3, 1);
+        monitor.observe(100, 102);
+        assert_eq!(check_feed_drift(&monitor), None);
+        monitor.observe(100, 105);
+        let failure = check_feed_drift(&monitor).expect("feed drift");
+        assert_eq!(
+            failure,
+            HealthCheckFailure::FeedDrift {
+                drift: -5,
+                threshold: 3
+            }
+        );
+        assert_eq!(
+            failure.to_string(),
+            "slot feed drifted -5 slot(s) from the reference RPC (threshold 3)"
+        );
+    }
+
+    #[test]
+    fn it_should_report_every_failed_check() {
+        let slot_tracker = AtomicSlotTracker::new(3 * DEFAULT_SLOTS_PER_EPOCH);
+        let status = check_health(&slot_tracker, &schedule(), 0, DEFAULT_HEALTH_MAX_SLOT_AGE);
+        assert_eq!(
+            status.failures,
+            vec![
+                HealthCheckFailure::LeaderScheduleNotLoaded {
+                    slot: 3 * DEFAULT_SLOTS_PER_EPOCH
+                },
+                HealthCheckFailure::NoConnectionReady,
+            ]
+        );
+        assert_eq!(
+            status.to_string(),
+            format!(
+                "unhealthy: no leader schedule loaded for slot {}, no TPU connection ready",
+                3 * DEFAULT_SLOTS_PER_EPOCH
+            )
+        );
+    }
+}
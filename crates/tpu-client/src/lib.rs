@@ -36,11 +36,30 @@
 //!
 //! # feature-flag supports
 //!
+//! - **metrics**: Enable the leader prediction, leader schedule and warm connection metrics of [`crate::metrics`], with
+//!   [`crate::metrics::metrics_handler`] rendering a scrape body
 //! - **prometheus**: Enable prometheus metrics exposition module [`crate::prom`]
+//! - **rpc** (default): Enable the Solana RPC client backed services: leader schedule and contact-info refresh,
+//!   stake info, [`crate::slot_drift`] and the [YellowstoneTpuSender](`crate::yellowstone_grpc::sender::YellowstoneTpuSender`).
+//!   Without it, the slot tracker is driven by gRPC only and the leader schedule is static, see
+//!   [`crate::rpc::schedule::ManagedLeaderSchedule::from_static_schedules`]
 //! - **yellowstone-grpc**: Enable Yellowstone gRPC based TPU sender implementation [`crate::yellowstone_grpc`]
 //! - **bytes** : Enable `bytes` crate based transaction representation support in TPU sender
+//! - **test-utils**: Expose static schedule constructors, synthetic slot and epoch updates
+//!   (`AtomicSlotTracker::push_slot`, `ManagedLeaderSchedule::stage_epoch`) and [`crate::clock::MockClock`] used by
+//!   tests and benchmarks
+//! - **tokio-console**: Name background tasks for [tokio-console](https://github.com/tokio-rs/console), requires
+//!   `RUSTFLAGS="--cfg tokio_unstable"`, see [`crate::task`]
 //!
 ///
+/// module for recent blockhash tracking
+///
+pub mod blockhash;
+///
+/// module for the pluggable clock used by time-dependent components
+///
+pub mod clock;
+///
 /// module for top-level cnfiguration objects
 ///
 pub mod config;
@@ -49,10 +68,28 @@ pub mod config;
 ///
 pub mod core;
 ///
+/// module for transaction deduplication
+///
+pub mod dedup;
+///
+/// module for forwarding transactions to leaders with retries
+///
+pub mod forwarder;
+///
+/// module for subsystem health checks
+///
+pub mod health;
+///
 /// module for common tpu sender implementation
 ///
 pub mod sender;
 
+///
+/// module for leader prediction, leader schedule and warm connection metrics
+///
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
 ///
 /// module to enable prometheus metrics exposition
 ///
@@ -60,15 +97,46 @@ pub mod sender;
 pub mod prom;
 
 ///
-/// module for RPC utilities
+/// module for RPC utilities, the RPC client backed services require the `rpc` feature
 ///
 pub mod rpc;
 
+///
+/// module for leader schedule mismatch detection
+///
+pub mod schedule_mismatch;
+
+///
+/// module for the bounded history of recent sends
+///
+pub mod send_history;
+
+///
+/// module for leader skip rate tracking
+///
+pub mod skip_rate;
+
 ///
 /// module for slot tracking
 ///
 pub mod slot;
 
+///
+/// module for slot drift detection between the slot feed and a reference RPC
+///
+#[cfg(feature = "rpc")]
+pub mod slot_drift;
+
+///
+/// module for background task naming
+///
+pub mod task;
+
+///
+/// module for warm QUIC connection management
+///
+pub mod warm_connection;
+
 ///
 /// module to host utility that utilize Yellowstone gRPC services
 ///
@@ -0,0 +1,184 @@
+use {
+    prometheus::{
+        Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+    },
+    std::time::Duration,
+};
+
+lazy_static::lazy_static! {
+    static ref LEADER_PREDICTION_SERVED_CNT: IntCounter = IntCounter::new(
+        "leader_prediction_served", "Number of upcoming leader predictions served"
+    ).unwrap();
+
+    static ref LEADERS_PREDICTED_PER_CALL: Histogram = Histogram::with_opts(
+        HistogramOpts::new("leaders_predicted_per_call", "Number of leaders returned by an upcoming leader prediction")
+            .buckets(vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 8.0, 10.0, 16.0, 32.0])
+    ).unwrap();
+
+    static ref LEADER_PREDICTION_CACHE_HIT_CNT: IntCounter = IntCounter::new(
+        "leader_prediction_cache_hit", "Number of upcoming leader predictions served from the prediction cache"
+    ).unwrap();
+
+    static ref LEADER_PREDICTION_SHORTFALL_CNT: IntCounter = IntCounter::new(
+        "leader_prediction_shortfall", "Number of upcoming leader predictions missing more leaders than the warn threshold"
+    ).unwrap();
+
+    static ref LEADER_PREDICTION_TRUNCATED_CNT: IntCounter = IntCounter::new(
+        "leader_prediction_truncated", "Number of leaders missing from upcoming leader predictions because the window went past the known leader schedules"
+    ).unwrap();
+
+    static ref LEADER_SCHEDULE_STALE: IntGauge = IntGauge::new(
+        "leader_schedule_stale", "1 if the last leader schedule update failed and the last loaded schedule is served"
+    ).unwrap();
+
+    static ref LEADER_PREDICTION_ERROR_CNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("leader_prediction_error", "Number of upcoming leader predictions or schedule lookups that failed"),
+        &["reason"]
+    ).unwrap();
+
+    static ref SLOT_TRACKER_AGE_MS: IntGauge = IntGauge::new(
+        "slot_tracker_age_ms", "Time since the slot tracker last received a slot update, observed at prediction time"
+    ).unwrap();
+
+    static ref WARM_CONNECTION_ACTIVE_GAUGE: IntGauge = IntGauge::new(
+        "warm_connection_active", "Number of established warm connections"
+    ).unwrap();
+
+    static ref WARM_CONNECTION_CONNECT_CNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("warm_connection_connect", "Outcome of warm connection attempts"),
+        &["status"]
+    ).unwrap();
+
+    static ref WARM_CONNECTION_SEND_CNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("warm_connection_send", "Outcome of transaction sends over warm connections"),
+        &["status"]
+    ).unwrap();
+}
+
+pub fn incr_leader_prediction_served(leaders: usize) {
+    LEADER_PREDICTION_SERVED_CNT.inc();
+    LEADERS_PREDICTED_PER_CALL.observe(leaders as f64);
+}
+
+pub fn incr_leader_prediction_cache_hit() {
+    LEADER_PREDICTION_CACHE_HIT_CNT.inc();
+}
+
+pub fn incr_leader_prediction_shortfall() {
+    LEADER_PREDICTION_SHORTFALL_CNT.inc();
+}
+
+pub fn incr_leader_prediction_truncated(truncated: usize) {
+    LEADER_PREDICTION_TRUNCATED_CNT.inc_by(truncated as u64);
+}
+
+pub fn set_leader_schedule_stale(stale: bool) {
+    LEADER_SCHEDULE_STALE.set(i64::from(stale));
+}
+
+pub fn incr_leader_prediction_error(reason: &str) {
+    LEADER_PREDICTION_ERROR_CNT
+        .with_label_values(&[reason])
+        .inc();
+}
+
+pub fn set_slot_tracker_age(age: Duration) {
+    SLOT_TRACKER_AGE_MS.set(age.as_millis() as i64);
+}
+
+pub fn set_warm_connection_active_cnt(active: usize) {
+    WARM_CONNECTION_ACTIVE_GAUGE.set(active as i64);
+}
+
+pub fn incr_warm_connection_connect(status: &str) {
+    WARM_CONNECTION_CONNECT_CNT
+        .with_label_values(&[status])
+        .inc();
+}
+
+pub fn incr_warm_connection_send(status: &str) {
+    WARM_CONNECTION_SEND_CNT.with_label_values(&[status]).inc();
+}
+
+pub fn register_metrics(reg: &Registry) {
+    reg.register(Box::new(LEADER_PREDICTION_SERVED_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(LEADERS_PREDICTED_PER_CALL.clone()))
+        .unwrap();
+    reg.register(Box::new(LEADER_PREDICTION_CACHE_HIT_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(LEADER_PREDICTION_SHORTFALL_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(LEADER_PREDICTION_TRUNCATED_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(LEADER_SCHEDULE_STALE.clone()))
+        .unwrap();
+    reg.register(Box::new(LEADER_PREDICTION_ERROR_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(SLOT_TRACKER_AGE_MS.clone())).unwrap();
+    reg.register(Box::new(WARM_CONNECTION_ACTIVE_GAUGE.clone()))
+        .unwrap();
+    reg.register(Box::new(WARM_CONNECTION_CONNECT_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(WARM_CONNECTION_SEND_CNT.clone()))
+        .unwrap();
+}
+
+///
+/// Returns the metrics of `reg` in the Prometheus text exposition format, ready to be served as a scrape body.
+///
+pub fn metrics_handler(reg: &Registry) -> String {
+    TextEncoder::new()
+        .encode_to_string(&reg.gather())
+        .unwrap_or_else(|e| {
+            tracing::error!("could not encode metrics: {e}");
+            String::new()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///
+    /// Parses the value of the first sample of `name` in a text exposition body.
+    ///
+    fn sample_value(body: &str, name: &str) -> Option<f64> {
+        body.lines()
+            .filter(|line| !line.starts_with('#'))
+            .find_map(|line| {
+                let (metric, value) = line.rsplit_once(' ')?;
+                let metric_name = metric.split('{').next()?;
+                (metric_name == name).then(|| value.parse().ok())?
+            })
+    }
+
+    #[test]
+    fn it_should_scrape_registered_metrics() {
+        let reg = Registry::new();
+        register_metrics(&reg);
+
+        incr_leader_prediction_served(5);
+        incr_leader_prediction_error("stale_slot");
+        incr_leader_prediction_truncated(2);
+        set_leader_schedule_stale(true);
+        set_slot_tracker_age(Duration::from_millis(1500));
+        set_warm_connection_active_cnt(3);
+        incr_warm_connection_connect("success");
+        incr_warm_connection_send("failure");
+
+        let body = metrics_handler(&reg);
+        assert!(body.contains("# TYPE leader_prediction_served counter"));
+        assert!(body.contains("# TYPE warm_connection_active gauge"));
+        assert!(body.contains("leader_prediction_error{reason=\"stale_slot\"}"));
+        assert!(body.contains("warm_connection_send{status=\"failure\"}"));
+        assert!(sample_value(&body, "leader_prediction_served").is_some_and(|v| v >= 1.0));
+        assert!(sample_value(&body, "leaders_predicted_per_call_count").is_some_and(|v| v >= 1.0));
+        assert!(sample_value(&body, "warm_connection_connect").is_some_and(|v| v >= 1.0));
+        assert!(sample_value(&body, "warm_connection_send").is_some_and(|v| v >= 1.0));
+        assert!(sample_value(&body, "leader_prediction_truncated").is_some_and(|v| v >= 2.0));
+        assert!(sample_value(&body, "leader_schedule_stale").is_some());
+        assert!(sample_value(&body, "slot_tracker_age_ms").is_some());
+        assert!(sample_value(&body, "warm_connection_active").is_some());
+    }
+}
@@ -81,6 +81,93 @@ lazy_static::lazy_static! {
         "quic_gw_tx_connection_cache_miss", "Number of misses transaction got forward to a new connection to remote peer validators"
     ).unwrap();
 
+    static ref CONTACT_INFO_CACHE_HIT_CNT: IntCounter = IntCounter::new(
+        "contact_info_cache_hit", "Number of contact info lookups served from the cache"
+    ).unwrap();
+
+    static ref CONTACT_INFO_CACHE_MISS_CNT: IntCounter = IntCounter::new(
+        "contact_info_cache_miss", "Number of contact info lookups for unknown or stale entries"
+    ).unwrap();
+
+    static ref BLOCKHASH_EXPIRED_REJECTED_CNT: IntCounter = IntCounter::new(
+        "blockhash_expired_rejected", "Number of transactions rejected before sending because their blockhash expired"
+    ).unwrap();
+
+    static ref GRPC_RECONNECT_ATTEMPT_CNT: IntCounter = IntCounter::new(
+        "grpc_reconnect_attempt", "Number of attempts to re-subscribe to the Yellowstone gRPC slot stream"
+    ).unwrap();
+
+    static ref GRPC_ENDPOINT_FAILOVER_CNT: IntCounter = IntCounter::new(
+        "grpc_endpoint_failover", "Number of failovers to the next Yellowstone gRPC endpoint"
+    ).unwrap();
+
+    static ref GRPC_ACTIVE_ENDPOINT_INDEX: IntGauge = IntGauge::new(
+        "grpc_active_endpoint_index", "Index of the Yellowstone gRPC endpoint in use, 0 is the primary"
+    ).unwrap();
+
+    static ref DEDUP_CACHE_HIT_CNT: IntCounter = IntCounter::new(
+        "dedup_cache_hit", "Number of transaction sends skipped because the signature was recently sent"
+    ).unwrap();
+
+    static ref FORWARD_TXN_OUTCOME_CNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("forward_txn_outcome", "Outcome of transaction forwarding attempts to the current leader"),
+        &["outcome"]
+    ).unwrap();
+
+    static ref FORWARD_SEND_TIMEOUT_CNT: IntCounter = IntCounter::new(
+        "forward_send_timeout", "Number of sends to a leader that did not complete within the send timeout"
+    ).unwrap();
+
+    static ref SCHEDULE_MISMATCH_CNT: IntCounter = IntCounter::new(
+        "schedule_mismatch", "Number of blocks produced by another validator than the scheduled leader"
+    ).unwrap();
+
+    static ref BACKGROUND_TASK_EXIT_CNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("background_task_exit", "Number of background task exits by task and reason"),
+        &["task", "reason"]
+    ).unwrap();
+
+    static ref SLOT_FEED_DRIFT: IntGauge = IntGauge::new(
+        "slot_feed_drift", "Slot of the feed minus the slot of the reference RPC, negative when the feed is behind"
+    ).unwrap();
+
+    static ref FEED_DRIFT: IntGauge = IntGauge::new(
+        "feed_drift", "1 if the slot feed drifted from the reference RPC beyond the threshold for a sustained period"
+    ).unwrap();
+
+    static ref WARM_CONNECTION_EVICTION_CNT: IntCounter = IntCounter::new(
+        "warm_connection_eviction", "Number of warm connections evicted to stay under the connection cap"
+    ).unwrap();
+
+    static ref WARM_CONNECTION_OPEN_REFUSED_CNT: IntCounter = IntCounter::new(
+        "warm_connection_open_refused", "Number of warm connections refused because the connection cap was reached"
+    ).unwrap();
+
+    static ref WARM_CONNECTION_INTENDED_TARGET_CNT: IntCounter = IntCounter::new(
+        "warm_connection_intended_target", "Number of warm connections skipped in dry-run mode"
+    ).unwrap();
+
+    static ref WARM_CONNECTION_ENDPOINT_GAUGE: IntGauge = IntGauge::new(
+        "warm_connection_endpoint", "Number of local QUIC endpoints, one UDP socket each, used by the warm connections"
+    ).unwrap();
+
+    static ref WARM_CONNECTION_LOCAL_LEADER_SKIP_CNT: IntCounter = IntCounter::new(
+        "warm_connection_local_leader_skip", "Number of times a leader matching the local node identity was not warmed"
+    ).unwrap();
+
+    static ref WARM_CONNECTION_LEADER_BUSY_CNT: IntCounter = IntCounter::new(
+        "warm_connection_leader_busy", "Number of sends refused because too many sends were already queued to the leader"
+    ).unwrap();
+
+    static ref WARM_CONNECTION_EVENT_CNT: IntCounterVec = IntCounterVec::new(
+        Opts::new("warm_connection_event", "Number of warm connection lifecycle events by kind"),
+        &["kind"]
+    ).unwrap();
+
+    static ref TPU_SENDER_QUEUE_DEPTH: IntGauge = IntGauge::new(
+        "tpu_sender_queue_depth", "Number of transactions queued to the TPU sender driver, not yet dispatched to a worker"
+    ).unwrap();
+
     static ref QUIC_GW_TX_BLOCKED_BY_CONNECTING_GAUGE: IntGauge = IntGauge::new(
         "quic_gw_tx_blocked_by_connecting", "Number of transactions waiting for remote peer connection to be established"
     ).unwrap();
@@ -177,6 +264,9 @@ pub fn observe_quic_gw_connection_time(duration: Duration) {
 pub fn set_quic_gw_tx_blocked_by_connecting_cnt(blocked: usize) {
     QUIC_GW_TX_BLOCKED_BY_CONNECTING_GAUGE.set(blocked as i64);
 }
+pub fn set_tpu_sender_queue_depth(depth: usize) {
+    TPU_SENDER_QUEUE_DEPTH.set(depth as i64);
+}
 pub fn set_quic_gw_connecting_cnt(connecting: usize) {
     QUIC_GW_CONNECTING_GAUGE.set(connecting as i64);
 }
@@ -205,6 +295,111 @@ pub fn incr_quic_gw_tx_connection_cache_miss_cnt() {
     QUIC_GW_TX_CONNECTION_CACHE_MISS_CNT.inc();
 }
 
+pub fn incr_contact_info_cache_hit_cnt() {
+    CONTACT_INFO_CACHE_HIT_CNT.inc();
+}
+pub fn incr_contact_info_cache_miss_cnt() {
+    CONTACT_INFO_CACHE_MISS_CNT.inc();
+}
+
+pub fn incr_blockhash_expired_rejected_cnt() {
+    BLOCKHASH_EXPIRED_REJECTED_CNT.inc();
+}
+
+pub fn incr_grpc_reconnect_attempt_cnt() {
+    GRPC_RECONNECT_ATTEMPT_CNT.inc();
+}
+
+pub fn incr_grpc_endpoint_failover_cnt() {
+    GRPC_ENDPOINT_FAILOVER_CNT.inc();
+}
+
+pub fn set_grpc_active_endpoint_index(index: usize) {
+    GRPC_ACTIVE_ENDPOINT_INDEX.set(index as i64);
+}
+
+pub fn incr_dedup_cache_hit_cnt() {
+    DEDUP_CACHE_HIT_CNT.inc();
+}
+
+pub fn incr_forward_txn_outcome(outcome: &str) {
+    FORWARD_TXN_OUTCOME_CNT.with_label_values(&[outcome]).inc();
+}
+
+pub fn incr_forward_send_timeout_cnt() {
+    FORWARD_SEND_TIMEOUT_CNT.inc();
+}
+
+pub fn incr_schedule_mismatch_cnt() {
+    SCHEDULE_MISMATCH_CNT.inc();
+}
+
+#[cfg(test)]
+pub(crate) fn schedule_mismatch_cnt() -> u64 {
+    SCHEDULE_MISMATCH_CNT.get()
+}
+
+pub fn incr_background_task_exit(task: &str, reason: &str) {
+    BACKGROUND_TASK_EXIT_CNT
+        .with_label_values(&[task, reason])
+        .inc();
+}
+
+pub fn set_slot_feed_drift(drift: i64) {
+    SLOT_FEED_DRIFT.set(drift);
+}
+
+#[cfg(test)]
+pub(crate) fn slot_feed_drift() -> i64 {
+    SLOT_FEED_DRIFT.get()
+}
+
+pub fn set_feed_drift(drifting: bool) {
+    FEED_DRIFT.set(i64::from(drifting));
+}
+
+#[cfg(test)]
+pub(crate) fn feed_drift() -> i64 {
+    FEED_DRIFT.get()
+}
+
+pub fn incr_warm_connection_eviction_cnt(amount: u64) {
+    WARM_CONNECTION_EVICTION_CNT.inc_by(amount);
+}
+pub fn incr_warm_connection_open_refused_cnt(amount: u64) {
+    WARM_CONNECTION_OPEN_REFUSED_CNT.inc_by(amount);
+}
+
+pub fn incr_warm_connection_intended_target_cnt() {
+    WARM_CONNECTION_INTENDED_TARGET_CNT.inc();
+}
+
+#[cfg(test)]
+pub(crate) fn warm_connection_intended_target_cnt() -> u64 {
+    WARM_CONNECTION_INTENDED_TARGET_CNT.get()
+}
+
+pub fn set_warm_connection_endpoint_cnt(endpoints: usize) {
+    WARM_CONNECTION_ENDPOINT_GAUGE.set(endpoints as i64);
+}
+
+pub fn incr_warm_connection_local_leader_skip_cnt() {
+    WARM_CONNECTION_LOCAL_LEADER_SKIP_CNT.inc();
+}
+
+#[cfg(test)]
+pub(crate) fn warm_connection_local_leader_skip_cnt() -> u64 {
+    WARM_CONNECTION_LOCAL_LEADER_SKIP_CNT.get()
+}
+
+pub fn incr_warm_connection_leader_busy_cnt() {
+    WARM_CONNECTION_LEADER_BUSY_CNT.inc();
+}
+
+pub fn incr_warm_connection_event(kind: &str) {
+    WARM_CONNECTION_EVENT_CNT.with_label_values(&[kind]).inc();
+}
+
 pub fn observe_leader_rtt(leader: Pubkey, rtt: Duration) {
     LEADER_RTT
         .with_label_values(&[&leader.to_string()])
@@ -240,10 +435,49 @@ pub fn register_metrics(reg: &Registry) {
         .unwrap();
     reg.register(Box::new(QUIC_GW_TX_BLOCKED_BY_CONNECTING_GAUGE.clone()))
         .unwrap();
+    reg.register(Box::new(TPU_SENDER_QUEUE_DEPTH.clone()))
+        .unwrap();
     reg.register(Box::new(QUIC_GW_TX_CONNECTION_CACHE_HIT_CNT.clone()))
         .unwrap();
     reg.register(Box::new(QUIC_GW_TX_CONNECTION_CACHE_MISS_CNT.clone()))
         .unwrap();
+    reg.register(Box::new(CONTACT_INFO_CACHE_HIT_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(CONTACT_INFO_CACHE_MISS_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(BLOCKHASH_EXPIRED_REJECTED_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(GRPC_RECONNECT_ATTEMPT_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(GRPC_ENDPOINT_FAILOVER_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(GRPC_ACTIVE_ENDPOINT_INDEX.clone()))
+        .unwrap();
+    reg.register(Box::new(DEDUP_CACHE_HIT_CNT.clone())).unwrap();
+    reg.register(Box::new(FORWARD_TXN_OUTCOME_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(FORWARD_SEND_TIMEOUT_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(SCHEDULE_MISMATCH_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(BACKGROUND_TASK_EXIT_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(SLOT_FEED_DRIFT.clone())).unwrap();
+    reg.register(Box::new(FEED_DRIFT.clone())).unwrap();
+    reg.register(Box::new(WARM_CONNECTION_EVICTION_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(WARM_CONNECTION_OPEN_REFUSED_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(WARM_CONNECTION_INTENDED_TARGET_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(WARM_CONNECTION_ENDPOINT_GAUGE.clone()))
+        .unwrap();
+    reg.register(Box::new(WARM_CONNECTION_LOCAL_LEADER_SKIP_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(WARM_CONNECTION_LEADER_BUSY_CNT.clone()))
+        .unwrap();
+    reg.register(Box::new(WARM_CONNECTION_EVENT_CNT.clone()))
+        .unwrap();
     reg.register(Box::new(QUIC_GW_CONNECTION_TIME_HIST.clone()))
         .unwrap();
     reg.register(Box::new(QUIC_GW_REMOTE_PEER_ADDR_CHANGES_DETECTED.clone()))
@@ -0,0 +1,635 @@
+//!
+//! Contact info resolution from leader public keys to TPU QUIC socket addresses.
+//!
+//! [`ContactInfoTable`] is an in-memory view over the cluster contact info returned by `getClusterNodes`.
+//! See [`crate::rpc::tpu_info::RpcClusterTpuQuicInfoService::contact_info_table`] for a periodically refreshed source.
+//!
+//! # QUIC port fallback
+//!
+//! Some validators only advertise their UDP TPU ports. Agave binds its TPU and TPU forwards QUIC ports at a fixed
+//! [`QUIC_PORT_OFFSET`] from the UDP ones, so QUIC addresses are derived from the UDP ones when missing.
+//!
+//! # TPU vs TPU forwards
+//!
+//! Both ports are resolved independently, a validator may advertise one but not the other.
+//! [`ContactInfoTable::contact_info`] returns both so callers can pick the right port per target.
+//!
+//! # Caching
+//!
+//! [`ContactInfoCache`] serves lookups from memory and refreshes the cluster contact info in the background,
+//! see [`spawn_contact_info_cache`]. The cache requires the `rpc` feature.
+//!
+#[cfg(all(feature = "prometheus", feature = "rpc"))]
+use crate::prom;
+#[cfg(feature = "rpc")]
+use {
+    crate::task::{CONTACT_INFO_CACHE_TASK, spawn_named},
+    serde::Deserialize,
+    solana_client::{client_error::ClientError, nonblocking::rpc_client::RpcClient},
+    std::{
+        sync::{
+            Arc, RwLock,
+            atomic::{AtomicU64, Ordering},
+        },
+        time::Instant,
+    },
+    tokio::task::JoinHandle,
+    tokio_util::sync::CancellationToken,
+};
+use {
+    crate::{
+        core::{AddressResolver, LeaderTpuInfoService, TpuAddrs},
+        rpc::tpu_info::RpcTpuQuicContactInfo,
+    },
+    solana_pubkey::Pubkey,
+    solana_quic_definitions::QUIC_PORT_OFFSET,
+    solana_rpc_client_api::response::RpcContactInfo,
+    std::{collections::HashMap, net::SocketAddr, str::FromStr, time::Duration},
+};
+
+pub const DEFAULT_CONTACT_INFO_CACHE_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+pub const DEFAULT_CONTACT_INFO_CACHE_MAX_AGE: Duration = Duration::from_secs(180);
+
+///
+/// Derives a QUIC socket address from a UDP TPU socket address.
+///
+/// Returns `None` if the resulting port would overflow.
+///
+pub fn quic_addr_from_udp_addr(udp_addr: SocketAddr) -> Option<SocketAddr> {
+    let port = udp_addr.port().checked_add(QUIC_PORT_OFFSET)?;
+    Some(SocketAddr::new(udp_addr.ip(), port))
+}
+
+///
+/// Resolves the TPU QUIC socket address advertised by a cluster node.
+///
+/// Falls back to the UDP TPU address shifted by [`QUIC_PORT_OFFSET`] if the node does not advertise a QUIC port.
+///
+pub fn resolve_tpu_quic_addr(contact_info: &RpcContactInfo) -> Option<SocketAddr> {
+    contact_info.tpu_quic.or_else(|| {
+        let quic_addr = contact_info.tpu.and_then(quic_addr_from_udp_addr);
+        if let Some(quic_addr) = quic_addr {
+            tracing::trace!(
+                "{} does not advertise a TPU QUIC port, derived {} from its TPU port",
+                contact_info.pubkey,
+                quic_addr
+            );
+        }
+        quic_addr
+    })
+}
+
+///
+/// Resolves the TPU forwards QUIC socket address advertised by a cluster node.
+///
+/// Falls back to the UDP TPU forwards address shifted by [`QUIC_PORT_OFFSET`] if the node does not advertise a QUIC port.
+///
+pub fn resolve_tpu_forwards_quic_addr(contact_info: &RpcContactInfo) -> Option<SocketAddr> {
+    contact_info.tpu_forwards_quic.or_else(|| {
+        let quic_addr = contact_info.tpu_forwards.and_then(quic_addr_from_udp_addr);
+        if let Some(quic_addr) = quic_addr {
+            tracing::trace!(
+                "{} does not advertise a TPU forwards QUIC port, derived {} from its TPU forwards port",
+                contact_info.pubkey,
+                quic_addr
+            );
+        }
+        quic_addr
+    })
+}
+
+///
+/// A lookup table from leader public keys to their TPU QUIC contact info.
+///
+#[derive(Clone, Debug, Default)]
+pub struct ContactInfoTable {
+    entries: HashMap<Pubkey, RpcTpuQuicContactInfo>,
+}
+
+impl ContactInfoTable {
+    ///
+    /// Builds a table from a `getClusterNodes` response.
+    ///
+    /// Nodes with an invalid pubkey are skipped.
+    ///
+    pub fn from_cluster_nodes(nodes: &[RpcContactInfo]) -> Self {
+        nodes
+            .iter()
+            .filter_map(|node| {
+                let Ok(pubkey) = Pubkey::from_str(&node.pubkey) else {
+                    tracing::warn!("Skipping cluster node with invalid pubkey: {}", node.pubkey);
+                    return None;
+                };
+                Some((pubkey, RpcTpuQuicContactInfo::from(node)))
+            })
+            .collect()
+    }
+
+    ///
+    /// Get the TPU QUIC socket address of `leader`.
+    ///
+    pub fn tpu_quic_addr(&self, leader: &Pubkey) -> Option<SocketAddr> {
+        self.entries.get(leader).and_then(|info| info.tpu_quic)
+    }
+
+    ///
+    /// Get the TPU forwards QUIC socket address of `leader`.
+    ///
+    pub fn tpu_forwards_quic_addr(&self, leader: &Pubkey) -> Option<SocketAddr> {
+        self.entries
+            .get(leader)
+            .and_then(|info| info.tpu_forwards_quic)
+    }
+
+    ///
+    /// Get both TPU QUIC ports of `leader`, each of them may be missing.
+    ///
+    pub fn contact_info(&self, leader: &Pubkey) -> Option<&RpcTpuQuicContactInfo> {
+        self.entries.get(leader)
+    }
+
+    ///
+    /// Number of nodes in the table.
+    ///
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    ///
+    /// Returns `true` if the table holds no node.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl FromIterator<(Pubkey, RpcTpuQuicContactInfo)> for ContactInfoTable {
+    fn from_iter<T: IntoIterator<Item = (Pubkey, RpcTpuQuicContactInfo)>>(iter: T) -> Self {
+        Self {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl From<HashMap<Pubkey, RpcTpuQuicContactInfo>> for ContactInfoTable {
+    fn from(entries: HashMap<Pubkey, RpcTpuQuicContactInfo>) -> Self {
+        Self { entries }
+    }
+}
+
+impl LeaderTpuInfoService for ContactInfoTable {
+    fn get_quic_tpu_socket_addr(&self, leader_pubkey: &Pubkey) -> Option<SocketAddr> {
+        self.tpu_quic_addr(leader_pubkey)
+    }
+
+    fn get_quic_tpu_fwd_socket_addr(&self, leader_pubkey: &Pubkey) -> Option<SocketAddr> {
+        self.tpu_forwards_quic_addr(leader_pubkey)
+    }
+}
+
+impl AddressResolver for ContactInfoTable {
+    fn resolve(&self, leader: &Pubkey) -> Option<TpuAddrs> {
+        self.contact_info(leader).map(TpuAddrs::from)
+    }
+}
+
+///
+/// Configuration for the [`ContactInfoCache`].
+///
+#[cfg(feature = "rpc")]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContactInfoCacheConfig {
+    /// Interval between cluster contact info refreshes.
+    #[serde(
+        with = "humantime_serde",
+        default = "ContactInfoCacheConfig::default_refresh_interval"
+    )]
+    pub refresh_interval: Duration,
+    /// Entries not refreshed for longer than this are considered stale and are not served.
+    #[serde(
+        with = "humantime_serde",
+        default = "ContactInfoCacheConfig::default_max_age"
+    )]
+    pub max_age: Duration,
+}
+
+#[cfg(feature = "rpc")]
+impl ContactInfoCacheConfig {
+    pub const fn default_refresh_interval() -> Duration {
+        DEFAULT_CONTACT_INFO_CACHE_REFRESH_INTERVAL
+    }
+
+    pub const fn default_max_age() -> Duration {
+        DEFAULT_CONTACT_INFO_CACHE_MAX_AGE
+    }
+}
+
+#[cfg(feature = "rpc")]
+impl Default for ContactInfoCacheConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval: Self::default_refresh_interval(),
+            max_age: Self::default_max_age(),
+        }
+    }
+}
+
+#[cfg(feature = "rpc")]
+#[derive(Debug, Clone)]
+struct CachedContactInfo {
+    contact_info: RpcTpuQuicContactInfo,
+    refreshed_at: Instant,
+}
+
+#[cfg(feature = "rpc")]
+#[derive(Debug, Default)]
+struct ContactInfoCacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[cfg(feature = "rpc")]
+struct OnDrop {
+    handle: CancellationToken,
+}
+
+#[cfg(feature = "rpc")]
+impl Drop for OnDrop {
+    fn drop(&mut self) {
+        self.handle.cancel();
+    }
+}
+
+///
+/// A cache of cluster contact info, refreshed periodically from `getClusterNodes`.
+///
+/// Each entry tracks when it was last refreshed: entries older than [`ContactInfoCacheConfig::max_age`]
+/// are treated as stale and lookups return `None` rather than a potentially defunct address.
+///
+/// See [`spawn_contact_info_cache`] for creating an instance of this cache.
+///
+/// # Clone
+///
+/// Cheap to clone, clones share the same cache.
+///
+/// # Safety
+///
+/// This cache is thread-safe.
+///
+#[cfg(feature = "rpc")]
+#[derive(Clone)]
+pub struct ContactInfoCache {
+    inner: Arc<InnerContactInfoCache>,
+    _on_drop: Arc<OnDrop>,
+}
+
+#[cfg(feature = "rpc")]
+struct InnerContactInfoCache {
+    entries: RwLock<HashMap<Pubkey, CachedContactInfo>>,
+    stats: ContactInfoCacheStats,
+    rpc_client: Arc<RpcClient>,
+    max_age: Duration,
+}
+
+#[cfg(feature = "rpc")]
+impl InnerContactInfoCache {
+    async fn refresh_now(&self) -> Result<usize, ClientError> {
+        let nodes = self.rpc_client.get_cluster_nodes().await?;
+        self.apply_cluster_nodes(&nodes, Instant::now());
+        Ok(nodes.len())
+    }
+
+    fn apply_cluster_nodes(&self, nodes: &[RpcContactInfo], refreshed_at: Instant) {
+        let mut entries = self.entries.write().expect("write");
+        for (pubkey, contact_info) in ContactInfoTable::from_cluster_nodes(nodes).entries {
+            entries.insert(
+                pubkey,
+                CachedContactInfo {
+                    contact_info,
+                    refreshed_at,
+                },
+            );
+        }
+        // Nodes missing from the latest refreshes eventually age out.
+        let max_age = self.max_age;
+        entries.retain(|_, cached| cached.refreshed_at.elapsed() <= max_age);
+        tracing::trace!("Contact info cache refreshed, {} entries.", entries.len());
+    }
+}
+
+#[cfg(feature = "rpc")]
+impl ContactInfoCache {
+    fn new(
+        rpc_client: Arc<RpcClient>,
+        max_age: Duration,
+        cancellation_token: CancellationToken,
+    ) -> Self {
+        Self {
+            inner: Arc::new(InnerContactInfoCache {
+                entries: Default::default(),
+                stats: Default::default(),
+                rpc_client,
+                max_age,
+            }),
+            _on_drop: Arc::new(OnDrop {
+                handle: cancellation_token,
+            }),
+        }
+    }
+
+    ///
+    /// Refreshes the cache from `getClusterNodes` right away.
+    ///
+    /// Returns the number of nodes returned by the RPC.
+    ///
+    pub async fn refresh_now(&self) -> Result<usize, ClientError> {
+        self.inner.refresh_now().await
+    }
+
+    ///
+    /// Get both TPU QUIC ports of `leader`, or `None` if unknown or stale.
+    ///
+    pub fn contact_info(&self, leader: &Pubkey) -> Option<RpcTpuQuicContactInfo> {
+        let contact_info = self
+            .inner
+            .entries
+            .read()
+            .expect("read")
+            .get(leader)
+            .filter(|cached| cached.refreshed_at.elapsed() <= self.inner.max_age)
+            .map(|cached| cached.contact_info.clone());
+        if contact_info.is_some() {
+            self.inner.stats.hits.fetch_add(1, Ordering::Relaxed);
+            #[cfg(feature = "prometheus")]
+            {
+                prom::incr_contact_info_cache_hit_cnt();
+            }
+        } else {
+            self.inner.stats.misses.fetch_add(1, Ordering::Relaxed);
+            #[cfg(feature = "prometheus")]
+            {
+                prom::incr_contact_info_cache_miss_cnt();
+            }
+        }
+        contact_info
+    }
+
+    ///
+    /// Get the TPU QUIC socket address of `leader`, or `None` if unknown or stale.
+    ///
+    pub fn tpu_quic_addr(&self, leader: &Pubkey) -> Option<SocketAddr> {
+        self.contact_info(leader).and_then(|info| info.tpu_quic)
+    }
+
+    ///
+    /// Get the TPU forwards QUIC socket address of `leader`, or `None` if unknown or stale.
+    ///
+    pub fn tpu_forwards_quic_addr(&self, leader: &Pubkey) -> Option<SocketAddr> {
+        self.contact_info(leader)
+            .and_then(|info| info.tpu_forwards_quic)
+    }
+
+    ///
+    /// Time elapsed since the entry of `leader` was last refreshed.
+    ///
+    pub fn entry_age(&self, leader: &Pubkey) -> Option<Duration> {
+        self.inner
+            .entries
+            .read()
+            .expect("read")
+            .get(leader)
+            .map(|cached| cached.refreshed_at.elapsed())
+    }
+
+    ///
+    /// Ratio of lookups served from the cache since its creation, `0.0` if no lookup happened yet.
+    ///
+    pub fn hit_rate(&self) -> f64 {
+        let hits = self.inner.stats.hits.load(Ordering::Relaxed);
+        let misses = self.inner.stats.misses.load(Ordering::Relaxed);
+        let total = hits + misses;
+        if total == 0 {
+            0.0
+        } else {
+            hits as f64 / total as f64
+        }
+    }
+}
+
+#[cfg(feature = "rpc")]
+impl LeaderTpuInfoService for ContactInfoCache {
+    fn get_quic_tpu_socket_addr(&self, leader_pubkey: &Pubkey) -> Option<SocketAddr> {
+        self.tpu_quic_addr(leader_pubkey)
+    }
+
+    fn get_quic_tpu_fwd_socket_addr(&self, leader_pubkey: &Pubkey) -> Option<SocketAddr> {
+        self.tpu_forwards_quic_addr(leader_pubkey)
+    }
+}
+
+#[cfg(feature = "rpc")]
+async fn contact_info_cache_refresh_loop(
+    cache: Arc<InnerContactInfoCache>,
+    refresh_interval: Duration,
+    cancellation_token: CancellationToken,
+) {
+    let mut interval = tokio::time::interval(refresh_interval);
+    // The first tick completes immediately, the cache has just been populated.
+    interval.tick().await;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = cancellation_token.cancelled() => {
+                tracing::info!("Cancellation requested, exiting contact info cache refresh loop.");
+                break;
+            }
+        }
+        if let Err(e) = cache.refresh_now().await {
+            // Keep serving cached entries until they become stale.
+            tracing::warn!("Failed to refresh contact info cache: {:?}", e);
+        }
+    }
+}
+
+///
+/// Creates a new [`ContactInfoCache`] along with its background refresh task.
+///
+/// The cache is populated before returning.
+///
+/// Dropping the `JoinHandle` will not stop the background task; to stop it, drop all [`ContactInfoCache`] instances.
+///
+#[cfg(feature = "rpc")]
+pub async fn spawn_contact_info_cache(
+    rpc_client: Arc<RpcClient>,
+    config: ContactInfoCacheConfig,
+) -> Result<(ContactInfoCache, JoinHandle<()>), ClientError> {
+    let cancellation_token = CancellationToken::new();
+    let cache = ContactInfoCache::new(rpc_client, config.max_age, cancellation_token.clone());
+    cache.refresh_now().await?;
+
+    // The background task only holds the inner state, so dropping all handles cancels it.
+    let handle = spawn_named(
+        CONTACT_INFO_CACHE_TASK,
+        contact_info_cache_refresh_loop(
+            Arc::clone(&cache.inner),
+            config.refresh_interval,
+            cancellation_token,
+        ),
+    );
+    Ok((cache, handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::config::TpuPortKind};
+
+    fn contact_info(
+        pubkey: &Pubkey,
+        tpu: Option<SocketAddr>,
+        tpu_quic: Option<SocketAddr>,
+    ) -> RpcContactInfo {
+        RpcContactInfo {
+            pubkey: pubkey.to_string(),
+            gossip: None,
+            tpu,
+            rpc: None,
+            tvu: None,
+            tpu_quic,
+            tpu_forwards: None,
+            tpu_forwards_quic: None,
+            tpu_vote: None,
+            serve_repair: None,
+            pubsub: None,
+            version: None,
+            feature_set: None,
+            shred_version: None,
+        }
+    }
+
+    #[test]
+    fn test_tpu_quic_addr_resolution() {
+        let quic_node = Pubkey::new_unique();
+        let udp_only_node = Pubkey::new_unique();
+        let no_tpu_node = Pubkey::new_unique();
+        let table = ContactInfoTable::from_cluster_nodes(&[
+            contact_info(
+                &quic_node,
+                Some("127.0.0.1:8000".parse().unwrap()),
+                Some("127.0.0.1:9000".parse().unwrap()),
+            ),
+            contact_info(
+                &udp_only_node,
+                Some("127.0.0.1:8000".parse().unwrap()),
+                None,
+            ),
+            contact_info(&no_tpu_node, None, None),
+        ]);
+
+        assert_eq!(table.len(), 3);
+        // Advertised QUIC port wins over the derived one.
+        assert_eq!(
+            table.tpu_quic_addr(&quic_node),
+            Some("127.0.0.1:9000".parse().unwrap())
+        );
+        // Derived from the UDP TPU port.
+        assert_eq!(
+            table.tpu_quic_addr(&udp_only_node),
+            Some(SocketAddr::new(
+                "127.0.0.1".parse().unwrap(),
+                8000 + QUIC_PORT_OFFSET
+            ))
+        );
+        assert_eq!(table.tpu_quic_addr(&no_tpu_node), None);
+        assert_eq!(table.tpu_quic_addr(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_quic_addr_from_udp_addr_overflow() {
+        assert_eq!(
+            quic_addr_from_udp_addr("127.0.0.1:65535".parse().unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tpu_and_tpu_forwards_resolution() {
+        let both_node = Pubkey::new_unique();
+        let tpu_only_node = Pubkey::new_unique();
+        let forwards_only_node = Pubkey::new_unique();
+
+        let mut both = contact_info(&both_node, None, Some("127.0.0.1:9000".parse().unwrap()));
+        both.tpu_forwards_quic = Some("127.0.0.1:9001".parse().unwrap());
+        let tpu_only = contact_info(
+            &tpu_only_node,
+            None,
+            Some("127.0.0.1:9100".parse().unwrap()),
+        );
+        let mut forwards_only = contact_info(&forwards_only_node, None, None);
+        // UDP only forwards port
+        forwards_only.tpu_forwards = Some("127.0.0.1:9200".parse().unwrap());
+
+        let table = ContactInfoTable::from_cluster_nodes(&[both, tpu_only, forwards_only]);
+
+        let info = table.contact_info(&both_node).unwrap();
+        assert_eq!(info.tpu_quic, Some("127.0.0.1:9000".parse().unwrap()));
+        assert_eq!(
+            info.tpu_forwards_quic,
+            Some("127.0.0.1:9001".parse().unwrap())
+        );
+        assert_eq!(
+            info.addr(TpuPortKind::Forwards),
+            table.tpu_forwards_quic_addr(&both_node)
+        );
+        assert_eq!(
+            info.addr(TpuPortKind::Normal),
+            table.tpu_quic_addr(&both_node)
+        );
+
+        let info = table.contact_info(&tpu_only_node).unwrap();
+        assert!(info.tpu_quic.is_some());
+        assert_eq!(info.tpu_forwards_quic, None);
+
+        let info = table.contact_info(&forwards_only_node).unwrap();
+        assert_eq!(info.tpu_quic, None);
+        assert_eq!(
+            info.tpu_forwards_quic,
+            Some(SocketAddr::new(
+                "127.0.0.1".parse().unwrap(),
+                9200 + QUIC_PORT_OFFSET
+            ))
+        );
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_contact_info_cache_entries_expire() {
+        let node = Pubkey::new_unique();
+        let cache = ContactInfoCache::new(
+            Arc::new(RpcClient::new_mock("succeeds".to_string())),
+            Duration::from_millis(50),
+            CancellationToken::new(),
+        );
+        let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        cache
+            .inner
+            .apply_cluster_nodes(&[contact_info(&node, None, Some(addr))], Instant::now());
+
+        assert_eq!(cache.tpu_quic_addr(&node), Some(addr));
+        assert_eq!(cache.tpu_quic_addr(&Pubkey::new_unique()), None);
+        assert_eq!(cache.hit_rate(), 0.5);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(cache.entry_age(&node).unwrap() > Duration::from_millis(50));
+        // Stale entries are not served
+        assert_eq!(cache.tpu_quic_addr(&node), None);
+        assert_eq!(cache.hit_rate(), 1.0 / 3.0);
+
+        // A refresh without the node evicts it, a refresh with it serves it again.
+        cache.inner.apply_cluster_nodes(&[], Instant::now());
+        assert_eq!(cache.entry_age(&node), None);
+        cache
+            .inner
+            .apply_cluster_nodes(&[contact_info(&node, None, Some(addr))], Instant::now());
+        assert_eq!(cache.tpu_quic_addr(&node), Some(addr));
+    }
+}
@@ -1,3 +1,14 @@
+//!
+//! Leader schedule and contact-info data, and their RPC-backed refresh.
+//!
+//! The schedule and contact-info tables are always available. Fetching and refreshing them from a Solana RPC
+//! node requires the `rpc` feature: without it, schedules are static, see
+//! [`schedule::ManagedLeaderSchedule::from_static_schedules`].
+//!
+///
+/// Submodule for contact-info resolution utilities
+///
+pub mod contact;
 ///
 /// Submodule for leader-schedule related utilities
 ///
@@ -5,11 +16,13 @@ pub mod schedule;
 ///
 /// Submodule for Solana RPC utilities
 ///
+#[cfg(feature = "rpc")]
 pub mod solana_rpc_utils;
 
 ///
 /// Submodule for stake-related utilities
 ///
+#[cfg(feature = "rpc")]
 pub mod stake;
 
 ///
@@ -1,39 +1,94 @@
+#[cfg(feature = "metrics")]
+use crate::metrics;
+#[cfg(feature = "rpc")]
 use {
+    crate::{
+        forwarder::jittered_exponential_backoff,
+        task::{LEADER_SCHEDULE_TASK, TaskExitGuard, TaskExitReason, spawn_named},
+    },
     futures::future::join,
+    solana_client::nonblocking::rpc_client::RpcClient,
+    tokio::task::JoinHandle,
+};
+use {
     serde::Deserialize,
-    solana_client::{client_error, nonblocking::rpc_client::RpcClient},
-    solana_clock::DEFAULT_SLOTS_PER_EPOCH,
+    solana_clock::{DEFAULT_SLOTS_PER_EPOCH, Epoch, NUM_CONSECUTIVE_LEADER_SLOTS, Slot},
     solana_pubkey::Pubkey,
+    solana_rpc_client_api::client_error,
     std::{
+        collections::{HashMap, VecDeque},
+        ops::ControlFlow,
         str::FromStr,
-        sync::{Arc, RwLock, atomic::AtomicBool},
+        sync::{
+            Arc, OnceLock, RwLock,
+            atomic::{AtomicBool, AtomicU64, Ordering},
+        },
     },
-    tokio::task::JoinHandle,
+    tokio::sync::{Mutex as AsyncMutex, Notify},
 };
 
 pub const DEFAULT_AUTO_LEADER_SCHEDULE_CHECK_INTERVAL: std::time::Duration =
     std::time::Duration::from_secs(300);
 
+pub const DEFAULT_PREFETCH_SLOTS_BEFORE_EPOCH_END: u64 = 1000;
+
+///
+/// Default delay before the first retry of a failed background schedule update.
+///
+pub const DEFAULT_REFRESH_RETRY_BASE_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+///
+/// Default maximum delay between retries of a failed background schedule update.
+///
+pub const DEFAULT_REFRESH_RETRY_MAX_DELAY: std::time::Duration = std::time::Duration::from_secs(60);
+
+///
+/// Default timeout of the epoch schedule load done by [`ManagedLeaderSchedule::get_leader_async`].
+///
+pub const DEFAULT_LAZY_LOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+///
+/// Default number of epoch schedules kept in memory: current and next epochs.
+///
+pub const DEFAULT_MAX_CACHED_EPOCHS: usize = 2;
+
+///
+/// Initial [`ManagedLeaderSchedule::trigger_prefetch`] generation, never reached by the schedules generation.
+///
+const NO_PREFETCH_GENERATION: u64 = u64::MAX;
+
 ///
 /// A compact representation of the leader schedule for an epoch,
 /// where each leader pubkey appears once for every 4 consecutive slots they lead.
 ///
+/// Cloning is cheap, the schedule is shared behind a reference-counter.
+///
 #[derive(Clone, Debug)]
 pub struct CompactSortedSchedule {
-    pub first_slot: u64,
-    schedule: Vec<Pubkey>,
+    pub first_slot: Slot,
+    schedule: Arc<[Pubkey]>,
 }
 
 impl CompactSortedSchedule {
+    ///
+    /// Creates a schedule from one leader per 4-slot rotation, starting at `first_slot`.
+    ///
+    pub fn new(first_slot: Slot, schedule: Vec<Pubkey>) -> Self {
+        Self {
+            first_slot,
+            schedule: schedule.into(),
+        }
+    }
+
     #[inline]
-    pub fn last_slot(&self) -> u64 {
+    pub fn last_slot(&self) -> Slot {
         self.first_slot + DEFAULT_SLOTS_PER_EPOCH - 1
     }
 
     ///
     /// Get the leader pubkey for a given slot in the epoch
     ///
-    pub fn get(&self, slot: &u64) -> Option<&Pubkey> {
+    pub fn get(&self, slot: &Slot) -> Option<&Pubkey> {
         let first_slot = self.first_slot;
         let max_slot = first_slot + DEFAULT_SLOTS_PER_EPOCH;
         if slot < &first_slot || slot >= &max_slot {
@@ -48,7 +103,7 @@ impl CompactSortedSchedule {
     ///
     /// Iterator over (slot, leader_pubkey) pairs for the entire epoch
     ///
-    pub fn iter_unnested_schedule(&self) -> impl Iterator<Item = (u64, &Pubkey)> {
+    pub fn iter_unnested_schedule(&self) -> impl Iterator<Item = (Slot, &Pubkey)> {
         let first_slot = self.first_slot;
         self.schedule
             .iter()
@@ -63,7 +118,7 @@ impl CompactSortedSchedule {
 }
 
 pub fn unnest_rpc_get_leader_schedule_resp<'a, 'iter>(
-    slot: u64,
+    slot: Slot,
     resp: impl IntoIterator<Item = (&'iter String, &'iter Vec<usize>)>,
 ) -> CompactSortedSchedule
 where
@@ -87,10 +142,11 @@ where
 
     CompactSortedSchedule {
         first_slot: epoch * DEFAULT_SLOTS_PER_EPOCH,
-        schedule: ret,
+        schedule: ret.into(),
     }
 }
 
+#[cfg(feature = "rpc")]
 #[async_trait::async_trait]
 pub trait ScheduleExt {
     ///
@@ -99,16 +155,17 @@ pub trait ScheduleExt {
     ///
     async fn get_unnested_leader_schedule(
         &self,
-        slot_ctx: Option<u64>,
-    ) -> Result<Option<CompactSortedSchedule>, client_error::ClientError>;
+        slot_ctx: Option<Slot>,
+    ) -> Result<Option<CompactSortedSchedule>, client_error::Error>;
 }
 
+#[cfg(feature = "rpc")]
 #[async_trait::async_trait]
 impl ScheduleExt for RpcClient {
     async fn get_unnested_leader_schedule(
         &self,
-        slot_ctx: Option<u64>,
-    ) -> Result<Option<CompactSortedSchedule>, client_error::ClientError> {
+        slot_ctx: Option<Slot>,
+    ) -> Result<Option<CompactSortedSchedule>, client_error::Error> {
         let referenced_slot = match slot_ctx {
             Some(slot) => slot,
             None => {
@@ -128,6 +185,196 @@ impl ScheduleExt for RpcClient {
 struct InnerManagedLeaderSchedule {
     double_buffer: [CompactSortedSchedule; 2],
     fail: AtomicBool,
+    /// Set when the last schedule update failed, the loaded schedules are served until an update succeeds.
+    stale: AtomicBool,
+    /// Leader to leader-boundary slots index of the current epoch, lazily built by `slots_for_leader`.
+    reverse_index: OnceLock<HashMap<Pubkey, Vec<Slot>>>,
+    /// Schedules of epochs older than the current one, sorted by first slot.
+    history: VecDeque<CompactSortedSchedule>,
+    /// Maximum number of schedules kept, including the double buffer.
+    max_cached_epochs: usize,
+    /// Bumped whenever the loaded schedules change or get poisoned, shared with [`ManagedLeaderSchedule`].
+    generation: Arc<AtomicU64>,
+}
+
+impl InnerManagedLeaderSchedule {
+    fn new(double_buffer: [CompactSortedSchedule; 2], max_cached_epochs: usize) -> Self {
+        Self {
+            double_buffer,
+            fail: AtomicBool::new(false),
+            stale: AtomicBool::new(false),
+            reverse_index: OnceLock::new(),
+            history: VecDeque::new(),
+            max_cached_epochs,
+            generation: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    fn bump_generation(&self) {
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+
+    fn poison(&self) {
+        // Relaxed ordering is sufficient here since fail does not protect any data.
+        self.fail.store(true, Ordering::Relaxed);
+        self.bump_generation();
+    }
+
+    fn set_double_buffer(&mut self, double_buffer: [CompactSortedSchedule; 2]) {
+        let previous = std::mem::replace(&mut self.double_buffer, double_buffer);
+        for schedule in previous {
+            self.push_history(schedule);
+        }
+        self.reverse_index = OnceLock::new();
+        self.evict_history();
+        self.bump_generation();
+    }
+
+    fn cached_epoch_count(&self) -> usize {
+        self.double_buffer.len() + self.history.len()
+    }
+
+    ///
+    /// Keeps `schedule` around for lookups in recent history, if it is older than the current epoch.
+    ///
+    fn push_history(&mut self, schedule: CompactSortedSchedule) {
+        if schedule.first_slot >= self.double_buffer[0].first_slot
+            || self
+                .history
+                .iter()
+                .any(|cached| cached.first_slot == schedule.first_slot)
+        {
+            return;
+        }
+        self.history.push_back(schedule);
+        self.history
+            .make_contiguous()
+            .sort_unstable_by_key(|cached| cached.first_slot);
+    }
+
+    ///
+    /// Evicts the oldest schedules until at most `max_cached_epochs` are cached.
+    ///
+    /// The current and next epoch schedules are never evicted.
+    ///
+    fn evict_history(&mut self) {
+        while self.cached_epoch_count() > self.max_cached_epochs {
+            let Some(evicted) = self.history.pop_front() else {
+                break;
+            };
+            tracing::debug!(
+                "AutoLeaderSchedule: evicted epoch {} schedule",
+                evicted.first_slot / DEFAULT_SLOTS_PER_EPOCH
+            );
+        }
+    }
+
+    fn set_next_schedule(&mut self, next_schedule: CompactSortedSchedule) {
+        // The reverse index only covers the current epoch, no need to invalidate it.
+        self.double_buffer[1] = next_schedule;
+        self.bump_generation();
+    }
+
+    ///
+    /// Promotes the next epoch schedule to current, the previous current one is kept
+    /// in history up to `max_cached_epochs`.
+    ///
+    fn rotate(&mut self, next_schedule: CompactSortedSchedule) {
+        self.double_buffer.swap(0, 1);
+        let previous = std::mem::replace(&mut self.double_buffer[1], next_schedule);
+        self.push_history(previous);
+        self.reverse_index = OnceLock::new();
+        self.evict_history();
+        self.bump_generation();
+    }
+
+    fn reverse_index(&self) -> &HashMap<Pubkey, Vec<Slot>> {
+        self.reverse_index.get_or_init(|| {
+            let current = &self.double_buffer[0];
+            let mut index: HashMap<Pubkey, Vec<Slot>> = HashMap::new();
+            for (slot_idx, leader) in current.schedule.iter().enumerate() {
+                if leader == &Pubkey::default() {
+                    continue;
+                }
+                index
+                    .entry(*leader)
+                    .or_default()
+                    .push(current.first_slot + slot_idx as u64 * 4);
+            }
+            index
+        })
+    }
+
+    fn snapshot(&self) -> LeaderScheduleSnapshot {
+        let mut schedules = self
+            .history
+            .iter()
+            .chain(self.double_buffer.iter())
+            .cloned()
+            .collect::<Vec<_>>();
+        schedules.sort_unstable_by_key(|schedule| schedule.first_slot);
+        schedules.dedup_by_key(|schedule| schedule.first_slot);
+        LeaderScheduleSnapshot { schedules }
+    }
+
+    fn lookup_leader(&self, slot: Slot) -> LeaderLookup {
+        let schedule = if slot >= self.double_buffer[1].first_slot {
+            &self.double_buffer[1]
+        } else if slot >= self.double_buffer[0].first_slot {
+            &self.double_buffer[0]
+        } else {
+            self.history
+                .iter()
+                .rev()
+                .find(|cached| slot >= cached.first_slot)
+                .unwrap_or(&self.double_buffer[0])
+        };
+        match schedule.get(&slot) {
+            // Unassigned leader slots are filled with the default pubkey, see `unnest_rpc_get_leader_schedule_resp`.
+            Some(leader) if leader == &Pubkey::default() => LeaderLookup::Gap,
+            Some(leader) => LeaderLookup::Found(*leader),
+            None if slot > schedule.last_slot() => LeaderLookup::PastKnownSchedule,
+            None => LeaderLookup::BeforeKnownSchedule,
+        }
+    }
+
+    fn is_initialized(&self) -> bool {
+        self.double_buffer
+            .iter()
+            .any(|schedule| !schedule.schedule.is_empty())
+    }
+
+    fn loaded_epochs(&self) -> Vec<Epoch> {
+        let mut epochs = self
+            .history
+            .iter()
+            .chain(self.double_buffer.iter())
+            .filter(|schedule| !schedule.schedule.is_empty())
+            .map(|schedule| schedule.first_slot / DEFAULT_SLOTS_PER_EPOCH)
+            .collect::<Vec<_>>();
+        epochs.sort_unstable();
+        epochs.dedup();
+        epochs
+    }
+
+    fn is_poisoned(&self) -> bool {
+        // Relaxed ordering is sufficient here since fail does not protect any data.
+        // We already use RwLock to protect the double_buffer data.
+        self.fail.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    fn is_stale(&self) -> bool {
+        self.stale.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    fn set_stale(&self, stale: bool) {
+        self.stale
+            .store(stale, std::sync::atomic::Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        {
+            metrics::set_leader_schedule_stale(stale);
+        }
+    }
 }
 
 ///
@@ -143,52 +390,875 @@ struct InnerManagedLeaderSchedule {
 ///
 /// You can clone (cheaply) the `ManagedLeaderSchedule` to share it across multiple tasks.
 ///
+/// # Refresh
+///
+/// Use [`ManagedLeaderSchedule::refresh`] or [`ManagedLeaderSchedule::trigger_refresh`] to force a reload
+/// of the current epoch schedules, e.g. when the cached schedule is suspected to be wrong.
+///
+/// # Stale schedule
+///
+/// A failed update, e.g. while the RPC backend is unreachable, keeps the last loaded schedules: lookups keep
+/// being served from them and [`ManagedLeaderSchedule::is_stale`] returns `true` until an update succeeds.
+/// The background task retries failed updates with an exponential backoff.
+///
+/// # Static schedule
+///
+/// Without the `rpc` feature, e.g. when the slot tracker is driven by gRPC only, there is no backend to fetch
+/// schedules from: use [`ManagedLeaderSchedule::from_static_schedules`], loaded from a `getLeaderSchedule` dump
+/// with [`unnest_rpc_get_leader_schedule_resp`].
+///
 #[derive(Clone)]
 pub struct ManagedLeaderSchedule {
     inner: Arc<RwLock<InnerManagedLeaderSchedule>>,
+    /// `None` for static schedules, see [`ManagedLeaderSchedule::from_static_schedules`].
+    #[cfg(feature = "rpc")]
+    rpc_client: Option<Arc<RpcClient>>,
+    refresh_notify: Arc<Notify>,
+    /// See [`ManagedLeaderSchedule::generation`], shared with the inner schedules so it is read without locking.
+    generation: Arc<AtomicU64>,
+    /// Generation at which the last prefetch was triggered, see [`ManagedLeaderSchedule::trigger_prefetch`].
+    prefetch_generation: Arc<AtomicU64>,
+    /// Serializes the epoch loads of [`ManagedLeaderSchedule::get_leader_async`].
+    lazy_load_lock: Arc<AsyncMutex<()>>,
+    lazy_load_timeout: std::time::Duration,
+}
+
+///
+/// A point-in-time copy of the schedules held by a [`ManagedLeaderSchedule`].
+///
+/// See [`ManagedLeaderSchedule::snapshot`], the underlying schedules are shared so taking a snapshot is cheap.
+///
+#[derive(Clone, Debug)]
+pub struct LeaderScheduleSnapshot {
+    /// Cached epoch schedules, sorted by first slot.
+    schedules: Vec<CompactSortedSchedule>,
+}
+
+impl LeaderScheduleSnapshot {
+    ///
+    /// Epoch schedules held by the snapshot, sorted by first slot.
+    ///
+    pub fn schedules(&self) -> &[CompactSortedSchedule] {
+        &self.schedules
+    }
+
+    ///
+    /// Compares the leader of every leader boundary between `self` and a `newer` snapshot.
+    ///
+    /// Returns `(slot, old_leader, new_leader)` for every boundary whose leader differs, where `None`
+    /// means no leader is assigned. Only epochs present in both snapshots are compared.
+    ///
+    pub fn diff(
+        &self,
+        newer: &LeaderScheduleSnapshot,
+    ) -> Vec<(Slot, Option<Pubkey>, Option<Pubkey>)> {
+        let as_leader = |leader: &Pubkey| (leader != &Pubkey::default()).then_some(*leader);
+        let mut diff = Vec::new();
+        for old in &self.schedules {
+            let Some(new) = newer
+                .schedules
+                .iter()
+                .find(|new| new.first_slot == old.first_slot)
+            else {
+                continue;
+            };
+            if Arc::ptr_eq(&old.schedule, &new.schedule) {
+                continue;
+            }
+            let len = old.schedule.len().max(new.schedule.len());
+            for slot_idx in 0..len {
+                let old_leader = old.schedule.get(slot_idx).and_then(as_leader);
+                let new_leader = new.schedule.get(slot_idx).and_then(as_leader);
+                if old_leader != new_leader {
+                    diff.push((old.first_slot + slot_idx as u64 * 4, old_leader, new_leader));
+                }
+            }
+        }
+        diff
+    }
+}
+
+///
+/// Result of [`ManagedLeaderSchedule::lookup_leader`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderLookup {
+    ///
+    /// The leader scheduled for the slot.
+    ///
+    Found(Pubkey),
+    ///
+    /// The slot is past the last slot of the latest known schedule (e.g. next epoch schedule not loaded yet).
+    ///
+    PastKnownSchedule,
+    ///
+    /// The slot is before the first slot of the known schedules.
+    ///
+    BeforeKnownSchedule,
+    ///
+    /// The slot is covered by a known schedule, but no leader is assigned to it.
+    ///
+    Gap,
+}
+
+impl LeaderLookup {
+    ///
+    /// Returns the leader if one was found.
+    ///
+    pub const fn leader(self) -> Option<Pubkey> {
+        match self {
+            LeaderLookup::Found(leader) => Some(leader),
+            _ => None,
+        }
+    }
+}
+
+///
+/// Error indicating that the AutoLeaderSchedule background update task has failed.
+///
+#[derive(Debug, thiserror::Error)]
+#[error("auto leader schedule poisoned")]
+pub struct PoisonError;
+
+///
+/// Error returned by [`ManagedLeaderSchedule::refresh`] and [`ManagedLeaderSchedule::get_leader`].
+///
+#[derive(Debug, thiserror::Error)]
+pub enum ScheduleError {
+    ///
+    /// The background update task has failed, the schedule backend is gone.
+    ///
+    #[error(transparent)]
+    Poisoned(#[from] PoisonError),
+    ///
+    /// The schedules could not be fetched from the RPC backend.
+    ///
+    #[error(transparent)]
+    Rpc(#[from] client_error::Error),
+    ///
+    /// The schedule is static, there is no backend to load schedules from, see
+    /// [`ManagedLeaderSchedule::from_static_schedules`].
+    ///
+    #[error("leader schedule is static")]
+    StaticSchedule,
+    ///
+    /// The RPC backend has no leader schedule for the epoch, e.g. an epoch too far ahead of the cluster.
+    ///
+    #[error("leader schedule not available for epoch {0}")]
+    ScheduleNotAvailable(Epoch),
+    ///
+    /// No schedule has been loaded yet.
+    ///
+    #[error("leader schedule not initialized")]
+    NotInitialized,
+    ///
+    /// The slot is before the first slot of the loaded schedules, e.g. its epoch has been evicted.
+    ///
+    #[error("slot {0} is before the loaded leader schedules")]
+    SlotBeforeLoadedEpoch(Slot),
+    ///
+    /// The slot is past the last slot of the loaded schedules, e.g. the next epoch schedule is not fetched yet.
+    ///
+    #[error("slot {0} is after the loaded leader schedules")]
+    SlotAfterLoadedEpoch(Slot),
+    ///
+    /// The schedule of the epoch could not be loaded within the timeout, see [`ManagedLeaderSchedule::get_leader_async`].
+    ///
+    #[error("timed out loading leader schedule for epoch {0}")]
+    LoadTimeout(Epoch),
+}
+
+///
+/// Error returned by [`ManagedLeaderSchedule::validate_covers`].
+///
+#[derive(Debug, thiserror::Error)]
+pub enum CoverageError {
+    ///
+    /// The background update task has failed, the schedule backend is gone.
+    ///
+    #[error(transparent)]
+    Poisoned(#[from] PoisonError),
+    ///
+    /// No schedule has been loaded yet.
+    ///
+    #[error("leader schedule not initialized")]
+    NotInitialized,
+    ///
+    /// The slot is before the first slot of the loaded schedules.
+    ///
+    #[error("slot {slot} is before the loaded leader schedules")]
+    BeforeKnownSchedule { slot: Slot },
+    ///
+    /// The loaded schedules end before `slot`, short of the requested window.
+    ///
+    #[error("leader schedules end before slot {slot}, coverage requested until slot {until}")]
+    PastKnownSchedule { slot: Slot, until: Slot },
+    ///
+    /// No leader is assigned to `slot` in the loaded schedules.
+    ///
+    #[error("no leader assigned to slot {slot}")]
+    Gap { slot: Slot },
+}
+
+///
+/// Fetches the schedules of `epoch` and `epoch + 1`.
+///
+#[cfg(feature = "rpc")]
+async fn fetch_epoch_schedules(
+    rpc_client: &RpcClient,
+    epoch: Epoch,
+) -> Result<[CompactSortedSchedule; 2], ScheduleError> {
+    let first_slot_current_epoch = epoch * DEFAULT_SLOTS_PER_EPOCH;
+    let next_epoch_first_slot = (epoch + 1) * DEFAULT_SLOTS_PER_EPOCH;
+
+    let current_schedule_fut =
+        rpc_client.get_unnested_leader_schedule(Some(first_slot_current_epoch));
+
+    let next_schedule_fut = rpc_client.get_unnested_leader_schedule(Some(next_epoch_first_slot));
+
+    let (current_schedule, next_schedule) = join(current_schedule_fut, next_schedule_fut).await;
+    let current_schedule = current_schedule?.ok_or(ScheduleError::ScheduleNotAvailable(epoch))?;
+    let next_schedule = next_schedule?.ok_or(ScheduleError::ScheduleNotAvailable(epoch + 1))?;
+    Ok([current_schedule, next_schedule])
+}
+
+fn schedule_range(schedule: &CompactSortedSchedule) -> std::ops::RangeInclusive<Slot> {
+    schedule.first_slot..=schedule.last_slot()
 }
 
-///
-/// Error indicating that the AutoLeaderSchedule background update task has failed.
-///
-#[derive(Debug, thiserror::Error)]
-#[error("auto leader schedule poisoned")]
-pub struct PoisonError;
+impl ManagedLeaderSchedule {
+    ///
+    /// Creates a schedule serving static current and next epoch schedules, never refreshed.
+    ///
+    /// This is the schedule source without the `rpc` feature. [`ManagedLeaderSchedule::refresh`] fails with
+    /// [`ScheduleError::StaticSchedule`] and [`ManagedLeaderSchedule::get_leader_async`] does not load epochs past the
+    /// given ones: the caller swaps in new schedules with [`ManagedLeaderSchedule::set_static_schedules`] as epochs
+    /// progress.
+    ///
+    pub fn from_static_schedules(
+        current: CompactSortedSchedule,
+        next: CompactSortedSchedule,
+    ) -> Self {
+        let inner = InnerManagedLeaderSchedule::new([current, next], DEFAULT_MAX_CACHED_EPOCHS);
+        Self {
+            generation: Arc::clone(&inner.generation),
+            prefetch_generation: Arc::new(AtomicU64::new(NO_PREFETCH_GENERATION)),
+            inner: Arc::new(RwLock::new(inner)),
+            #[cfg(feature = "rpc")]
+            rpc_client: None,
+            refresh_notify: Arc::new(Notify::new()),
+            lazy_load_lock: Default::default(),
+            lazy_load_timeout: DEFAULT_LAZY_LOAD_TIMEOUT,
+        }
+    }
+
+    ///
+    /// Atomically swaps in new current and next epoch schedules, e.g. at an epoch boundary for a static schedule.
+    ///
+    /// The previous schedules are kept in history, up to `max_cached_epochs`, and the stale flag is cleared.
+    ///
+    pub fn set_static_schedules(
+        &self,
+        current: CompactSortedSchedule,
+        next: CompactSortedSchedule,
+    ) {
+        let mut schedules = self.inner.write().unwrap();
+        schedules.set_double_buffer([current, next]);
+        schedules.set_stale(false);
+    }
+
+    ///
+    /// Creates a schedule from static current and next epoch schedules, backed by a mock RPC client.
+    ///
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn from_schedules(current: CompactSortedSchedule, next: CompactSortedSchedule) -> Self {
+        let inner = InnerManagedLeaderSchedule::new([current, next], DEFAULT_MAX_CACHED_EPOCHS);
+        Self {
+            generation: Arc::clone(&inner.generation),
+            prefetch_generation: Arc::new(AtomicU64::new(NO_PREFETCH_GENERATION)),
+            inner: Arc::new(RwLock::new(inner)),
+            #[cfg(feature = "rpc")]
+            rpc_client: Some(Arc::new(RpcClient::new_mock("succeeds".to_string()))),
+            refresh_notify: Arc::new(Notify::new()),
+            lazy_load_lock: Default::default(),
+            lazy_load_timeout: DEFAULT_LAZY_LOAD_TIMEOUT,
+        }
+    }
+
+    ///
+    /// Stages the schedule of an upcoming epoch, e.g. to drive integration tests through epoch transitions.
+    ///
+    /// `schedule` replaces the next epoch schedule if both start at the same slot. If `schedule` is the epoch after
+    /// it, the next epoch is promoted to current first, like the background update task does at an epoch boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `schedule` is neither the next epoch nor the one after it.
+    ///
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn stage_epoch(&self, schedule: CompactSortedSchedule) {
+        let next_first_slot = self.inner.read().unwrap().double_buffer[1].first_slot;
+        if schedule.first_slot == next_first_slot {
+            self.inner.write().unwrap().set_next_schedule(schedule);
+            return;
+        }
+        assert_eq!(
+            schedule.first_slot,
+            next_first_slot + DEFAULT_SLOTS_PER_EPOCH,
+            "only the next epoch or the one after it can be staged"
+        );
+        self.inner.write().unwrap().rotate(schedule);
+    }
+
+    ///
+    /// Creates a schedule cycling through `leaders` from `epoch_start_slot`, each holding `slots_per_leader`
+    /// consecutive slots, e.g. for tests and benchmarks.
+    ///
+    /// Like [`ManagedLeaderSchedule::from_static_schedules`], the schedule is never refreshed.
+    ///
+    /// The current epoch starts at `epoch_start_slot` with `leaders[0]`, the next epoch continues the rotation.
+    /// The leader returned by [`ManagedLeaderSchedule::get_leader`] for `slot` is
+    /// `leaders[((slot - epoch_start_slot) / slots_per_leader) % leaders.len()]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaders` is empty, or if `slots_per_leader` is not a non-zero multiple of
+    /// [`NUM_CONSECUTIVE_LEADER_SLOTS`], the width of the schedule entries.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use {
+    ///     solana_pubkey::Pubkey,
+    ///     std::sync::Arc,
+    ///     yellowstone_jet_tpu_client::{
+    ///         core::UpcomingLeaderPredictor, rpc::schedule::ManagedLeaderSchedule,
+    ///         slot::AtomicSlotTracker, yellowstone_grpc::schedule::YellowstoneUpcomingLeader,
+    ///     },
+    /// };
+    ///
+    /// let [a, b, c] = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+    /// let schedule = ManagedLeaderSchedule::from_static(0, vec![a, b, c], 4);
+    /// assert_eq!(schedule.get_leader(0).unwrap(), Some(a));
+    /// assert_eq!(schedule.get_leader(7).unwrap(), Some(b));
+    /// assert_eq!(schedule.get_leader(12).unwrap(), Some(a));
+    ///
+    /// // Slot 9 is led by `c`, the window starts at the previous leader.
+    /// let predictor =
+    ///     YellowstoneUpcomingLeader::builder(Arc::new(AtomicSlotTracker::new(9)), schedule)
+    ///         .max_slot_age(None)
+    ///         .build()
+    ///         .expect("valid predictor options");
+    /// assert_eq!(predictor.try_predict_next_n_leaders(4), vec![b, c, a, b]);
+    /// ```
+    ///
+    pub fn from_static(
+        epoch_start_slot: Slot,
+        leaders: Vec<Pubkey>,
+        slots_per_leader: u64,
+    ) -> Self {
+        assert!(
+            !leaders.is_empty(),
+            "static schedule needs at least one leader"
+        );
+        assert!(
+            slots_per_leader > 0 && slots_per_leader % NUM_CONSECUTIVE_LEADER_SLOTS == 0,
+            "slots_per_leader must be a non-zero multiple of {NUM_CONSECUTIVE_LEADER_SLOTS}"
+        );
+        let entries_per_leader = (slots_per_leader / NUM_CONSECUTIVE_LEADER_SLOTS) as usize;
+        let entries_per_epoch = (DEFAULT_SLOTS_PER_EPOCH / NUM_CONSECUTIVE_LEADER_SLOTS) as usize;
+        let mut rotation = leaders
+            .iter()
+            .flat_map(|leader| std::iter::repeat_n(*leader, entries_per_leader))
+            .cycle();
+        let current = rotation.by_ref().take(entries_per_epoch).collect();
+        let next = rotation.take(entries_per_epoch).collect();
+        Self::from_static_schedules(
+            CompactSortedSchedule::new(epoch_start_slot, current),
+            CompactSortedSchedule::new(epoch_start_slot + DEFAULT_SLOTS_PER_EPOCH, next),
+        )
+    }
+
+    ///
+    /// Re-fetches the schedules of the current and next epochs and atomically swaps them in.
+    ///
+    /// Concurrent lookups observe either the previous or the new schedules, never a mix of both.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScheduleError`] if the schedule is poisoned or if the schedules could not be fetched,
+    /// in which case the previous schedules are kept.
+    ///
+    pub async fn refresh(&self) -> Result<(), ScheduleError> {
+        if self.inner.read().unwrap().is_poisoned() {
+            return Err(PoisonError.into());
+        }
+        let (epoch, double_buffer) = match self.fetch_current_schedules().await {
+            Ok(fetched) => fetched,
+            Err(ScheduleError::StaticSchedule) => return Err(ScheduleError::StaticSchedule),
+            Err(e) => {
+                self.inner.read().unwrap().set_stale(true);
+                return Err(e);
+            }
+        };
+
+        let mut schedules = self.inner.write().unwrap();
+        tracing::info!(
+            "ManagedLeaderSchedule: refreshing epoch {}, before: current={:?} next={:?}",
+            epoch,
+            schedule_range(&schedules.double_buffer[0]),
+            schedule_range(&schedules.double_buffer[1]),
+        );
+        schedules.set_double_buffer(double_buffer);
+        schedules.set_stale(false);
+        tracing::info!(
+            "ManagedLeaderSchedule: refreshed epoch {}, after: current={:?} next={:?}",
+            epoch,
+            schedule_range(&schedules.double_buffer[0]),
+            schedule_range(&schedules.double_buffer[1]),
+        );
+        Ok(())
+    }
+
+    ///
+    /// Fetches the current epoch and the schedules of the current and next epochs.
+    ///
+    #[cfg(feature = "rpc")]
+    async fn fetch_current_schedules(
+        &self,
+    ) -> Result<(Epoch, [CompactSortedSchedule; 2]), ScheduleError> {
+        let rpc_client = self
+            .rpc_client
+            .as_deref()
+            .ok_or(ScheduleError::StaticSchedule)?;
+        let epoch = rpc_client.get_epoch_info().await?.epoch;
+        fetch_epoch_schedules(rpc_client, epoch)
+            .await
+            .map(|double_buffer| (epoch, double_buffer))
+    }
+
+    #[cfg(not(feature = "rpc"))]
+    async fn fetch_current_schedules(
+        &self,
+    ) -> Result<(Epoch, [CompactSortedSchedule; 2]), ScheduleError> {
+        Err(ScheduleError::StaticSchedule)
+    }
+
+    ///
+    /// Fetches the schedule of `epoch`.
+    ///
+    #[cfg(feature = "rpc")]
+    async fn fetch_epoch_schedule(
+        &self,
+        epoch: Epoch,
+    ) -> Result<CompactSortedSchedule, ScheduleError> {
+        let rpc_client = self
+            .rpc_client
+            .as_deref()
+            .ok_or(ScheduleError::StaticSchedule)?;
+        rpc_client
+            .get_unnested_leader_schedule(Some(epoch * DEFAULT_SLOTS_PER_EPOCH))
+            .await?
+            .ok_or(ScheduleError::ScheduleNotAvailable(epoch))
+    }
+
+    #[cfg(not(feature = "rpc"))]
+    async fn fetch_epoch_schedule(
+        &self,
+        _epoch: Epoch,
+    ) -> Result<CompactSortedSchedule, ScheduleError> {
+        Err(ScheduleError::StaticSchedule)
+    }
+
+    ///
+    /// Returns `true` if schedules can be fetched, `false` for a static schedule.
+    ///
+    fn has_schedule_source(&self) -> bool {
+        #[cfg(feature = "rpc")]
+        {
+            self.rpc_client.is_some()
+        }
+        #[cfg(not(feature = "rpc"))]
+        {
+            false
+        }
+    }
+
+    ///
+    /// Returns `true` if the last schedule update failed, lookups are served from the last loaded schedules.
+    ///
+    pub fn is_stale(&self) -> bool {
+        self.inner.read().unwrap().is_stale()
+    }
+
+    ///
+    /// Counter bumped whenever the loaded schedules change, e.g. on refresh or epoch rotation, or get poisoned.
+    ///
+    /// Read without locking the schedules: results derived from the schedules can be cached and invalidated once
+    /// the generation moves.
+    ///
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    ///
+    /// Asks the background update task to reload the current epoch schedules as soon as possible.
+    ///
+    /// Unlike [`ManagedLeaderSchedule::refresh`], this does not wait for the reload to complete.
+    ///
+    pub fn trigger_refresh(&self) {
+        self.refresh_notify.notify_one();
+    }
+
+    ///
+    /// Asks the background update task to load the schedules missing past the loaded ones, e.g. when a lookup fails
+    /// with [`ScheduleError::SlotAfterLoadedEpoch`] at an epoch rollover, before the next epoch is prefetched.
+    ///
+    /// The refresh loads the schedules of the current cluster epoch and the next one. It is only triggered once
+    /// `current_slot` reached the last loaded epoch: before that, the background task prefetches the next epoch
+    /// ahead of the boundary, and lookups further ahead are past the horizon of the cluster schedules anyway.
+    /// It is triggered at most once per [`ManagedLeaderSchedule::generation`], the lookups failing until the schedules
+    /// change don't trigger a refresh each. Returns `true` if a refresh was triggered.
+    ///
+    pub fn trigger_prefetch(&self, current_slot: Slot) -> bool {
+        let last_loaded_epoch =
+            self.inner.read().unwrap().double_buffer[1].first_slot / DEFAULT_SLOTS_PER_EPOCH;
+        if current_slot / DEFAULT_SLOTS_PER_EPOCH < last_loaded_epoch {
+            return false;
+        }
+        let generation = self.generation();
+        if self.prefetch_generation.swap(generation, Ordering::AcqRel) == generation {
+            return false;
+        }
+        tracing::debug!("ManagedLeaderSchedule: triggering a prefetch at generation {generation}");
+        self.trigger_refresh();
+        true
+    }
+
+    ///
+    /// Consumes a pending refresh request, see [`ManagedLeaderSchedule::trigger_refresh`].
+    ///
+    #[cfg(test)]
+    pub(crate) fn take_refresh_request(&self) -> bool {
+        use futures::FutureExt;
+        self.refresh_notify.notified().now_or_never().is_some()
+    }
+
+    ///
+    /// Get the leader for a given slot.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(Pubkey))` if the leader for the slot is found.
+    /// - `Ok(None)` if the slot is covered by the loaded schedules but has no leader assigned.
+    ///
+    /// # Errors
+    ///
+    /// - [`ScheduleError::Poisoned`] if the background update task has failed.
+    /// - [`ScheduleError::NotInitialized`] if no schedule has been loaded yet.
+    /// - [`ScheduleError::SlotBeforeLoadedEpoch`] or [`ScheduleError::SlotAfterLoadedEpoch`] if the slot is out of
+    ///   range of the loaded schedules.
+    ///
+    pub fn get_leader(&self, slot: Slot) -> Result<Option<Pubkey>, ScheduleError> {
+        let schedules = self.inner.read().unwrap();
+        if schedules.is_poisoned() {
+            return Err(PoisonError.into());
+        }
+        if !schedules.is_initialized() {
+            return Err(ScheduleError::NotInitialized);
+        }
+        match schedules.lookup_leader(slot) {
+            LeaderLookup::Found(leader) => Ok(Some(leader)),
+            LeaderLookup::Gap => Ok(None),
+            LeaderLookup::BeforeKnownSchedule => Err(ScheduleError::SlotBeforeLoadedEpoch(slot)),
+            LeaderLookup::PastKnownSchedule => Err(ScheduleError::SlotAfterLoadedEpoch(slot)),
+        }
+    }
+
+    ///
+    /// Same as [`ManagedLeaderSchedule::get_leader`], but loads the epoch schedule of `slot` if it is not loaded.
+    ///
+    /// Slots covered by the loaded schedules are served from memory without awaiting. On a miss, the epoch schedule
+    /// is fetched from RPC and cached, so the next lookups hit the cache:
+    ///
+    /// - the epoch after the loaded next epoch is promoted, the loaded next epoch becomes current,
+    /// - an epoch older than the loaded schedules is kept in history, up to `max_cached_epochs`,
+    /// - any other epoch is only used to answer this lookup, and a refresh is triggered since the loaded schedules
+    ///   are stale.
+    ///
+    /// Concurrent misses are serialized, the first one loads the epoch and the others hit the cache.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`ManagedLeaderSchedule::get_leader`], plus:
+    ///
+    /// - [`ScheduleError::ScheduleNotAvailable`] if RPC has no schedule for the epoch yet.
+    /// - [`ScheduleError::Rpc`] if the schedule could not be fetched.
+    /// - [`ScheduleError::LoadTimeout`] if the load took longer than `lazy_load_timeout`,
+    ///   see [`ManagedLeaderScheduleConfig::lazy_load_timeout`].
+    ///
+    /// A static schedule, see [`ManagedLeaderSchedule::from_static_schedules`], has nothing to load: misses fail like
+    /// [`ManagedLeaderSchedule::get_leader`].
+    ///
+    pub async fn get_leader_async(&self, slot: Slot) -> Result<Option<Pubkey>, ScheduleError> {
+        match self.get_leader(slot) {
+            Err(
+                ScheduleError::SlotBeforeLoadedEpoch(_) | ScheduleError::SlotAfterLoadedEpoch(_),
+            ) if self.has_schedule_source() => {}
+            result => return result,
+        }
+        let epoch = slot / DEFAULT_SLOTS_PER_EPOCH;
+        tokio::time::timeout(self.lazy_load_timeout, self.load_epoch_for(slot, epoch))
+            .await
+            .map_err(|_| ScheduleError::LoadTimeout(epoch))?
+    }
+
+    async fn load_epoch_for(
+        &self,
+        slot: Slot,
+        epoch: Epoch,
+    ) -> Result<Option<Pubkey>, ScheduleError> {
+        let _guard = self.lazy_load_lock.lock().await;
+        // Another caller may have loaded the epoch while we were waiting for the lock.
+        match self.get_leader(slot) {
+            Err(
+                ScheduleError::SlotBeforeLoadedEpoch(_) | ScheduleError::SlotAfterLoadedEpoch(_),
+            ) => {}
+            result => return result,
+        }
+        tracing::debug!("ManagedLeaderSchedule: lazily loading epoch {epoch} for slot {slot}");
+        let schedule = self.fetch_epoch_schedule(epoch).await?;
+        let leader = schedule
+            .get(&slot)
+            .filter(|leader| **leader != Pubkey::default())
+            .copied();
+
+        let mut schedules = self.inner.write().unwrap();
+        if schedules.is_poisoned() {
+            return Err(PoisonError.into());
+        }
+        let current_epoch = schedules.double_buffer[0].first_slot / DEFAULT_SLOTS_PER_EPOCH;
+        let next_epoch = schedules.double_buffer[1].first_slot / DEFAULT_SLOTS_PER_EPOCH;
+        if epoch == next_epoch + 1 {
+            schedules.rotate(schedule);
+        } else if epoch < current_epoch {
+            schedules.push_history(schedule);
+            schedules.evict_history();
+        } else {
+            tracing::warn!(
+                "ManagedLeaderSchedule: loaded schedules (epochs {current_epoch}..={next_epoch}) are stale for epoch {epoch}, triggering refresh"
+            );
+            self.trigger_refresh();
+        }
+        Ok(leader)
+    }
+
+    ///
+    /// Lookup the leader for a given slot.
+    ///
+    /// Slots past the current epoch are transparently looked up in the next epoch schedule.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoisonError` if the background update task has failed.
+    ///
+    pub fn lookup_leader(&self, slot: Slot) -> Result<LeaderLookup, PoisonError> {
+        let schedules = self.inner.read().unwrap();
+        if schedules.is_poisoned() {
+            return Err(PoisonError);
+        }
+        Ok(schedules.lookup_leader(slot))
+    }
+
+    ///
+    /// Takes a cheap point-in-time copy of the cached schedules.
+    ///
+    pub fn snapshot(&self) -> LeaderScheduleSnapshot {
+        self.inner.read().unwrap().snapshot()
+    }
+
+    ///
+    /// Compares the current schedules against an `other`, older, snapshot.
+    ///
+    /// Returns `(slot, old_leader, new_leader)` for every leader boundary that flipped leaders,
+    /// see [`LeaderScheduleSnapshot::diff`].
+    ///
+    pub fn diff(
+        &self,
+        other: &LeaderScheduleSnapshot,
+    ) -> Vec<(Slot, Option<Pubkey>, Option<Pubkey>)> {
+        other.diff(&self.snapshot())
+    }
+
+    ///
+    /// Number of epoch schedules currently held in memory, bounded by `max_cached_epochs`.
+    ///
+    pub fn cached_epoch_count(&self) -> usize {
+        self.inner.read().unwrap().cached_epoch_count()
+    }
+
+    ///
+    /// Returns `true` once a current or next epoch schedule is loaded, without triggering a load.
+    ///
+    /// Lookups fail with [`ScheduleError::NotInitialized`] until then. Poisoning is reported separately, by the
+    /// lookups themselves.
+    ///
+    pub fn is_initialized(&self) -> bool {
+        self.inner.read().unwrap().is_initialized()
+    }
+
+    ///
+    /// Epochs of the schedules currently held in memory, in ascending order, without triggering a load.
+    ///
+    /// Empty schedules, e.g. before the first load, are not reported.
+    ///
+    pub fn loaded_epochs(&self) -> Vec<Epoch> {
+        self.inner.read().unwrap().loaded_epochs()
+    }
+
+    ///
+    /// Get every leader-boundary slot assigned to `leader` in the current epoch, in ascending order.
+    ///
+    /// This is the inverse of [`ManagedLeaderSchedule::get_leader`]. The reverse index is built on first use
+    /// and rebuilt after the schedule is refreshed.
+    ///
+    /// Returns an empty vec if `leader` is not part of the current epoch schedule.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoisonError` if the background update task has failed.
+    ///
+    pub fn slots_for_leader(&self, leader: &Pubkey) -> Result<Vec<Slot>, PoisonError> {
+        let schedules = self.inner.read().unwrap();
+        if schedules.is_poisoned() {
+            return Err(PoisonError);
+        }
+        Ok(schedules
+            .reverse_index()
+            .get(leader)
+            .cloned()
+            .unwrap_or_default())
+    }
 
-impl ManagedLeaderSchedule {
     ///
-    /// Get the leader for a given slot.
+    /// Get the leaders of `count` slots starting at `start_boundary`, spaced by `stride` slots.
     ///
-    /// # Returns
+    /// Equivalent to calling [`ManagedLeaderSchedule::get_leader`] for each slot, but the internal lock is acquired once.
     ///
-    /// - `Ok(Some(Pubkey))` if the leader for the slot is found.
-    /// - `Ok(None)` if the slot is out of range of the current schedules.
-    /// - `Err(PoisonError)` if the background update task has failed.
+    /// # Errors
+    ///
+    /// Returns `PoisonError` if the background update task has failed.
+    ///
+    pub fn get_leaders_for_range(
+        &self,
+        start_boundary: Slot,
+        count: usize,
+        stride: u64,
+    ) -> Result<Vec<Option<Pubkey>>, PoisonError> {
+        self.lookup_leaders_for_range(start_boundary, count, stride)
+            .map(|lookups| lookups.into_iter().map(LeaderLookup::leader).collect())
+    }
+
+    ///
+    /// Same as [`ManagedLeaderSchedule::get_leaders_for_range`], but returns a [`LeaderLookup`] per slot.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PoisonError` if the background update task has failed.
+    ///
+    pub fn lookup_leaders_for_range(
+        &self,
+        start_boundary: Slot,
+        count: usize,
+        stride: u64,
+    ) -> Result<Vec<LeaderLookup>, PoisonError> {
+        let mut lookups = Vec::with_capacity(count);
+        self.visit_leaders_for_range(start_boundary, count, stride, |_, lookup| {
+            lookups.push(lookup);
+            ControlFlow::Continue(())
+        })?;
+        Ok(lookups)
+    }
+
+    ///
+    /// Calls `f` with the slot and [`LeaderLookup`] of `count` slots starting at `start_boundary`, spaced by `stride` slots.
+    ///
+    /// Unlike [`ManagedLeaderSchedule::lookup_leaders_for_range`], no intermediate vec is allocated and the scan
+    /// stops as soon as `f` returns [`ControlFlow::Break`]. The internal lock is acquired once and held while `f` runs.
     ///
     /// # Errors
     ///
     /// Returns `PoisonError` if the background update task has failed.
     ///
-    pub fn get_leader(&self, slot: u64) -> Result<Option<Pubkey>, PoisonError> {
+    pub fn visit_leaders_for_range<F>(
+        &self,
+        start_boundary: Slot,
+        count: usize,
+        stride: u64,
+        mut f: F,
+    ) -> Result<(), PoisonError>
+    where
+        F: FnMut(Slot, LeaderLookup) -> ControlFlow<()>,
+    {
         let schedules = self.inner.read().unwrap();
-        // Relaxed ordering is sufficient here since fail does not protect any data.
-        // We already use RwLock to protect the double_buffer data.
-        if schedules.fail.load(std::sync::atomic::Ordering::Relaxed) {
+        if schedules.is_poisoned() {
             return Err(PoisonError);
         }
-        let schedule = if slot >= schedules.double_buffer[1].first_slot {
-            &schedules.double_buffer[1]
-        } else {
-            &schedules.double_buffer[0]
-        };
-        Ok(schedule.get(&slot).cloned())
+        for i in 0..count as u64 {
+            let slot = start_boundary.saturating_add(i * stride);
+            if f(slot, schedules.lookup_leader(slot)).is_break() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Checks that every leader boundary from `current_slot` to `current_slot + future_slots` has a known leader.
+    ///
+    /// Meant to be called at startup, before serving predictions: a schedule that does not cover the prediction
+    /// window would otherwise silently yield empty or short predictions.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first uncovered slot, see [`CoverageError`].
+    ///
+    pub fn validate_covers(
+        &self,
+        current_slot: Slot,
+        future_slots: u64,
+    ) -> Result<(), CoverageError> {
+        let schedules = self.inner.read().unwrap();
+        if schedules.is_poisoned() {
+            return Err(PoisonError.into());
+        }
+        if !schedules.is_initialized() {
+            return Err(CoverageError::NotInitialized);
+        }
+        let until = current_slot.saturating_add(future_slots);
+        let first_boundary = current_slot - current_slot % NUM_CONSECUTIVE_LEADER_SLOTS;
+        for slot in (first_boundary..=until).step_by(NUM_CONSECUTIVE_LEADER_SLOTS as usize) {
+            match schedules.lookup_leader(slot) {
+                LeaderLookup::Found(_) => {}
+                LeaderLookup::Gap => return Err(CoverageError::Gap { slot }),
+                LeaderLookup::BeforeKnownSchedule => {
+                    return Err(CoverageError::BeforeKnownSchedule { slot });
+                }
+                LeaderLookup::PastKnownSchedule => {
+                    return Err(CoverageError::PastKnownSchedule { slot, until });
+                }
+            }
+        }
+        Ok(())
     }
 }
 
+#[cfg(feature = "rpc")]
 async fn auto_leader_schedule_loop(
     config: ManagedLeaderScheduleConfig,
     shared: Arc<RwLock<InnerManagedLeaderSchedule>>,
     rpc_client: Arc<RpcClient>,
+    refresh_notify: Arc<Notify>,
     cancellation_token: tokio_util::sync::CancellationToken,
 ) {
     pub struct OnDrop {
@@ -203,9 +1273,7 @@ async fn auto_leader_schedule_loop(
                 };
                 // Since we are already synchronously dropping (RwLock), it's safe to use Relaxed ordering here.
                 // This trick have been taking from std::sync::poison implementation!
-                schedules
-                    .fail
-                    .store(true, std::sync::atomic::Ordering::Relaxed);
+                schedules.poison();
             }
         }
     }
@@ -213,70 +1281,171 @@ async fn auto_leader_schedule_loop(
     let initial = shared.read().expect("read").double_buffer.clone();
 
     let mut current_epoch = initial[0].first_slot / DEFAULT_SLOTS_PER_EPOCH;
+    let mut failed_attempts = 0;
+    let mut retry_forced_refresh = false;
 
     loop {
-        tokio::select! {
-            _ = tokio::time::sleep(config.check_interval) => { }
+        let wait = if failed_attempts == 0 {
+            config.check_interval
+        } else {
+            jittered_exponential_backoff(
+                config.refresh_retry_base_delay,
+                config.refresh_retry_max_delay,
+                failed_attempts,
+            )
+        };
+        let forced_refresh = tokio::select! {
+            _ = tokio::time::sleep(wait) => false,
+            _ = refresh_notify.notified() => true,
             _ = cancellation_token.cancelled() => {
                 tracing::info!("AutoLeaderSchedule: cancellation requested, exiting loop");
                 break;
             }
-        }
+        };
+        // A failed forced refresh is retried as such.
+        let forced_refresh = forced_refresh || retry_forced_refresh;
 
-        let epoch = rpc_client
-            .get_epoch_info()
-            .await
-            .expect("rpc_client.get_epoch_info")
-            .epoch;
+        match update_schedules(
+            &config,
+            &shared,
+            &rpc_client,
+            &mut current_epoch,
+            forced_refresh,
+        )
+        .await
+        {
+            Ok(()) => {
+                if failed_attempts > 0 {
+                    tracing::info!(
+                        "AutoLeaderSchedule: schedule update recovered after {} failed attempts",
+                        failed_attempts
+                    );
+                    shared.read().expect("read").set_stale(false);
+                }
+                failed_attempts = 0;
+                retry_forced_refresh = false;
+            }
+            Err(e) => {
+                failed_attempts += 1;
+                retry_forced_refresh = forced_refresh;
+                tracing::warn!(
+                    "AutoLeaderSchedule: schedule update failed (attempt {}), serving the last loaded schedule: {}",
+                    failed_attempts,
+                    e
+                );
+                shared.read().expect("read").set_stale(true);
+            }
+        }
+    }
+}
 
-        if epoch == current_epoch {
-            tracing::debug!("AutoLeaderSchedule: still in epoch {}", current_epoch);
+///
+/// Runs one check of the background update task, fetching new schedules if needed.
+///
+/// `current_epoch` only advances once the schedules of the new epoch are loaded, a failed update leaves the loaded
+/// schedules untouched.
+///
+#[cfg(feature = "rpc")]
+async fn update_schedules(
+    config: &ManagedLeaderScheduleConfig,
+    shared: &RwLock<InnerManagedLeaderSchedule>,
+    rpc_client: &RpcClient,
+    current_epoch: &mut Epoch,
+    forced_refresh: bool,
+) -> Result<(), ScheduleError> {
+    let epoch_info = rpc_client.get_epoch_info().await?;
+    let epoch = epoch_info.epoch;
+    let first_slot_epoch = epoch * DEFAULT_SLOTS_PER_EPOCH;
+    let next_epoch_first_slot = (epoch + 1) * DEFAULT_SLOTS_PER_EPOCH;
+    let loaded_next_first_slot = shared.read().expect("read").double_buffer[1].first_slot;
 
-            // Only fetch the next schedule if we're still in the same epoch
-            // Making sure we have the freshest schedule ready when we transition
-            let next_epoch_first_slot = (current_epoch + 1) * DEFAULT_SLOTS_PER_EPOCH;
-            let next_schedule = rpc_client
-                .get_unnested_leader_schedule(Some(next_epoch_first_slot))
-                .await
-                .expect("rpc_client.get_unnested_leader_schedule next")
-                .expect("None next schedule");
-            {
-                let mut schedules = shared.write().expect("write");
-                schedules.double_buffer[1] = next_schedule;
-            }
+    if epoch == *current_epoch && !forced_refresh {
+        let slots_left = epoch_info
+            .slots_in_epoch
+            .saturating_sub(epoch_info.slot_index);
+        let next_loaded = loaded_next_first_slot == next_epoch_first_slot;
+        if next_loaded
+            && !should_prefetch_next_epoch(slots_left, config.prefetch_slots_before_epoch_end)
+        {
+            tracing::debug!(
+                "AutoLeaderSchedule: still in epoch {}, {} slots left",
+                current_epoch,
+                slots_left
+            );
+            return Ok(());
+        }
 
-            continue;
+        // Fetch the next schedule ahead of the epoch boundary
+        // Making sure we have the freshest schedule ready when we transition
+        tracing::debug!(
+            "AutoLeaderSchedule: prefetching epoch {} schedule, {} slots left in epoch {}",
+            *current_epoch + 1,
+            slots_left,
+            current_epoch
+        );
+        let next_schedule = rpc_client
+            .get_unnested_leader_schedule(Some(next_epoch_first_slot))
+            .await?
+            .ok_or(ScheduleError::ScheduleNotAvailable(epoch + 1))?;
+        shared
+            .write()
+            .expect("write")
+            .set_next_schedule(next_schedule);
+    } else if !forced_refresh
+        && epoch == *current_epoch + 1
+        && loaded_next_first_slot == first_slot_epoch
+    {
+        // The prefetched schedule already covers the new epoch, rotate the buffers
+        // so the previous epoch schedule is dropped.
+        tracing::info!(
+            "AutoLeaderSchedule: detected epoch change {} -> {}, rotating schedules",
+            current_epoch,
+            epoch
+        );
+        let next_schedule = rpc_client
+            .get_unnested_leader_schedule(Some(next_epoch_first_slot))
+            .await?
+            .ok_or(ScheduleError::ScheduleNotAvailable(epoch + 1))?;
+        shared.write().expect("write").rotate(next_schedule);
+        *current_epoch = epoch;
+    } else {
+        if forced_refresh {
+            tracing::info!("AutoLeaderSchedule: forced refresh of epoch {}", epoch);
         } else {
             tracing::info!(
                 "AutoLeaderSchedule: detected epoch change {} -> {}",
                 current_epoch,
                 epoch
             );
-            current_epoch = epoch;
-            let first_slot_current_epoch = current_epoch * DEFAULT_SLOTS_PER_EPOCH;
-            let next_epoch_first_slot = (current_epoch + 1) * DEFAULT_SLOTS_PER_EPOCH;
-
-            let current_schedule_fut =
-                rpc_client.get_unnested_leader_schedule(Some(first_slot_current_epoch));
-
-            let next_schedule_fut =
-                rpc_client.get_unnested_leader_schedule(Some(next_epoch_first_slot));
-
-            let (current_schedule, next_schedule) =
-                join(current_schedule_fut, next_schedule_fut).await;
-            let current_schedule = current_schedule
-                .expect("rpc_client.get_unnested_leader_schedule current")
-                .expect("None current schedule");
-            let next_schedule = next_schedule
-                .expect("rpc_client.get_unnested_leader_schedule next")
-                .expect("None next schedule");
-
-            {
-                let mut schedules = shared.write().expect("write");
-                schedules.double_buffer = [current_schedule, next_schedule];
-            }
         }
+        let double_buffer = fetch_epoch_schedules(rpc_client, epoch).await?;
+
+        let mut schedules = shared.write().expect("write");
+        tracing::info!(
+            "AutoLeaderSchedule: before: current={:?} next={:?}",
+            schedule_range(&schedules.double_buffer[0]),
+            schedule_range(&schedules.double_buffer[1]),
+        );
+        schedules.set_double_buffer(double_buffer);
+        tracing::info!(
+            "AutoLeaderSchedule: after: current={:?} next={:?}",
+            schedule_range(&schedules.double_buffer[0]),
+            schedule_range(&schedules.double_buffer[1]),
+        );
+        *current_epoch = epoch;
     }
+    Ok(())
+}
+
+///
+/// Returns `true` once the epoch enters its last `prefetch_slots_before_epoch_end` slots.
+///
+#[cfg(feature = "rpc")]
+fn should_prefetch_next_epoch(
+    slots_left_in_epoch: u64,
+    prefetch_slots_before_epoch_end: u64,
+) -> bool {
+    slots_left_in_epoch <= prefetch_slots_before_epoch_end
 }
 
 ///
@@ -291,6 +1460,45 @@ pub struct ManagedLeaderScheduleConfig {
         default = "ManagedLeaderScheduleConfig::default_check_interval"
     )]
     pub check_interval: std::time::Duration,
+    ///
+    /// How many slots before the end of the epoch the next epoch schedule is re-fetched.
+    ///
+    /// `check_interval` should be shorter than the duration of this window for the prefetch to happen.
+    ///
+    #[serde(default = "ManagedLeaderScheduleConfig::default_prefetch_slots_before_epoch_end")]
+    pub prefetch_slots_before_epoch_end: u64,
+    ///
+    /// Maximum number of epoch schedules kept in memory, including the current and next epochs.
+    ///
+    /// Schedules of epochs older than the current one are kept for lookups in recent history and evicted oldest first.
+    /// Values below [`DEFAULT_MAX_CACHED_EPOCHS`] only keep the current and next epochs.
+    ///
+    #[serde(default = "ManagedLeaderScheduleConfig::default_max_cached_epochs")]
+    pub max_cached_epochs: usize,
+    ///
+    /// Timeout of the epoch schedule load done by [`ManagedLeaderSchedule::get_leader_async`] on a miss.
+    ///
+    #[serde(
+        with = "humantime_serde",
+        default = "ManagedLeaderScheduleConfig::default_lazy_load_timeout"
+    )]
+    pub lazy_load_timeout: std::time::Duration,
+    ///
+    /// Delay before the first retry of a failed background schedule update, doubling on each failure.
+    ///
+    #[serde(
+        with = "humantime_serde",
+        default = "ManagedLeaderScheduleConfig::default_refresh_retry_base_delay"
+    )]
+    pub refresh_retry_base_delay: std::time::Duration,
+    ///
+    /// Maximum delay between retries of a failed background schedule update.
+    ///
+    #[serde(
+        with = "humantime_serde",
+        default = "ManagedLeaderScheduleConfig::default_refresh_retry_max_delay"
+    )]
+    pub refresh_retry_max_delay: std::time::Duration,
 }
 
 impl ManagedLeaderScheduleConfig {
@@ -300,12 +1508,52 @@ impl ManagedLeaderScheduleConfig {
     pub fn default_check_interval() -> std::time::Duration {
         DEFAULT_AUTO_LEADER_SCHEDULE_CHECK_INTERVAL
     }
+
+    ///
+    /// Default number of slots before the end of the epoch to prefetch the next epoch schedule.
+    ///
+    pub const fn default_prefetch_slots_before_epoch_end() -> u64 {
+        DEFAULT_PREFETCH_SLOTS_BEFORE_EPOCH_END
+    }
+
+    ///
+    /// Default maximum number of cached epoch schedules.
+    ///
+    pub const fn default_max_cached_epochs() -> usize {
+        DEFAULT_MAX_CACHED_EPOCHS
+    }
+
+    ///
+    /// Default timeout of lazy epoch schedule loads.
+    ///
+    pub const fn default_lazy_load_timeout() -> std::time::Duration {
+        DEFAULT_LAZY_LOAD_TIMEOUT
+    }
+
+    ///
+    /// Default delay before the first retry of a failed schedule update.
+    ///
+    pub const fn default_refresh_retry_base_delay() -> std::time::Duration {
+        DEFAULT_REFRESH_RETRY_BASE_DELAY
+    }
+
+    ///
+    /// Default maximum delay between retries of a failed schedule update.
+    ///
+    pub const fn default_refresh_retry_max_delay() -> std::time::Duration {
+        DEFAULT_REFRESH_RETRY_MAX_DELAY
+    }
 }
 
 impl Default for ManagedLeaderScheduleConfig {
     fn default() -> Self {
         Self {
             check_interval: Self::default_check_interval(),
+            prefetch_slots_before_epoch_end: Self::default_prefetch_slots_before_epoch_end(),
+            max_cached_epochs: Self::default_max_cached_epochs(),
+            lazy_load_timeout: Self::default_lazy_load_timeout(),
+            refresh_retry_base_delay: Self::default_refresh_retry_base_delay(),
+            refresh_retry_max_delay: Self::default_refresh_retry_max_delay(),
         }
     }
 }
@@ -313,10 +1561,31 @@ impl Default for ManagedLeaderScheduleConfig {
 ///
 /// Spawn a managed leader schedule that automatically updates as epochs progress.
 ///
+#[cfg(feature = "rpc")]
 pub async fn spawn_managed_leader_schedule(
     rpc_client: Arc<RpcClient>,
     config: ManagedLeaderScheduleConfig,
-) -> Result<(ManagedLeaderSchedule, JoinHandle<()>), client_error::ClientError> {
+) -> Result<(ManagedLeaderSchedule, JoinHandle<()>), client_error::Error> {
+    spawn_managed_leader_schedule_with_cancellation(
+        rpc_client,
+        config,
+        tokio_util::sync::CancellationToken::new(),
+    )
+    .await
+}
+
+///
+/// Same as [`spawn_managed_leader_schedule`], the background task exits as soon as `cancellation_token` is cancelled,
+/// including while an RPC request is in flight.
+///
+/// Once the task exited, lookups on the schedule return [`PoisonError`].
+///
+#[cfg(feature = "rpc")]
+pub async fn spawn_managed_leader_schedule_with_cancellation(
+    rpc_client: Arc<RpcClient>,
+    config: ManagedLeaderScheduleConfig,
+    cancellation_token: tokio_util::sync::CancellationToken,
+) -> Result<(ManagedLeaderSchedule, JoinHandle<()>), client_error::Error> {
     let initial_schedule = rpc_client
         .get_unnested_leader_schedule(None)
         .await?
@@ -330,30 +1599,72 @@ pub async fn spawn_managed_leader_schedule(
         .await?
         .expect("Failed to fetch next leader schedule");
 
-    let shared = Arc::new(RwLock::new(InnerManagedLeaderSchedule {
-        double_buffer: [initial_schedule, next_schedule],
-        fail: AtomicBool::new(false),
-    }));
+    let shared = Arc::new(RwLock::new(InnerManagedLeaderSchedule::new(
+        [initial_schedule, next_schedule],
+        config.max_cached_epochs,
+    )));
 
+    let lazy_load_timeout = config.lazy_load_timeout;
     let shared_clone = shared.clone();
-    let cancellation_token = tokio_util::sync::CancellationToken::new();
+    let refresh_notify = Arc::new(Notify::new());
+    let loop_refresh_notify = Arc::clone(&refresh_notify);
+    let loop_rpc_client = Arc::clone(&rpc_client);
     let loop_ct = cancellation_token.clone();
-    let jh = tokio::spawn(async move {
-        auto_leader_schedule_loop(config, shared_clone, rpc_client, loop_ct).await;
+    let jh = spawn_named(LEADER_SCHEDULE_TASK, async move {
+        let exit = TaskExitGuard::new(LEADER_SCHEDULE_TASK);
+        cancellation_token
+            .run_until_cancelled(auto_leader_schedule_loop(
+                config,
+                shared_clone,
+                loop_rpc_client,
+                loop_refresh_notify,
+                loop_ct,
+            ))
+            .await;
+        // The loop only exits once cancelled, a panic unwinds past this point.
+        exit.set_reason(TaskExitReason::Shutdown);
     });
 
-    Ok((ManagedLeaderSchedule { inner: shared }, jh))
+    Ok((
+        ManagedLeaderSchedule {
+            generation: Arc::clone(&shared.read().expect("read").generation),
+            prefetch_generation: Arc::new(AtomicU64::new(NO_PREFETCH_GENERATION)),
+            inner: shared,
+            rpc_client: Some(rpc_client),
+            refresh_notify,
+            lazy_load_lock: Default::default(),
+            lazy_load_timeout,
+        },
+        jh,
+    ))
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "rpc"))]
 mod tests {
     use {
+        super::{
+            CompactSortedSchedule, LeaderLookup, ManagedLeaderSchedule,
+            ManagedLeaderScheduleConfig, ScheduleError, auto_leader_schedule_loop,
+        },
         rand::distr::{Distribution, weighted::WeightedIndex},
+        serde_json::json,
+        solana_client::nonblocking::rpc_client::RpcClient,
         solana_clock::DEFAULT_SLOTS_PER_EPOCH,
         solana_pubkey::Pubkey,
-        std::collections::BTreeMap,
+        solana_rpc_client_api::request::RpcRequest,
+        std::{
+            collections::{BTreeMap, HashMap},
+            ops::ControlFlow,
+            sync::Arc,
+            time::Duration,
+        },
+        tokio_util::sync::CancellationToken,
     };
 
+    fn unique_leaders(n: u64) -> Vec<Pubkey> {
+        (0..n).map(|_| Pubkey::new_unique()).collect()
+    }
+
     fn exponential_distribution(n: usize, base: f64, r: f64) -> Vec<u64> {
         // returns a vector of stake weights
         (0..n).map(|i| (base * r.powi(i as i32)) as u64).collect()
@@ -396,7 +1707,6 @@ mod tests {
                 nested_schedule.contains_key(&pubkey_str),
                 "Unknown pubkey {pubkey_str}",
             );
-            // println!("Slot {} assigned to leader {}", slot, pubkey_str);
             actual.entry(pubkey_str).or_default().push(slot as usize);
         }
 
@@ -410,4 +1720,730 @@ mod tests {
         assert!(diff.is_empty(), "Mismatched keys: {diff:?}");
         assert_eq!(nested_schedule, actual);
     }
+
+    #[test]
+    fn test_lookup_leader_across_epoch_boundary() {
+        let current_leaders = (0..DEFAULT_SLOTS_PER_EPOCH / 4)
+            .map(|_| Pubkey::new_unique())
+            .collect::<Vec<_>>();
+        let mut next_leaders = (0..DEFAULT_SLOTS_PER_EPOCH / 4)
+            .map(|_| Pubkey::new_unique())
+            .collect::<Vec<_>>();
+        // Simulate a hole in the RPC response
+        next_leaders[1] = Pubkey::default();
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, current_leaders.clone()),
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH * 2, next_leaders.clone()),
+        );
+
+        let last_slot_current_epoch = DEFAULT_SLOTS_PER_EPOCH * 2 - 1;
+        assert_eq!(
+            schedule.lookup_leader(last_slot_current_epoch).unwrap(),
+            LeaderLookup::Found(*current_leaders.last().unwrap())
+        );
+        assert_eq!(
+            schedule.lookup_leader(last_slot_current_epoch + 1).unwrap(),
+            LeaderLookup::Found(next_leaders[0])
+        );
+        assert_eq!(
+            schedule.lookup_leader(last_slot_current_epoch + 5).unwrap(),
+            LeaderLookup::Gap
+        );
+        assert_eq!(
+            schedule.get_leader(last_slot_current_epoch + 5).unwrap(),
+            None
+        );
+        assert_eq!(
+            schedule.lookup_leader(DEFAULT_SLOTS_PER_EPOCH * 3).unwrap(),
+            LeaderLookup::PastKnownSchedule
+        );
+        assert_eq!(
+            schedule.lookup_leader(DEFAULT_SLOTS_PER_EPOCH - 1).unwrap(),
+            LeaderLookup::BeforeKnownSchedule
+        );
+    }
+
+    #[test]
+    fn test_get_leaders_for_range_matches_get_leader() {
+        let mut current_leaders = unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4);
+        current_leaders[2] = Pubkey::default();
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, current_leaders),
+            CompactSortedSchedule::new(
+                DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+        );
+
+        // Crosses the epoch boundary and runs past the known schedules.
+        for start in [
+            0,
+            DEFAULT_SLOTS_PER_EPOCH - 8,
+            DEFAULT_SLOTS_PER_EPOCH * 2 - 8,
+        ] {
+            let expected = (0..6)
+                .map(|i| schedule.get_leader(start + i * 4).unwrap())
+                .collect::<Vec<_>>();
+            let actual = schedule.get_leaders_for_range(start, 6, 4).unwrap();
+            assert_eq!(actual, expected);
+        }
+        assert_eq!(schedule.get_leaders_for_range(0, 6, 4).unwrap()[2], None);
+        assert!(schedule.get_leaders_for_range(0, 0, 4).unwrap().is_empty());
+
+        schedule
+            .inner
+            .read()
+            .unwrap()
+            .fail
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        assert!(schedule.get_leaders_for_range(0, 6, 4).is_err());
+    }
+
+    #[test]
+    fn test_visit_leaders_for_range_stops_on_break() {
+        let current_leaders = unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4);
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, current_leaders.clone()),
+            CompactSortedSchedule::new(
+                DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+        );
+
+        let mut visited = Vec::new();
+        schedule
+            .visit_leaders_for_range(8, 10, 4, |slot, lookup| {
+                visited.push((slot, lookup));
+                if visited.len() == 3 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            })
+            .unwrap();
+        assert_eq!(
+            visited,
+            vec![
+                (8, LeaderLookup::Found(current_leaders[2])),
+                (12, LeaderLookup::Found(current_leaders[3])),
+                (16, LeaderLookup::Found(current_leaders[4])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slots_for_leader() {
+        let mut current_leaders = unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4);
+        let leader = current_leaders[1];
+        current_leaders[5] = leader;
+        let next_leaders = unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4);
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, current_leaders.clone()),
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH * 2, next_leaders.clone()),
+        );
+
+        assert_eq!(
+            schedule.slots_for_leader(&leader).unwrap(),
+            vec![DEFAULT_SLOTS_PER_EPOCH + 4, DEFAULT_SLOTS_PER_EPOCH + 20]
+        );
+        for slot in schedule.slots_for_leader(&leader).unwrap() {
+            assert_eq!(schedule.get_leader(slot).unwrap(), Some(leader));
+        }
+        assert!(
+            schedule
+                .slots_for_leader(&Pubkey::new_unique())
+                .unwrap()
+                .is_empty()
+        );
+        // Only the current epoch is indexed
+        assert!(
+            schedule
+                .slots_for_leader(&next_leaders[0])
+                .unwrap()
+                .is_empty()
+        );
+
+        // Refreshing the schedule invalidates the reverse index
+        schedule.inner.write().unwrap().set_double_buffer([
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH * 2, next_leaders.clone()),
+            CompactSortedSchedule::new(
+                DEFAULT_SLOTS_PER_EPOCH * 3,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+        ]);
+        assert!(schedule.slots_for_leader(&leader).unwrap().is_empty());
+        assert_eq!(
+            schedule.slots_for_leader(&next_leaders[0]).unwrap(),
+            vec![DEFAULT_SLOTS_PER_EPOCH * 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_should_fail_when_poisoned() {
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4)),
+            CompactSortedSchedule::new(
+                DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+        );
+        schedule
+            .inner
+            .read()
+            .unwrap()
+            .fail
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        assert!(matches!(
+            schedule.refresh().await,
+            Err(ScheduleError::Poisoned(_))
+        ));
+    }
+
+    ///
+    /// Mock RPC client reporting `epoch` as the current epoch, with `leader` leading the first slots of one
+    /// fetched epoch schedule: each mocked response is served once, the other schedule fetch gets an empty one.
+    ///
+    fn mock_rpc_client(epoch: u64, leader: Pubkey) -> Arc<RpcClient> {
+        Arc::new(RpcClient::new_mock_with_mocks(
+            "succeeds".to_string(),
+            HashMap::from([
+                (
+                    RpcRequest::GetEpochInfo,
+                    json!({
+                        "epoch": epoch,
+                        "slotIndex": 0,
+                        "slotsInEpoch": DEFAULT_SLOTS_PER_EPOCH,
+                        "absoluteSlot": epoch * DEFAULT_SLOTS_PER_EPOCH,
+                        "blockHeight": epoch * DEFAULT_SLOTS_PER_EPOCH,
+                        "transactionCount": null,
+                    }),
+                ),
+                (
+                    RpcRequest::GetLeaderSchedule,
+                    json!({ (leader.to_string()): [0, 1, 2, 3] }),
+                ),
+            ]),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_refresh_should_swap_in_fetched_schedules() {
+        let previous_leaders = unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4);
+        let mut schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, previous_leaders.clone()),
+            CompactSortedSchedule::new(
+                DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+        );
+        let leader = Pubkey::new_unique();
+        schedule.rpc_client = Some(mock_rpc_client(2, leader));
+        schedule.inner.read().unwrap().set_stale(true);
+        let generation = schedule.generation();
+        assert_eq!(schedule.get_leader(0).unwrap(), Some(previous_leaders[0]));
+
+        schedule.refresh().await.expect("refresh");
+
+        assert!(schedule.generation() > generation);
+        assert!(!schedule.is_stale());
+        assert_eq!(schedule.loaded_epochs(), vec![2, 3]);
+        let refreshed = [2 * DEFAULT_SLOTS_PER_EPOCH, 3 * DEFAULT_SLOTS_PER_EPOCH]
+            .map(|slot| schedule.get_leader(slot).expect("refreshed epoch"));
+        assert!(refreshed.contains(&Some(leader)), "{refreshed:?}");
+    }
+
+    #[tokio::test]
+    async fn test_trigger_refresh_should_reload_current_epoch() {
+        let previous_leaders = unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4);
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, previous_leaders.clone()),
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, previous_leaders.clone()),
+        );
+        // Pending requests are consumed once.
+        schedule.trigger_refresh();
+        assert!(schedule.take_refresh_request());
+        assert!(!schedule.take_refresh_request());
+
+        // The periodic check never fires, only the triggered refresh reloads the schedules.
+        let config = ManagedLeaderScheduleConfig {
+            check_interval: Duration::from_secs(3600),
+            ..Default::default()
+        };
+        let leader = Pubkey::new_unique();
+        let cancellation_token = CancellationToken::new();
+        let jh = tokio::spawn(auto_leader_schedule_loop(
+            config,
+            Arc::clone(&schedule.inner),
+            mock_rpc_client(0, leader),
+            Arc::clone(&schedule.refresh_notify),
+            cancellation_token.clone(),
+        ));
+        let generation = schedule.generation();
+        schedule.trigger_refresh();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while schedule.generation() == generation {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("schedules refreshed");
+
+        let refreshed = [0, DEFAULT_SLOTS_PER_EPOCH]
+            .map(|slot| schedule.get_leader(slot).expect("refreshed epoch"));
+        assert!(refreshed.contains(&Some(leader)), "{refreshed:?}");
+        assert!(
+            !refreshed.contains(&Some(previous_leaders[0])),
+            "{refreshed:?}"
+        );
+
+        cancellation_token.cancel();
+        jh.await.expect("update task");
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_should_keep_serving_stale_schedule() {
+        let leaders = unique_leaders(3);
+        let schedule = ManagedLeaderSchedule::from_static(0, leaders.clone(), 4);
+        let config = ManagedLeaderScheduleConfig {
+            check_interval: Duration::from_millis(10),
+            refresh_retry_base_delay: Duration::from_millis(10),
+            refresh_retry_max_delay: Duration::from_millis(10),
+            ..Default::default()
+        };
+        // The mock RPC backend fails every request.
+        let rpc_client = Arc::new(RpcClient::new_mock("fails".to_string()));
+        let cancellation_token = CancellationToken::new();
+        let jh = tokio::spawn(auto_leader_schedule_loop(
+            config,
+            Arc::clone(&schedule.inner),
+            Arc::clone(&rpc_client),
+            Arc::clone(&schedule.refresh_notify),
+            cancellation_token.clone(),
+        ));
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !schedule.is_stale() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("schedule marked stale");
+        schedule.trigger_refresh();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert!(!jh.is_finished(), "update task survived the failures");
+        assert!(schedule.is_stale());
+        for slot in [0, 5, 10, DEFAULT_SLOTS_PER_EPOCH] {
+            let expected = leaders[(slot / 4) as usize % leaders.len()];
+            assert_eq!(schedule.get_leader(slot).unwrap(), Some(expected));
+        }
+
+        // An explicit refresh failure is non-destructive too.
+        let rpc_schedule = ManagedLeaderSchedule {
+            rpc_client: Some(rpc_client),
+            ..schedule.clone()
+        };
+        assert!(matches!(
+            rpc_schedule.refresh().await,
+            Err(ScheduleError::Rpc(_))
+        ));
+        assert_eq!(schedule.get_leader(0).unwrap(), Some(leaders[0]));
+
+        cancellation_token.cancel();
+        jh.await.expect("update task");
+    }
+
+    #[test]
+    fn test_from_static_should_rotate_leaders_across_epochs() {
+        let leaders = unique_leaders(3);
+        let first_slot = 10 * DEFAULT_SLOTS_PER_EPOCH;
+        let schedule = ManagedLeaderSchedule::from_static(first_slot, leaders.clone(), 8);
+        for slot in first_slot..first_slot + 2 * DEFAULT_SLOTS_PER_EPOCH {
+            let expected = leaders[((slot - first_slot) / 8) as usize % leaders.len()];
+            assert_eq!(
+                schedule.get_leader(slot).unwrap(),
+                Some(expected),
+                "slot {slot}"
+            );
+        }
+        assert!(matches!(
+            schedule.get_leader(first_slot - 1),
+            Err(ScheduleError::SlotBeforeLoadedEpoch(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_covers_should_report_the_first_uncovered_slot() {
+        let schedule = ManagedLeaderSchedule::from_static(0, unique_leaders(3), 4);
+        assert!(schedule.validate_covers(10, 64).is_ok());
+
+        // The next epoch is the last loaded one, the window runs past its end.
+        let current_slot = 2 * DEFAULT_SLOTS_PER_EPOCH - 8;
+        assert!(matches!(
+            schedule.validate_covers(current_slot, 64),
+            Err(CoverageError::PastKnownSchedule { slot, until })
+                if slot == 2 * DEFAULT_SLOTS_PER_EPOCH && until == current_slot + 64
+        ));
+
+        let schedule =
+            ManagedLeaderSchedule::from_static(DEFAULT_SLOTS_PER_EPOCH, unique_leaders(3), 4);
+        assert!(matches!(
+            schedule.validate_covers(DEFAULT_SLOTS_PER_EPOCH - 6, 64),
+            Err(CoverageError::BeforeKnownSchedule { slot }) if slot == DEFAULT_SLOTS_PER_EPOCH - 8
+        ));
+    }
+
+    #[test]
+    fn test_validate_covers_should_reject_gaps_and_empty_schedules() {
+        let mut leaders = unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4);
+        leaders[5] = Pubkey::default();
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, leaders),
+            CompactSortedSchedule::new(
+                DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+        );
+        assert!(schedule.validate_covers(0, 16).is_ok());
+        assert!(matches!(
+            schedule.validate_covers(0, 64),
+            Err(CoverageError::Gap { slot: 20 })
+        ));
+
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, Vec::new()),
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, Vec::new()),
+        );
+        assert!(matches!(
+            schedule.validate_covers(0, 64),
+            Err(CoverageError::NotInitialized)
+        ));
+    }
+
+    #[test]
+    fn it_should_report_initialization_and_loaded_epochs() {
+        let schedule = ManagedLeaderSchedule::from_static_schedules(
+            CompactSortedSchedule::new(0, Vec::new()),
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, Vec::new()),
+        );
+        assert!(!schedule.is_initialized());
+        assert!(schedule.loaded_epochs().is_empty());
+
+        schedule.set_static_schedules(
+            CompactSortedSchedule::new(
+                DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+            CompactSortedSchedule::new(
+                2 * DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+        );
+        assert!(schedule.is_initialized());
+        assert_eq!(schedule.loaded_epochs(), vec![1, 2]);
+
+        // Past epochs are reported while cached, none by default.
+        schedule.set_static_schedules(
+            CompactSortedSchedule::new(
+                2 * DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+            CompactSortedSchedule::new(
+                3 * DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+        );
+        assert_eq!(schedule.loaded_epochs(), vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_get_leader_async_should_load_missing_epoch_once() {
+        let leader = Pubkey::new_unique();
+        let mut schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4)),
+            CompactSortedSchedule::new(
+                DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+        );
+        // The mocked response is served once, a second fetch would not return `leader`.
+        schedule.rpc_client = Some(Arc::new(RpcClient::new_mock_with_mocks(
+            "succeeds".to_string(),
+            HashMap::from([(
+                RpcRequest::GetLeaderSchedule,
+                json!({ (leader.to_string()): [4, 5, 6, 7] }),
+            )]),
+        )));
+        let slot = 2 * DEFAULT_SLOTS_PER_EPOCH + 5;
+        assert!(matches!(
+            schedule.get_leader(slot),
+            Err(ScheduleError::SlotAfterLoadedEpoch(_))
+        ));
+
+        assert_eq!(
+            schedule.get_leader_async(slot).await.expect("load"),
+            Some(leader)
+        );
+        // The epoch is now cached, the sync lookup hits it.
+        assert_eq!(schedule.get_leader(slot).expect("cached"), Some(leader));
+        assert_eq!(
+            schedule.get_leader_async(slot).await.expect("cached"),
+            Some(leader)
+        );
+        assert_eq!(
+            schedule
+                .get_leader_async(2 * DEFAULT_SLOTS_PER_EPOCH)
+                .await
+                .expect("cached"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_trigger_prefetch_should_refresh_once_per_generation() {
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4)),
+            CompactSortedSchedule::new(
+                DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+        );
+        assert!(!schedule.take_refresh_request());
+        // The next epoch is prefetched ahead of the boundary by the background task.
+        assert!(!schedule.trigger_prefetch(DEFAULT_SLOTS_PER_EPOCH - 1));
+        assert!(!schedule.take_refresh_request());
+
+        assert!(schedule.trigger_prefetch(DEFAULT_SLOTS_PER_EPOCH));
+        assert!(schedule.take_refresh_request());
+        // Lookups keep missing until the schedules change, they don't refresh again.
+        assert!(!schedule.trigger_prefetch(DEFAULT_SLOTS_PER_EPOCH + 1));
+        assert!(!schedule.take_refresh_request());
+
+        schedule.set_static_schedules(
+            CompactSortedSchedule::new(
+                DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+            CompactSortedSchedule::new(
+                2 * DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+        );
+        assert!(!schedule.trigger_prefetch(DEFAULT_SLOTS_PER_EPOCH + 1));
+        assert!(schedule.trigger_prefetch(3 * DEFAULT_SLOTS_PER_EPOCH));
+        assert!(schedule.take_refresh_request());
+    }
+
+    #[test]
+    fn test_get_leader_should_return_structured_errors() {
+        let leaders = unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4);
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, leaders.clone()),
+            CompactSortedSchedule::new(
+                2 * DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+        );
+        assert_eq!(
+            schedule.get_leader(DEFAULT_SLOTS_PER_EPOCH).unwrap(),
+            Some(leaders[0])
+        );
+        assert!(matches!(
+            schedule.get_leader(DEFAULT_SLOTS_PER_EPOCH - 1),
+            Err(ScheduleError::SlotBeforeLoadedEpoch(slot)) if slot == DEFAULT_SLOTS_PER_EPOCH - 1
+        ));
+        assert!(matches!(
+            schedule.get_leader(3 * DEFAULT_SLOTS_PER_EPOCH),
+            Err(ScheduleError::SlotAfterLoadedEpoch(slot)) if slot == 3 * DEFAULT_SLOTS_PER_EPOCH
+        ));
+
+        schedule
+            .inner
+            .read()
+            .unwrap()
+            .fail
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        assert!(matches!(
+            schedule.get_leader(DEFAULT_SLOTS_PER_EPOCH),
+            Err(ScheduleError::Poisoned(_))
+        ));
+
+        let empty = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, Vec::new()),
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, Vec::new()),
+        );
+        assert!(matches!(
+            empty.get_leader(0),
+            Err(ScheduleError::NotInitialized)
+        ));
+    }
+
+    #[test]
+    fn test_should_prefetch_next_epoch() {
+        assert!(!super::should_prefetch_next_epoch(1001, 1000));
+        assert!(super::should_prefetch_next_epoch(1000, 1000));
+        assert!(super::should_prefetch_next_epoch(0, 1000));
+        assert!(!super::should_prefetch_next_epoch(1, 0));
+    }
+
+    #[test]
+    fn test_lookup_should_be_continuous_across_rotation() {
+        let epoch_2_leaders = unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4);
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(
+                DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            ),
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH * 2, epoch_2_leaders.clone()),
+        );
+
+        let boundary = DEFAULT_SLOTS_PER_EPOCH * 2;
+        // Look ahead a few leaders, as the predictor would.
+        let look_ahead = 16;
+        for slot in boundary - 1000..boundary + 1000 {
+            if slot == boundary {
+                schedule
+                    .inner
+                    .write()
+                    .unwrap()
+                    .rotate(CompactSortedSchedule::new(
+                        DEFAULT_SLOTS_PER_EPOCH * 3,
+                        unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+                    ));
+            }
+            for lookup_slot in [slot, slot + look_ahead] {
+                assert!(
+                    schedule.get_leader(lookup_slot).unwrap().is_some(),
+                    "no leader for slot {lookup_slot} at slot {slot}"
+                );
+            }
+        }
+
+        // The previous epoch has been dropped, the new current epoch is the former next one.
+        assert_eq!(
+            schedule.lookup_leader(boundary - 1).unwrap(),
+            LeaderLookup::BeforeKnownSchedule
+        );
+        assert_eq!(
+            schedule.get_leader(boundary).unwrap(),
+            Some(epoch_2_leaders[0])
+        );
+    }
+
+    #[test]
+    fn test_cached_epochs_should_be_bounded() {
+        let epoch_schedule = |epoch: u64| {
+            CompactSortedSchedule::new(
+                epoch * DEFAULT_SLOTS_PER_EPOCH,
+                unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4),
+            )
+        };
+
+        // Default: only current and next epochs are kept.
+        let schedule = ManagedLeaderSchedule::from_schedules(epoch_schedule(0), epoch_schedule(1));
+        for epoch in 2..10 {
+            schedule
+                .inner
+                .write()
+                .unwrap()
+                .rotate(epoch_schedule(epoch));
+            assert_eq!(schedule.cached_epoch_count(), 2);
+        }
+        assert_eq!(
+            schedule
+                .lookup_leader(8 * DEFAULT_SLOTS_PER_EPOCH - 1)
+                .unwrap(),
+            LeaderLookup::BeforeKnownSchedule
+        );
+
+        // Keep one epoch of history.
+        let schedule = ManagedLeaderSchedule::from_schedules(epoch_schedule(0), epoch_schedule(1));
+        schedule.inner.write().unwrap().max_cached_epochs = 3;
+        for epoch in 2..10 {
+            schedule
+                .inner
+                .write()
+                .unwrap()
+                .rotate(epoch_schedule(epoch));
+            assert!(schedule.cached_epoch_count() <= 3);
+        }
+        assert_eq!(schedule.cached_epoch_count(), 3);
+        // Previous epoch lookups still succeed, older ones have been evicted.
+        assert!(
+            schedule
+                .get_leader(8 * DEFAULT_SLOTS_PER_EPOCH - 1)
+                .unwrap()
+                .is_some()
+        );
+        assert!(matches!(
+            schedule.get_leader(7 * DEFAULT_SLOTS_PER_EPOCH - 1),
+            Err(ScheduleError::SlotBeforeLoadedEpoch(_))
+        ));
+
+        // A full reload moves older epochs to history and still honors the bound.
+        schedule
+            .inner
+            .write()
+            .unwrap()
+            .set_double_buffer([epoch_schedule(10), epoch_schedule(11)]);
+        assert_eq!(schedule.cached_epoch_count(), 3);
+        assert!(
+            schedule
+                .get_leader(10 * DEFAULT_SLOTS_PER_EPOCH - 1)
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_diff_identical_schedule_is_empty() {
+        let current_leaders = unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4);
+        let next_leaders = unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4);
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, current_leaders.clone()),
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, next_leaders.clone()),
+        );
+        let snapshot = schedule.snapshot();
+        assert_eq!(snapshot.schedules().len(), 2);
+        assert!(schedule.diff(&snapshot).is_empty());
+
+        // Same content, different allocation
+        schedule.inner.write().unwrap().set_double_buffer([
+            CompactSortedSchedule::new(0, current_leaders.clone()),
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, next_leaders.clone()),
+        ]);
+        assert!(schedule.diff(&snapshot).is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_flipped_boundaries() {
+        let current_leaders = unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4);
+        let next_leaders = unique_leaders(DEFAULT_SLOTS_PER_EPOCH / 4);
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, current_leaders.clone()),
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, next_leaders.clone()),
+        );
+        let snapshot = schedule.snapshot();
+
+        let mut reshuffled = next_leaders.clone();
+        let new_leader = Pubkey::new_unique();
+        reshuffled[3] = new_leader;
+        reshuffled[7] = Pubkey::default();
+        schedule.inner.write().unwrap().set_double_buffer([
+            CompactSortedSchedule::new(0, current_leaders.clone()),
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, reshuffled),
+        ]);
+
+        assert_eq!(
+            schedule.diff(&snapshot),
+            vec![
+                (
+                    DEFAULT_SLOTS_PER_EPOCH + 12,
+                    Some(next_leaders[3]),
+                    Some(new_leader)
+                ),
+                (DEFAULT_SLOTS_PER_EPOCH + 28, Some(next_leaders[7]), None),
+            ]
+        );
+    }
 }
@@ -2,7 +2,11 @@
 /// THIS FILE AS BEEN IMPORTED FROM JET
 /// TODO: CREATE A COMMON LIB
 use {
-    crate::{core::ValidatorStakeInfoService, rpc::solana_rpc_utils::SolanaRpcErrorKindExt},
+    crate::{
+        core::ValidatorStakeInfoService,
+        rpc::solana_rpc_utils::SolanaRpcErrorKindExt,
+        task::{STAKE_INFO_TASK, spawn_named},
+    },
     futures::{StreamExt, stream},
     serde::Deserialize,
     solana_client::{nonblocking::rpc_client::RpcClient, rpc_response::RpcVoteAccountStatus},
@@ -267,7 +271,7 @@ pub(crate) async fn build_validator_stake_info_service_inner(
         drop_rx,
     };
 
-    let jh = tokio::spawn(task.run());
+    let jh = spawn_named(STAKE_INFO_TASK, task.run());
 
     (ret, jh)
 }
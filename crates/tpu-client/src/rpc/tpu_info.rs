@@ -8,15 +8,29 @@
 //! allowing clients to retrieve the appropriate TPU QUIC address for a given leader.
 //!
 //! The service runs a background task to refresh the cluster nodes info at a configurable interval.
+//! It requires the `rpc` feature, [`RpcTpuQuicContactInfo`] is always available.
 //!
 use {
-    crate::{core::LeaderTpuInfoService, rpc::solana_rpc_utils::SolanaRpcErrorKindExt},
+    crate::{
+        config::TpuPortKind,
+        core::TpuAddrs,
+        rpc::contact::{resolve_tpu_forwards_quic_addr, resolve_tpu_quic_addr},
+    },
+    solana_rpc_client_api::response::RpcContactInfo,
+    std::net::SocketAddr,
+};
+#[cfg(feature = "rpc")]
+use {
+    crate::{
+        core::{AddressResolver, LeaderTpuInfoService},
+        rpc::{contact::ContactInfoTable, solana_rpc_utils::SolanaRpcErrorKindExt},
+        task::{TPU_INFO_TASK, spawn_named},
+    },
     serde::Deserialize,
-    solana_client::{nonblocking::rpc_client::RpcClient, rpc_response::RpcContactInfo},
+    solana_client::nonblocking::rpc_client::RpcClient,
     solana_pubkey::Pubkey,
     std::{
         collections::HashMap,
-        net::SocketAddr,
         panic,
         str::FromStr,
         sync::{Arc, RwLock},
@@ -31,6 +45,9 @@ pub const DEFAULT_MAX_RETRY_ATTEMPTS: usize = 3;
 ///
 /// TPU QUIC contact info for a Solana cluster node.
 ///
+/// Each port is `None` if the node advertises neither the QUIC port nor the UDP port it is derived from,
+/// see [`resolve_tpu_quic_addr`] and [`resolve_tpu_forwards_quic_addr`].
+///
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct RpcTpuQuicContactInfo {
     /// TPU QUIC socket address
@@ -39,11 +56,32 @@ pub struct RpcTpuQuicContactInfo {
     pub tpu_forwards_quic: Option<SocketAddr>,
 }
 
+impl From<&RpcTpuQuicContactInfo> for TpuAddrs {
+    fn from(contact_info: &RpcTpuQuicContactInfo) -> Self {
+        Self {
+            tpu_quic: contact_info.tpu_quic,
+            tpu_forwards_quic: contact_info.tpu_forwards_quic,
+        }
+    }
+}
+
 impl From<&RpcContactInfo> for RpcTpuQuicContactInfo {
     fn from(rpc_contact_info: &RpcContactInfo) -> Self {
         Self {
-            tpu_quic: rpc_contact_info.tpu_quic,
-            tpu_forwards_quic: rpc_contact_info.tpu_forwards_quic,
+            tpu_quic: resolve_tpu_quic_addr(rpc_contact_info),
+            tpu_forwards_quic: resolve_tpu_forwards_quic_addr(rpc_contact_info),
+        }
+    }
+}
+
+impl RpcTpuQuicContactInfo {
+    ///
+    /// Get the socket address of the given port kind.
+    ///
+    pub const fn addr(&self, tpu_port_kind: TpuPortKind) -> Option<SocketAddr> {
+        match tpu_port_kind {
+            TpuPortKind::Normal => self.tpu_quic,
+            TpuPortKind::Forwards => self.tpu_forwards_quic,
         }
     }
 }
@@ -62,12 +100,14 @@ impl From<&RpcContactInfo> for RpcTpuQuicContactInfo {
 ///
 /// This service is thread-safe.
 ///
+#[cfg(feature = "rpc")]
 #[derive(Clone)]
 pub struct RpcClusterTpuQuicInfoService {
     shared: Arc<RwLock<HashMap<Pubkey, RpcTpuQuicContactInfo>>>,
     _on_drop: Arc<OnDrop>,
 }
 
+#[cfg(feature = "rpc")]
 impl RpcClusterTpuQuicInfoService {
     ///
     /// Get the current topology mapping of leader pubkeys to their TPU QUIC contact info.
@@ -75,12 +115,21 @@ impl RpcClusterTpuQuicInfoService {
     pub fn get_current_topology_mapping(&self) -> HashMap<Pubkey, RpcTpuQuicContactInfo> {
         self.shared.read().expect("read lock").clone()
     }
+
+    ///
+    /// Get a [`ContactInfoTable`] snapshot of the current topology.
+    ///
+    pub fn contact_info_table(&self) -> ContactInfoTable {
+        ContactInfoTable::from(self.get_current_topology_mapping())
+    }
 }
 
+#[cfg(feature = "rpc")]
 struct OnDrop {
     handle: CancellationToken,
 }
 
+#[cfg(feature = "rpc")]
 impl Drop for OnDrop {
     fn drop(&mut self) {
         self.handle.cancel();
@@ -90,6 +139,7 @@ impl Drop for OnDrop {
 ///
 /// Configuration for the [`RpcClusterTpuQuicInfoService`].
 ///
+#[cfg(feature = "rpc")]
 #[derive(Debug, Clone, Deserialize)]
 pub struct RpcClusterTpuQuicInfoServiceConfig {
     /// Interval between cluster info refreshes.
@@ -103,6 +153,7 @@ pub struct RpcClusterTpuQuicInfoServiceConfig {
     pub max_retry_attempts: usize,
 }
 
+#[cfg(feature = "rpc")]
 impl RpcClusterTpuQuicInfoServiceConfig {
     pub fn default_refresh_interval() -> std::time::Duration {
         DEFAULT_REFRESH_INTERVAL
@@ -113,6 +164,7 @@ impl RpcClusterTpuQuicInfoServiceConfig {
     }
 }
 
+#[cfg(feature = "rpc")]
 impl Default for RpcClusterTpuQuicInfoServiceConfig {
     fn default() -> Self {
         Self {
@@ -122,11 +174,13 @@ impl Default for RpcClusterTpuQuicInfoServiceConfig {
     }
 }
 
+#[cfg(feature = "rpc")]
 enum Diff {
     Upsert((Pubkey, RpcTpuQuicContactInfo)),
     Remove(Pubkey),
 }
 
+#[cfg(feature = "rpc")]
 fn detect_tpu_contact_info_diff(
     current: &HashMap<Pubkey, RpcTpuQuicContactInfo>,
     new: &[RpcContactInfo],
@@ -161,6 +215,7 @@ fn detect_tpu_contact_info_diff(
     ret
 }
 
+#[cfg(feature = "rpc")]
 fn apply_diff_patch(
     current: &mut HashMap<Pubkey, RpcTpuQuicContactInfo>,
     diffs: impl IntoIterator<Item = Diff>,
@@ -177,6 +232,7 @@ fn apply_diff_patch(
     }
 }
 
+#[cfg(feature = "rpc")]
 async fn cluster_info_refresh_loop(
     rpc_client: Arc<RpcClient>,
     shared: Arc<RwLock<HashMap<Pubkey, RpcTpuQuicContactInfo>>>,
@@ -244,6 +300,7 @@ async fn cluster_info_refresh_loop(
 ///
 /// Dropping the `JoinHandle` will not stop the background task; to stop it, drop the all [`RpcClusterTpuQuicInfoService`] instance.
 ///
+#[cfg(feature = "rpc")]
 pub async fn rpc_cluster_tpu_info_service(
     rpc_client: Arc<RpcClient>,
     config: RpcClusterTpuQuicInfoServiceConfig,
@@ -276,16 +333,15 @@ pub async fn rpc_cluster_tpu_info_service(
         _on_drop: Arc::new(on_drop),
     };
 
-    let handle = tokio::spawn(cluster_info_refresh_loop(
-        rpc_client,
-        shared,
-        config,
-        cancellation_token,
-    ));
+    let handle = spawn_named(
+        TPU_INFO_TASK,
+        cluster_info_refresh_loop(rpc_client, shared, config, cancellation_token),
+    );
 
     Ok((ret, handle))
 }
 
+#[cfg(feature = "rpc")]
 impl LeaderTpuInfoService for RpcClusterTpuQuicInfoService {
     fn get_quic_tpu_socket_addr(&self, leader_pubkey: &Pubkey) -> Option<SocketAddr> {
         self.shared
@@ -304,7 +360,18 @@ impl LeaderTpuInfoService for RpcClusterTpuQuicInfoService {
     }
 }
 
-#[cfg(test)]
+#[cfg(feature = "rpc")]
+impl AddressResolver for RpcClusterTpuQuicInfoService {
+    fn resolve(&self, leader: &Pubkey) -> Option<TpuAddrs> {
+        self.shared
+            .read()
+            .expect("read lock")
+            .get(leader)
+            .map(TpuAddrs::from)
+    }
+}
+
+#[cfg(all(test, feature = "rpc"))]
 mod tests {
     use super::*;
 
@@ -0,0 +1,209 @@
+//!
+//! Leader schedule mismatch detection.
+//!
+//! A stale or wrong [`ManagedLeaderSchedule`], e.g. loaded for the wrong epoch, sends transactions to validators that
+//! are not the leader. [`ScheduleMismatchTracker`] compares the leader scheduled at each slot against the validator
+//! that actually produced the block, and asks the schedule to refresh once the recent mismatch rate reaches a
+//! threshold.
+//!
+//! When using [`crate::yellowstone_grpc`], it is fed from the gRPC block-meta stream, see
+//! [`crate::yellowstone_grpc::slot_tracker::YellowstoneSlotTrackerOk::schedule_mismatches`].
+//!
+#[cfg(feature = "prometheus")]
+use crate::prom;
+use {
+    crate::rpc::schedule::ManagedLeaderSchedule,
+    solana_clock::Slot,
+    solana_pubkey::Pubkey,
+    std::{
+        collections::VecDeque,
+        sync::{
+            Mutex, OnceLock,
+            atomic::{AtomicU64, Ordering},
+        },
+    },
+};
+
+///
+/// Default number of recent observations over which the mismatch rate is computed.
+///
+pub const DEFAULT_SCHEDULE_MISMATCH_WINDOW: usize = 128;
+
+///
+/// Default mismatch rate, over a full window, from which a schedule refresh is triggered.
+///
+pub const DEFAULT_SCHEDULE_MISMATCH_REFRESH_THRESHOLD: f64 = 0.25;
+
+#[derive(Debug, Default)]
+struct MismatchWindow {
+    ///
+    /// `true` if the observed block producer differed from the scheduled leader.
+    ///
+    observations: VecDeque<bool>,
+    mismatch_cnt: usize,
+}
+
+///
+/// Compares the scheduled leader of each observed block against its actual producer.
+///
+/// Nothing is compared until a schedule is attached with [`ScheduleMismatchTracker::attach_schedule`].
+///
+/// # Safety
+///
+/// This struct is thread-safe. Share it using an atomic reference-counter.
+///
+pub struct ScheduleMismatchTracker {
+    schedule: OnceLock<ManagedLeaderSchedule>,
+    window: usize,
+    refresh_threshold: f64,
+    inner: Mutex<MismatchWindow>,
+    mismatches: AtomicU64,
+    refreshes: AtomicU64,
+}
+
+impl Default for ScheduleMismatchTracker {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_SCHEDULE_MISMATCH_WINDOW,
+            DEFAULT_SCHEDULE_MISMATCH_REFRESH_THRESHOLD,
+        )
+    }
+}
+
+impl ScheduleMismatchTracker {
+    ///
+    /// Creates a tracker computing the mismatch rate over the last `window` observations, triggering a schedule
+    /// refresh once a full window reaches `refresh_threshold`.
+    ///
+    pub fn new(window: usize, refresh_threshold: f64) -> Self {
+        Self {
+            schedule: OnceLock::new(),
+            window: window.max(1),
+            refresh_threshold,
+            inner: Default::default(),
+            mismatches: AtomicU64::new(0),
+            refreshes: AtomicU64::new(0),
+        }
+    }
+
+    ///
+    /// Sets the schedule observations are compared against, returns `false` if one was already attached.
+    ///
+    pub fn attach_schedule(&self, schedule: ManagedLeaderSchedule) -> bool {
+        self.schedule.set(schedule).is_ok()
+    }
+
+    ///
+    /// Records that `producer` produced the block at `slot`.
+    ///
+    /// Returns `Some(true)` on mismatch, `Some(false)` if the producer is the scheduled leader, `None` if the slot
+    /// could not be compared: no schedule attached, or the slot is outside the loaded schedule.
+    ///
+    pub fn observe(&self, slot: Slot, producer: Pubkey) -> Option<bool> {
+        let schedule = self.schedule.get()?;
+        let expected = match schedule.get_leader(slot) {
+            Ok(Some(expected)) => expected,
+            Ok(None) => return None,
+            Err(e) => {
+                tracing::trace!("Cannot compare block producer at slot {slot}: {e}");
+                return None;
+            }
+        };
+        let mismatch = expected != producer;
+        if mismatch {
+            tracing::debug!(
+                "Leader schedule mismatch at slot {slot}: scheduled {expected}, produced by {producer}"
+            );
+            self.mismatches.fetch_add(1, Ordering::Relaxed);
+            #[cfg(feature = "prometheus")]
+            {
+                prom::incr_schedule_mismatch_cnt();
+            }
+        }
+
+        let mut inner = self.inner.lock().expect("schedule mismatch lock");
+        inner.observations.push_back(mismatch);
+        inner.mismatch_cnt += usize::from(mismatch);
+        if inner.observations.len() > self.window {
+            let evicted = inner.observations.pop_front().expect("non-empty");
+            inner.mismatch_cnt -= usize::from(evicted);
+        }
+        let rate = inner.mismatch_cnt as f64 / inner.observations.len() as f64;
+        if inner.observations.len() == self.window && rate >= self.refresh_threshold {
+            tracing::warn!(
+                "Leader schedule mismatch rate {:.2} over the last {} blocks, triggering a schedule refresh",
+                rate,
+                self.window
+            );
+            // Start over, the refreshed schedule gets a full window before the next refresh.
+            *inner = MismatchWindow::default();
+            self.refreshes.fetch_add(1, Ordering::Relaxed);
+            schedule.trigger_refresh();
+        }
+        Some(mismatch)
+    }
+
+    ///
+    /// Returns the fraction of mismatches among the recent observations, `None` if there is none.
+    ///
+    /// Observations are reset whenever a refresh is triggered.
+    ///
+    pub fn mismatch_rate(&self) -> Option<f64> {
+        let inner = self.inner.lock().expect("schedule mismatch lock");
+        (!inner.observations.is_empty())
+            .then(|| inner.mismatch_cnt as f64 / inner.observations.len() as f64)
+    }
+
+    ///
+    /// Total number of mismatches observed.
+    ///
+    pub fn mismatch_count(&self) -> u64 {
+        self.mismatches.load(Ordering::Relaxed)
+    }
+
+    ///
+    /// Number of schedule refreshes triggered by a high mismatch rate.
+    ///
+    pub fn refreshes_triggered(&self) -> u64 {
+        self.refreshes.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, crate::rpc::schedule::CompactSortedSchedule,
+        solana_clock::DEFAULT_SLOTS_PER_EPOCH,
+    };
+
+    #[test]
+    fn it_should_count_mismatches_and_trigger_refresh() {
+        let scheduled = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let leaders = vec![scheduled; (DEFAULT_SLOTS_PER_EPOCH / 4) as usize];
+        let schedule = ManagedLeaderSchedule::from_schedules(
+            CompactSortedSchedule::new(0, leaders.clone()),
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, leaders),
+        );
+        let tracker = ScheduleMismatchTracker::new(4, 0.5);
+        assert_eq!(tracker.observe(0, other), None);
+        assert!(tracker.attach_schedule(schedule));
+
+        #[cfg(feature = "prometheus")]
+        let metric_before = prom::schedule_mismatch_cnt();
+        assert_eq!(tracker.observe(0, scheduled), Some(false));
+        assert_eq!(tracker.observe(1, other), Some(true));
+        assert_eq!(tracker.mismatch_count(), 1);
+        assert_eq!(tracker.mismatch_rate(), Some(0.5));
+        #[cfg(feature = "prometheus")]
+        assert!(prom::schedule_mismatch_cnt() > metric_before);
+        // Outside the loaded schedule: not compared.
+        assert_eq!(tracker.observe(10 * DEFAULT_SLOTS_PER_EPOCH, other), None);
+
+        assert_eq!(tracker.observe(2, scheduled), Some(false));
+        assert_eq!(tracker.refreshes_triggered(), 0);
+        assert_eq!(tracker.observe(3, other), Some(true));
+        assert_eq!(tracker.refreshes_triggered(), 1);
+        assert_eq!(tracker.mismatch_rate(), None);
+    }
+}
@@ -0,0 +1,266 @@
+//!
+//! Bounded history of the recent sends, for post-mortem analysis of landing failures.
+//!
+//! [`SendHistory`] retains the last `capacity` transactions sent by a [`crate::forwarder::LeaderForwarder`], see
+//! [`crate::forwarder::LeaderForwarder::with_send_history`]: their signature, when they were sent, the leaders they
+//! were sent to and the outcome of each send. [`SendHistory::recent`] returns them most recent first.
+//!
+//! # Contention
+//!
+//! The history is a ring buffer of independently locked slots: a send claims its slot with an atomic increment and
+//! only locks that slot, so concurrent sends do not contend unless the history wraps around in between.
+//! Reading the history locks one slot at a time.
+//!
+use {
+    solana_pubkey::Pubkey,
+    solana_signature::Signature,
+    std::{
+        fmt,
+        num::NonZeroUsize,
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicU64, Ordering},
+        },
+        time::SystemTime,
+    },
+};
+
+///
+/// Default number of sends retained by a [`SendHistory`].
+///
+pub const DEFAULT_SEND_HISTORY_CAPACITY: NonZeroUsize = NonZeroUsize::new(1024).unwrap();
+
+///
+/// Send of a transaction to one leader in a [`SendRecord`].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendTarget {
+    pub leader: Pubkey,
+    ///
+    /// Outcome of the send, `None` while it is in flight. Errors are kept as their description.
+    ///
+    pub outcome: Option<Result<(), String>>,
+}
+
+///
+/// Transaction retained by a [`SendHistory`].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendRecord {
+    ///
+    /// Signature of the transaction, `None` if it is malformed or unsigned.
+    ///
+    pub signature: Option<Signature>,
+    ///
+    /// When the send started.
+    ///
+    pub sent_at: SystemTime,
+    ///
+    /// Sends to the leaders, in the order they were started. A leader retried by
+    /// [`crate::forwarder::LeaderForwarder::forward`] appears once per attempt.
+    ///
+    pub targets: Vec<SendTarget>,
+}
+
+///
+/// Ring buffer of the recent sends, see the [module documentation](crate::send_history).
+///
+/// # Safety
+///
+/// This struct is thread-safe. Share it using an atomic reference-counter.
+///
+pub struct SendHistory {
+    /// Slot `seq % capacity` holds the record of sequence number `seq`.
+    slots: Box<[Mutex<Option<(u64, SendRecord)>>]>,
+    /// Sequence number of the next record.
+    next_seq: AtomicU64,
+}
+
+impl Default for SendHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEND_HISTORY_CAPACITY)
+    }
+}
+
+impl SendHistory {
+    ///
+    /// Creates an empty history retaining the last `capacity` sends.
+    ///
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            slots: (0..capacity.get()).map(|_| Mutex::new(None)).collect(),
+            next_seq: AtomicU64::new(0),
+        }
+    }
+
+    ///
+    /// Maximum number of sends retained.
+    ///
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn slot(&self, seq: u64) -> &Mutex<Option<(u64, SendRecord)>> {
+        &self.slots[(seq % self.slots.len() as u64) as usize]
+    }
+
+    ///
+    /// Records a new send of the transaction `signature` to `targets`, whose outcomes are pending.
+    ///
+    /// The oldest record is overwritten once the history is full.
+    /// Record further targets and outcomes through the returned [`SendRecorder`].
+    ///
+    pub fn start(
+        self: &Arc<Self>,
+        signature: Option<Signature>,
+        targets: &[Pubkey],
+    ) -> SendRecorder {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let record = SendRecord {
+            signature,
+            sent_at: SystemTime::now(),
+            targets: targets
+                .iter()
+                .map(|leader| SendTarget {
+                    leader: *leader,
+                    outcome: None,
+                })
+                .collect(),
+        };
+        *self.slot(seq).lock().expect("send history lock") = Some((seq, record));
+        SendRecorder {
+            history: Arc::clone(self),
+            seq,
+        }
+    }
+
+    ///
+    /// Returns up to `limit` of the retained sends, most recent first.
+    ///
+    pub fn recent(&self, limit: usize) -> Vec<SendRecord> {
+        let next_seq = self.next_seq.load(Ordering::Relaxed);
+        let oldest_seq = next_seq.saturating_sub(self.slots.len() as u64);
+        (oldest_seq..next_seq)
+            .rev()
+            .filter_map(
+                |seq| match &*self.slot(seq).lock().expect("send history lock") {
+                    // The slot may have been claimed but not written yet, or overwritten by a newer send.
+                    Some((record_seq, record)) if *record_seq == seq => Some(record.clone()),
+                    _ => None,
+                },
+            )
+            .take(limit)
+            .collect()
+    }
+}
+
+///
+/// Handle to a [`SendRecord`] of a [`SendHistory`], see [`SendHistory::start`].
+///
+/// Outcomes recorded after the record was overwritten by newer sends are dropped.
+///
+#[derive(Clone)]
+pub struct SendRecorder {
+    history: Arc<SendHistory>,
+    seq: u64,
+}
+
+impl SendRecorder {
+    ///
+    /// Records the outcome of the send to `leader`.
+    ///
+    /// Completes the first pending send to `leader`, or appends a new target if there is none, e.g. for a retry.
+    ///
+    pub fn record<E: fmt::Display>(&self, leader: Pubkey, result: &Result<(), E>) {
+        let outcome = result.as_ref().map(|_| ()).map_err(ToString::to_string);
+        let mut slot = self
+            .history
+            .slot(self.seq)
+            .lock()
+            .expect("send history lock");
+        let Some((seq, record)) = slot.as_mut() else {
+            return;
+        };
+        if *seq != self.seq {
+            return;
+        }
+        match record
+            .targets
+            .iter_mut()
+            .find(|target| target.leader == leader && target.outcome.is_none())
+        {
+            Some(target) => target.outcome = Some(outcome),
+            None => record.targets.push(SendTarget {
+                leader,
+                outcome: Some(outcome),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_cap_the_history_and_return_the_most_recent_sends_first() {
+        let history = Arc::new(SendHistory::new(NonZeroUsize::new(3).unwrap()));
+        assert!(history.recent(10).is_empty());
+
+        let leader = Pubkey::new_unique();
+        let signatures = (0..5u8)
+            .map(|i| Signature::from([i; 64]))
+            .collect::<Vec<_>>();
+        let recorders = signatures
+            .iter()
+            .map(|signature| history.start(Some(*signature), &[leader]))
+            .collect::<Vec<_>>();
+
+        let recent = history.recent(10);
+        assert_eq!(recent.len(), history.capacity());
+        assert_eq!(
+            recent
+                .iter()
+                .map(|record| record.signature)
+                .collect::<Vec<_>>(),
+            vec![
+                Some(signatures[4]),
+                Some(signatures[3]),
+                Some(signatures[2])
+            ]
+        );
+        assert_eq!(history.recent(2).len(), 2);
+        assert_eq!(history.recent(2)[0].signature, Some(signatures[4]));
+
+        // Overwritten records are not updated anymore.
+        recorders[0].record(leader, &Err("lost"));
+        recorders[4].record(leader, &Ok::<_, String>(()));
+        recorders[4].record(leader, &Err("retried"));
+        let recent = history.recent(10);
+        assert!(
+            recent
+                .iter()
+                .all(|record| record.signature != Some(signatures[0]))
+        );
+        assert_eq!(
+            recent[0].targets,
+            vec![
+                SendTarget {
+                    leader,
+                    outcome: Some(Ok(())),
+                },
+                SendTarget {
+                    leader,
+                    outcome: Some(Err("retried".to_owned())),
+                },
+            ]
+        );
+        assert_eq!(
+            recent[1].targets,
+            vec![SendTarget {
+                leader,
+                outcome: None,
+            }]
+        );
+    }
+}
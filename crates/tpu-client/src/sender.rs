@@ -2,9 +2,10 @@ use {
     crate::{
         config::TpuSenderConfig,
         core::{
-            ConnectionEvictionStrategy, LeaderTpuInfoService, TpuSenderDriverSpawner,
-            TpuSenderIdentityUpdater, TpuSenderResponseCallback, TpuSenderSessionContext,
-            TpuSenderTxn, UpcomingLeaderPredictor, ValidatorStakeInfoService,
+            ActiveConnectionCount, ConnectionEvictionStrategy, LeaderTpuInfoService,
+            TpuSenderDriverSpawner, TpuSenderIdentityUpdater, TpuSenderResponseCallback,
+            TpuSenderSessionContext, TpuSenderTxn, UpcomingLeaderPredictor,
+            ValidatorStakeInfoService,
         },
     },
     solana_keypair::Keypair,
@@ -25,12 +26,41 @@ pub struct TpuSender {
     // We do this pre-cautionarily to avoid potential issues with miss-managed identity updates.
     identity_updated: Arc<Mutex<TpuSenderIdentityUpdater>>,
     txn_tx: mpsc::Sender<TpuSenderTxn>,
+    active_connections: ActiveConnectionCount,
 }
 
 #[derive(Debug, thiserror::Error)]
 #[error("disconnected")]
 pub struct TpuSenderError(TpuSenderTxn);
 
+///
+/// Error returned by [`TpuSender::try_send_txn`], giving back the transaction that was not queued.
+///
+#[derive(Debug, thiserror::Error)]
+pub enum SubmitError {
+    ///
+    /// The transaction channel is at capacity, the transaction was not queued.
+    ///
+    #[error("tpu sender queue full")]
+    QueueFull(TpuSenderTxn),
+    ///
+    /// The TPU sender task is gone.
+    ///
+    #[error("disconnected")]
+    Closed(TpuSenderTxn),
+}
+
+impl SubmitError {
+    ///
+    /// Returns the transaction that was not queued.
+    ///
+    pub fn into_txn(self) -> TpuSenderTxn {
+        match self {
+            SubmitError::QueueFull(txn) | SubmitError::Closed(txn) => txn,
+        }
+    }
+}
+
 impl TpuSender {
     ///
     /// Sends a transaction to the TPU sender task.
@@ -41,6 +71,46 @@ impl TpuSender {
         self.txn_tx.send(txn).await.map_err(|e| TpuSenderError(e.0))
     }
 
+    ///
+    /// Queues a transaction to the TPU sender task without waiting for channel capacity.
+    ///
+    /// Unlike [`TpuSender::send_txn`], which waits until the TPU sender task frees a slot, a full channel
+    /// returns [`SubmitError::QueueFull`] immediately so callers can shed load instead of piling up.
+    ///
+    pub fn try_send_txn(&mut self, txn: TpuSenderTxn) -> Result<(), SubmitError> {
+        self.txn_tx.try_send(txn).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(txn) => SubmitError::QueueFull(txn),
+            mpsc::error::TrySendError::Closed(txn) => SubmitError::Closed(txn),
+        })
+    }
+
+    ///
+    /// Returns the number of transactions queued to the TPU sender task.
+    ///
+    pub fn queue_depth(&self) -> usize {
+        self.txn_tx.max_capacity() - self.txn_tx.capacity()
+    }
+
+    ///
+    /// Returns the capacity of the transaction channel to the TPU sender task.
+    ///
+    pub fn queue_capacity(&self) -> usize {
+        self.txn_tx.max_capacity()
+    }
+
+    ///
+    /// Creates a sender queuing transactions to `txn_tx`, without a TPU sender task behind it.
+    ///
+    #[cfg(test)]
+    pub(crate) fn from_channel(txn_tx: mpsc::Sender<TpuSenderTxn>) -> Self {
+        let (cnc_tx, _cnc_rx) = mpsc::channel(1);
+        Self {
+            identity_updated: Arc::new(Mutex::new(TpuSenderIdentityUpdater { cnc_tx })),
+            txn_tx,
+            active_connections: Default::default(),
+        }
+    }
+
     ///
     /// Updates the identity used by the TPU sender.
     ///
@@ -51,6 +121,13 @@ impl TpuSender {
             .update_identity(new_identity)
             .await;
     }
+
+    ///
+    /// Returns the number of active remote peer connections of the TPU sender driver.
+    ///
+    pub fn active_connection_count(&self) -> usize {
+        self.active_connections.get()
+    }
 }
 
 ///
@@ -106,10 +183,60 @@ where
         identity_updater,
         driver_tx_sink,
         driver_join_handle: _,
+        active_connections,
     } = session;
 
     TpuSender {
         identity_updated: Arc::new(Mutex::new(identity_updater)),
         txn_tx: driver_tx_sink,
+        active_connections,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, crate::core::TpuSenderIdentityUpdater, bytes::Bytes, solana_pubkey::Pubkey,
+        solana_signature::Signature,
+    };
+
+    fn txn() -> TpuSenderTxn {
+        TpuSenderTxn {
+            tx_sig: Signature::new_unique(),
+            remote_peer: Pubkey::new_unique(),
+            wire: Bytes::from_static(b"txn"),
+        }
+    }
+
+    #[test]
+    fn test_try_send_txn_should_return_queue_full_when_saturated() {
+        let (txn_tx, mut txn_rx) = mpsc::channel(2);
+        let (cnc_tx, _cnc_rx) = mpsc::channel(1);
+        let mut sender = TpuSender {
+            identity_updated: Arc::new(Mutex::new(TpuSenderIdentityUpdater { cnc_tx })),
+            txn_tx,
+            active_connections: Default::default(),
+        };
+
+        sender.try_send_txn(txn()).expect("queued");
+        sender.try_send_txn(txn()).expect("queued");
+        assert_eq!(sender.queue_depth(), 2);
+
+        let rejected = txn();
+        let sig = rejected.tx_sig;
+        let err = sender.try_send_txn(rejected).expect_err("queue is full");
+        assert!(matches!(err, SubmitError::QueueFull(_)));
+        assert_eq!(err.into_txn().tx_sig, sig);
+        assert_eq!(sender.queue_depth(), sender.queue_capacity());
+
+        txn_rx.try_recv().expect("queued txn");
+        assert_eq!(sender.queue_depth(), 1);
+        sender.try_send_txn(txn()).expect("queued");
+
+        drop(txn_rx);
+        assert!(matches!(
+            sender.try_send_txn(txn()),
+            Err(SubmitError::Closed(_))
+        ));
     }
 }
@@ -0,0 +1,196 @@
+//!
+//! Leader skip rate tracking.
+//!
+//! Some validators consistently skip their leader slots, warming a connection to such a leader wastes a connection
+//! slot. [`SkipRateTracker`] records which recent slots produced a block, so predictions can be weighted by the
+//! skip rate of each leader over its own slots.
+//!
+//! When using [`crate::yellowstone_grpc`], it is fed from the gRPC block-meta stream, see
+//! [`crate::yellowstone_grpc::slot_tracker::YellowstoneSlotTrackerOk::skip_rates`].
+//!
+use {
+    solana_clock::Slot,
+    std::{
+        collections::{BTreeMap, btree_map::Entry},
+        sync::Mutex,
+    },
+};
+
+///
+/// Default number of slots, relative to the latest recorded block, over which slot outcomes are remembered.
+///
+pub const DEFAULT_SKIP_RATE_WINDOW_SLOTS: u64 = 20_000;
+
+#[derive(Debug, Default)]
+struct SkipRateTrackerInner {
+    ///
+    /// `true` if the slot produced a block, `false` if it was skipped.
+    ///
+    produced: BTreeMap<Slot, bool>,
+    skipped_cnt: usize,
+}
+
+impl SkipRateTrackerInner {
+    fn latest_slot(&self) -> Slot {
+        self.produced
+            .last_key_value()
+            .map(|(slot, _)| *slot)
+            .unwrap_or_default()
+    }
+}
+
+///
+/// Tracks which recent slots produced a block and which were skipped.
+///
+/// Outcomes older than the window relative to the latest recorded block are evicted.
+///
+/// # Safety
+///
+/// This struct is thread-safe. Share it using an atomic reference-counter.
+///
+#[derive(Debug)]
+pub struct SkipRateTracker {
+    window_slots: u64,
+    inner: Mutex<SkipRateTrackerInner>,
+}
+
+impl Default for SkipRateTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_SKIP_RATE_WINDOW_SLOTS)
+    }
+}
+
+impl SkipRateTracker {
+    ///
+    /// Creates a tracker remembering slot outcomes over the last `window_slots` slots.
+    ///
+    pub fn new(window_slots: u64) -> Self {
+        Self {
+            window_slots,
+            inner: Default::default(),
+        }
+    }
+
+    ///
+    /// Records the block produced at `slot` on top of `parent_slot`, every slot in between was skipped.
+    ///
+    /// A slot observed as produced on any fork stays produced.
+    ///
+    pub fn record_block(&self, slot: Slot, parent_slot: Slot) {
+        let mut guard = self.inner.lock().expect("skip rate tracker lock");
+        let inner = &mut *guard;
+        let latest_slot = inner.latest_slot().max(slot);
+        let window_start = latest_slot.saturating_sub(self.window_slots);
+        if slot < window_start {
+            return;
+        }
+        for skipped in parent_slot.saturating_add(1).max(window_start)..slot {
+            if let Entry::Vacant(entry) = inner.produced.entry(skipped) {
+                entry.insert(false);
+                inner.skipped_cnt += 1;
+            }
+        }
+        if inner.produced.insert(slot, true) == Some(false) {
+            inner.skipped_cnt -= 1;
+        }
+
+        let mut kept = inner.produced.split_off(&window_start);
+        std::mem::swap(&mut kept, &mut inner.produced);
+        inner.skipped_cnt -= kept.values().filter(|produced| !**produced).count();
+    }
+
+    ///
+    /// Returns the fraction of skipped slots among `slots` with a recorded outcome, `None` if none is recorded.
+    ///
+    pub fn skip_rate_of(&self, slots: &[Slot]) -> Option<f64> {
+        let inner = self.inner.lock().expect("skip rate tracker lock");
+        let (observed, skipped) = slots
+            .iter()
+            .filter_map(|slot| inner.produced.get(slot))
+            .fold((0usize, 0usize), |(observed, skipped), produced| {
+                (observed + 1, skipped + usize::from(!*produced))
+            });
+        (observed > 0).then(|| skipped as f64 / observed as f64)
+    }
+
+    ///
+    /// Returns the fraction of skipped slots among every recorded slot, `None` until a block is recorded.
+    ///
+    pub fn cluster_skip_rate(&self) -> Option<f64> {
+        let inner = self.inner.lock().expect("skip rate tracker lock");
+        (!inner.produced.is_empty()).then(|| inner.skipped_cnt as f64 / inner.produced.len() as f64)
+    }
+
+    ///
+    /// Returns the confidence, between `0.0` and `1.0`, that a leader scheduled at `slots` produces its blocks.
+    ///
+    /// The confidence is one minus the skip rate of recorded `slots`. Leaders without any recorded slot are
+    /// neutral and get the cluster skip rate, or full confidence before any block is recorded.
+    ///
+    pub fn confidence_of(&self, slots: &[Slot]) -> f64 {
+        let skip_rate = self
+            .skip_rate_of(slots)
+            .or_else(|| self.cluster_skip_rate())
+            .unwrap_or_default();
+        1.0 - skip_rate
+    }
+
+    ///
+    /// Number of slot outcomes currently tracked.
+    ///
+    pub fn len(&self) -> usize {
+        self.inner
+            .lock()
+            .expect("skip rate tracker lock")
+            .produced
+            .len()
+    }
+
+    ///
+    /// Returns `true` if no slot outcome is tracked.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_record_skipped_slots_between_parent_and_block() {
+        let tracker = SkipRateTracker::default();
+        assert_eq!(tracker.cluster_skip_rate(), None);
+        tracker.record_block(10, 9);
+        tracker.record_block(14, 10);
+
+        assert_eq!(tracker.len(), 5);
+        assert_eq!(tracker.skip_rate_of(&[10, 14]), Some(0.0));
+        assert_eq!(tracker.skip_rate_of(&[11, 12, 13, 14]), Some(0.75));
+        assert_eq!(tracker.skip_rate_of(&[100]), None);
+        assert_eq!(tracker.cluster_skip_rate(), Some(0.6));
+
+        // A block on another fork overrides the skipped outcome.
+        tracker.record_block(12, 10);
+        assert_eq!(tracker.skip_rate_of(&[11, 12, 13]), Some(2.0 / 3.0));
+        assert_eq!(tracker.cluster_skip_rate(), Some(0.4));
+    }
+
+    #[test]
+    fn it_should_evict_outcomes_outside_the_window() {
+        let tracker = SkipRateTracker::new(10);
+        tracker.record_block(1, 0);
+        tracker.record_block(5, 1);
+        tracker.record_block(20, 18);
+
+        assert_eq!(tracker.len(), 2);
+        assert_eq!(tracker.skip_rate_of(&[1, 2, 3, 4, 5]), None);
+        assert_eq!(tracker.cluster_skip_rate(), Some(0.5));
+
+        // A long gap only records skips within the window.
+        tracker.record_block(100, 20);
+        assert_eq!(tracker.len(), 11);
+        assert_eq!(tracker.confidence_of(&[]), 1.0 - 10.0 / 11.0);
+    }
+}
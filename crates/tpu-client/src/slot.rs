@@ -1,4 +1,85 @@
-use std::sync::atomic::{AtomicBool, AtomicU64};
+///
+/// Slot number, re-exported so slot positions are not confused with epochs or slot counts across the API.
+///
+pub use solana_clock::Slot;
+use {
+    crate::clock::{Clock, SystemClock},
+    solana_clock::{DEFAULT_MS_PER_SLOT, Epoch},
+    solana_commitment_config::CommitmentLevel,
+    solana_epoch_schedule::EpochSchedule,
+    std::{
+        sync::{
+            Arc, OnceLock,
+            atomic::{AtomicBool, AtomicU64},
+        },
+        time::{Duration, Instant},
+    },
+    tokio::sync::watch,
+};
+
+///
+/// Default wall-clock duration of a slot used by [`AtomicSlotTracker::estimated_slot`].
+///
+pub const DEFAULT_SLOT_DURATION: Duration = Duration::from_millis(DEFAULT_MS_PER_SLOT);
+
+///
+/// Maximum number of slots [`AtomicSlotTracker::estimated_slot`] can run ahead of the last observed slot.
+///
+pub const MAX_ESTIMATED_SLOT_DRIFT: u64 = 2;
+
+///
+/// Sentinel held by an [`AtomicSlotTracker`] that has not observed any slot yet, never a valid slot.
+///
+pub const UNSET_SLOT: Slot = Slot::MAX;
+
+///
+/// Position of a slot within its epoch, see [`AtomicSlotTracker::epoch_info`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochInfo {
+    pub epoch: Epoch,
+    pub slot: Slot,
+    ///
+    /// Index of the slot within its epoch, starting at `0`.
+    ///
+    pub slot_index: u64,
+    pub slots_in_epoch: u64,
+    ///
+    /// Number of slots left in the epoch after this one, `0` on the last slot of the epoch.
+    ///
+    pub slots_remaining: u64,
+}
+
+impl EpochInfo {
+    ///
+    /// Computes the position of `slot` within its epoch according to `epoch_schedule`, including warmup epochs.
+    ///
+    pub fn from_slot(epoch_schedule: &EpochSchedule, slot: Slot) -> Self {
+        let (epoch, slot_index) = epoch_schedule.get_epoch_and_slot_index(slot);
+        let slots_in_epoch = epoch_schedule.get_slots_in_epoch(epoch);
+        Self {
+            epoch,
+            slot,
+            slot_index,
+            slots_in_epoch,
+            slots_remaining: slots_in_epoch.saturating_sub(slot_index + 1),
+        }
+    }
+
+    ///
+    /// Returns `true` if the slot is the first slot of its epoch.
+    ///
+    pub const fn is_epoch_start(&self) -> bool {
+        self.slot_index == 0
+    }
+
+    ///
+    /// Returns `true` if the slot is the last slot of its epoch.
+    ///
+    pub const fn is_epoch_end(&self) -> bool {
+        self.slots_remaining == 0
+    }
+}
 
 ///
 /// An atomic slot tracker that can be shared across tasks.
@@ -7,41 +88,620 @@ use std::sync::atomic::{AtomicBool, AtomicU64};
 ///
 /// This struct is thread-safe. Shared it using an atomic reference-counter.
 ///
-/// # Poisoning
+/// # Lock-free
+///
+/// The slot lives in a single [`AtomicU64`]: [`AtomicSlotTracker::load`] is a wait-free atomic read that can't fail,
+/// and the background task publishes new slots with release stores. A panicking reader or writer can't leave the
+/// tracker in an inconsistent state.
+///
+/// # Closing
+///
+/// The tracker is closed once the background task updating it exits, panics or is dropped, see
+/// [`AtomicSlotTracker::is_closed`]. Loads keep returning the last observed slot, callers that must not act on a
+/// frozen slot check [`AtomicSlotTracker::is_closed`].
+///
+/// # Commitment levels
+///
+/// The slot returned by [`AtomicSlotTracker::load`] is the processed slot.
+/// Confirmed and finalized slots are tracked separately, see [`AtomicSlotTracker::load_commitment`].
 ///
-/// The slot tracker can be poisoned if the background task updating it panics or is dropped.
+/// # Subscription
 ///
+/// Use [`AtomicSlotTracker::subscribe`] to be notified when the slot advances instead of polling [`AtomicSlotTracker::load`].
+///
+/// # Readiness
+///
+/// A tracker created with [`AtomicSlotTracker::pending`] holds the [`UNSET_SLOT`] sentinel until the first update
+/// arrives, see [`AtomicSlotTracker::is_ready`]. Startup code should await
+/// [`AtomicSlotTracker::wait_until_ready`] before serving predictions.
+///
+/// # Staleness
+///
+/// While the upstream slot feed is being re-established, the tracker is marked stale, see [`AtomicSlotTracker::is_stale`].
+///
+/// # Clock
+///
+/// [`AtomicSlotTracker::age`] and [`AtomicSlotTracker::estimated_slot`] measure the time elapsed since the last update
+/// with the [`Clock`] installed through [`AtomicSlotTracker::with_clock`], the system clock by default.
+///
+/// # Epochs
+///
+/// [`AtomicSlotTracker::epoch_info`] locates the slot within its epoch, using the cluster [`EpochSchedule`] installed
+/// once through [`AtomicSlotTracker::set_epoch_schedule`], or [`EpochSchedule::without_warmup`] until then.
 ///
 pub struct AtomicSlotTracker {
+    /// Processed slot, [`UNSET_SLOT`] until a slot has been observed.
     pub(crate) slot: AtomicU64,
-    pub(crate) closed: AtomicBool,
+    /// Set once the background task updating the tracker has exited.
+    closed: AtomicBool,
+    /// Set while the upstream slot feed is disconnected.
+    stale: AtomicBool,
+    /// Confirmed slot + 1, `0` means no confirmed slot observed yet.
+    confirmed_slot: AtomicU64,
+    /// Finalized slot + 1, `0` means no finalized slot observed yet.
+    finalized_slot: AtomicU64,
+    /// Reference point of `last_updated_nanos`.
+    created_at: Instant,
+    /// Nanoseconds elapsed since `created_at` at the last slot update.
+    last_updated_nanos: AtomicU64,
+    /// Notifies subscribers whenever the slot advances.
+    slot_tx: watch::Sender<Slot>,
+    /// Slot duration used to interpolate the slot between updates, in nanoseconds.
+    slot_duration_nanos: AtomicU64,
+    /// Cluster epoch schedule, usually fetched once from RPC.
+    epoch_schedule: OnceLock<EpochSchedule>,
+    clock: Arc<dyn Clock + Send + Sync>,
 }
 
-#[derive(Debug, thiserror::Error)]
-#[error("AtomicSlotTracker poisoned, driver task may have panicked at slot {0}")]
-pub struct PoisonError(u64);
+///
+/// Error returned by [`AtomicSlotTracker::wait_until_ready`] when no slot is observed in time.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("no slot observed within {0:?}")]
+pub struct TimeoutError(pub Duration);
 
 impl AtomicSlotTracker {
-    #[allow(dead_code)]
-    pub(crate) fn new(initial_slot: u64) -> Self {
+    ///
+    /// Creates a tracker at `initial_slot`, the slot only advances through the crate slot feeds, or
+    /// `AtomicSlotTracker::push_slot` with the `test-utils` feature.
+    ///
+    pub fn new(initial_slot: Slot) -> Self {
+        Self::with_initial_slot(initial_slot)
+    }
+
+    ///
+    /// Creates a tracker without an observed slot, ready once the first slot is stored, see
+    /// [`AtomicSlotTracker::wait_until_ready`].
+    ///
+    /// The loaded slot is `0` until then.
+    ///
+    pub fn pending() -> Self {
+        Self::with_initial_slot(UNSET_SLOT)
+    }
+
+    fn with_initial_slot(initial_slot: Slot) -> Self {
+        let clock = Arc::new(SystemClock);
         Self {
             slot: AtomicU64::new(initial_slot),
             closed: AtomicBool::new(false),
+            stale: AtomicBool::new(false),
+            confirmed_slot: AtomicU64::new(0),
+            finalized_slot: AtomicU64::new(0),
+            created_at: clock.now(),
+            last_updated_nanos: AtomicU64::new(0),
+            slot_tx: watch::Sender::new(unset_as_zero(initial_slot)),
+            slot_duration_nanos: AtomicU64::new(DEFAULT_SLOT_DURATION.as_nanos() as u64),
+            epoch_schedule: OnceLock::new(),
+            clock,
         }
     }
 
+    ///
+    /// Replaces the clock measuring the time elapsed since the last update, see the
+    /// [clock section](AtomicSlotTracker#clock).
+    ///
+    /// The tracker counts as updated at the current instant of `clock`.
+    ///
+    pub fn with_clock(mut self, clock: Arc<dyn Clock + Send + Sync>) -> Self {
+        self.created_at = clock.now();
+        self.last_updated_nanos = AtomicU64::new(0);
+        self.clock = clock;
+        self
+    }
+
+    ///
+    /// Stores a new slot, refreshes the last update timestamp and notifies subscribers.
+    ///
+    /// Slots that are not strictly greater than the current one are ignored.
+    ///
+    /// Returns `true` if the slot advanced.
+    ///
+    pub(crate) fn store(&self, slot: Slot) -> bool {
+        // Update the atomics before subscribers are notified, so they observe the new slot through `load`.
+        self.slot_tx.send_if_modified(|current| {
+            let observed = self.slot.load(std::sync::atomic::Ordering::Relaxed);
+            if slot == UNSET_SLOT || (observed != UNSET_SLOT && slot <= observed) {
+                return false;
+            }
+            *current = slot;
+            self.slot.store(slot, std::sync::atomic::Ordering::Release);
+            let elapsed_nanos = self
+                .clock
+                .now()
+                .saturating_duration_since(self.created_at)
+                .as_nanos() as u64;
+            self.last_updated_nanos
+                .store(elapsed_nanos, std::sync::atomic::Ordering::Relaxed);
+            true
+        })
+    }
+
+    ///
+    /// Feeds a synthetic processed slot, as if observed from the slot feed: the tracker becomes ready, its
+    /// subscribers are notified and the last update timestamp is refreshed.
+    ///
+    /// Drives integration tests through slot transitions without a gRPC feed. Slots that are not strictly greater
+    /// than the current one are ignored.
+    ///
+    /// Returns `true` if the slot advanced.
+    ///
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn push_slot(&self, slot: Slot) -> bool {
+        self.store(slot)
+    }
+
+    ///
+    /// Stores a new slot for the given commitment level.
+    ///
+    /// Each level is monotonic: slots that are not strictly greater than the current one for that level are ignored.
+    /// A finalized slot is also confirmed.
+    ///
+    /// Returns `true` if the slot advanced.
+    ///
+    pub(crate) fn store_commitment(&self, commitment: CommitmentLevel, slot: Slot) -> bool {
+        let encoded = slot.saturating_add(1);
+        match commitment {
+            CommitmentLevel::Processed => self.store(slot),
+            CommitmentLevel::Confirmed => {
+                self.confirmed_slot
+                    .fetch_max(encoded, std::sync::atomic::Ordering::Relaxed)
+                    < encoded
+            }
+            CommitmentLevel::Finalized => {
+                self.confirmed_slot
+                    .fetch_max(encoded, std::sync::atomic::Ordering::Relaxed);
+                self.finalized_slot
+                    .fetch_max(encoded, std::sync::atomic::Ordering::Relaxed)
+                    < encoded
+            }
+        }
+    }
+
+    ///
+    /// Loads the latest slot observed for the given commitment level.
+    ///
+    /// Returns `None` if no slot has been observed yet for that level.
+    ///
+    pub fn load_commitment(&self, commitment: CommitmentLevel) -> Option<Slot> {
+        let encoded = match commitment {
+            CommitmentLevel::Processed => {
+                let slot = self.slot.load(std::sync::atomic::Ordering::Acquire);
+                return (slot != UNSET_SLOT).then_some(slot);
+            }
+            CommitmentLevel::Confirmed => &self.confirmed_slot,
+            CommitmentLevel::Finalized => &self.finalized_slot,
+        }
+        .load(std::sync::atomic::Ordering::Relaxed);
+        encoded.checked_sub(1)
+    }
+
+    ///
+    /// Marks the tracker as stale or fresh.
+    ///
+    pub(crate) fn set_stale(&self, stale: bool) {
+        self.stale
+            .store(stale, std::sync::atomic::Ordering::Release);
+    }
+
+    ///
+    /// Returns `true` while the upstream slot feed is disconnected and the tracked slot may lag behind the cluster.
+    ///
+    pub fn is_stale(&self) -> bool {
+        self.stale.load(std::sync::atomic::Ordering::Acquire)
+    }
+
+    ///
+    /// Subscribes to slot updates.
+    ///
+    /// The returned receiver is notified every time the tracked slot advances, so downstream tasks can
+    /// `.changed().await` instead of polling [`AtomicSlotTracker::load`].
+    ///
+    /// Closing is not signalled through the channel, use [`AtomicSlotTracker::is_closed`] to check it.
+    ///
+    pub fn subscribe(&self) -> watch::Receiver<Slot> {
+        self.slot_tx.subscribe()
+    }
+
+    ///
+    /// Returns the instant of the last slot update.
+    ///
+    pub fn last_updated_at(&self) -> Instant {
+        let nanos = self
+            .last_updated_nanos
+            .load(std::sync::atomic::Ordering::Relaxed);
+        self.created_at + Duration::from_nanos(nanos)
+    }
+
+    ///
+    /// Returns how long ago the slot was last updated.
+    ///
+    /// A growing age means the upstream slot feed has stalled.
+    ///
+    pub fn age(&self) -> Duration {
+        self.clock
+            .now()
+            .saturating_duration_since(self.last_updated_at())
+    }
+
+    ///
+    /// Sets the slot duration used by [`AtomicSlotTracker::estimated_slot`], defaults to [`DEFAULT_SLOT_DURATION`].
+    ///
+    /// A zero duration disables interpolation.
+    ///
+    pub fn set_slot_duration(&self, slot_duration: Duration) {
+        self.slot_duration_nanos.store(
+            slot_duration.as_nanos() as u64,
+            std::sync::atomic::Ordering::Relaxed,
+        );
+    }
+
+    ///
+    /// Returns the slot duration used by [`AtomicSlotTracker::estimated_slot`].
+    ///
+    pub fn slot_duration(&self) -> Duration {
+        Duration::from_nanos(
+            self.slot_duration_nanos
+                .load(std::sync::atomic::Ordering::Relaxed),
+        )
+    }
+
+    ///
+    /// Estimates the current slot by advancing the last observed slot by the wall-clock time elapsed since its update.
+    ///
+    /// Slot updates arrive in discrete ticks, the estimate smooths the drift in between.
+    /// The estimate never exceeds the last observed slot by more than [`MAX_ESTIMATED_SLOT_DRIFT`].
+    /// No estimation is done while the tracker is stale.
+    /// Use [`AtomicSlotTracker::load`] for the exact observed slot.
+    ///
+    pub fn estimated_slot(&self) -> Slot {
+        self.estimate_from(self.load())
+    }
+
+    pub(crate) fn estimate_from(&self, observed_slot: Slot) -> Slot {
+        let slot_duration_nanos = self
+            .slot_duration_nanos
+            .load(std::sync::atomic::Ordering::Relaxed);
+        if slot_duration_nanos == 0 || self.is_stale() {
+            return observed_slot;
+        }
+        let elapsed_slots = (self.age().as_nanos() / slot_duration_nanos as u128) as u64;
+        observed_slot.saturating_add(elapsed_slots.min(MAX_ESTIMATED_SLOT_DRIFT))
+    }
+
+    ///
+    /// Installs the cluster epoch schedule used by [`AtomicSlotTracker::epoch_info`].
+    ///
+    /// The epoch schedule never changes for a given cluster: only the first call is honored, and `false` is returned
+    /// if a schedule was already installed.
+    ///
+    pub fn set_epoch_schedule(&self, epoch_schedule: EpochSchedule) -> bool {
+        self.epoch_schedule.set(epoch_schedule).is_ok()
+    }
+
+    ///
+    /// Returns the position of the last observed slot within its epoch.
+    ///
+    /// See [`EpochInfo::from_slot`], the epoch schedule defaults to [`EpochSchedule::without_warmup`] until one is
+    /// installed with [`AtomicSlotTracker::set_epoch_schedule`].
+    ///
+    pub fn epoch_info(&self) -> EpochInfo {
+        self.epoch_info_at(self.load())
+    }
+
+    ///
+    /// Same as [`AtomicSlotTracker::epoch_info`], for an arbitrary `slot`.
+    ///
+    pub fn epoch_info_at(&self, slot: Slot) -> EpochInfo {
+        match self.epoch_schedule.get() {
+            Some(epoch_schedule) => EpochInfo::from_slot(epoch_schedule, slot),
+            None => EpochInfo::from_slot(&EpochSchedule::without_warmup(), slot),
+        }
+    }
+
+    ///
+    /// Returns the first slot of `epoch` in the epoch schedule of [`AtomicSlotTracker::epoch_info`], saturating at
+    /// [`Slot::MAX`] for epochs past the last representable slot.
+    ///
+    pub fn first_slot_in_epoch(&self, epoch: Epoch) -> Slot {
+        let without_warmup = EpochSchedule::without_warmup();
+        let epoch_schedule = self.epoch_schedule.get().unwrap_or(&without_warmup);
+        if epoch <= epoch_schedule.first_normal_epoch {
+            return epoch_schedule.get_first_slot_in_epoch(epoch);
+        }
+        (epoch - epoch_schedule.first_normal_epoch)
+            .saturating_mul(epoch_schedule.slots_per_epoch)
+            .saturating_add(epoch_schedule.first_normal_slot)
+    }
+
     ///
     /// Load the current slot.
     ///
-    /// Returns an error if the slot tracker is poisoned.
+    /// A single atomic read that never fails: returns the last observed slot, even if the tracker is closed, and `0`
+    /// until a slot has been observed, see [`AtomicSlotTracker::is_ready`].
+    ///
+    pub fn load(&self) -> Slot {
+        unset_as_zero(self.slot.load(std::sync::atomic::Ordering::Acquire))
+    }
+
+    ///
+    /// Returns `true` once a slot has been observed, always `true` for a tracker created with
+    /// [`AtomicSlotTracker::new`].
+    ///
+    pub fn is_ready(&self) -> bool {
+        self.slot.load(std::sync::atomic::Ordering::Acquire) != UNSET_SLOT
+    }
+
+    ///
+    /// Waits until a slot has been observed, see [`AtomicSlotTracker::is_ready`], and returns the last observed slot.
+    ///
+    /// Resolves right away if the tracker is already ready.
+    ///
+    /// # Errors
     ///
-    pub fn load(&self) -> Result<u64, PoisonError> {
-        let is_closed = self.closed.load(std::sync::atomic::Ordering::Acquire);
-        let slot = self.slot.load(std::sync::atomic::Ordering::Relaxed);
-        if is_closed {
-            Err(PoisonError(slot))
-        } else {
-            Ok(slot)
+    /// Returns [`TimeoutError`] if no slot is observed within `timeout`.
+    ///
+    pub async fn wait_until_ready(&self, timeout: Duration) -> Result<Slot, TimeoutError> {
+        let mut slot_rx = self.subscribe();
+        // The sender lives as long as the tracker, waiting can't fail.
+        let ready = slot_rx.wait_for(|_| self.is_ready());
+        match tokio::time::timeout(timeout, ready).await {
+            Ok(_) => Ok(self.load()),
+            Err(_) => Err(TimeoutError(timeout)),
         }
     }
+
+    ///
+    /// Returns `true` once the background task updating the tracker has exited, the loaded slot is frozen from then on.
+    ///
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(std::sync::atomic::Ordering::Acquire)
+    }
+
+    ///
+    /// Closes the tracker, see the [closing section](AtomicSlotTracker#closing).
+    ///
+    pub(crate) fn close(&self) {
+        self.closed
+            .store(true, std::sync::atomic::Ordering::Release);
+    }
+}
+
+const fn unset_as_zero(slot: Slot) -> Slot {
+    if slot == UNSET_SLOT { 0 } else { slot }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::clock::MockClock};
+
+    fn mock_clock_tracker(initial_slot: Slot) -> (AtomicSlotTracker, Arc<MockClock>) {
+        let clock = Arc::new(MockClock::new());
+        let slot_tracker = AtomicSlotTracker::new(initial_slot).with_clock(Arc::clone(&clock) as _);
+        (slot_tracker, clock)
+    }
+
+    #[test]
+    fn age_should_follow_the_clock() {
+        let (slot_tracker, clock) = mock_clock_tracker(100);
+        assert_eq!(slot_tracker.age(), Duration::ZERO);
+
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(slot_tracker.age(), Duration::from_secs(3));
+
+        // An update resets the age, ignored slots do not.
+        slot_tracker.store(101);
+        assert_eq!(slot_tracker.age(), Duration::ZERO);
+        assert_eq!(slot_tracker.last_updated_at(), clock.now());
+        clock.advance(Duration::from_millis(500));
+        slot_tracker.store(100);
+        assert_eq!(slot_tracker.age(), Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn it_should_wait_until_the_first_slot_is_observed() {
+        assert_eq!(
+            AtomicSlotTracker::new(7)
+                .wait_until_ready(Duration::ZERO)
+                .await,
+            Ok(7)
+        );
+
+        let slot_tracker = Arc::new(AtomicSlotTracker::pending());
+        assert!(!slot_tracker.is_ready());
+        assert_eq!(
+            slot_tracker
+                .wait_until_ready(Duration::from_millis(50))
+                .await,
+            Err(TimeoutError(Duration::from_millis(50)))
+        );
+
+        let feed = tokio::spawn({
+            let slot_tracker = Arc::clone(&slot_tracker);
+            async move {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                slot_tracker.store(42);
+            }
+        });
+        assert_eq!(
+            slot_tracker.wait_until_ready(Duration::from_secs(5)).await,
+            Ok(42)
+        );
+        assert!(slot_tracker.is_ready());
+        feed.await.expect("feed");
+    }
+
+    #[test]
+    fn epoch_info_should_follow_mainnet_epoch_schedule() {
+        // mainnet-beta: 432,000 slots per epoch, no warmup
+        let slot_tracker = AtomicSlotTracker::new(600 * 432_000 + 5);
+        let expected = EpochInfo {
+            epoch: 600,
+            slot: 600 * 432_000 + 5,
+            slot_index: 5,
+            slots_in_epoch: 432_000,
+            slots_remaining: 431_994,
+        };
+        assert_eq!(slot_tracker.epoch_info(), expected);
+        assert!(slot_tracker.set_epoch_schedule(EpochSchedule::custom(432_000, 432_000, false)));
+        assert!(!slot_tracker.set_epoch_schedule(EpochSchedule::custom(8_192, 8_192, true)));
+        assert_eq!(slot_tracker.epoch_info(), expected);
+
+        slot_tracker.store(601 * 432_000 - 1);
+        assert!(slot_tracker.epoch_info().is_epoch_end());
+        slot_tracker.store(601 * 432_000);
+        let epoch_info = slot_tracker.epoch_info();
+        assert_eq!(epoch_info.epoch, 601);
+        assert!(epoch_info.is_epoch_start());
+    }
+
+    #[test]
+    fn epoch_info_should_follow_warmup_epochs() {
+        // devnet/testnet: 432,000 slots per epoch after warmup epochs doubling from 32 slots.
+        let epoch_schedule = EpochSchedule::custom(432_000, 432_000, true);
+        let first_normal_slot = 32 * ((1 << 14) - 1);
+        for (slot, epoch, slot_index, slots_in_epoch) in [
+            (0, 0, 0, 32),
+            (31, 0, 31, 32),
+            (32, 1, 0, 64),
+            (95, 1, 63, 64),
+            (96, 2, 0, 128),
+            (first_normal_slot - 1, 13, 262_143, 262_144),
+            (first_normal_slot, 14, 0, 432_000),
+            (first_normal_slot + 432_000 + 7, 15, 7, 432_000),
+        ] {
+            assert_eq!(
+                EpochInfo::from_slot(&epoch_schedule, slot),
+                EpochInfo {
+                    epoch,
+                    slot,
+                    slot_index,
+                    slots_in_epoch,
+                    slots_remaining: slots_in_epoch - slot_index - 1,
+                },
+                "slot {slot}"
+            );
+        }
+
+        let slot_tracker = AtomicSlotTracker::new(first_normal_slot - 1);
+        assert!(slot_tracker.set_epoch_schedule(epoch_schedule));
+        let epoch_info = slot_tracker.epoch_info();
+        assert_eq!(epoch_info.epoch, 13);
+        assert!(epoch_info.is_epoch_end());
+    }
+
+    #[test]
+    fn estimated_slot_should_advance_with_elapsed_time() {
+        let (slot_tracker, clock) = mock_clock_tracker(100);
+        slot_tracker.set_slot_duration(Duration::from_millis(20));
+        assert_eq!(slot_tracker.estimated_slot(), 100);
+
+        clock.advance(Duration::from_millis(19));
+        assert_eq!(slot_tracker.estimated_slot(), 100);
+        clock.advance(Duration::from_millis(6));
+        assert_eq!(slot_tracker.estimated_slot(), 101);
+        // The raw slot stays exact
+        assert_eq!(slot_tracker.load(), 100);
+    }
+
+    #[test]
+    fn estimated_slot_should_be_clamped() {
+        let (slot_tracker, clock) = mock_clock_tracker(100);
+        slot_tracker.set_slot_duration(Duration::from_millis(1));
+        clock.advance(Duration::from_millis(20));
+        assert_eq!(
+            slot_tracker.estimated_slot(),
+            100 + MAX_ESTIMATED_SLOT_DRIFT
+        );
+
+        // A fresh update resets the interpolation
+        slot_tracker.set_slot_duration(Duration::from_secs(60));
+        slot_tracker.store(105);
+        assert_eq!(slot_tracker.estimated_slot(), 105);
+    }
+
+    #[test]
+    fn estimated_slot_should_not_advance_when_stale() {
+        let (slot_tracker, clock) = mock_clock_tracker(100);
+        slot_tracker.set_slot_duration(Duration::from_millis(1));
+        slot_tracker.set_stale(true);
+        clock.advance(Duration::from_millis(5));
+        assert!(slot_tracker.is_stale());
+        assert_eq!(slot_tracker.estimated_slot(), 100);
+
+        slot_tracker.set_stale(false);
+        assert_eq!(
+            slot_tracker.estimated_slot(),
+            100 + MAX_ESTIMATED_SLOT_DRIFT
+        );
+    }
+
+    #[test]
+    fn estimated_slot_should_be_exact_with_zero_slot_duration() {
+        let (slot_tracker, clock) = mock_clock_tracker(100);
+        slot_tracker.set_slot_duration(Duration::ZERO);
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(slot_tracker.estimated_slot(), 100);
+    }
+
+    #[test]
+    fn closed_tracker_should_keep_loading_the_last_observed_slot() {
+        let slot_tracker = AtomicSlotTracker::new(100);
+        slot_tracker.store(101);
+        assert!(!slot_tracker.is_closed());
+
+        slot_tracker.close();
+        assert!(slot_tracker.is_closed());
+        assert_eq!(slot_tracker.load(), 101);
+        assert_eq!(
+            slot_tracker.load_commitment(CommitmentLevel::Processed),
+            Some(101)
+        );
+    }
+
+    #[test]
+    fn pending_tracker_should_hold_the_unset_sentinel_until_the_first_slot() {
+        let slot_tracker = AtomicSlotTracker::pending();
+        assert_eq!(
+            slot_tracker.slot.load(std::sync::atomic::Ordering::Relaxed),
+            UNSET_SLOT
+        );
+        assert!(!slot_tracker.is_ready());
+        assert_eq!(slot_tracker.load(), 0);
+        assert_eq!(
+            slot_tracker.load_commitment(CommitmentLevel::Processed),
+            None
+        );
+
+        // Genesis is a valid first slot, the sentinel is never stored.
+        assert!(slot_tracker.store(0));
+        assert!(slot_tracker.is_ready());
+        assert_eq!(slot_tracker.load(), 0);
+        assert!(!slot_tracker.store(UNSET_SLOT));
+        assert!(!slot_tracker.store(0));
+        assert!(slot_tracker.store(1));
+        assert_eq!(
+            slot_tracker.load_commitment(CommitmentLevel::Processed),
+            Some(1)
+        );
+    }
 }
@@ -0,0 +1,311 @@
+//!
+//! Slot drift detection between the slot feed and a reference RPC.
+//!
+//! A lagging or forked gRPC provider can stay *alive*, keeping the slot tracker fresh, while consistently reporting
+//! a slot behind the cluster: leader predictions are then subtly wrong without any disconnection being noticed.
+//! [`SlotDriftMonitor`] periodically compares the slot of the [`AtomicSlotTracker`] against the slot polled from a
+//! reference RPC and raises the `feed_drift` flag, see [`SlotDriftMonitor::is_drifting`], once the drift stays beyond
+//! a threshold for several consecutive polls.
+//!
+//! Use [`spawn_slot_drift_monitor`] to run the polling loop in the background and
+//! [`crate::health::check_feed_drift`] to report the flag in health checks.
+//!
+#[cfg(feature = "prometheus")]
+use crate::prom;
+use {
+    crate::{
+        slot::AtomicSlotTracker,
+        task::{SLOT_DRIFT_MONITOR_TASK, spawn_named},
+    },
+    serde::Deserialize,
+    solana_client::{client_error::ClientError, nonblocking::rpc_client::RpcClient},
+    solana_clock::Slot,
+    solana_commitment_config::CommitmentConfig,
+    std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    },
+    tokio::{task::JoinHandle, time::MissedTickBehavior},
+};
+
+///
+/// Default interval between two reference RPC slot polls.
+///
+pub const DEFAULT_SLOT_DRIFT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+///
+/// Default maximum absolute drift, in slots, before a poll counts as drifting.
+///
+pub const DEFAULT_SLOT_DRIFT_THRESHOLD: u64 = 4;
+
+///
+/// Default number of consecutive drifting polls before the `feed_drift` flag is raised.
+///
+pub const DEFAULT_SLOT_DRIFT_SUSTAINED_POLLS: usize = 3;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SlotDriftMonitorConfig {
+    ///
+    /// Interval between two reference RPC slot polls.
+    ///
+    #[serde(
+        with = "humantime_serde",
+        default = "SlotDriftMonitorConfig::default_poll_interval"
+    )]
+    pub poll_interval: Duration,
+    ///
+    /// Maximum absolute drift, in slots, before a poll counts as drifting.
+    ///
+    /// Both slots are polled at the processed commitment, a small drift is expected from the polling latency alone.
+    ///
+    #[serde(default = "SlotDriftMonitorConfig::default_threshold")]
+    pub threshold: u64,
+    ///
+    /// Number of consecutive drifting polls before the `feed_drift` flag is raised.
+    ///
+    #[serde(default = "SlotDriftMonitorConfig::default_sustained_polls")]
+    pub sustained_polls: usize,
+}
+
+impl SlotDriftMonitorConfig {
+    pub const fn default_poll_interval() -> Duration {
+        DEFAULT_SLOT_DRIFT_POLL_INTERVAL
+    }
+
+    pub const fn default_threshold() -> u64 {
+        DEFAULT_SLOT_DRIFT_THRESHOLD
+    }
+
+    pub const fn default_sustained_polls() -> usize {
+        DEFAULT_SLOT_DRIFT_SUSTAINED_POLLS
+    }
+}
+
+impl Default for SlotDriftMonitorConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Self::default_poll_interval(),
+            threshold: Self::default_threshold(),
+            sustained_polls: Self::default_sustained_polls(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct DriftState {
+    ///
+    /// Last observed drift, `None` until the first observation.
+    ///
+    drift: Option<i64>,
+    ///
+    /// Number of consecutive observations beyond the threshold.
+    ///
+    consecutive: usize,
+    drifting: bool,
+}
+
+///
+/// Tracks the drift between the slot feed and a reference RPC.
+///
+/// # Safety
+///
+/// This struct is thread-safe. Share it using an atomic reference-counter.
+///
+pub struct SlotDriftMonitor {
+    threshold: u64,
+    sustained_polls: usize,
+    inner: Mutex<DriftState>,
+}
+
+impl SlotDriftMonitor {
+    ///
+    /// Creates a monitor raising the `feed_drift` flag once `sustained_polls` consecutive observations drift by more
+    /// than `threshold` slots.
+    ///
+    pub fn new(threshold: u64, sustained_polls: usize) -> Self {
+        Self {
+            threshold,
+            sustained_polls: sustained_polls.max(1),
+            inner: Default::default(),
+        }
+    }
+
+    ///
+    /// Records a comparison between the slot of the feed and the slot of the reference RPC.
+    ///
+    /// Returns the signed drift, `feed_slot - reference_slot`: negative when the feed is behind.
+    /// A single observation within the threshold clears the `feed_drift` flag.
+    ///
+    pub fn observe(&self, feed_slot: Slot, reference_slot: Slot) -> i64 {
+        let drift = feed_slot as i64 - reference_slot as i64;
+        let mut inner = self.inner.lock().expect("slot drift lock");
+        inner.drift = Some(drift);
+        if drift.unsigned_abs() > self.threshold {
+            inner.consecutive = inner.consecutive.saturating_add(1);
+        } else {
+            inner.consecutive = 0;
+        }
+        let drifting = inner.consecutive >= self.sustained_polls;
+        if drifting != inner.drifting {
+            if drifting {
+                tracing::warn!(
+                    "Slot feed drifted {drift} slot(s) from the reference RPC for {} consecutive polls (feed slot {feed_slot}, reference slot {reference_slot})",
+                    inner.consecutive
+                );
+            } else {
+                tracing::info!("Slot feed caught up with the reference RPC, drift {drift} slot(s)");
+            }
+            inner.drifting = drifting;
+        }
+        drift
+    }
+
+    ///
+    /// Polls the slot of `reference` and compares it against the slot of `slot_tracker`, see
+    /// [`SlotDriftMonitor::observe`].
+    ///
+    /// The feed slot is loaded right after the reference RPC responds, the polling latency only makes the feed look
+    /// ahead. The drift and the flag are published through the `slot_feed_drift` and `feed_drift` metrics.
+    /// Returns `Ok(None)` if the slot tracker is closed.
+    ///
+    pub async fn poll(
+        &self,
+        slot_tracker: &AtomicSlotTracker,
+        reference: &RpcClient,
+    ) -> Result<Option<i64>, ClientError> {
+        let reference_slot = reference
+            .get_slot_with_commitment(CommitmentConfig::processed())
+            .await?;
+        if slot_tracker.is_closed() {
+            return Ok(None);
+        }
+        let feed_slot = slot_tracker.load();
+        let drift = self.observe(feed_slot, reference_slot);
+        #[cfg(feature = "prometheus")]
+        {
+            prom::set_slot_feed_drift(drift);
+            prom::set_feed_drift(self.is_drifting());
+        }
+        Ok(Some(drift))
+    }
+
+    ///
+    /// Last observed drift, `feed_slot - reference_slot`, `None` until the first observation.
+    ///
+    pub fn drift(&self) -> Option<i64> {
+        self.inner.lock().expect("slot drift lock").drift
+    }
+
+    ///
+    /// The `feed_drift` flag: `true` while the drift stays beyond the threshold for the configured number of
+    /// consecutive polls.
+    ///
+    pub fn is_drifting(&self) -> bool {
+        self.inner.lock().expect("slot drift lock").drifting
+    }
+
+    ///
+    /// Maximum absolute drift, in slots, before an observation counts as drifting.
+    ///
+    pub const fn threshold(&self) -> u64 {
+        self.threshold
+    }
+}
+
+impl From<&SlotDriftMonitorConfig> for SlotDriftMonitor {
+    fn from(config: &SlotDriftMonitorConfig) -> Self {
+        Self::new(config.threshold, config.sustained_polls)
+    }
+}
+
+///
+/// Spawns a background task polling `reference` every [`SlotDriftMonitorConfig::poll_interval`] and comparing its
+/// slot against `slot_tracker`.
+///
+/// Failed polls are logged and skipped, the last drift is kept. The task exits once the slot tracker is closed.
+///
+pub fn spawn_slot_drift_monitor(
+    slot_tracker: Arc<AtomicSlotTracker>,
+    reference: Arc<RpcClient>,
+    config: SlotDriftMonitorConfig,
+) -> (Arc<SlotDriftMonitor>, JoinHandle<()>) {
+    let monitor = Arc::new(SlotDriftMonitor::from(&config));
+    let jh = spawn_named(SLOT_DRIFT_MONITOR_TASK, {
+        let monitor = Arc::clone(&monitor);
+        async move {
+            let mut interval = tokio::time::interval(config.poll_interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match monitor.poll(&slot_tracker, &reference).await {
+                    Ok(Some(drift)) => tracing::trace!("Slot feed drift: {drift} slot(s)"),
+                    Ok(None) => {
+                        tracing::debug!("Slot tracker closed, stopping the slot drift monitor");
+                        break;
+                    }
+                    Err(e) => tracing::warn!("Failed to poll the reference RPC slot: {e}"),
+                }
+            }
+        }
+    });
+    (monitor, jh)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::rpc::solana_rpc_utils::testkit::{MockRpcSender, return_sucess},
+        solana_client::{rpc_client::RpcClientConfig, rpc_request::RpcRequest},
+    };
+
+    fn reference_rpc(slot: Slot) -> RpcClient {
+        let mock_rpc_sender = MockRpcSender::default();
+        mock_rpc_sender.set_method_return(RpcRequest::GetSlot, return_sucess(slot));
+        RpcClient::new_sender(mock_rpc_sender, RpcClientConfig::default())
+    }
+
+    #[tokio::test]
+    async fn it_should_flag_a_sustained_drift_from_the_reference_rpc() {
+        let slot_tracker = AtomicSlotTracker::new(100);
+        let reference = reference_rpc(105);
+        let monitor = SlotDriftMonitor::new(3, 2);
+        assert_eq!(monitor.drift(), None);
+
+        let drift = monitor.poll(&slot_tracker, &reference).await.expect("poll");
+        assert_eq!(drift, Some(-5));
+        assert_eq!(monitor.drift(), Some(-5));
+        // A single drifting poll is not sustained.
+        assert!(!monitor.is_drifting());
+        #[cfg(feature = "prometheus")]
+        {
+            assert_eq!(prom::slot_feed_drift(), -5);
+            assert_eq!(prom::feed_drift(), 0);
+        }
+
+        monitor.poll(&slot_tracker, &reference).await.expect("poll");
+        assert!(monitor.is_drifting());
+        #[cfg(feature = "prometheus")]
+        {
+            assert_eq!(prom::slot_feed_drift(), -5);
+            assert_eq!(prom::feed_drift(), 1);
+        }
+
+        // Caught up, within the threshold.
+        slot_tracker.store(103);
+        let drift = monitor.poll(&slot_tracker, &reference).await.expect("poll");
+        assert_eq!(drift, Some(-2));
+        assert!(!monitor.is_drifting());
+        #[cfg(feature = "prometheus")]
+        {
+            assert_eq!(prom::slot_feed_drift(), -2);
+            assert_eq!(prom::feed_drift(), 0);
+        }
+
+        // The drift must be sustained again before the flag is raised.
+        assert_eq!(monitor.observe(110, 100), 10);
+        assert!(!monitor.is_drifting());
+        assert_eq!(monitor.observe(111, 100), 11);
+        assert!(monitor.is_drifting());
+    }
+}
@@ -0,0 +1,347 @@
+//!
+//! Names of the background tasks spawned by this crate.
+//!
+//! With the `tokio-console` feature flag, background tasks are spawned through [`tokio::task::Builder`] with the
+//! names below, so [tokio-console](https://github.com/tokio-rs/console) shows which task is wedged.
+//! Task instrumentation is an unstable tokio API, the binary must be built with:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg tokio_unstable" cargo build --features tokio-console
+//! ```
+//!
+//! and install a console subscriber, e.g. `console_subscriber::init()`.
+//! Without `--cfg tokio_unstable`, the feature flag is a no-op and tasks are spawned unnamed.
+//!
+//! # Exit reasons
+//!
+//! The slot tracker and leader schedule tasks poison their shared state when they exit. To tell a clean stop from
+//! a crash, they hold a [`TaskExitGuard`] logging and metering why they exited, see [`TaskExitReason`] and
+//! [`task_exit_count`].
+//!
+#[cfg(feature = "prometheus")]
+use crate::prom;
+use {
+    std::{
+        collections::HashMap,
+        fmt,
+        future::Future,
+        sync::{LazyLock, Mutex, OnceLock},
+    },
+    tokio::{runtime::Handle, task::JoinHandle},
+};
+
+pub const TPU_SENDER_DRIVER_TASK: &str = "jet-tpu-client:tpu-sender-driver";
+pub const REMOTE_PEER_ADDR_WATCHER_TASK: &str = "jet-tpu-client:remote-peer-addr-watcher";
+pub const LEADER_SCHEDULE_TASK: &str = "jet-tpu-client:leader-schedule";
+pub const CONTACT_INFO_CACHE_TASK: &str = "jet-tpu-client:contact-info-cache";
+pub const TPU_INFO_TASK: &str = "jet-tpu-client:tpu-info";
+pub const STAKE_INFO_TASK: &str = "jet-tpu-client:stake-info";
+pub const SLOT_TRACKER_TASK: &str = "jet-tpu-client:slot-tracker";
+pub const SLOT_DRIFT_MONITOR_TASK: &str = "jet-tpu-client:slot-drift-monitor";
+pub const WARM_CONNECTION_MANAGER_TASK: &str = "jet-tpu-client:warm-connection-manager";
+pub const DEPENDENCY_OVERSEER_TASK: &str = "jet-tpu-client:dependency-overseer";
+
+///
+/// Spawns `future` on the current runtime, named `name` when task instrumentation is enabled.
+///
+#[track_caller]
+pub(crate) fn spawn_named<F>(name: &'static str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    spawn_named_on(name, future, &Handle::current())
+}
+
+///
+/// Spawns `future` on the `handle` runtime, named `name` when task instrumentation is enabled.
+///
+#[track_caller]
+pub(crate) fn spawn_named_on<F>(
+    name: &'static str,
+    future: F,
+    handle: &Handle,
+) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(all(feature = "tokio-console", tokio_unstable))]
+    {
+        tokio::task::Builder::new()
+            .name(name)
+            .spawn_on(future, handle)
+            .expect("spawn named task")
+    }
+    #[cfg(not(all(feature = "tokio-console", tokio_unstable)))]
+    {
+        let _ = name;
+        handle.spawn(future)
+    }
+}
+
+///
+/// Why a background task exited.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskExitReason {
+    ///
+    /// The task was asked to stop, e.g. through its cancellation token.
+    ///
+    Shutdown,
+    ///
+    /// The upstream stream feeding the task ended or failed for good, e.g. the gRPC slot subscription.
+    ///
+    StreamError,
+    ///
+    /// The task panicked.
+    ///
+    Panicked,
+    ///
+    /// The task was dropped before completing, e.g. aborted or its runtime shut down.
+    ///
+    Aborted,
+}
+
+impl TaskExitReason {
+    const ALL: [Self; 4] = [
+        Self::Shutdown,
+        Self::StreamError,
+        Self::Panicked,
+        Self::Aborted,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Shutdown => "shutdown",
+            Self::StreamError => "stream_error",
+            Self::Panicked => "panicked",
+            Self::Aborted => "aborted",
+        }
+    }
+
+    ///
+    /// Returns `true` if the task stopped on request rather than crashed.
+    ///
+    pub const fn is_clean(self) -> bool {
+        matches!(self, Self::Shutdown)
+    }
+
+    const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for TaskExitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+static TASK_EXITS: LazyLock<Mutex<HashMap<&'static str, [u64; TaskExitReason::ALL.len()]>>> =
+    LazyLock::new(Default::default);
+
+///
+/// Number of times the background tasks named `task` exited for `reason` since the process started.
+///
+pub fn task_exit_count(task: &str, reason: TaskExitReason) -> u64 {
+    TASK_EXITS
+        .lock()
+        .expect("task exits lock")
+        .get(task)
+        .map_or(0, |counts| counts[reason.index()])
+}
+
+///
+/// Records why a background task exited when dropped, held by the task for its whole lifetime.
+///
+/// The task sets its exit reason with [`TaskExitGuard::set_reason`] before returning. Otherwise, the guard records
+/// [`TaskExitReason::Panicked`] if dropped while unwinding, [`TaskExitReason::Aborted`] if the task future was
+/// dropped before completing.
+///
+pub(crate) struct TaskExitGuard {
+    task: &'static str,
+    reason: OnceLock<TaskExitReason>,
+}
+
+impl TaskExitGuard {
+    pub(crate) const fn new(task: &'static str) -> Self {
+        Self {
+            task,
+            reason: OnceLock::new(),
+        }
+    }
+
+    ///
+    /// Sets the exit reason of the task, only the first reason is kept.
+    ///
+    pub(crate) fn set_reason(&self, reason: TaskExitReason) {
+        let _ = self.reason.set(reason);
+    }
+}
+
+impl Drop for TaskExitGuard {
+    fn drop(&mut self) {
+        let reason = match self.reason.get() {
+            Some(reason) => *reason,
+            None if std::thread::panicking() => TaskExitReason::Panicked,
+            None => TaskExitReason::Aborted,
+        };
+        let task = self.task;
+        match reason {
+            TaskExitReason::Shutdown => tracing::info!("Background task {task} exited: {reason}"),
+            TaskExitReason::Aborted => tracing::warn!("Background task {task} exited: {reason}"),
+            TaskExitReason::StreamError | TaskExitReason::Panicked => {
+                tracing::error!("Background task {task} exited: {reason}")
+            }
+        }
+        #[cfg(feature = "prometheus")]
+        {
+            prom::incr_background_task_exit(task, reason.as_str());
+        }
+        // Never panic while unwinding, a poisoned lock only loses the count.
+        if let Ok(mut exits) = TASK_EXITS.lock() {
+            exits.entry(task).or_default()[reason.index()] += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod exit_tests {
+    use super::*;
+
+    fn failing_update() {
+        panic!("updater failed");
+    }
+
+    #[tokio::test]
+    async fn it_should_record_a_panicking_task_as_panicked() {
+        const TASK: &str = "jet-tpu-client:test-panicking-updater";
+        let jh = spawn_named(TASK, async {
+            let _exit = TaskExitGuard::new(TASK);
+            tokio::task::yield_now().await;
+            failing_update();
+        });
+        assert!(jh.await.expect_err("panicked").is_panic());
+        assert_eq!(task_exit_count(TASK, TaskExitReason::Panicked), 1);
+        assert_eq!(task_exit_count(TASK, TaskExitReason::Aborted), 0);
+    }
+
+    #[tokio::test]
+    async fn it_should_distinguish_shutdown_stream_error_and_abort() {
+        const TASK: &str = "jet-tpu-client:test-updater";
+        spawn_named(TASK, async {
+            let exit = TaskExitGuard::new(TASK);
+            exit.set_reason(TaskExitReason::Shutdown);
+        })
+        .await
+        .expect("task");
+        spawn_named(TASK, async {
+            let exit = TaskExitGuard::new(TASK);
+            exit.set_reason(TaskExitReason::StreamError);
+            // The first reason wins
+            exit.set_reason(TaskExitReason::Shutdown);
+        })
+        .await
+        .expect("task");
+        let jh = spawn_named(TASK, async {
+            let _exit = TaskExitGuard::new(TASK);
+            std::future::pending::<()>().await;
+        });
+        jh.abort();
+        assert!(jh.await.expect_err("aborted").is_cancelled());
+
+        assert_eq!(task_exit_count(TASK, TaskExitReason::Shutdown), 1);
+        assert_eq!(task_exit_count(TASK, TaskExitReason::StreamError), 1);
+        assert_eq!(task_exit_count(TASK, TaskExitReason::Aborted), 1);
+        assert_eq!(task_exit_count(TASK, TaskExitReason::Panicked), 0);
+        assert!(TaskExitReason::Shutdown.is_clean());
+        assert!(!TaskExitReason::StreamError.is_clean());
+    }
+}
+
+#[cfg(all(test, feature = "tokio-console", tokio_unstable))]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            core::IgnorantLeaderPredictor,
+            rpc::contact::ContactInfoTable,
+            warm_connection::{WarmConnectionManagerConfig, spawn_warm_connection_manager},
+        },
+        std::sync::{Arc, Mutex},
+        tokio::sync::watch,
+        tracing_subscriber::{Layer, layer::Context, layer::SubscriberExt},
+    };
+
+    ///
+    /// Layer recording the name of every spawned task, from the tokio `runtime.spawn` spans.
+    ///
+    #[derive(Clone, Default)]
+    struct TaskNameRecorder(Arc<Mutex<Vec<String>>>);
+
+    struct TaskNameVisitor<'a>(&'a mut Option<String>);
+
+    impl tracing::field::Visit for TaskNameVisitor<'_> {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            if field.name() == "task.name" {
+                *self.0 = Some(value.to_string());
+            }
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "task.name" {
+                *self.0 = Some(format!("{value:?}"));
+            }
+        }
+    }
+
+    impl<S: tracing::Subscriber> Layer<S> for TaskNameRecorder {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            _id: &tracing::span::Id,
+            _ctx: Context<'_, S>,
+        ) {
+            if attrs.metadata().name() != "runtime.spawn" {
+                return;
+            }
+            let mut name = None;
+            attrs.record(&mut TaskNameVisitor(&mut name));
+            if let Some(name) = name {
+                self.0.lock().unwrap().push(name);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_spawn_named_background_tasks() {
+        let recorder = TaskNameRecorder::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+
+        spawn_named(LEADER_SCHEDULE_TASK, async {})
+            .await
+            .expect("task");
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        let (_manager, _jh) = spawn_warm_connection_manager(
+            WarmConnectionManagerConfig::default(),
+            slot_rx,
+            Arc::new(IgnorantLeaderPredictor),
+            Arc::new(ContactInfoTable::default()),
+        )
+        .expect("spawn");
+
+        let names = recorder.0.lock().unwrap().clone();
+        assert!(
+            names.iter().any(|name| name == LEADER_SCHEDULE_TASK),
+            "{names:?}"
+        );
+        assert!(
+            names
+                .iter()
+                .any(|name| name == WARM_CONNECTION_MANAGER_TASK),
+            "{names:?}"
+        );
+    }
+}
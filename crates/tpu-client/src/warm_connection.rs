@@ -0,0 +1,4431 @@
+//!
+//! Keeps QUIC connections to upcoming leaders warm.
+//!
+//! # Overview
+//!
+//! [`WarmConnectionManager`] follows the slot stream: on each slot change it asks an [`UpcomingLeaderPredictor`]
+//! for the next leaders, resolves their TPU addresses through a [`LeaderTpuInfoService`] and opens the QUIC connections
+//! it does not already have.
+//!
+//! Opened connections are kept alive by QUIC PING frames sent every [`WarmConnectionManagerConfig::keep_alive_interval`],
+//! so they are not dropped by the leader for idleness and are ready by the time its rotation starts.
+//! Connections opened together, e.g. when several leaders enter the window at once, would ping in lockstep: each
+//! connection shortens its interval by a deterministic offset derived from the leader pubkey, up to
+//! [`WarmConnectionManagerConfig::keep_alive_jitter`], so pings drift apart.
+//!
+//! # Prediction and warm windows
+//!
+//! Two knobs size the windows followed by the manager:
+//!
+//! - [`WarmConnectionManagerConfig::prediction_lookahead`] is the number of leaders predicted on each slot change.
+//!   The predicted window is published for analytics and logging, see
+//!   [`WarmConnectionManager::subscribe_predicted_window`].
+//! - [`WarmConnectionManagerConfig::lookahead`] is the number of leaders connections are kept warm to: the first
+//!   `lookahead` distinct leaders of the predicted window whose TPU address resolves.
+//!
+//! The prediction window defaults to the warm window, it must not be shorter.
+//!
+//! A connection takes a handshake and a round trip to establish: a leader entering the warm window right before its
+//! rotation may not be connected in time. [`WarmConnectionManagerConfig::warmup_lead_slots`] extends the warm window
+//! forward: on top of the first `lookahead` leaders, every predicted leader whose rotation starts within
+//! `warmup_lead_slots` slots of the current slot is warmed, so a leader starts warming at least that many slots
+//! before it becomes current. The prediction window grows to cover the lead.
+//!
+//! Leaders that fall out of the predicted window are not disconnected right away: their connection is closed
+//! once they have been out of the window for longer than [`WarmConnectionManagerConfig::grace_period`].
+//! This avoids connection churn when predictions flap around leader boundaries.
+//!
+//! # Connection cap
+//!
+//! The number of open connections is bounded by [`WarmConnectionManagerConfig::max_connections`].
+//! When opening a new connection would exceed the cap, the least-recently-used connection is evicted.
+//! Leaders in the current predicted window are never evicted: if all connections belong to the window,
+//! the new connection is refused and reattempted on the next slot change.
+//!
+//! Connections are opened in the [`WarmConnectionManagerConfig::warming_order`] order, so under the cap the first
+//! leaders of that order get the connections: by default the current leader, then the upcoming ones.
+//!
+//! # Pinned leaders
+//!
+//! [`WarmConnectionManager::warm`] pins leaders known in advance, e.g. ahead of a scheduled transfer, on top of the
+//! predicted window. Pinned leaders are connected right away and kept connected like the leaders of the predicted
+//! window, they are neither evicted nor closed by the grace period, until
+//! [`WarmConnectionManagerConfig::pin_ttl`] elapsed since they were last pinned. They are connected after the
+//! predicted window, so under the connection cap the predicted leaders get the connections first.
+//! See [`WarmConnectionManager::origin`] to tell pinned connections from predicted ones.
+//!
+//! # Circuit breaker
+//!
+//! A leader whose TPU is unreachable would otherwise be reattempted on every slot change.
+//! After [`WarmConnectionManagerConfig::circuit_breaker_failure_threshold`] consecutive connection failures, the
+//! breaker of the leader opens: connection attempts are skipped for
+//! [`WarmConnectionManagerConfig::circuit_breaker_cooldown`]. The breaker then becomes half-open and a single probe
+//! is attempted: a successful connection closes the breaker, a failure opens it for another cooldown.
+//! The leader stays in the predicted window meanwhile, see [`WarmConnectionManager::circuit_breaker_state`].
+//!
+//! # Batches
+//!
+//! [`WarmConnectionManager::send_batch`] sends a bundle of transactions to one leader over its warm connection,
+//! opening up to [`WarmConnectionManagerConfig::max_concurrent_streams`] streams at once. quinn holds a stream open
+//! until the leader grants stream credit, so the leader's own concurrent stream limit, advertised during the
+//! handshake, is respected too: excess transactions wait for an earlier stream to complete.
+//!
+//! # Send concurrency
+//!
+//! Many callers sending to the same leader at once, e.g. a burst of fan-outs while it is current, could open more
+//! streams than its TPU accepts. [`WarmConnectionManagerConfig::max_streams_per_leader`] caps the sends in flight to
+//! each leader across all callers of [`WarmConnectionManager::send`], [`WarmConnectionManager::send_batch`] and
+//! [`crate::forwarder::LeaderForwarder`]: excess sends wait in a FIFO queue for an earlier send to complete. Up to
+//! [`WarmConnectionManagerConfig::max_queued_sends_per_leader`] sends wait per leader, further sends fail right away
+//! with [`SendError::LeaderBusy`]. See [`WarmConnectionManager::in_flight_sends`] for the sends in flight.
+//!
+//! # Statistics
+//!
+//! Each managed leader has a [`ConnectionStats`], see [`WarmConnectionManager::stats`].
+//! RTT and bytes sent come from quinn's statistics of the current connection, stream opens and failures are
+//! accumulated by the manager for as long as the leader is managed.
+//!
+//! # Adaptive fan-out
+//!
+//! On every slot change, the manager folds the RTT of each established connection into an exponential moving
+//! average, with a smoothing factor of `0.2`: `ema = 0.8 * ema + 0.2 * rtt`, see [`WarmConnectionManager::rtt_ema`].
+//! [`WarmConnectionManager::adaptive_fanout_width`] widens a base fan-out, e.g. the number of leaders passed to
+//! [`crate::forwarder::LeaderForwarder::send_to_upcoming_leaders`], proportionally to the average RTT above
+//! [`WarmConnectionManagerConfig::adaptive_fanout_rtt_baseline`]:
+//!
+//! ```text
+//! width = min(ceil(base * max(1, ema / baseline)), cap)
+//! ```
+//!
+//! where `cap` is [`WarmConnectionManagerConfig::adaptive_fanout_max_width`], bounded by
+//! [`WarmConnectionManagerConfig::max_connections`]. The base width is used as is until an RTT is observed.
+//!
+//! [`WarmConnectionManager::debug_snapshot`] captures the whole pipeline at once: the current slot and epoch, the
+//! predicted window with the slots and resolved addresses of its leaders, and the statistics of every connection.
+//!
+//! # Identity
+//!
+//! Leaders prioritize QUIC streams from staked peers. Connections present a TLS certificate derived from the
+//! identity set through [`WarmConnectionManagerBuilder::with_identity`], generated once and shared by all connections.
+//! Without an identity, a random one is used: connections are unstaked and get throttled by the leaders.
+//!
+//! [`WarmConnectionManager::rotate_identity`] installs a new identity, along with a new local endpoint, for future
+//! connections. Existing connections keep the previous identity until they are closed.
+//!
+//! # Local endpoints
+//!
+//! All the connections of an identity share its local QUIC endpoints, bound once: one UDP socket per address family
+//! allowed by [`WarmConnectionManagerConfig::address_family_preference`], however many leaders are warmed.
+//! The endpoints of a rotated identity stay in use until its last connection closes.
+//! See [`WarmConnectionManager::local_endpoints`] for the endpoints in use.
+//!
+//! # Local node
+//!
+//! A validator running the manager is itself in the leader schedule: forwarding to its own TPU would loop
+//! transactions back over the network. With [`WarmConnectionManagerBuilder::with_local_identity`], predicted or
+//! pinned leaders matching the identity of the local node are never connected, each skip is logged and counted, and
+//! opening a stream to it fails with [`OpenStreamError::LocalLeader`] so callers can hand the transaction to the local
+//! node instead. The connection identity may differ from the local node identity, e.g. a staked identity used for
+//! forwarding only.
+//!
+//! # Clock
+//!
+//! The grace period, the pin TTL and the circuit breaker cooldown are measured with the [`Clock`] set through
+//! [`WarmConnectionManagerBuilder::with_clock`], the system clock by default.
+//!
+//! # Connection events
+//!
+//! [`WarmConnectionManager::subscribe_connection_events`] streams the lifecycle of every warm connection as
+//! [`ConnectionEvent`]s, e.g. to tell why a warm connection reset: established, migrated to a new path, lost by the
+//! leader or the network, or closed by the manager. Losses and migrations are observed on slot changes, the other
+//! events as they happen. Each event is counted in the `warm_connection_event` metric.
+//!
+//! # Probes
+//!
+//! [`WarmConnectionManager::probe`] checks that the TPU of a leader is reachable, the networking equivalent of a ping:
+//! it completes a QUIC handshake with the current identity, reports the timing and negotiated parameters as a
+//! [`ProbeResult`], then closes the connection without sending anything. Probes don't affect the warm connections.
+//!
+//! # Dry run
+//!
+//! With [`WarmConnectionManagerConfig::dry_run`], the manager predicts and resolves the warm window as usual but
+//! never touches the network: no local endpoint is bound and no connection is opened. Each connection it would have
+//! opened is logged and counted as an intended target instead, see [`WarmConnectionManager::intended_target_count`],
+//! so a new configuration can be validated against a live cluster before it sends anything.
+//!
+//! See [`spawn_warm_connection_manager`] or [`WarmConnectionManager::builder`] to create a manager.
+//!
+#[cfg(feature = "metrics")]
+use crate::metrics;
+#[cfg(feature = "prometheus")]
+use crate::prom;
+use {
+    crate::{
+        clock::{Clock, SystemClock},
+        config::{
+            AddressFamily, CongestionControl, TpuPortKind, deserialize_port_range, serialize_pubkey,
+        },
+        core::{
+            AddressFamilyTpuInfoService, AddressResolver, ClientEndpoints, ConnectingError,
+            LeaderTpuInfoService, PredictedLeader, ResolverTpuInfoService, UpcomingLeaderPredictor,
+            ValidatorStakeInfoService, socket_addr_to_quic_server_name, tpu_client_config,
+            tpu_transport_config,
+        },
+        slot::{AtomicSlotTracker, EpochInfo},
+        task::{WARM_CONNECTION_MANAGER_TASK, spawn_named},
+    },
+    futures::{StreamExt, stream},
+    quinn::{
+        ClientConfig, ClosedStream, ConnectError, Connection, ConnectionError, Endpoint,
+        SendStream, WriteError, crypto::rustls::HandshakeData,
+    },
+    rustls::pki_types::CertificateDer,
+    serde::{Deserialize, Serialize, Serializer, ser::SerializeStructVariant},
+    solana_clock::{Epoch, NUM_CONSECUTIVE_LEADER_SLOTS, Slot},
+    solana_epoch_schedule::EpochSchedule,
+    solana_keypair::Keypair,
+    solana_net_utils::{PortRange, VALIDATOR_PORT_RANGE},
+    solana_pubkey::Pubkey,
+    solana_signer::Signer,
+    solana_tls_utils::{
+        QuicClientCertificate, get_pubkey_from_tls_certificate, new_dummy_x509_certificate,
+    },
+    std::{
+        cmp::Reverse,
+        collections::{HashMap, HashSet},
+        fmt,
+        net::{IpAddr, SocketAddr},
+        num::NonZeroUsize,
+        ops::Range,
+        sync::{
+            Arc, Mutex, RwLock,
+            atomic::{AtomicU64, AtomicUsize, Ordering},
+        },
+        time::{Duration, Instant},
+    },
+    tokio::{
+        sync::{Notify, OwnedSemaphorePermit, Semaphore, TryAcquireError, broadcast, watch},
+        task::{JoinHandle, JoinSet},
+    },
+    tokio_util::sync::CancellationToken,
+    tracing::Instrument,
+};
+
+pub const DEFAULT_WARM_CONNECTION_LOOKAHEAD: NonZeroUsize = NonZeroUsize::new(5).unwrap();
+pub const DEFAULT_WARM_CONNECTION_GRACE_PERIOD: Duration = Duration::from_secs(10);
+pub const DEFAULT_WARM_CONNECTION_CONNECTING_TIMEOUT: Duration = Duration::from_secs(2);
+pub const DEFAULT_WARM_CONNECTION_MAX_LOCAL_BINDING_PORT_ATTEMPTS: usize = 3;
+pub const DEFAULT_WARM_CONNECTION_MAX_CONNECTIONS: usize = 64;
+pub const DEFAULT_WARM_CONNECTION_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5);
+pub const DEFAULT_WARM_CONNECTION_KEEP_ALIVE_JITTER: Duration = Duration::from_secs(1);
+pub const DEFAULT_WARM_CONNECTION_MAX_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+pub const DEFAULT_WARM_CONNECTION_CIRCUIT_BREAKER_FAILURE_THRESHOLD: u32 = 3;
+pub const DEFAULT_WARM_CONNECTION_CIRCUIT_BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+pub const DEFAULT_WARM_CONNECTION_MAX_CONCURRENT_STREAMS: NonZeroUsize =
+    NonZeroUsize::new(64).unwrap();
+pub const DEFAULT_WARM_CONNECTION_MAX_QUEUED_SENDS_PER_LEADER: usize = 1024;
+pub const DEFAULT_WARM_CONNECTION_ADAPTIVE_FANOUT_RTT_BASELINE: Duration =
+    Duration::from_millis(100);
+pub const DEFAULT_WARM_CONNECTION_PIN_TTL: Duration = Duration::from_secs(60);
+
+///
+/// Number of [`ConnectionEvent`]s buffered per subscriber, older events are dropped for lagging subscribers.
+///
+const CONNECTION_EVENTS_CAPACITY: usize = 1024;
+
+///
+/// Smoothing factor of the RTT exponential moving average, see the
+/// [module documentation](crate::warm_connection#adaptive-fan-out).
+///
+const RTT_EMA_ALPHA: f64 = 0.2;
+
+///
+/// Configuration for the [`WarmConnectionManager`].
+///
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct WarmConnectionManagerConfig {
+    ///
+    /// Number of upcoming leaders to keep a warm connection to.
+    ///
+    /// Connections are opened to the first `lookahead` distinct leaders of the predicted window with a known TPU
+    /// address.
+    ///
+    #[serde(default = "WarmConnectionManagerConfig::default_lookahead")]
+    pub lookahead: NonZeroUsize,
+
+    ///
+    /// Number of upcoming leaders predicted on each slot change, defaults to `lookahead`.
+    ///
+    /// Leaders predicted beyond the warm window are only published, see
+    /// [`WarmConnectionManager::subscribe_predicted_window`]. Must not be shorter than `lookahead`.
+    ///
+    #[serde(default)]
+    pub prediction_lookahead: Option<NonZeroUsize>,
+
+    ///
+    /// Number of slots before its rotation a predicted leader starts warming, `0` by default.
+    ///
+    /// Leaders whose rotation starts within `warmup_lead_slots` of the current slot are warmed on top of the first
+    /// `lookahead` leaders, see the [module documentation](crate::warm_connection#prediction-and-warm-windows).
+    /// They count against [`WarmConnectionManagerConfig::max_connections`] like any warm leader.
+    ///
+    #[serde(default)]
+    pub warmup_lead_slots: u64,
+
+    ///
+    /// How long a leader may stay out of the predicted window before its connection is closed.
+    ///
+    #[serde(
+        default = "WarmConnectionManagerConfig::default_grace_period",
+        with = "humantime_serde"
+    )]
+    pub grace_period: Duration,
+
+    ///
+    /// Timeout for establishing a connection to a leader.
+    ///
+    #[serde(
+        default = "WarmConnectionManagerConfig::default_connecting_timeout",
+        with = "humantime_serde"
+    )]
+    pub connecting_timeout: Duration,
+
+    ///
+    /// Maximum idle timeout for QUIC connections.
+    ///
+    /// The effective timeout is the minimum of this value and the one advertised by the leader.
+    ///
+    #[serde(
+        default = "WarmConnectionManagerConfig::default_max_idle_timeout",
+        with = "humantime_serde"
+    )]
+    pub max_idle_timeout: Duration,
+
+    ///
+    /// Interval between QUIC keep-alive PING frames.
+    ///
+    /// Must be shorter than the effective idle timeout for connections to survive between leader rotations.
+    ///
+    #[serde(
+        default = "WarmConnectionManagerConfig::default_keep_alive_interval",
+        with = "humantime_serde"
+    )]
+    pub keep_alive_interval: Duration,
+
+    ///
+    /// Window over which keep-alive intervals are spread, so connections opened together don't ping in lockstep.
+    ///
+    /// Each connection subtracts a deterministic offset in `[0, keep_alive_jitter)`, seeded by the leader pubkey,
+    /// from [`WarmConnectionManagerConfig::keep_alive_interval`]. The offset is capped at half the interval.
+    /// `0` disables the jitter.
+    ///
+    #[serde(
+        default = "WarmConnectionManagerConfig::default_keep_alive_jitter",
+        with = "humantime_serde"
+    )]
+    pub keep_alive_jitter: Duration,
+
+    ///
+    /// Which TPU port to connect to, default is "forwards".
+    ///
+    #[serde(default)]
+    pub tpu_port: TpuPortKind,
+
+    ///
+    /// Address families of the TPU addresses to connect to, default is "any".
+    ///
+    /// A local endpoint is bound per allowed family. Leaders whose address is in an excluded family are not warmed.
+    ///
+    #[serde(default)]
+    pub address_family_preference: AddressFamily,
+
+    ///
+    /// Congestion controller of the warm connections, default is "cubic", see [`CongestionControl`] for the tradeoffs.
+    ///
+    #[serde(default)]
+    pub congestion_controller: CongestionControl,
+
+    ///
+    /// Port range to bind the local QUIC endpoint to.
+    ///
+    #[serde(
+        deserialize_with = "deserialize_port_range",
+        default = "WarmConnectionManagerConfig::default_port_range"
+    )]
+    pub endpoint_port_range: PortRange,
+
+    ///
+    /// Maximum number of attempts to bind the local QUIC endpoint.
+    ///
+    #[serde(default = "WarmConnectionManagerConfig::default_max_local_port_binding_attempts")]
+    pub max_local_port_binding_attempts: usize,
+
+    ///
+    /// Maximum number of connections open or being opened at once.
+    ///
+    #[serde(default = "WarmConnectionManagerConfig::default_max_connections")]
+    pub max_connections: usize,
+
+    ///
+    /// Order in which the warm window is connected, default is "current_leader_first".
+    ///
+    /// Under [`WarmConnectionManagerConfig::max_connections`], the leaders ordered first get the connections.
+    ///
+    #[serde(default)]
+    pub warming_order: WarmingOrder,
+
+    ///
+    /// Number of consecutive connection failures to a leader before its circuit breaker opens, `0` disables it.
+    ///
+    #[serde(default = "WarmConnectionManagerConfig::default_circuit_breaker_failure_threshold")]
+    pub circuit_breaker_failure_threshold: u32,
+
+    ///
+    /// How long connection attempts to a leader are skipped once its circuit breaker opened.
+    ///
+    #[serde(
+        default = "WarmConnectionManagerConfig::default_circuit_breaker_cooldown",
+        with = "humantime_serde"
+    )]
+    pub circuit_breaker_cooldown: Duration,
+
+    ///
+    /// Maximum number of streams [`WarmConnectionManager::send_batch`] opens at once on a connection.
+    ///
+    /// The leader may grant fewer, in which case the batch waits for stream credit.
+    ///
+    #[serde(default = "WarmConnectionManagerConfig::default_max_concurrent_streams")]
+    pub max_concurrent_streams: NonZeroUsize,
+
+    ///
+    /// Maximum number of sends in flight at once to a leader, across all callers, unlimited by default.
+    ///
+    /// See the [module documentation](crate::warm_connection#send-concurrency).
+    ///
+    #[serde(default)]
+    pub max_streams_per_leader: Option<NonZeroUsize>,
+
+    ///
+    /// Maximum number of sends waiting for [`WarmConnectionManagerConfig::max_streams_per_leader`] per leader,
+    /// further sends fail with [`SendError::LeaderBusy`].
+    ///
+    #[serde(default = "WarmConnectionManagerConfig::default_max_queued_sends_per_leader")]
+    pub max_queued_sends_per_leader: usize,
+
+    ///
+    /// Predicts and resolves the warm window without binding endpoints nor opening connections, see the
+    /// [module documentation](crate::warm_connection#dry-run).
+    ///
+    /// Intended targets stay in the [`WarmConnectionState::Connecting`] state and count against
+    /// [`WarmConnectionManagerConfig::max_connections`], as real connection attempts would.
+    ///
+    #[serde(default)]
+    pub dry_run: bool,
+
+    ///
+    /// RTT up to which [`WarmConnectionManager::adaptive_fanout_width`] keeps the base fan-out, `0` disables the
+    /// adaptation.
+    ///
+    #[serde(
+        default = "WarmConnectionManagerConfig::default_adaptive_fanout_rtt_baseline",
+        with = "humantime_serde"
+    )]
+    pub adaptive_fanout_rtt_baseline: Duration,
+
+    ///
+    /// Maximum width returned by [`WarmConnectionManager::adaptive_fanout_width`], defaults to `max_connections`.
+    ///
+    /// Never exceeds [`WarmConnectionManagerConfig::max_connections`]: leaders beyond it can't all be connected.
+    ///
+    #[serde(default)]
+    pub adaptive_fanout_max_width: Option<NonZeroUsize>,
+
+    ///
+    /// How long a leader pinned with [`WarmConnectionManager::warm`] stays pinned, renewed by pinning it again.
+    ///
+    /// Once the pin expires, the leader is handled like any leader out of the predicted window: its connection is
+    /// closed after [`WarmConnectionManagerConfig::grace_period`].
+    ///
+    #[serde(
+        default = "WarmConnectionManagerConfig::default_pin_ttl",
+        with = "humantime_serde"
+    )]
+    pub pin_ttl: Duration,
+}
+
+impl WarmConnectionManagerConfig {
+    pub const fn default_lookahead() -> NonZeroUsize {
+        DEFAULT_WARM_CONNECTION_LOOKAHEAD
+    }
+
+    ///
+    /// Number of upcoming leaders predicted on each slot change, see [`WarmConnectionManagerConfig::prediction_lookahead`].
+    ///
+    /// Grows beyond `lookahead` to cover [`WarmConnectionManagerConfig::warmup_lead_slots`], assuming
+    /// [`NUM_CONSECUTIVE_LEADER_SLOTS`] slots per leader.
+    ///
+    pub fn effective_prediction_lookahead(&self) -> NonZeroUsize {
+        let prediction_lookahead = self.prediction_lookahead.unwrap_or(self.lookahead);
+        if self.warmup_lead_slots == 0 {
+            return prediction_lookahead;
+        }
+        // The current rotation is not covered by the lead.
+        let lead_leaders = self
+            .warmup_lead_slots
+            .div_ceil(NUM_CONSECUTIVE_LEADER_SLOTS)
+            .saturating_add(1);
+        prediction_lookahead.max(self.lookahead.saturating_add(lead_leaders as usize))
+    }
+
+    pub const fn default_grace_period() -> Duration {
+        DEFAULT_WARM_CONNECTION_GRACE_PERIOD
+    }
+
+    pub const fn default_connecting_timeout() -> Duration {
+        DEFAULT_WARM_CONNECTION_CONNECTING_TIMEOUT
+    }
+
+    pub const fn default_max_idle_timeout() -> Duration {
+        DEFAULT_WARM_CONNECTION_MAX_IDLE_TIMEOUT
+    }
+
+    pub const fn default_keep_alive_interval() -> Duration {
+        DEFAULT_WARM_CONNECTION_KEEP_ALIVE_INTERVAL
+    }
+
+    pub const fn default_keep_alive_jitter() -> Duration {
+        DEFAULT_WARM_CONNECTION_KEEP_ALIVE_JITTER
+    }
+
+    pub const fn default_port_range() -> PortRange {
+        VALIDATOR_PORT_RANGE
+    }
+
+    pub const fn default_max_local_port_binding_attempts() -> usize {
+        DEFAULT_WARM_CONNECTION_MAX_LOCAL_BINDING_PORT_ATTEMPTS
+    }
+
+    pub const fn default_max_connections() -> usize {
+        DEFAULT_WARM_CONNECTION_MAX_CONNECTIONS
+    }
+
+    pub const fn default_circuit_breaker_failure_threshold() -> u32 {
+        DEFAULT_WARM_CONNECTION_CIRCUIT_BREAKER_FAILURE_THRESHOLD
+    }
+
+    pub const fn default_circuit_breaker_cooldown() -> Duration {
+        DEFAULT_WARM_CONNECTION_CIRCUIT_BREAKER_COOLDOWN
+    }
+
+    pub const fn default_max_concurrent_streams() -> NonZeroUsize {
+        DEFAULT_WARM_CONNECTION_MAX_CONCURRENT_STREAMS
+    }
+
+    pub const fn default_max_queued_sends_per_leader() -> usize {
+        DEFAULT_WARM_CONNECTION_MAX_QUEUED_SENDS_PER_LEADER
+    }
+
+    pub const fn default_adaptive_fanout_rtt_baseline() -> Duration {
+        DEFAULT_WARM_CONNECTION_ADAPTIVE_FANOUT_RTT_BASELINE
+    }
+
+    pub const fn default_pin_ttl() -> Duration {
+        DEFAULT_WARM_CONNECTION_PIN_TTL
+    }
+
+    ///
+    /// Maximum adaptive fan-out width, see [`WarmConnectionManagerConfig::adaptive_fanout_max_width`].
+    ///
+    pub fn effective_adaptive_fanout_max_width(&self) -> usize {
+        self.adaptive_fanout_max_width
+            .map_or(self.max_connections, |max_width| {
+                max_width.get().min(self.max_connections)
+            })
+    }
+}
+
+impl Default for WarmConnectionManagerConfig {
+    fn default() -> Self {
+        Self {
+            lookahead: Self::default_lookahead(),
+            prediction_lookahead: None,
+            warmup_lead_slots: 0,
+            grace_period: Self::default_grace_period(),
+            connecting_timeout: Self::default_connecting_timeout(),
+            max_idle_timeout: Self::default_max_idle_timeout(),
+            keep_alive_interval: Self::default_keep_alive_interval(),
+            keep_alive_jitter: Self::default_keep_alive_jitter(),
+            tpu_port: TpuPortKind::default(),
+            address_family_preference: AddressFamily::default(),
+            congestion_controller: CongestionControl::default(),
+            endpoint_port_range: Self::default_port_range(),
+            max_local_port_binding_attempts: Self::default_max_local_port_binding_attempts(),
+            max_connections: Self::default_max_connections(),
+            warming_order: WarmingOrder::default(),
+            circuit_breaker_failure_threshold: Self::default_circuit_breaker_failure_threshold(),
+            circuit_breaker_cooldown: Self::default_circuit_breaker_cooldown(),
+            max_concurrent_streams: Self::default_max_concurrent_streams(),
+            max_streams_per_leader: None,
+            max_queued_sends_per_leader: Self::default_max_queued_sends_per_leader(),
+            dry_run: false,
+            adaptive_fanout_rtt_baseline: Self::default_adaptive_fanout_rtt_baseline(),
+            adaptive_fanout_max_width: None,
+            pin_ttl: Self::default_pin_ttl(),
+        }
+    }
+}
+
+///
+/// Order in which the [`WarmConnectionManager`] connects the leaders of the warm window.
+///
+#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmingOrder {
+    ///
+    /// The current leader first, then the upcoming leaders in rotation order, and the look-behind leaders whose
+    /// rotation already ended last.
+    ///
+    /// Leaders keep the predicted order when the predictor does not know their slots, see
+    /// [`UpcomingLeaderPredictor::try_predict_leader_window`].
+    ///
+    #[default]
+    CurrentLeaderFirst,
+    ///
+    /// The predicted order, rotated by one leader on every slot change so no leader is always connected last.
+    ///
+    RoundRobin,
+    ///
+    /// By decreasing stake, see [`WarmConnectionManagerBuilder::with_stake_info`].
+    ///
+    /// Leaders with the same, or unknown, stake are ordered like [`WarmingOrder::CurrentLeaderFirst`].
+    ///
+    StakeWeighted,
+}
+
+///
+/// Orders the leaders of the warm `window` according to `order`.
+///
+/// `predicted_window` is the window resolved at `slot`, in prediction order.
+///
+fn order_warm_window(
+    order: WarmingOrder,
+    slot: Slot,
+    predicted_window: &[PredictedWindowEntry],
+    mut window: Vec<Pubkey>,
+    stake_info: Option<&(dyn ValidatorStakeInfoService + Send + Sync)>,
+    round_robin_offset: usize,
+) -> Vec<Pubkey> {
+    // A leader rotated out once all its predicted rotations ended, e.g. the look-behind leader.
+    let mut upcoming = HashSet::with_capacity(predicted_window.len());
+    let mut rotated_out = HashSet::new();
+    for entry in predicted_window {
+        match &entry.predicted.slots {
+            Some(slots) if slots.end <= slot => rotated_out.insert(entry.predicted.leader),
+            _ => upcoming.insert(entry.predicted.leader),
+        };
+    }
+    let is_rotated_out =
+        |leader: &Pubkey| rotated_out.contains(leader) && !upcoming.contains(leader);
+    match order {
+        WarmingOrder::CurrentLeaderFirst => window.sort_by_key(is_rotated_out),
+        WarmingOrder::RoundRobin => {
+            if !window.is_empty() {
+                let offset = round_robin_offset % window.len();
+                window.rotate_left(offset);
+            }
+        }
+        WarmingOrder::StakeWeighted => {
+            window.sort_by_key(is_rotated_out);
+            if let Some(stake_info) = stake_info {
+                window.sort_by_key(|leader| {
+                    Reverse(stake_info.get_stake_info(leader).unwrap_or_default())
+                });
+            }
+        }
+    }
+    window
+}
+
+///
+/// Error returned by [`spawn_warm_connection_manager`].
+///
+#[derive(Debug, thiserror::Error)]
+pub enum WarmConnectionManagerError {
+    ///
+    /// No local port could be bound for the QUIC endpoint.
+    ///
+    #[error("failed to bind any local QUIC endpoint in port range {0:?}")]
+    EndpointBind(PortRange),
+    ///
+    /// The prediction window is shorter than the warm window.
+    ///
+    #[error(
+        "prediction lookahead {prediction_lookahead} is shorter than the warm lookahead {lookahead}"
+    )]
+    PredictionLookaheadTooShort {
+        lookahead: NonZeroUsize,
+        prediction_lookahead: NonZeroUsize,
+    },
+}
+
+///
+/// Error returned by [`WarmConnectionManager::open_uni`].
+///
+#[derive(Debug, thiserror::Error)]
+pub enum OpenStreamError {
+    ///
+    /// There is no established connection to the leader.
+    ///
+    #[error("no warm connection to leader {0}")]
+    NotConnected(Pubkey),
+    ///
+    /// The leader is the local node, see [`WarmConnectionManagerBuilder::with_local_identity`].
+    ///
+    #[error("leader {0} is the local node")]
+    LocalLeader(Pubkey),
+    ///
+    /// The connection failed while opening the stream.
+    ///
+    #[error(transparent)]
+    ConnectionLost(#[from] ConnectionError),
+}
+
+///
+/// Error sending a transaction through [`WarmConnectionManager::send`] or [`WarmConnectionManager::send_batch`].
+///
+#[derive(Debug, thiserror::Error)]
+pub enum SendError {
+    ///
+    /// Too many sends are already waiting for the leader, see
+    /// [`WarmConnectionManagerConfig::max_queued_sends_per_leader`].
+    ///
+    #[error("too many queued sends to leader {0}")]
+    LeaderBusy(Pubkey),
+    ///
+    /// The stream could not be opened.
+    ///
+    #[error(transparent)]
+    OpenStream(#[from] OpenStreamError),
+    ///
+    /// The transaction could not be written to the stream.
+    ///
+    #[error(transparent)]
+    Write(#[from] WriteError),
+    ///
+    /// The stream was closed before it could be finished.
+    ///
+    #[error(transparent)]
+    Finish(#[from] ClosedStream),
+}
+
+///
+/// Former name of [`SendError`].
+///
+pub type SendBatchError = SendError;
+
+///
+/// Error returned by [`WarmConnectionManager::probe`].
+///
+#[derive(Debug, thiserror::Error)]
+pub enum ProbeError {
+    ///
+    /// The TPU address of the leader is unknown.
+    ///
+    #[error("unknown TPU address for leader {0}")]
+    UnknownTpuAddress(Pubkey),
+    ///
+    /// The manager runs in dry-run mode, without local endpoints, see [`WarmConnectionManagerConfig::dry_run`].
+    ///
+    #[error("dry run, no local endpoint to probe from")]
+    DryRun,
+    ///
+    /// No local endpoint is bound for the address family of the TPU address.
+    ///
+    #[error("no local QUIC endpoint bound for the address family of {0}")]
+    NoEndpointForAddressFamily(SocketAddr),
+    ///
+    /// The connection could not be initiated.
+    ///
+    #[error(transparent)]
+    Connect(#[from] ConnectError),
+    ///
+    /// The handshake failed, or did not complete within [`WarmConnectionManagerConfig::connecting_timeout`].
+    ///
+    #[error(transparent)]
+    Connection(#[from] ConnectionError),
+}
+
+///
+/// Outcome of a successful [`WarmConnectionManager::probe`].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeResult {
+    pub leader: Pubkey,
+    pub path: ConnectionPath,
+    ///
+    /// Time to complete the handshake, from the first packet sent.
+    ///
+    pub handshake_time: Duration,
+    ///
+    /// Round-trip time estimated from the handshake.
+    ///
+    pub rtt: Duration,
+    ///
+    /// Negotiated ALPN protocol.
+    ///
+    pub alpn_protocol: Option<Vec<u8>>,
+    ///
+    /// Identity in the certificate presented by the leader, `None` if it does not embed one.
+    ///
+    pub peer_identity: Option<Pubkey>,
+    ///
+    /// Maximum datagram payload accepted by the leader, `None` if it does not support datagrams.
+    ///
+    pub max_datagram_size: Option<usize>,
+    ///
+    /// Maximum transmission unit of the path, in bytes.
+    ///
+    pub mtu: u16,
+}
+
+///
+/// Health statistics of the warm connection to a leader.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConnectionStats {
+    ///
+    /// Current state of the connection.
+    ///
+    pub state: WarmConnectionState,
+    ///
+    /// Smoothed RTT of the current connection, `None` if not connected.
+    ///
+    #[serde(with = "humantime_serde")]
+    pub rtt: Option<Duration>,
+    ///
+    /// Number of streams successfully opened through [`WarmConnectionManager::open_uni`].
+    ///
+    pub stream_opens: u64,
+    ///
+    /// Number of failed connection attempts, lost connections and failed stream opens.
+    ///
+    pub failures: u64,
+    ///
+    /// Description of the last failure, if any.
+    ///
+    pub last_error: Option<String>,
+    ///
+    /// UDP bytes sent over the current connection.
+    ///
+    pub bytes_sent: u64,
+    ///
+    /// State of the circuit breaker guarding connection attempts to the leader.
+    ///
+    pub circuit_breaker: CircuitBreakerState,
+}
+
+#[derive(Debug, Default)]
+struct ConnectionCounters {
+    stream_opens: AtomicU64,
+    failures: AtomicU64,
+    last_error: Mutex<Option<String>>,
+}
+
+impl ConnectionCounters {
+    fn record_stream_open(&self) {
+        self.stream_opens.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_failure(&self, err: impl fmt::Display) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
+        *self.last_error.lock().expect("lock") = Some(err.to_string());
+    }
+}
+
+///
+/// Per-leader send limits, see [`WarmConnectionManagerConfig::max_streams_per_leader`].
+///
+#[derive(Debug, Clone, Copy)]
+struct SendLimit {
+    max_in_flight: Option<NonZeroUsize>,
+    max_queued: usize,
+}
+
+impl Default for SendLimit {
+    fn default() -> Self {
+        Self {
+            max_in_flight: None,
+            max_queued: DEFAULT_WARM_CONNECTION_MAX_QUEUED_SENDS_PER_LEADER,
+        }
+    }
+}
+
+///
+/// Gates the concurrent sends to a leader, see the [module documentation](crate::warm_connection#send-concurrency).
+///
+#[derive(Debug)]
+struct LeaderSendLimiter {
+    /// `None` if the number of sends in flight is unlimited.
+    semaphore: Option<Arc<Semaphore>>,
+    max_queued: usize,
+    queued: AtomicUsize,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl LeaderSendLimiter {
+    fn new(limit: SendLimit) -> Self {
+        Self {
+            semaphore: limit
+                .max_in_flight
+                .map(|max_in_flight| Arc::new(Semaphore::new(max_in_flight.get()))),
+            max_queued: limit.max_queued,
+            queued: AtomicUsize::new(0),
+            in_flight: Default::default(),
+        }
+    }
+
+    ///
+    /// Waits for a free send slot, failing with [`SendError::LeaderBusy`] if the queue is full.
+    ///
+    /// Cancellation safe: dropping the future leaves the queue.
+    ///
+    async fn acquire(&self, leader: &Pubkey) -> Result<LeaderSendPermit, SendError> {
+        let permit = match &self.semaphore {
+            None => None,
+            Some(semaphore) => match Arc::clone(semaphore).try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(TryAcquireError::NoPermits) => {
+                    if self.queued.fetch_add(1, Ordering::Relaxed) >= self.max_queued {
+                        self.queued.fetch_sub(1, Ordering::Relaxed);
+                        #[cfg(feature = "prometheus")]
+                        {
+                            prom::incr_warm_connection_leader_busy_cnt();
+                        }
+                        return Err(SendError::LeaderBusy(*leader));
+                    }
+                    let _queued = QueuedSend(&self.queued);
+                    Some(
+                        Arc::clone(semaphore)
+                            .acquire_owned()
+                            .await
+                            .expect("send semaphore is never closed"),
+                    )
+                }
+                Err(TryAcquireError::Closed) => unreachable!("send semaphore is never closed"),
+            },
+        };
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        Ok(LeaderSendPermit {
+            _permit: permit,
+            in_flight: Arc::clone(&self.in_flight),
+        })
+    }
+
+    fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+}
+
+///
+/// Leaves the queue of a [`LeaderSendLimiter`] once dropped.
+///
+struct QueuedSend<'a>(&'a AtomicUsize);
+
+impl Drop for QueuedSend<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+///
+/// Slot of a send in flight to a leader, see [`WarmConnectionManager::acquire_send_permit`].
+///
+/// The slot is released once dropped.
+///
+#[derive(Debug)]
+pub struct LeaderSendPermit {
+    _permit: Option<OwnedSemaphorePermit>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for LeaderSendPermit {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+///
+/// State of the warm connection to a leader.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmConnectionState {
+    ///
+    /// A connection attempt is in progress.
+    ///
+    Connecting,
+    ///
+    /// The connection is established.
+    ///
+    Ready,
+    ///
+    /// The last connection attempt failed or the connection was lost, it is reattempted on the next slot change
+    /// if the leader is still in the predicted window.
+    ///
+    Failed,
+}
+
+///
+/// Why the [`WarmConnectionManager`] manages a leader, see [`WarmConnectionManager::origin`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmConnectionOrigin {
+    ///
+    /// The leader is, or was within the grace period, part of the predicted window or pinned.
+    ///
+    Predicted,
+    ///
+    /// The leader is pinned through [`WarmConnectionManager::warm`], whether it is predicted or not.
+    ///
+    Pinned,
+}
+
+///
+/// Network path of a warm connection.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionPath {
+    pub remote_addr: SocketAddr,
+    ///
+    /// Local IP address the connection is sent from, `None` if the platform does not report it.
+    ///
+    pub local_ip: Option<IpAddr>,
+}
+
+impl ConnectionPath {
+    fn of(conn: &Connection) -> Self {
+        Self {
+            remote_addr: conn.remote_address(),
+            local_ip: conn.local_ip(),
+        }
+    }
+}
+
+///
+/// Why the [`WarmConnectionManager`] closed a connection, see [`ConnectionEvent::Closed`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionCloseCause {
+    ///
+    /// The leader was out of the predicted window for longer than [`WarmConnectionManagerConfig::grace_period`].
+    ///
+    OutOfWindow,
+    ///
+    /// The connection was evicted to make room under [`WarmConnectionManagerConfig::max_connections`].
+    ///
+    Evicted,
+    ///
+    /// The manager shut down.
+    ///
+    Shutdown,
+}
+
+///
+/// Lifecycle event of a warm connection, see the [module documentation](crate::warm_connection#connection-events).
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    ///
+    /// A connection to the leader was established.
+    ///
+    Established {
+        leader: Pubkey,
+        path: ConnectionPath,
+    },
+    ///
+    /// The path of the connection to the leader changed, e.g. after a local address change.
+    ///
+    Migrated {
+        leader: Pubkey,
+        from: ConnectionPath,
+        to: ConnectionPath,
+    },
+    ///
+    /// The connection to the leader was closed by the leader, timed out or failed.
+    ///
+    Lost {
+        leader: Pubkey,
+        reason: ConnectionError,
+    },
+    ///
+    /// The manager closed the connection to the leader.
+    ///
+    Closed {
+        leader: Pubkey,
+        cause: ConnectionCloseCause,
+    },
+}
+
+impl ConnectionEvent {
+    pub const fn leader(&self) -> &Pubkey {
+        match self {
+            Self::Established { leader, .. }
+            | Self::Migrated { leader, .. }
+            | Self::Lost { leader, .. }
+            | Self::Closed { leader, .. } => leader,
+        }
+    }
+}
+
+///
+/// State of the circuit breaker guarding connection attempts to a leader, see the
+/// [module documentation](crate::warm_connection#circuit-breaker).
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitBreakerState {
+    ///
+    /// Connection attempts are allowed.
+    ///
+    Closed,
+    ///
+    /// Too many consecutive connection failures, attempts are skipped until `until`.
+    ///
+    Open { until: Instant },
+    ///
+    /// The cooldown elapsed, the next attempt probes the leader.
+    ///
+    HalfOpen,
+}
+
+///
+/// Serializes an open breaker with the milliseconds left in its cooldown, as an [`Instant`] has no absolute value.
+///
+impl Serialize for CircuitBreakerState {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Closed => serializer.serialize_unit_variant("CircuitBreakerState", 0, "closed"),
+            Self::Open { until } => {
+                let remaining = until.saturating_duration_since(Instant::now());
+                let mut state =
+                    serializer.serialize_struct_variant("CircuitBreakerState", 1, "open", 1)?;
+                state.serialize_field("remaining_ms", &(remaining.as_millis() as u64))?;
+                state.end()
+            }
+            Self::HalfOpen => {
+                serializer.serialize_unit_variant("CircuitBreakerState", 2, "half_open")
+            }
+        }
+    }
+}
+
+///
+/// Snapshot of the pipeline followed by the [`WarmConnectionManager`], from the leader prediction to the warm
+/// connections, see [`WarmConnectionManager::debug_snapshot`].
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PipelineSnapshot {
+    ///
+    /// Slot of the last slot change, `None` before the first one.
+    ///
+    pub slot: Option<Slot>,
+    ///
+    /// Epoch of [`PipelineSnapshot::slot`].
+    ///
+    pub epoch: Option<Epoch>,
+    ///
+    /// Leaders predicted at the last slot change, in prediction order.
+    ///
+    pub predicted_window: Vec<PredictedLeaderSnapshot>,
+    ///
+    /// Every managed leader along with its connection statistics, sorted by leader.
+    ///
+    /// Includes the leaders out of the predicted window whose connection is still in its grace period.
+    ///
+    pub connections: Vec<LeaderConnectionSnapshot>,
+}
+
+///
+/// Leader of the predicted window in a [`PipelineSnapshot`].
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PredictedLeaderSnapshot {
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub leader: Pubkey,
+    ///
+    /// Slots of the rotation, `None` if the predictor does not know them.
+    ///
+    pub slots: Option<Range<Slot>>,
+    ///
+    /// TPU address resolved at the last slot change, `None` if unknown.
+    ///
+    pub addr: Option<SocketAddr>,
+    ///
+    /// Whether the leader is part of the warm window.
+    ///
+    pub warm: bool,
+    ///
+    /// State of the connection to the leader, `None` if the leader is not managed.
+    ///
+    pub state: Option<WarmConnectionState>,
+}
+
+///
+/// Managed leader in a [`PipelineSnapshot`].
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LeaderConnectionSnapshot {
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub leader: Pubkey,
+    pub origin: WarmConnectionOrigin,
+    #[serde(flatten)]
+    pub stats: ConnectionStats,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            failure_threshold: DEFAULT_WARM_CONNECTION_CIRCUIT_BREAKER_FAILURE_THRESHOLD,
+            cooldown: DEFAULT_WARM_CONNECTION_CIRCUIT_BREAKER_COOLDOWN,
+        }
+    }
+}
+
+struct WarmConnectionEntry {
+    state: WarmConnectionState,
+    connection: Option<Connection>,
+    /// Last time the leader was part of the predicted window.
+    last_in_window: Instant,
+    /// Nanoseconds elapsed since [`WarmConnectionTable::created_at`] at the last use of the entry.
+    last_used_nanos: AtomicU64,
+    counters: Arc<ConnectionCounters>,
+    send_limiter: Arc<LeaderSendLimiter>,
+    /// Path of the established connection as last reported, `None` once its loss is reported.
+    path: Option<ConnectionPath>,
+    /// Connection failures since the last established connection.
+    consecutive_failures: u32,
+    /// Set while the circuit breaker is open or half-open.
+    breaker_open_until: Option<Instant>,
+}
+
+impl WarmConnectionEntry {
+    fn circuit_breaker_state(&self, now: Instant) -> CircuitBreakerState {
+        match self.breaker_open_until {
+            None => CircuitBreakerState::Closed,
+            Some(until) if now < until => CircuitBreakerState::Open { until },
+            Some(_) => CircuitBreakerState::HalfOpen,
+        }
+    }
+
+    ///
+    /// Records a failed connection attempt or a lost connection, opening the circuit breaker past its threshold.
+    ///
+    /// A failed probe of a half-open breaker reopens it right away.
+    ///
+    fn record_connection_failure(
+        &mut self,
+        err: impl fmt::Display,
+        circuit_breaker: CircuitBreaker,
+        now: Instant,
+    ) {
+        self.counters.record_failure(err);
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        if circuit_breaker.failure_threshold > 0
+            && self.consecutive_failures >= circuit_breaker.failure_threshold
+        {
+            self.breaker_open_until = Some(now + circuit_breaker.cooldown);
+        }
+    }
+
+    ///
+    /// Reports the loss, or the migration, of the established connection since the last observation.
+    ///
+    fn observe_connection(&mut self, leader: Pubkey) -> Option<ConnectionEvent> {
+        let conn = self.connection.as_ref()?;
+        let reported = self.path?;
+        if let Some(reason) = conn.close_reason() {
+            self.path = None;
+            return Some(ConnectionEvent::Lost { leader, reason });
+        }
+        let path = ConnectionPath::of(conn);
+        if path == reported {
+            return None;
+        }
+        self.path = Some(path);
+        Some(ConnectionEvent::Migrated {
+            leader,
+            from: reported,
+            to: path,
+        })
+    }
+
+    ///
+    /// Whether the entry holds a socket accounted against [`WarmConnectionManagerConfig::max_connections`].
+    ///
+    fn holds_connection(&self) -> bool {
+        match self.state {
+            WarmConnectionState::Connecting => true,
+            WarmConnectionState::Ready => !self.needs_connect(),
+            WarmConnectionState::Failed => false,
+        }
+    }
+
+    fn needs_connect(&self) -> bool {
+        match self.state {
+            WarmConnectionState::Connecting => false,
+            WarmConnectionState::Ready => self
+                .connection
+                .as_ref()
+                .is_none_or(|conn| conn.close_reason().is_some()),
+            WarmConnectionState::Failed => true,
+        }
+    }
+
+    fn stats(&self, now: Instant) -> ConnectionStats {
+        let live_connection = self
+            .connection
+            .as_ref()
+            .filter(|conn| conn.close_reason().is_none());
+        ConnectionStats {
+            state: if self.state == WarmConnectionState::Ready && live_connection.is_none() {
+                WarmConnectionState::Failed
+            } else {
+                self.state
+            },
+            rtt: live_connection.map(Connection::rtt),
+            stream_opens: self.counters.stream_opens.load(Ordering::Relaxed),
+            failures: self.counters.failures.load(Ordering::Relaxed),
+            last_error: self.counters.last_error.lock().expect("lock").clone(),
+            bytes_sent: self
+                .connection
+                .as_ref()
+                .map(|conn| conn.stats().udp_tx.bytes)
+                .unwrap_or_default(),
+            circuit_breaker: self.circuit_breaker_state(now),
+        }
+    }
+}
+
+///
+/// Outcome of [`WarmConnectionTable::refresh_window`].
+///
+#[derive(Default)]
+struct WindowUpdate {
+    /// Leaders a connection attempt must be spawned for.
+    to_connect: Vec<Pubkey>,
+    /// Leaders evicted to make room, along with their connection if established.
+    evicted: Vec<(Pubkey, Option<Connection>)>,
+    /// Number of connections refused because of the cap.
+    refused: usize,
+    /// Number of connection attempts skipped because the leader circuit breaker is open.
+    breaker_open: usize,
+}
+
+///
+/// Leader of the predicted window, as resolved at the last slot change.
+///
+struct PredictedWindowEntry {
+    predicted: PredictedLeader,
+    addr: Option<SocketAddr>,
+    /// Whether the leader is part of the warm window.
+    warm: bool,
+}
+
+struct WarmConnectionTable {
+    entries: HashMap<Pubkey, WarmConnectionEntry>,
+    /// Slot and predicted window of the last slot change.
+    prediction: Option<(Slot, Vec<PredictedWindowEntry>)>,
+    max_connections: usize,
+    circuit_breaker: CircuitBreaker,
+    send_limit: SendLimit,
+    clock: Arc<dyn Clock + Send + Sync>,
+    /// Reference point of [`WarmConnectionEntry::last_used_nanos`].
+    created_at: Instant,
+    /// Number of connections skipped in dry-run mode.
+    intended_targets: u64,
+    /// Exponential moving average of the RTT of the established connections.
+    rtt_ema: Option<Duration>,
+    /// Leaders pinned through [`WarmConnectionManager::warm`], along with the expiration of their pin.
+    pinned: HashMap<Pubkey, Instant>,
+}
+
+impl WarmConnectionTable {
+    fn new(max_connections: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            prediction: None,
+            max_connections,
+            circuit_breaker: CircuitBreaker::default(),
+            send_limit: SendLimit::default(),
+            clock: Arc::new(SystemClock),
+            created_at: Instant::now(),
+            intended_targets: 0,
+            rtt_ema: None,
+            pinned: HashMap::new(),
+        }
+    }
+
+    fn with_circuit_breaker(mut self, failure_threshold: u32, cooldown: Duration) -> Self {
+        self.circuit_breaker = CircuitBreaker {
+            failure_threshold,
+            cooldown,
+        };
+        self
+    }
+
+    fn with_send_limit(mut self, max_in_flight: Option<NonZeroUsize>, max_queued: usize) -> Self {
+        self.send_limit = SendLimit {
+            max_in_flight,
+            max_queued,
+        };
+        self
+    }
+
+    fn with_clock(mut self, clock: Arc<dyn Clock + Send + Sync>) -> Self {
+        self.created_at = clock.now();
+        self.clock = clock;
+        self
+    }
+
+    fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    fn nanos_since_creation(&self, instant: Instant) -> u64 {
+        instant
+            .saturating_duration_since(self.created_at)
+            .as_nanos() as u64
+    }
+
+    fn touch(&self, entry: &WarmConnectionEntry) {
+        entry
+            .last_used_nanos
+            .fetch_max(self.nanos_since_creation(self.now()), Ordering::Relaxed);
+    }
+
+    ///
+    /// Marks every leader of `window` as in-window at `now`.
+    ///
+    /// Leaders a connection attempt must be spawned for have their state set to [`WarmConnectionState::Connecting`].
+    /// Leaders with an open circuit breaker are skipped.
+    /// If the cap is reached, the least-recently-used leader outside of `window` is evicted first.
+    ///
+    fn refresh_window(&mut self, window: &[Pubkey], now: Instant) -> WindowUpdate {
+        let now_nanos = self.nanos_since_creation(now);
+        let circuit_breaker = self.circuit_breaker;
+        let send_limit = self.send_limit;
+        let window_set = window.iter().copied().collect::<HashSet<_>>();
+        let mut visited = HashSet::with_capacity(window.len());
+        let mut update = WindowUpdate::default();
+        for leader in window {
+            if !visited.insert(*leader) {
+                continue;
+            }
+            let entry = self
+                .entries
+                .entry(*leader)
+                .or_insert_with(|| WarmConnectionEntry {
+                    state: WarmConnectionState::Failed,
+                    connection: None,
+                    last_in_window: now,
+                    last_used_nanos: AtomicU64::new(now_nanos),
+                    counters: Default::default(),
+                    send_limiter: Arc::new(LeaderSendLimiter::new(send_limit)),
+                    path: None,
+                    consecutive_failures: 0,
+                    breaker_open_until: None,
+                });
+            entry.last_in_window = now;
+            entry
+                .last_used_nanos
+                .fetch_max(now_nanos, Ordering::Relaxed);
+            if !entry.needs_connect() {
+                continue;
+            }
+            if let Some(reason) = entry
+                .connection
+                .as_ref()
+                .and_then(|conn| conn.close_reason())
+            {
+                entry.record_connection_failure(reason, circuit_breaker, now);
+                entry.state = WarmConnectionState::Failed;
+                entry.connection = None;
+            }
+            if matches!(
+                entry.circuit_breaker_state(now),
+                CircuitBreakerState::Open { .. }
+            ) {
+                update.breaker_open += 1;
+                continue;
+            }
+
+            let open_connections = self
+                .entries
+                .values()
+                .filter(|entry| entry.holds_connection())
+                .count();
+            if open_connections >= self.max_connections {
+                match self.least_recently_used_outside(&window_set) {
+                    Some(lru) => {
+                        let evicted = self.entries.remove(&lru).expect("lru entry");
+                        update.evicted.push((lru, evicted.connection));
+                    }
+                    None => {
+                        update.refused += 1;
+                        continue;
+                    }
+                }
+            }
+
+            let entry = self.entries.get_mut(leader).expect("window entry");
+            entry.state = WarmConnectionState::Connecting;
+            entry.connection = None;
+            update.to_connect.push(*leader);
+        }
+        update
+    }
+
+    fn least_recently_used_outside(&self, window: &HashSet<Pubkey>) -> Option<Pubkey> {
+        self.entries
+            .iter()
+            .filter(|(leader, entry)| entry.holds_connection() && !window.contains(*leader))
+            .min_by_key(|(_, entry)| entry.last_used_nanos.load(Ordering::Relaxed))
+            .map(|(leader, _)| *leader)
+    }
+
+    ///
+    /// Removes leaders that have been out of the window for longer than `grace_period`.
+    ///
+    /// Returns the connections to close.
+    ///
+    fn expire(&mut self, now: Instant, grace_period: Duration) -> Vec<(Pubkey, Connection)> {
+        let mut expired = Vec::new();
+        self.entries.retain(|leader, entry| {
+            if now.saturating_duration_since(entry.last_in_window) <= grace_period {
+                return true;
+            }
+            if let Some(conn) = entry.connection.take() {
+                expired.push((*leader, conn));
+            }
+            false
+        });
+        expired
+    }
+
+    ///
+    /// Records the outcome, at `now`, of a connection attempt, ignored if the leader expired in the meantime.
+    ///
+    /// Returns the connection back if it is not needed anymore.
+    ///
+    fn connected(
+        &mut self,
+        leader: Pubkey,
+        result: Result<Connection, ConnectingError>,
+        now: Instant,
+    ) -> Option<Connection> {
+        let circuit_breaker = self.circuit_breaker;
+        let Some(entry) = self.entries.get_mut(&leader) else {
+            return result.ok();
+        };
+        match result {
+            Ok(conn) => {
+                entry.state = WarmConnectionState::Ready;
+                entry.path = Some(ConnectionPath::of(&conn));
+                entry.connection = Some(conn);
+                entry.consecutive_failures = 0;
+                entry.breaker_open_until = None;
+            }
+            Err(e) => {
+                tracing::debug!("Failed to warm connection to leader {leader}: {e:?}");
+                entry.record_connection_failure(&e, circuit_breaker, now);
+                entry.state = WarmConnectionState::Failed;
+                entry.connection = None;
+                if let CircuitBreakerState::Open { until } = entry.circuit_breaker_state(now) {
+                    tracing::debug!(
+                        "Circuit breaker of leader {leader} open after {} consecutive failures, skipping connection attempts for {:?}",
+                        entry.consecutive_failures,
+                        until.saturating_duration_since(now)
+                    );
+                }
+            }
+        }
+        None
+    }
+
+    fn state(&self, leader: &Pubkey) -> Option<WarmConnectionState> {
+        self.entries.get(leader).map(|entry| match entry.state {
+            WarmConnectionState::Ready if entry.needs_connect() => WarmConnectionState::Failed,
+            state => state,
+        })
+    }
+
+    fn active_connection_count(&self) -> usize {
+        self.entries
+            .values()
+            .filter(|entry| entry.state == WarmConnectionState::Ready && !entry.needs_connect())
+            .count()
+    }
+
+    ///
+    /// Pins `leaders` until `until`, extending their current pin if any.
+    ///
+    fn pin(&mut self, leaders: &[Pubkey], until: Instant) {
+        for leader in leaders {
+            let pinned_until = self.pinned.entry(*leader).or_insert(until);
+            *pinned_until = (*pinned_until).max(until);
+        }
+    }
+
+    ///
+    /// Drops the pins expired at `now` and returns the pinned leaders, sorted.
+    ///
+    fn active_pins(&mut self, now: Instant) -> Vec<Pubkey> {
+        self.pinned.retain(|_, until| *until > now);
+        let mut pinned = self.pinned.keys().copied().collect::<Vec<_>>();
+        pinned.sort_unstable();
+        pinned
+    }
+
+    fn origin(&self, leader: &Pubkey, now: Instant) -> WarmConnectionOrigin {
+        if self.pinned.get(leader).is_some_and(|until| *until > now) {
+            WarmConnectionOrigin::Pinned
+        } else {
+            WarmConnectionOrigin::Predicted
+        }
+    }
+
+    fn record_rtt(&mut self, rtt: Duration) {
+        self.rtt_ema = Some(match self.rtt_ema {
+            Some(ema) => ema.mul_f64(1.0 - RTT_EMA_ALPHA) + rtt.mul_f64(RTT_EMA_ALPHA),
+            None => rtt,
+        });
+    }
+
+    ///
+    /// Folds the RTT of every established connection into the moving average.
+    ///
+    ///
+    /// Reports the established connections lost, or migrated, since the last observation.
+    ///
+    fn observe_connections(&mut self) -> Vec<ConnectionEvent> {
+        self.entries
+            .iter_mut()
+            .filter_map(|(leader, entry)| entry.observe_connection(*leader))
+            .collect()
+    }
+
+    fn sample_rtts(&mut self) {
+        let rtts = self
+            .entries
+            .values()
+            .filter(|entry| entry.state == WarmConnectionState::Ready && !entry.needs_connect())
+            .filter_map(|entry| entry.connection.as_ref().map(Connection::rtt))
+            .collect::<Vec<_>>();
+        for rtt in rtts {
+            self.record_rtt(rtt);
+        }
+    }
+}
+
+///
+/// Widens `base` proportionally to `rtt_ema` above `baseline`, up to `max_width`, see the
+/// [module documentation](crate::warm_connection#adaptive-fan-out).
+///
+fn adaptive_fanout_width(
+    base: usize,
+    rtt_ema: Option<Duration>,
+    baseline: Duration,
+    max_width: usize,
+) -> usize {
+    let width = match rtt_ema {
+        Some(ema) if !baseline.is_zero() && ema > baseline => {
+            (base as f64 * ema.as_secs_f64() / baseline.as_secs_f64()).ceil() as usize
+        }
+        _ => base,
+    };
+    width.min(max_width)
+}
+
+struct OnDrop {
+    handle: CancellationToken,
+}
+
+impl Drop for OnDrop {
+    fn drop(&mut self) {
+        self.handle.cancel();
+    }
+}
+
+///
+/// Opens and keeps warm QUIC connections to the upcoming leaders.
+///
+/// See [`spawn_warm_connection_manager`] for creating an instance of this manager and
+/// the [module documentation](crate::warm_connection) for the warming strategy.
+///
+/// # Clone
+///
+/// Cheap to clone, clones share the same connections.
+///
+/// # Safety
+///
+/// This manager is thread-safe.
+///
+#[derive(Clone)]
+pub struct WarmConnectionManager {
+    table: Arc<RwLock<WarmConnectionTable>>,
+    identity: Arc<RwLock<Arc<ClientIdentity>>>,
+    config: Arc<WarmConnectionManagerConfig>,
+    leader_predictor: Arc<dyn UpcomingLeaderPredictor + Send + Sync + 'static>,
+    leader_tpu_info_service: Arc<dyn LeaderTpuInfoService + Send + Sync + 'static>,
+    predicted_window: watch::Receiver<Vec<Pubkey>>,
+    /// Wakes up the background task to connect newly pinned leaders.
+    pin_notify: Arc<Notify>,
+    local_identity: Option<Pubkey>,
+    /// Endpoints of the rotated identities, until their last connection closes.
+    retired_endpoints: Arc<Mutex<Vec<ClientEndpoints>>>,
+    connection_events: broadcast::Sender<ConnectionEvent>,
+    slot_tracker: Option<Arc<AtomicSlotTracker>>,
+    _on_drop: Arc<OnDrop>,
+}
+
+impl WarmConnectionManager {
+    ///
+    /// Creates a builder to customize the manager before spawning it.
+    ///
+    pub fn builder(config: WarmConnectionManagerConfig) -> WarmConnectionManagerBuilder {
+        WarmConnectionManagerBuilder {
+            config,
+            identity: None,
+            local_identity: None,
+            stake_info: None,
+            clock: None,
+            slot_tracker: None,
+        }
+    }
+
+    ///
+    /// Identity presented to the leaders.
+    ///
+    pub fn identity(&self) -> Pubkey {
+        self.identity.read().expect("read").pubkey
+    }
+
+    ///
+    /// Identity of the local node, never connected, see [`WarmConnectionManagerBuilder::with_local_identity`].
+    ///
+    pub const fn local_identity(&self) -> Option<Pubkey> {
+        self.local_identity
+    }
+
+    ///
+    /// Installs a new identity for future connections.
+    ///
+    /// A new local endpoint is bound for the new identity. Existing connections keep using the previous identity
+    /// and endpoint until they are closed, so concurrent sends are not affected.
+    ///
+    pub fn rotate_identity(&self, identity: Keypair) -> Result<(), WarmConnectionManagerError> {
+        let new_identity = Arc::new(ClientIdentity::new(&identity, &self.config)?);
+        let old_identity = std::mem::replace(
+            &mut *self.identity.write().expect("write"),
+            Arc::clone(&new_identity),
+        );
+        tracing::info!(
+            "Rotated warm connection identity from {} to {}",
+            old_identity.pubkey,
+            new_identity.pubkey
+        );
+        if let Some(endpoints) = &old_identity.endpoints {
+            self.retired_endpoints
+                .lock()
+                .expect("retired endpoints lock")
+                .push(endpoints.clone());
+        }
+        Ok(())
+    }
+
+    ///
+    /// Local addresses of the QUIC endpoints in use, one UDP socket each: the endpoints of the current identity, then
+    /// those of rotated identities still carrying connections. Empty in dry-run mode.
+    ///
+    /// See the [module documentation](crate::warm_connection#local-endpoints).
+    ///
+    pub fn local_endpoints(&self) -> Vec<SocketAddr> {
+        let identity = self.identity.read().expect("read").clone();
+        let retired = self
+            .retired_endpoints
+            .lock()
+            .expect("retired endpoints lock");
+        identity
+            .endpoints
+            .iter()
+            .flat_map(ClientEndpoints::iter)
+            .chain(
+                retired
+                    .iter()
+                    .flat_map(ClientEndpoints::iter)
+                    .filter(|endpoint| endpoint.open_connections() > 0),
+            )
+            .filter_map(|endpoint| endpoint.local_addr().ok())
+            .collect()
+    }
+
+    ///
+    /// Number of established connections.
+    ///
+    pub fn active_connection_count(&self) -> usize {
+        self.table.read().expect("read").active_connection_count()
+    }
+
+    ///
+    /// Number of connections skipped in dry-run mode, see [`WarmConnectionManagerConfig::dry_run`].
+    ///
+    /// Always `0` outside of dry-run mode.
+    ///
+    pub fn intended_target_count(&self) -> u64 {
+        self.table.read().expect("read").intended_targets
+    }
+
+    ///
+    /// Exponential moving average of the RTT of the established connections, `None` until one is established.
+    ///
+    pub fn rtt_ema(&self) -> Option<Duration> {
+        self.table.read().expect("read").rtt_ema
+    }
+
+    ///
+    /// Widens the `base` fan-out width proportionally to [`WarmConnectionManager::rtt_ema`] above
+    /// [`WarmConnectionManagerConfig::adaptive_fanout_rtt_baseline`], see the
+    /// [module documentation](crate::warm_connection#adaptive-fan-out).
+    ///
+    /// The width is capped by [`WarmConnectionManagerConfig::adaptive_fanout_max_width`], `base` included.
+    ///
+    pub fn adaptive_fanout_width(&self, base: usize) -> usize {
+        adaptive_fanout_width(
+            base,
+            self.rtt_ema(),
+            self.config.adaptive_fanout_rtt_baseline,
+            self.config.effective_adaptive_fanout_max_width(),
+        )
+    }
+
+    ///
+    /// Leaders predicted at the last slot change, up to [`WarmConnectionManagerConfig::prediction_lookahead`].
+    ///
+    pub fn predicted_window(&self) -> Vec<Pubkey> {
+        self.predicted_window.borrow().clone()
+    }
+
+    ///
+    /// Subscribes to the predicted window, updated on every slot change.
+    ///
+    /// The window includes the leaders predicted beyond the warm window, e.g. for analytics or logging.
+    ///
+    pub fn subscribe_predicted_window(&self) -> watch::Receiver<Vec<Pubkey>> {
+        self.predicted_window.clone()
+    }
+
+    ///
+    /// Subscribes to the lifecycle events of the warm connections, see the
+    /// [module documentation](crate::warm_connection#connection-events).
+    ///
+    /// Only events published after the subscription are received.
+    ///
+    pub fn subscribe_connection_events(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.connection_events.subscribe()
+    }
+
+    ///
+    /// Opens, or keeps, connections to `leaders` on top of the predicted window, see the
+    /// [module documentation](crate::warm_connection#pinned-leaders).
+    ///
+    /// The leaders stay pinned for [`WarmConnectionManagerConfig::pin_ttl`], pinning a leader again renews its pin.
+    /// Connections are attempted right away rather than on the next slot change. Leaders whose TPU address does not
+    /// resolve are skipped until it does.
+    ///
+    pub fn warm(&self, leaders: &[Pubkey]) {
+        if leaders.is_empty() {
+            return;
+        }
+        let mut table = self.table.write().expect("write");
+        let until = table.now() + self.config.pin_ttl;
+        table.pin(leaders, until);
+        drop(table);
+        self.pin_notify.notify_one();
+    }
+
+    ///
+    /// Leaders currently pinned through [`WarmConnectionManager::warm`], sorted.
+    ///
+    pub fn pinned_leaders(&self) -> Vec<Pubkey> {
+        let table = self.table.read().expect("read");
+        let now = table.now();
+        let mut pinned = table
+            .pinned
+            .iter()
+            .filter(|(_, until)| **until > now)
+            .map(|(leader, _)| *leader)
+            .collect::<Vec<_>>();
+        pinned.sort_unstable();
+        pinned
+    }
+
+    ///
+    /// Whether the connection to `leader` is pinned or follows the predicted window, `None` if the leader is not
+    /// managed.
+    ///
+    /// A pinned leader is reported [`WarmConnectionOrigin::Pinned`] even if it is predicted too.
+    ///
+    pub fn origin(&self, leader: &Pubkey) -> Option<WarmConnectionOrigin> {
+        let table = self.table.read().expect("read");
+        table
+            .entries
+            .contains_key(leader)
+            .then(|| table.origin(leader, table.now()))
+    }
+
+    ///
+    /// State of the connection to `leader`, `None` if the leader is not managed.
+    ///
+    pub fn state(&self, leader: &Pubkey) -> Option<WarmConnectionState> {
+        self.table.read().expect("read").state(leader)
+    }
+
+    ///
+    /// State of the circuit breaker guarding connection attempts to `leader`, `None` if the leader is not managed.
+    ///
+    /// The leader is still predicted while its breaker is open, only the connection attempts are skipped.
+    ///
+    pub fn circuit_breaker_state(&self, leader: &Pubkey) -> Option<CircuitBreakerState> {
+        let table = self.table.read().expect("read");
+        table
+            .entries
+            .get(leader)
+            .map(|entry| entry.circuit_breaker_state(table.now()))
+    }
+
+    ///
+    /// Returns the soonest of the next `n` predicted leaders that can be reached right now, along with its TPU address.
+    ///
+    /// Scans the predicted window in order and skips leaders whose TPU address does not resolve or whose circuit
+    /// breaker is open, see [`WarmConnectionManager::circuit_breaker_state`].
+    ///
+    /// Returns `None` if no predicted leader is reachable.
+    ///
+    pub fn first_available_leader(&self, n: usize) -> Option<(Pubkey, SocketAddr)> {
+        let window = self.leader_predictor.try_predict_next_n_leaders(n);
+        let table = self.table.read().expect("read");
+        let now = table.now();
+        window.into_iter().find_map(|leader| {
+            let circuit_broken = table.entries.get(&leader).is_some_and(|entry| {
+                matches!(
+                    entry.circuit_breaker_state(now),
+                    CircuitBreakerState::Open { .. }
+                )
+            });
+            if circuit_broken {
+                tracing::trace!("Skipping leader {leader}, circuit breaker open");
+                return None;
+            }
+            let addr = self
+                .leader_tpu_info_service
+                .get_quic_dest_addr(&leader, self.config.tpu_port);
+            if addr.is_none() {
+                tracing::trace!("Skipping leader {leader}, unknown TPU address");
+            }
+            addr.map(|addr| (leader, addr))
+        })
+    }
+
+    ///
+    /// Returns the established connection to `leader`, if any.
+    ///
+    pub fn connection(&self, leader: &Pubkey) -> Option<Connection> {
+        let table = self.table.read().expect("read");
+        let entry = table
+            .entries
+            .get(leader)
+            .filter(|entry| !entry.needs_connect())?;
+        table.touch(entry);
+        entry.connection.clone()
+    }
+
+    ///
+    /// Checks that a QUIC connection to the TPU of `leader` can be established, without sending anything.
+    ///
+    /// Resolves the TPU address of `leader`, completes a handshake from the local endpoints with the current identity,
+    /// and closes the connection. The probe neither reuses nor replaces the warm connection to `leader`, and is not
+    /// subject to its circuit breaker. See the [module documentation](crate::warm_connection#probes).
+    ///
+    pub async fn probe(&self, leader: &Pubkey) -> Result<ProbeResult, ProbeError> {
+        let remote_addr = self
+            .leader_tpu_info_service
+            .get_quic_dest_addr(leader, self.config.tpu_port)
+            .ok_or(ProbeError::UnknownTpuAddress(*leader))?;
+        let identity = self.identity.read().expect("read").clone();
+        let endpoint = identity
+            .endpoints
+            .as_ref()
+            .ok_or(ProbeError::DryRun)?
+            .for_addr(&remote_addr)
+            .ok_or(ProbeError::NoEndpointForAddressFamily(remote_addr))?;
+        let server_name = socket_addr_to_quic_server_name(remote_addr);
+        let started_at = Instant::now();
+        let connecting = endpoint.connect_with(
+            identity.client_config.clone(),
+            remote_addr,
+            server_name.as_str(),
+        )?;
+        let conn = tokio::time::timeout(self.config.connecting_timeout, connecting)
+            .await
+            .map_err(|_| ConnectionError::TimedOut)??;
+        let handshake_time = started_at.elapsed();
+        let probe = ProbeResult {
+            leader: *leader,
+            path: ConnectionPath::of(&conn),
+            handshake_time,
+            rtt: conn.rtt(),
+            alpn_protocol: conn
+                .handshake_data()
+                .and_then(|data| data.downcast::<HandshakeData>().ok())
+                .and_then(|data| data.protocol),
+            peer_identity: conn
+                .peer_identity()
+                .and_then(|identity| identity.downcast::<Vec<CertificateDer<'static>>>().ok())
+                .and_then(|certificates| {
+                    certificates
+                        .first()
+                        .and_then(get_pubkey_from_tls_certificate)
+                }),
+            max_datagram_size: conn.max_datagram_size(),
+            mtu: conn.stats().path.current_mtu,
+        };
+        conn.close(0u32.into(), b"probe");
+        tracing::debug!("Probed leader {leader} at {remote_addr}: {probe:?}");
+        Ok(probe)
+    }
+
+    ///
+    /// Opens a unidirectional stream on the established connection to `leader`.
+    ///
+    /// Successful and failed opens are accounted in the leader [`ConnectionStats`].
+    /// The stream is not accounted against [`WarmConnectionManagerConfig::max_streams_per_leader`]: hold a
+    /// [`WarmConnectionManager::acquire_send_permit`] permit until it is finished.
+    ///
+    pub async fn open_uni(&self, leader: &Pubkey) -> Result<SendStream, OpenStreamError> {
+        let (conn, counters) = self.established(leader)?;
+        open_uni_counted(&conn, &counters).await
+    }
+
+    ///
+    /// Waits for a send slot to `leader`, see the [module documentation](crate::warm_connection#send-concurrency).
+    ///
+    /// Fails with [`SendError::LeaderBusy`] if too many sends are already waiting, or with
+    /// [`OpenStreamError::NotConnected`] if the leader is not managed. The slot is released once the permit is dropped.
+    ///
+    pub async fn acquire_send_permit(
+        &self,
+        leader: &Pubkey,
+    ) -> Result<LeaderSendPermit, SendError> {
+        self.send_limiter(leader)?.acquire(leader).await
+    }
+
+    ///
+    /// Number of sends currently in flight to `leader`, `0` if the leader is not managed.
+    ///
+    pub fn in_flight_sends(&self, leader: &Pubkey) -> usize {
+        self.table
+            .read()
+            .expect("read")
+            .entries
+            .get(leader)
+            .map_or(0, |entry| entry.send_limiter.in_flight())
+    }
+
+    ///
+    /// Sends `txn` on its own unidirectional stream of the established connection to `leader`, once a send slot is
+    /// available, see [`WarmConnectionManager::acquire_send_permit`].
+    ///
+    pub async fn send(&self, leader: &Pubkey, txn: &[u8]) -> Result<(), SendError> {
+        let result = async {
+            let _permit = self.acquire_send_permit(leader).await?;
+            let (conn, counters) = self.established(leader)?;
+            let mut stream = open_uni_counted(&conn, &counters).await?;
+            stream.write_all(txn).await?;
+            stream.finish()?;
+            Ok::<_, SendError>(())
+        }
+        .await;
+        #[cfg(feature = "metrics")]
+        {
+            metrics::incr_warm_connection_send(if result.is_ok() { "success" } else { "failure" });
+        }
+        result
+    }
+
+    ///
+    /// Sends each transaction of `txs` on its own unidirectional stream of the established connection to `leader`.
+    ///
+    /// Streams are opened concurrently, up to [`WarmConnectionManagerConfig::max_concurrent_streams`],
+    /// [`WarmConnectionManagerConfig::max_streams_per_leader`] and the stream credit granted by the leader; the
+    /// remaining transactions are queued until earlier streams complete.
+    ///
+    /// Returns the outcome of each transaction, in the order of `txs`. Every transaction fails with
+    /// [`OpenStreamError::NotConnected`] if there is no established connection to `leader`.
+    ///
+    pub async fn send_batch(&self, txs: &[Vec<u8>], leader: &Pubkey) -> Vec<Result<(), SendError>> {
+        let results = self.send_batch_unrecorded(txs, leader).await;
+        #[cfg(feature = "metrics")]
+        for result in &results {
+            metrics::incr_warm_connection_send(if result.is_ok() { "success" } else { "failure" });
+        }
+        results
+    }
+
+    async fn send_batch_unrecorded(
+        &self,
+        txs: &[Vec<u8>],
+        leader: &Pubkey,
+    ) -> Vec<Result<(), SendError>> {
+        let established = self
+            .established(leader)
+            .and_then(|(conn, counters)| Ok((conn, counters, self.send_limiter(leader)?)));
+        let (conn, counters, send_limiter) = match established {
+            Ok(established) => established,
+            Err(e) => {
+                let local = matches!(e, OpenStreamError::LocalLeader(_));
+                return txs
+                    .iter()
+                    .map(|_| {
+                        Err(if local {
+                            OpenStreamError::LocalLeader(*leader)
+                        } else {
+                            OpenStreamError::NotConnected(*leader)
+                        }
+                        .into())
+                    })
+                    .collect();
+            }
+        };
+        stream::iter(txs)
+            .map(|txn| {
+                let (conn, counters, send_limiter) = (&conn, &counters, &send_limiter);
+                async move {
+                    let _permit = send_limiter.acquire(leader).await?;
+                    let mut stream = open_uni_counted(conn, counters).await?;
+                    stream.write_all(txn).await?;
+                    stream.finish()?;
+                    Ok(())
+                }
+            })
+            .buffered(self.config.max_concurrent_streams.get())
+            .collect()
+            .await
+    }
+
+    fn established(
+        &self,
+        leader: &Pubkey,
+    ) -> Result<(Connection, Arc<ConnectionCounters>), OpenStreamError> {
+        if self.local_identity.as_ref() == Some(leader) {
+            return Err(OpenStreamError::LocalLeader(*leader));
+        }
+        let table = self.table.read().expect("read");
+        let entry = table
+            .entries
+            .get(leader)
+            .filter(|entry| !entry.needs_connect())
+            .ok_or(OpenStreamError::NotConnected(*leader))?;
+        table.touch(entry);
+        let conn = entry
+            .connection
+            .clone()
+            .ok_or(OpenStreamError::NotConnected(*leader))?;
+        Ok((conn, Arc::clone(&entry.counters)))
+    }
+
+    fn send_limiter(&self, leader: &Pubkey) -> Result<Arc<LeaderSendLimiter>, OpenStreamError> {
+        if self.local_identity.as_ref() == Some(leader) {
+            return Err(OpenStreamError::LocalLeader(*leader));
+        }
+        self.table
+            .read()
+            .expect("read")
+            .entries
+            .get(leader)
+            .map(|entry| Arc::clone(&entry.send_limiter))
+            .ok_or(OpenStreamError::NotConnected(*leader))
+    }
+
+    ///
+    /// Statistics of the connection to `leader`, `None` if the leader is not managed.
+    ///
+    pub fn stats(&self, leader: &Pubkey) -> Option<ConnectionStats> {
+        let table = self.table.read().expect("read");
+        table
+            .entries
+            .get(leader)
+            .map(|entry| entry.stats(table.now()))
+    }
+
+    ///
+    /// Captures the current slot and epoch, the predicted window with its resolved addresses, and the state of
+    /// every managed connection, e.g. for an admin endpoint.
+    ///
+    /// The snapshot is taken under a single lock, consistent with the last slot change. The epoch follows the epoch
+    /// schedule of the slot tracker set through [`WarmConnectionManagerBuilder::with_slot_tracker`], see
+    /// [`AtomicSlotTracker::epoch_info`], or [`EpochSchedule::without_warmup`] without one.
+    ///
+    pub fn debug_snapshot(&self) -> PipelineSnapshot {
+        let table = self.table.read().expect("read");
+        let (slot, window) = match &table.prediction {
+            Some((slot, window)) => (Some(*slot), window.as_slice()),
+            None => (None, [].as_slice()),
+        };
+        let predicted_window = window
+            .iter()
+            .map(|entry| PredictedLeaderSnapshot {
+                leader: entry.predicted.leader,
+                slots: entry.predicted.slots.clone(),
+                addr: entry.addr,
+                warm: entry.warm,
+                state: table.state(&entry.predicted.leader),
+            })
+            .collect();
+        let now = table.now();
+        let mut connections = table
+            .entries
+            .iter()
+            .map(|(leader, entry)| LeaderConnectionSnapshot {
+                leader: *leader,
+                origin: table.origin(leader, now),
+                stats: entry.stats(now),
+            })
+            .collect::<Vec<_>>();
+        connections.sort_unstable_by_key(|connection| connection.leader);
+        PipelineSnapshot {
+            slot,
+            epoch: slot.map(|slot| self.epoch_info_at(slot).epoch),
+            predicted_window,
+            connections,
+        }
+    }
+
+    fn epoch_info_at(&self, slot: Slot) -> EpochInfo {
+        match &self.slot_tracker {
+            Some(slot_tracker) => slot_tracker.epoch_info_at(slot),
+            None => EpochInfo::from_slot(&EpochSchedule::without_warmup(), slot),
+        }
+    }
+
+    ///
+    /// Statistics of the connections to every managed leader.
+    ///
+    pub fn stats_snapshot(&self) -> HashMap<Pubkey, ConnectionStats> {
+        let table = self.table.read().expect("read");
+        let now = table.now();
+        table
+            .entries
+            .iter()
+            .map(|(leader, entry)| (*leader, entry.stats(now)))
+            .collect()
+    }
+}
+
+///
+/// Keep-alive interval of the connection to `leader`, shortened by a deterministic offset in `[0, jitter)`.
+///
+/// The offset is capped at half of `keep_alive_interval`.
+///
+fn jittered_keep_alive_interval(
+    leader: &Pubkey,
+    keep_alive_interval: Duration,
+    jitter: Duration,
+) -> Duration {
+    let jitter_nanos = jitter.min(keep_alive_interval / 2).as_nanos() as u64;
+    if jitter_nanos == 0 {
+        return keep_alive_interval;
+    }
+    let bytes = leader.to_bytes();
+    let seed = u64::from_le_bytes(bytes[..8].try_into().expect("8 bytes"));
+    keep_alive_interval - Duration::from_nanos(seed % jitter_nanos)
+}
+
+///
+/// Opens a unidirectional stream on `conn`, accounting the outcome in `counters`.
+///
+async fn open_uni_counted(
+    conn: &Connection,
+    counters: &ConnectionCounters,
+) -> Result<SendStream, OpenStreamError> {
+    match conn.open_uni().await {
+        Ok(stream) => {
+            counters.record_stream_open();
+            Ok(stream)
+        }
+        Err(e) => {
+            counters.record_failure(&e);
+            Err(e.into())
+        }
+    }
+}
+
+async fn connect(
+    endpoint: Endpoint,
+    client_config: ClientConfig,
+    remote_peer_addr: SocketAddr,
+    connecting_timeout: Duration,
+) -> Result<Connection, ConnectingError> {
+    let server_name = socket_addr_to_quic_server_name(remote_peer_addr);
+    let connecting =
+        endpoint.connect_with(client_config, remote_peer_addr, server_name.as_str())?;
+    let conn = tokio::time::timeout(connecting_timeout, connecting)
+        .await
+        .map_err(|_| ConnectingError::ConnectionError(ConnectionError::TimedOut))??;
+    Ok(conn)
+}
+
+///
+/// Client identity shared by all the connections of a [`WarmConnectionManager`] opened while it is installed.
+///
+struct ClientIdentity {
+    pubkey: Pubkey,
+    client_config: ClientConfig,
+    /// `None` in dry-run mode.
+    endpoints: Option<ClientEndpoints>,
+}
+
+impl ClientIdentity {
+    fn new(
+        identity: &Keypair,
+        config: &WarmConnectionManagerConfig,
+    ) -> Result<Self, WarmConnectionManagerError> {
+        let endpoints = if config.dry_run {
+            None
+        } else {
+            let endpoints = ClientEndpoints::bind(
+                config.address_family_preference,
+                config.endpoint_port_range,
+                config.max_local_port_binding_attempts,
+            )
+            .ok_or(WarmConnectionManagerError::EndpointBind(
+                config.endpoint_port_range,
+            ))?;
+            Some(endpoints)
+        };
+        let (certificate, key) = new_dummy_x509_certificate(identity);
+        let cert = QuicClientCertificate { certificate, key };
+        Ok(Self {
+            pubkey: identity.pubkey(),
+            client_config: tpu_client_config(
+                &cert,
+                config.max_idle_timeout,
+                config.keep_alive_interval,
+                config.congestion_controller,
+            ),
+            endpoints,
+        })
+    }
+}
+
+struct WarmConnectionManagerLoop {
+    config: WarmConnectionManagerConfig,
+    table: Arc<RwLock<WarmConnectionTable>>,
+    identity: Arc<RwLock<Arc<ClientIdentity>>>,
+    leader_predictor: Arc<dyn UpcomingLeaderPredictor + Send + Sync + 'static>,
+    leader_tpu_info_service: Arc<dyn LeaderTpuInfoService + Send + Sync + 'static>,
+    connecting_tasks: JoinSet<(Pubkey, Result<Connection, ConnectingError>)>,
+    predicted_window: watch::Sender<Vec<Pubkey>>,
+    stake_info: Option<Arc<dyn ValidatorStakeInfoService + Send + Sync + 'static>>,
+    /// Number of slot changes, rotating the window with [`WarmingOrder::RoundRobin`].
+    round_robin_offset: usize,
+    pin_notify: Arc<Notify>,
+    local_identity: Option<Pubkey>,
+    retired_endpoints: Arc<Mutex<Vec<ClientEndpoints>>>,
+    connection_events: broadcast::Sender<ConnectionEvent>,
+    clock: Arc<dyn Clock + Send + Sync>,
+}
+
+impl WarmConnectionManagerLoop {
+    fn publish(&self, event: ConnectionEvent) {
+        tracing::debug!("Warm connection event: {event:?}");
+        #[cfg(feature = "prometheus")]
+        {
+            prom::incr_warm_connection_event(match event {
+                ConnectionEvent::Established { .. } => "established",
+                ConnectionEvent::Migrated { .. } => "migrated",
+                ConnectionEvent::Lost { .. } => "lost",
+                ConnectionEvent::Closed { .. } => "closed",
+            });
+        }
+        // No subscriber is not an error.
+        let _ = self.connection_events.send(event);
+    }
+
+    ///
+    /// Closes `conn`, publishing a [`ConnectionEvent::Closed`] unless it was already closed.
+    ///
+    fn close_connection(&self, leader: Pubkey, conn: &Connection, cause: ConnectionCloseCause) {
+        if conn.close_reason().is_some() {
+            return;
+        }
+        let reason: &[u8] = match cause {
+            ConnectionCloseCause::OutOfWindow => b"out of leader window",
+            ConnectionCloseCause::Evicted => b"evicted",
+            ConnectionCloseCause::Shutdown => b"shutdown",
+        };
+        conn.close(0u32.into(), reason);
+        self.publish(ConnectionEvent::Closed { leader, cause });
+    }
+
+    ///
+    /// Returns `true` if `leader` is the local node, logging and counting the skip.
+    ///
+    fn skip_local_leader(&self, leader: &Pubkey) -> bool {
+        if self.local_identity.as_ref() != Some(leader) {
+            return false;
+        }
+        tracing::debug!("Not warming leader {leader}, it is the local node");
+        #[cfg(feature = "prometheus")]
+        {
+            prom::incr_warm_connection_local_leader_skip_cnt();
+        }
+        true
+    }
+
+    ///
+    /// Resolves the TPU address of every leader of `predicted` and selects the warm window: the first `lookahead`
+    /// distinct leaders with a known TPU address, plus those whose rotation starts within `warmup_lead_slots` of
+    /// `slot`.
+    ///
+    fn resolve_window(
+        &self,
+        predicted: Vec<PredictedLeader>,
+        slot: Slot,
+    ) -> Vec<PredictedWindowEntry> {
+        let lead_end = slot.saturating_add(self.config.warmup_lead_slots);
+        let mut addrs = HashMap::with_capacity(predicted.len());
+        let mut warm = HashSet::with_capacity(self.config.lookahead.get());
+        predicted
+            .into_iter()
+            .map(|predicted| {
+                let leader = predicted.leader;
+                let addr = *addrs.entry(leader).or_insert_with(|| {
+                    let addr = self
+                        .leader_tpu_info_service
+                        .get_quic_dest_addr(&leader, self.config.tpu_port);
+                    if addr.is_none() {
+                        tracing::trace!("Not warming leader {leader}, unknown TPU address");
+                    }
+                    addr
+                });
+                let within_lead = self.config.warmup_lead_slots > 0
+                    && predicted
+                        .slots
+                        .as_ref()
+                        .is_some_and(|slots| slots.start <= lead_end);
+                let is_warm = addr.is_some()
+                    && !self.skip_local_leader(&leader)
+                    && (warm.contains(&leader)
+                        || ((warm.len() < self.config.lookahead.get() || within_lead)
+                            && warm.insert(leader)));
+                PredictedWindowEntry {
+                    predicted,
+                    addr,
+                    warm: is_warm,
+                }
+            })
+            .collect()
+    }
+
+    fn on_slot_change(&mut self, slot: Slot) {
+        self.reconcile(slot);
+        self.prune_retired_endpoints();
+        self.round_robin_offset = self.round_robin_offset.wrapping_add(1);
+    }
+
+    ///
+    /// Releases the endpoints of the rotated identities without connections left, closing their socket.
+    ///
+    fn prune_retired_endpoints(&self) {
+        let mut retired = self
+            .retired_endpoints
+            .lock()
+            .expect("retired endpoints lock");
+        retired.retain(|endpoints| {
+            endpoints
+                .iter()
+                .any(|endpoint| endpoint.open_connections() > 0)
+        });
+        #[cfg(feature = "prometheus")]
+        {
+            let current = self
+                .identity
+                .read()
+                .expect("read")
+                .endpoints
+                .as_ref()
+                .map_or(0, |endpoints| endpoints.iter().count());
+            let retired = retired
+                .iter()
+                .map(|endpoints| endpoints.iter().count())
+                .sum::<usize>();
+            prom::set_warm_connection_endpoint_cnt(current + retired);
+        }
+    }
+
+    ///
+    /// Connects the warm window predicted at `slot` followed by the pinned leaders, and closes the connections out
+    /// of both.
+    ///
+    fn reconcile(&mut self, slot: Slot) {
+        let predicted = self
+            .leader_predictor
+            .try_predict_leader_window(self.config.effective_prediction_lookahead().get());
+        let predicted_window = self.resolve_window(predicted, slot);
+        let mut addrs = HashMap::with_capacity(self.config.lookahead.get());
+        let window = predicted_window
+            .iter()
+            .filter(|entry| entry.warm)
+            .filter_map(|entry| {
+                let leader = entry.predicted.leader;
+                addrs
+                    .insert(leader, entry.addr?)
+                    .is_none()
+                    .then_some(leader)
+            })
+            .collect::<Vec<_>>();
+        let mut window = order_warm_window(
+            self.config.warming_order,
+            slot,
+            &predicted_window,
+            window,
+            self.stake_info.as_deref(),
+            self.round_robin_offset,
+        );
+        let now = self.clock.now();
+        let pinned = self.table.write().expect("write").active_pins(now);
+        for leader in pinned {
+            if addrs.contains_key(&leader) || self.skip_local_leader(&leader) {
+                continue;
+            }
+            match self
+                .leader_tpu_info_service
+                .get_quic_dest_addr(&leader, self.config.tpu_port)
+            {
+                Some(addr) => {
+                    addrs.insert(leader, addr);
+                    window.push(leader);
+                }
+                None => tracing::trace!("Not warming pinned leader {leader}, unknown TPU address"),
+            }
+        }
+        tracing::trace!(
+            "Predicted {} leader(s) at slot {slot}, warming {}",
+            predicted_window.len(),
+            window.len()
+        );
+        self.predicted_window.send_replace(
+            predicted_window
+                .iter()
+                .map(|entry| entry.predicted.leader)
+                .collect(),
+        );
+        let (events, update, expired) = {
+            let mut table = self.table.write().expect("write");
+            table.prediction = Some((slot, predicted_window));
+            table.sample_rtts();
+            let events = table.observe_connections();
+            let update = table.refresh_window(&window, now);
+            (events, update, table.expire(now, self.config.grace_period))
+        };
+        for event in events {
+            self.publish(event);
+        }
+
+        for (leader, conn) in expired {
+            tracing::debug!(
+                "Closing warm connection to leader {leader}, out of window at slot {slot}"
+            );
+            self.close_connection(leader, &conn, ConnectionCloseCause::OutOfWindow);
+        }
+
+        for (leader, maybe_conn) in &update.evicted {
+            tracing::debug!("Evicting warm connection to leader {leader} at slot {slot}");
+            if let Some(conn) = maybe_conn {
+                self.close_connection(*leader, conn, ConnectionCloseCause::Evicted);
+            }
+        }
+        #[cfg(feature = "prometheus")]
+        {
+            prom::incr_warm_connection_eviction_cnt(update.evicted.len() as u64);
+            prom::incr_warm_connection_open_refused_cnt(update.refused as u64);
+        }
+        #[cfg(feature = "metrics")]
+        {
+            metrics::set_warm_connection_active_cnt(
+                self.table.read().expect("read").active_connection_count(),
+            );
+        }
+        if update.breaker_open > 0 {
+            tracing::debug!(
+                "Skipped {} warm connection(s) at slot {slot}, circuit breaker open",
+                update.breaker_open
+            );
+        }
+        if update.refused > 0 {
+            tracing::warn!(
+                "Refused {} warm connection(s) at slot {slot}, max connections ({}) reached by the predicted window",
+                update.refused,
+                self.config.max_connections
+            );
+        }
+
+        if self.config.dry_run {
+            for leader in &update.to_connect {
+                tracing::info!(
+                    "Dry run: would warm connection to leader {leader} at {} at slot {slot}",
+                    addrs[leader]
+                );
+                #[cfg(feature = "prometheus")]
+                {
+                    prom::incr_warm_connection_intended_target_cnt();
+                }
+            }
+            self.table.write().expect("write").intended_targets += update.to_connect.len() as u64;
+            return;
+        }
+
+        let identity = self.identity.read().expect("read").clone();
+        for leader in update.to_connect {
+            let remote_peer_addr = addrs[&leader];
+            let Some(endpoint) = identity
+                .endpoints
+                .as_ref()
+                .and_then(|endpoints| endpoints.for_addr(&remote_peer_addr))
+                .cloned()
+            else {
+                let _ = self.table.write().expect("write").connected(
+                    leader,
+                    Err(ConnectingError::NoEndpointForAddressFamily(
+                        remote_peer_addr,
+                    )),
+                    now,
+                );
+                continue;
+            };
+            tracing::trace!("Warming connection to leader {leader} at {remote_peer_addr}");
+            let mut client_config = identity.client_config.clone();
+            if !self.config.keep_alive_jitter.is_zero() {
+                client_config.transport_config(Arc::new(tpu_transport_config(
+                    self.config.max_idle_timeout,
+                    jittered_keep_alive_interval(
+                        &leader,
+                        self.config.keep_alive_interval,
+                        self.config.keep_alive_jitter,
+                    ),
+                    self.config.congestion_controller,
+                )));
+            }
+            let fut = connect(
+                endpoint,
+                client_config,
+                remote_peer_addr,
+                self.config.connecting_timeout,
+            );
+            let span = tracing::debug_span!(
+                "connect",
+                leader = %leader,
+                remote_peer_addr = %remote_peer_addr,
+                slot
+            );
+            self.connecting_tasks
+                .spawn(async move { (leader, fut.await) }.instrument(span));
+        }
+    }
+
+    fn on_connecting_result(
+        &mut self,
+        leader: Pubkey,
+        result: Result<Connection, ConnectingError>,
+    ) {
+        #[cfg(feature = "metrics")]
+        {
+            metrics::incr_warm_connection_connect(if result.is_ok() {
+                "success"
+            } else {
+                "failure"
+            });
+        }
+        let path = result.as_ref().ok().map(ConnectionPath::of);
+        let mut table = self.table.write().expect("write");
+        let unused = table.connected(leader, result, self.clock.now());
+        #[cfg(feature = "metrics")]
+        {
+            metrics::set_warm_connection_active_cnt(table.active_connection_count());
+        }
+        drop(table);
+        match (unused, path) {
+            // Never reported as established.
+            (Some(conn), _) => conn.close(0u32.into(), b"out of leader window"),
+            (None, Some(path)) => self.publish(ConnectionEvent::Established { leader, path }),
+            (None, None) => {}
+        }
+    }
+
+    async fn run(
+        mut self,
+        mut slot_rx: watch::Receiver<Slot>,
+        cancellation_token: CancellationToken,
+    ) {
+        let initial_slot = *slot_rx.borrow_and_update();
+        self.on_slot_change(initial_slot);
+        let pin_notify = Arc::clone(&self.pin_notify);
+        loop {
+            tokio::select! {
+                result = slot_rx.changed() => {
+                    if result.is_err() {
+                        tracing::warn!("Slot stream closed, exiting warm connection manager loop.");
+                        break;
+                    }
+                    let slot = *slot_rx.borrow_and_update();
+                    self.on_slot_change(slot);
+                }
+                _ = pin_notify.notified() => {
+                    let slot = *slot_rx.borrow();
+                    self.reconcile(slot);
+                }
+                Some(result) = self.connecting_tasks.join_next() => {
+                    match result {
+                        Ok((leader, result)) => self.on_connecting_result(leader, result),
+                        Err(e) => tracing::error!("Warm connection task failed: {e:?}"),
+                    }
+                }
+                _ = cancellation_token.cancelled() => {
+                    tracing::info!("Cancellation requested, exiting warm connection manager loop.");
+                    break;
+                }
+            }
+        }
+
+        let entries = self
+            .table
+            .write()
+            .expect("write")
+            .entries
+            .drain()
+            .collect::<Vec<_>>();
+        for (leader, entry) in entries {
+            if let Some(conn) = entry.connection {
+                self.close_connection(leader, &conn, ConnectionCloseCause::Shutdown);
+            }
+        }
+    }
+}
+
+///
+/// Builder of [`WarmConnectionManager`], see [`WarmConnectionManager::builder`].
+///
+pub struct WarmConnectionManagerBuilder {
+    config: WarmConnectionManagerConfig,
+    identity: Option<Keypair>,
+    local_identity: Option<Pubkey>,
+    stake_info: Option<Arc<dyn ValidatorStakeInfoService + Send + Sync + 'static>>,
+    clock: Option<Arc<dyn Clock + Send + Sync>>,
+    slot_tracker: Option<Arc<AtomicSlotTracker>>,
+}
+
+impl WarmConnectionManagerBuilder {
+    ///
+    /// Sets the identity used for the client TLS certificate, so connections benefit from the identity stake at the leader.
+    ///
+    /// If unset, connections are unstaked and throttled by the leaders.
+    ///
+    pub fn with_identity(mut self, identity: Keypair) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    ///
+    /// Sets the identity of the local node, e.g. the validator running the manager, so predicted leaders matching it
+    /// are never connected, see the [module documentation](crate::warm_connection#local-node).
+    ///
+    pub fn with_local_identity(mut self, local_identity: Pubkey) -> Self {
+        self.local_identity = Some(local_identity);
+        self
+    }
+
+    ///
+    /// Sets the stake of the leaders, ordering the connections with [`WarmingOrder::StakeWeighted`].
+    ///
+    /// Without it, [`WarmingOrder::StakeWeighted`] behaves like [`WarmingOrder::CurrentLeaderFirst`].
+    ///
+    pub fn with_stake_info(
+        mut self,
+        stake_info: Arc<dyn ValidatorStakeInfoService + Send + Sync + 'static>,
+    ) -> Self {
+        self.stake_info = Some(stake_info);
+        self
+    }
+
+    ///
+    /// Sets the clock driving the grace period, the pin TTL and the circuit breaker cooldown, the system clock by
+    /// default, e.g. a [`crate::clock::MockClock`] in tests.
+    ///
+    /// Slot changes still come from the slot stream: the clock is only read when the manager wakes up.
+    ///
+    pub fn with_clock(mut self, clock: Arc<dyn Clock + Send + Sync>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    ///
+    /// Sets the slot tracker feeding `slot_rx`, so [`WarmConnectionManager::debug_snapshot`] locates slots within
+    /// their epoch with the cluster epoch schedule, see [`AtomicSlotTracker::set_epoch_schedule`].
+    ///
+    pub fn with_slot_tracker(mut self, slot_tracker: Arc<AtomicSlotTracker>) -> Self {
+        self.slot_tracker = Some(slot_tracker);
+        self
+    }
+
+    ///
+    /// Spawns the [`WarmConnectionManager`] along with its background task.
+    ///
+    /// The background task wakes up on every slot sent through `slot_rx`, see [`crate::slot::AtomicSlotTracker::subscribe`].
+    ///
+    /// Dropping the `JoinHandle` will not stop the background task; to stop it, drop all [`WarmConnectionManager`] instances.
+    ///
+    pub fn spawn(
+        self,
+        slot_rx: watch::Receiver<Slot>,
+        leader_predictor: Arc<dyn UpcomingLeaderPredictor + Send + Sync + 'static>,
+        leader_tpu_info_service: Arc<dyn LeaderTpuInfoService + Send + Sync + 'static>,
+    ) -> Result<(WarmConnectionManager, JoinHandle<()>), WarmConnectionManagerError> {
+        let Self {
+            config,
+            identity,
+            local_identity,
+            stake_info,
+            clock,
+            slot_tracker,
+        } = self;
+        let clock = clock.unwrap_or_else(|| Arc::new(SystemClock));
+        let leader_tpu_info_service: Arc<dyn LeaderTpuInfoService + Send + Sync + 'static> =
+            Arc::new(AddressFamilyTpuInfoService {
+                preference: config.address_family_preference,
+                other: leader_tpu_info_service,
+            });
+
+        let prediction_lookahead = config.effective_prediction_lookahead();
+        if prediction_lookahead < config.lookahead {
+            return Err(WarmConnectionManagerError::PredictionLookaheadTooShort {
+                lookahead: config.lookahead,
+                prediction_lookahead,
+            });
+        }
+        if config.warming_order == WarmingOrder::StakeWeighted && stake_info.is_none() {
+            tracing::warn!(
+                "Stake-weighted warming order without stake info, connecting the current leader first"
+            );
+        }
+        if config.dry_run {
+            tracing::info!("Warm connection manager in dry-run mode, no connection will be opened");
+        }
+        if config.keep_alive_interval >= config.max_idle_timeout {
+            tracing::warn!(
+                "Warm connection keep-alive interval {:?} is not shorter than the max idle timeout {:?}, idle connections will be dropped",
+                config.keep_alive_interval,
+                config.max_idle_timeout
+            );
+        }
+        let identity = Arc::new(RwLock::new(Arc::new(ClientIdentity::new(
+            &identity.unwrap_or_else(Keypair::new),
+            &config,
+        )?)));
+        let manager_config = Arc::new(config.clone());
+
+        let table = Arc::new(RwLock::new(
+            WarmConnectionTable::new(config.max_connections)
+                .with_circuit_breaker(
+                    config.circuit_breaker_failure_threshold,
+                    config.circuit_breaker_cooldown,
+                )
+                .with_send_limit(
+                    config.max_streams_per_leader,
+                    config.max_queued_sends_per_leader,
+                )
+                .with_clock(Arc::clone(&clock)),
+        ));
+        let cancellation_token = CancellationToken::new();
+        let (predicted_window_tx, predicted_window) = watch::channel(Vec::new());
+        let pin_notify = Arc::new(Notify::new());
+        let retired_endpoints = Arc::new(Mutex::new(Vec::new()));
+        let (connection_events, _) = broadcast::channel(CONNECTION_EVENTS_CAPACITY);
+        let ev_loop = WarmConnectionManagerLoop {
+            config,
+            table: Arc::clone(&table),
+            identity: Arc::clone(&identity),
+            leader_predictor: Arc::clone(&leader_predictor),
+            leader_tpu_info_service: Arc::clone(&leader_tpu_info_service),
+            connecting_tasks: JoinSet::new(),
+            predicted_window: predicted_window_tx,
+            stake_info,
+            round_robin_offset: 0,
+            pin_notify: Arc::clone(&pin_notify),
+            local_identity,
+            retired_endpoints: Arc::clone(&retired_endpoints),
+            connection_events: connection_events.clone(),
+            clock,
+        };
+        let jh = spawn_named(
+            WARM_CONNECTION_MANAGER_TASK,
+            ev_loop.run(slot_rx, cancellation_token.clone()),
+        );
+
+        Ok((
+            WarmConnectionManager {
+                table,
+                identity,
+                config: manager_config,
+                leader_predictor,
+                leader_tpu_info_service,
+                predicted_window,
+                pin_notify,
+                local_identity,
+                retired_endpoints,
+                connection_events,
+                slot_tracker,
+                _on_drop: Arc::new(OnDrop {
+                    handle: cancellation_token,
+                }),
+            },
+            jh,
+        ))
+    }
+
+    ///
+    /// Same as [`WarmConnectionManagerBuilder::spawn`], resolving the leader TPU addresses with `resolver`
+    /// instead of a [`LeaderTpuInfoService`], e.g. from a custom registry rather than gossip.
+    ///
+    pub fn spawn_with_resolver<R>(
+        self,
+        slot_rx: watch::Receiver<Slot>,
+        leader_predictor: Arc<dyn UpcomingLeaderPredictor + Send + Sync + 'static>,
+        resolver: R,
+    ) -> Result<(WarmConnectionManager, JoinHandle<()>), WarmConnectionManagerError>
+    where
+        R: AddressResolver + Send + Sync + 'static,
+    {
+        self.spawn(
+            slot_rx,
+            leader_predictor,
+            Arc::new(ResolverTpuInfoService { resolver }),
+        )
+    }
+}
+
+///
+/// Creates a new [`WarmConnectionManager`] along with its background task.
+///
+/// Connections use an unstaked identity, see [`WarmConnectionManager::builder`] to set one.
+///
+/// Dropping the `JoinHandle` will not stop the background task; to stop it, drop all [`WarmConnectionManager`] instances.
+///
+pub fn spawn_warm_connection_manager(
+    config: WarmConnectionManagerConfig,
+    slot_rx: watch::Receiver<Slot>,
+    leader_predictor: Arc<dyn UpcomingLeaderPredictor + Send + Sync + 'static>,
+    leader_tpu_info_service: Arc<dyn LeaderTpuInfoService + Send + Sync + 'static>,
+) -> Result<(WarmConnectionManager, JoinHandle<()>), WarmConnectionManagerError> {
+    WarmConnectionManager::builder(config).spawn(slot_rx, leader_predictor, leader_tpu_info_service)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use {
+        super::*,
+        crate::{
+            clock::MockClock,
+            core::{StaticAddressResolver, StaticLeaderPredictor, TpuAddrs, crypto_provider},
+            rpc::{contact::ContactInfoTable, tpu_info::RpcTpuQuicContactInfo},
+        },
+        quinn::{ServerConfig, TransportConfig, crypto::rustls::QuicServerConfig},
+        solana_clock::DEFAULT_SLOTS_PER_EPOCH,
+        solana_streamer::nonblocking::quic::ALPN_TPU_PROTOCOL_ID,
+        solana_tls_utils::SkipClientVerification,
+        tokio::sync::mpsc,
+    };
+
+    ///
+    /// Spawns a local QUIC server closing connections idle for longer than `idle_timeout`.
+    ///
+    /// Accepted connections are sent through the returned receiver, they stay open as long as it is not dropped.
+    ///
+    fn spawn_test_server(
+        idle_timeout: Duration,
+    ) -> (SocketAddr, mpsc::UnboundedReceiver<Connection>) {
+        let mut transport_config = TransportConfig::default();
+        transport_config.max_idle_timeout(Some(idle_timeout.try_into().unwrap()));
+        spawn_test_server_with_transport(transport_config)
+    }
+
+    ///
+    /// Same as [`spawn_test_server`], with a custom server transport configuration.
+    ///
+    pub(crate) fn spawn_test_server_with_transport(
+        transport_config: TransportConfig,
+    ) -> (SocketAddr, mpsc::UnboundedReceiver<Connection>) {
+        let (certificate, key) = new_dummy_x509_certificate(&Keypair::new());
+        let mut crypto = rustls::ServerConfig::builder_with_provider(Arc::new(crypto_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_client_cert_verifier(SkipClientVerification::new())
+            .with_single_cert(vec![certificate], key)
+            .unwrap();
+        crypto.alpn_protocols = vec![ALPN_TPU_PROTOCOL_ID.to_vec()];
+        let mut server_config =
+            ServerConfig::with_crypto(Arc::new(QuicServerConfig::try_from(crypto).unwrap()));
+        server_config.transport_config(Arc::new(transport_config));
+
+        let server = Endpoint::server(server_config, "127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = server.local_addr().unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(incoming) = server.accept().await {
+                if let Ok(conn) = incoming.await {
+                    let _ = tx.send(conn);
+                }
+            }
+        });
+        (addr, rx)
+    }
+
+    fn peer_certificate(conn: &Connection) -> CertificateDer<'static> {
+        conn.peer_identity()
+            .expect("peer identity")
+            .downcast::<Vec<CertificateDer<'static>>>()
+            .expect("certificate chain")
+            .into_iter()
+            .next()
+            .expect("certificate")
+    }
+
+    async fn connect_with_keep_alive(
+        server_addr: SocketAddr,
+        keep_alive_interval: Duration,
+        congestion_controller: CongestionControl,
+    ) -> Connection {
+        let (certificate, key) = new_dummy_x509_certificate(&Keypair::new());
+        let cert = QuicClientCertificate { certificate, key };
+        let client_config = tpu_client_config(
+            &cert,
+            DEFAULT_WARM_CONNECTION_MAX_IDLE_TIMEOUT,
+            keep_alive_interval,
+            congestion_controller,
+        );
+        let endpoint = Endpoint::client("127.0.0.1:0".parse().unwrap()).unwrap();
+        connect(
+            endpoint,
+            client_config,
+            server_addr,
+            DEFAULT_WARM_CONNECTION_CONNECTING_TIMEOUT,
+        )
+        .await
+        .expect("connect")
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_should_outlive_peer_idle_timeout() {
+        let peer_idle_timeout = Duration::from_millis(300);
+        // Keep the server side of the connections open.
+        let (server_addr, _server_connections) = spawn_test_server(peer_idle_timeout);
+
+        let kept_alive = connect_with_keep_alive(
+            server_addr,
+            Duration::from_millis(100),
+            CongestionControl::default(),
+        )
+        .await;
+        let not_kept_alive = connect_with_keep_alive(
+            server_addr,
+            Duration::from_secs(60),
+            CongestionControl::default(),
+        )
+        .await;
+
+        tokio::time::sleep(peer_idle_timeout * 4).await;
+        assert!(kept_alive.close_reason().is_none());
+        assert!(not_kept_alive.close_reason().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_should_send_with_each_congestion_controller() {
+        let (server_addr, _server_connections) = spawn_test_server(Duration::from_secs(10));
+        for congestion_controller in [CongestionControl::Cubic, CongestionControl::Bbr] {
+            let conn = connect_with_keep_alive(
+                server_addr,
+                DEFAULT_WARM_CONNECTION_KEEP_ALIVE_INTERVAL,
+                congestion_controller,
+            )
+            .await;
+            let mut stream = conn.open_uni().await.expect("open stream");
+            stream.write_all(&[0u8; 1024]).await.expect("write");
+            stream.finish().expect("finish");
+            assert!(conn.close_reason().is_none(), "{congestion_controller:?}");
+        }
+    }
+
+    #[test]
+    fn test_jittered_keep_alive_should_spread_pings() {
+        let keep_alive_interval = Duration::from_secs(5);
+        let jitter = Duration::from_secs(1);
+        let leaders = (0..32).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+
+        // Every connection opened at the same instant, timestamp of their 3rd ping.
+        let pings = leaders
+            .iter()
+            .map(|leader| 3 * jittered_keep_alive_interval(leader, keep_alive_interval, jitter))
+            .collect::<Vec<_>>();
+        let distinct = pings.iter().collect::<HashSet<_>>();
+        assert!(distinct.len() > leaders.len() / 2, "{pings:?}");
+        let earliest = *pings.iter().min().unwrap();
+        let latest = *pings.iter().max().unwrap();
+        assert!(latest - earliest >= jitter, "{pings:?}");
+        assert!(earliest > 3 * (keep_alive_interval - jitter));
+        assert!(latest <= 3 * keep_alive_interval);
+
+        // Deterministic per leader.
+        for (leader, ping) in leaders.iter().zip(&pings) {
+            assert_eq!(
+                3 * jittered_keep_alive_interval(leader, keep_alive_interval, jitter),
+                *ping
+            );
+        }
+        // Disabled, or capped at half the interval.
+        assert_eq!(
+            jittered_keep_alive_interval(&leaders[0], keep_alive_interval, Duration::ZERO),
+            keep_alive_interval
+        );
+        assert!(
+            jittered_keep_alive_interval(&leaders[0], keep_alive_interval, Duration::from_secs(60))
+                > keep_alive_interval / 2
+        );
+    }
+
+    fn failed_connect() -> Result<Connection, ConnectingError> {
+        Err(ConnectingError::PeerNotInLeaderSchedule)
+    }
+
+    #[test]
+    fn test_refresh_window_should_connect_new_and_failed_leaders_once() {
+        let leader1 = Pubkey::new_unique();
+        let leader2 = Pubkey::new_unique();
+        let mut table = WarmConnectionTable::new(usize::MAX);
+        let now = Instant::now();
+
+        let update = table.refresh_window(&[leader1, leader1, leader2], now);
+        assert_eq!(update.to_connect, vec![leader1, leader2]);
+        assert_eq!(table.state(&leader1), Some(WarmConnectionState::Connecting));
+
+        // Leaders already connecting are not reattempted
+        assert!(
+            table
+                .refresh_window(&[leader1, leader2], now)
+                .to_connect
+                .is_empty()
+        );
+
+        assert!(table.connected(leader1, failed_connect(), now).is_none());
+        assert_eq!(table.state(&leader1), Some(WarmConnectionState::Failed));
+        assert_eq!(table.active_connection_count(), 0);
+
+        let stats = table.entries[&leader1].stats(now);
+        assert_eq!(stats.state, WarmConnectionState::Failed);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.rtt, None);
+        assert_eq!(
+            stats.last_error,
+            Some(ConnectingError::PeerNotInLeaderSchedule.to_string())
+        );
+        assert_eq!(table.entries[&leader2].stats(now).failures, 0);
+
+        // Failed leaders are reattempted on the next slot change
+        assert_eq!(
+            table.refresh_window(&[leader1, leader2], now).to_connect,
+            vec![leader1]
+        );
+    }
+
+    #[test]
+    fn test_circuit_breaker_should_skip_leader_during_cooldown() {
+        let leader = Pubkey::new_unique();
+        let cooldown = Duration::from_secs(10);
+        let mut table = WarmConnectionTable::new(usize::MAX).with_circuit_breaker(2, cooldown);
+        let t0 = Instant::now();
+
+        for _ in 0..2 {
+            assert_eq!(table.refresh_window(&[leader], t0).to_connect, vec![leader]);
+            assert_eq!(
+                table.entries[&leader].circuit_breaker_state(t0),
+                CircuitBreakerState::Closed
+            );
+            assert!(table.connected(leader, failed_connect(), t0).is_none());
+        }
+        let open = CircuitBreakerState::Open {
+            until: t0 + cooldown,
+        };
+        assert_eq!(table.entries[&leader].stats(t0).circuit_breaker, open);
+
+        // Connection attempts are skipped while the breaker is open, the leader is still tracked.
+        let t1 = t0 + Duration::from_secs(5);
+        let update = table.refresh_window(&[leader], t1);
+        assert!(update.to_connect.is_empty());
+        assert_eq!(update.breaker_open, 1);
+        assert_eq!(table.state(&leader), Some(WarmConnectionState::Failed));
+
+        // Once the cooldown elapsed, a single probe is attempted.
+        let t2 = t0 + cooldown;
+        assert_eq!(
+            table.entries[&leader].circuit_breaker_state(t2),
+            CircuitBreakerState::HalfOpen
+        );
+        assert_eq!(table.refresh_window(&[leader], t2).to_connect, vec![leader]);
+        assert!(table.refresh_window(&[leader], t2).to_connect.is_empty());
+
+        // A failed probe reopens the breaker for another cooldown.
+        assert!(table.connected(leader, failed_connect(), t2).is_none());
+        assert_eq!(
+            table.entries[&leader].circuit_breaker_state(t2),
+            CircuitBreakerState::Open {
+                until: t2 + cooldown,
+            }
+        );
+        assert_eq!(table.refresh_window(&[leader], t2).breaker_open, 1);
+        assert_eq!(table.entries[&leader].stats(t2).failures, 3);
+    }
+
+    #[test]
+    fn test_adaptive_fanout_width_should_grow_with_rtt() {
+        const BASE: usize = 2;
+        const MAX_WIDTH: usize = 8;
+        let baseline = Duration::from_millis(100);
+        let mut table = WarmConnectionTable::new(MAX_WIDTH);
+        let width = |table: &WarmConnectionTable| {
+            adaptive_fanout_width(BASE, table.rtt_ema, baseline, MAX_WIDTH)
+        };
+        assert_eq!(width(&table), BASE);
+
+        // The first sample seeds the average, RTTs within the baseline keep the base width.
+        table.record_rtt(Duration::from_millis(80));
+        assert_eq!(table.rtt_ema, Some(Duration::from_millis(80)));
+        assert_eq!(width(&table), BASE);
+
+        let mut last_width = BASE;
+        for rtt_ms in [150, 250, 350] {
+            for _ in 0..50 {
+                table.record_rtt(Duration::from_millis(rtt_ms));
+            }
+            let ema = table.rtt_ema.expect("rtt ema");
+            assert!(ema.abs_diff(Duration::from_millis(rtt_ms)) < Duration::from_millis(1));
+            let width = width(&table);
+            assert!(width > last_width, "{width} <= {last_width} at {rtt_ms}ms");
+            last_width = width;
+        }
+        // 350ms is 3.5 times the baseline.
+        assert_eq!(last_width, 7);
+
+        table.record_rtt(Duration::from_secs(10));
+        assert_eq!(width(&table), MAX_WIDTH);
+        assert_eq!(
+            adaptive_fanout_width(MAX_WIDTH + 1, None, baseline, MAX_WIDTH),
+            MAX_WIDTH
+        );
+        assert_eq!(
+            adaptive_fanout_width(BASE, table.rtt_ema, Duration::ZERO, MAX_WIDTH),
+            BASE
+        );
+
+        let config = WarmConnectionManagerConfig {
+            max_connections: 4,
+            adaptive_fanout_max_width: NonZeroUsize::new(16),
+            ..Default::default()
+        };
+        assert_eq!(config.effective_adaptive_fanout_max_width(), 4);
+    }
+
+    #[test]
+    fn test_expire_should_respect_grace_period() {
+        let leader1 = Pubkey::new_unique();
+        let leader2 = Pubkey::new_unique();
+        let grace_period = Duration::from_secs(10);
+        let mut table = WarmConnectionTable::new(usize::MAX);
+        let t0 = Instant::now();
+
+        table.refresh_window(&[leader1, leader2], t0);
+        // leader1 fell out of the window
+        let t1 = t0 + Duration::from_secs(5);
+        table.refresh_window(&[leader2], t1);
+        assert!(table.expire(t1, grace_period).is_empty());
+        assert!(table.state(&leader1).is_some());
+
+        let t2 = t0 + Duration::from_secs(11);
+        table.refresh_window(&[leader2], t2);
+        table.expire(t2, grace_period);
+        assert_eq!(table.state(&leader1), None);
+        assert_eq!(table.state(&leader2), Some(WarmConnectionState::Connecting));
+
+        // Late connection results for expired leaders are ignored
+        assert!(table.connected(leader1, failed_connect(), t2).is_none());
+        assert_eq!(table.state(&leader1), None);
+    }
+
+    #[test]
+    fn test_refresh_window_should_evict_lru_but_never_the_current_window() {
+        let max_connections = 3;
+        let mut table = WarmConnectionTable::new(max_connections);
+        let leaders = (0..20).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let t0 = Instant::now();
+
+        // Slide a window of 3 distinct leaders, one leader at a time.
+        let mut total_evicted = 0;
+        for (i, window) in leaders.windows(max_connections).enumerate() {
+            let now = t0 + Duration::from_millis(400 * i as u64);
+            let update = table.refresh_window(window, now);
+            assert_eq!(update.refused, 0);
+            assert!(
+                update
+                    .evicted
+                    .iter()
+                    .all(|(leader, _)| !window.contains(leader))
+            );
+            total_evicted += update.evicted.len();
+            for leader in window {
+                assert_eq!(table.state(leader), Some(WarmConnectionState::Connecting));
+            }
+            let open = table
+                .entries
+                .values()
+                .filter(|entry| entry.holds_connection())
+                .count();
+            assert!(open <= max_connections);
+        }
+        assert_eq!(total_evicted, leaders.len() - max_connections);
+
+        // The least recently used leader goes first
+        let now = t0 + Duration::from_secs(60);
+        let window = &leaders[leaders.len() - max_connections..];
+        let old_leader = window[0];
+        let update = table.refresh_window(&[window[1], window[2], leaders[0]], now);
+        assert_eq!(update.to_connect, vec![leaders[0]]);
+        assert_eq!(
+            update
+                .evicted
+                .iter()
+                .map(|(leader, _)| *leader)
+                .collect::<Vec<_>>(),
+            vec![old_leader]
+        );
+
+        // A window larger than the cap refuses the extra leaders rather than evicting the window
+        let later = now + Duration::from_secs(1);
+        let extra = Pubkey::new_unique();
+        let update = table.refresh_window(&[window[1], window[2], leaders[0], extra], later);
+        assert_eq!(update.refused, 1);
+        assert!(update.evicted.is_empty());
+        assert_eq!(table.state(&extra), Some(WarmConnectionState::Failed));
+        for leader in [window[1], window[2], leaders[0]] {
+            assert_eq!(table.state(&leader), Some(WarmConnectionState::Connecting));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_identity_certificate_should_be_reused_across_connections() {
+        let (server_addr, mut server_connections) = spawn_test_server(Duration::from_secs(10));
+        let keypair = Keypair::new();
+        let identity = ClientIdentity::new(&keypair, &WarmConnectionManagerConfig::default())
+            .expect("identity");
+
+        let mut certificates = Vec::new();
+        for _ in 0..2 {
+            let _conn = connect(
+                identity
+                    .endpoints
+                    .as_ref()
+                    .and_then(|endpoints| endpoints.for_addr(&server_addr))
+                    .cloned()
+                    .expect("IPv4 endpoint"),
+                identity.client_config.clone(),
+                server_addr,
+                DEFAULT_WARM_CONNECTION_CONNECTING_TIMEOUT,
+            )
+            .await
+            .expect("connect");
+            let server_conn = server_connections.recv().await.expect("server connection");
+            certificates.push(peer_certificate(&server_conn));
+        }
+
+        assert_eq!(certificates[0], certificates[1]);
+        assert_eq!(
+            get_pubkey_from_tls_certificate(&certificates[0]),
+            Some(keypair.pubkey())
+        );
+        assert_eq!(identity.pubkey, keypair.pubkey());
+    }
+
+    struct TestPredictor(Mutex<Vec<Pubkey>>);
+
+    impl UpcomingLeaderPredictor for TestPredictor {
+        fn try_predict_next_n_leaders(&self, n: usize) -> Vec<Pubkey> {
+            let window = self.0.lock().unwrap();
+            window.iter().take(n).copied().collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_should_report_the_handshake_without_warming_a_connection() {
+        let (server_addr, mut server_connections) = spawn_test_server(Duration::from_secs(10));
+        let leader = Pubkey::new_unique();
+        let tpu_info = ContactInfoTable::from_iter([(
+            leader,
+            RpcTpuQuicContactInfo {
+                tpu_quic: Some(server_addr),
+                tpu_forwards_quic: Some(server_addr),
+            },
+        )]);
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        let (manager, _jh) = spawn_warm_connection_manager(
+            WarmConnectionManagerConfig::default(),
+            slot_rx,
+            Arc::new(TestPredictor(Mutex::new(Vec::new()))),
+            Arc::new(tpu_info),
+        )
+        .expect("spawn");
+
+        let probe = manager.probe(&leader).await.expect("probe");
+        assert_eq!(probe.leader, leader);
+        assert_eq!(probe.path.remote_addr, server_addr);
+        assert!(!probe.handshake_time.is_zero());
+        assert!(!probe.rtt.is_zero());
+        assert_eq!(probe.alpn_protocol, Some(ALPN_TPU_PROTOCOL_ID.to_vec()));
+        assert!(probe.peer_identity.is_some());
+        assert!(probe.max_datagram_size.is_some());
+        assert!(probe.mtu >= 1200);
+
+        // The probe connection is closed, and not kept warm.
+        let server_conn = server_connections.recv().await.expect("server connection");
+        assert!(matches!(
+            server_conn.closed().await,
+            ConnectionError::ApplicationClosed(_)
+        ));
+        assert_eq!(manager.state(&leader), None);
+        assert_eq!(manager.active_connection_count(), 0);
+
+        assert!(matches!(
+            manager.probe(&Pubkey::new_unique()).await,
+            Err(ProbeError::UnknownTpuAddress(_))
+        ));
+    }
+
+    async fn next_event(events: &mut broadcast::Receiver<ConnectionEvent>) -> ConnectionEvent {
+        tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("connection event")
+            .expect("event stream")
+    }
+
+    pub(crate) async fn wait_for_state(
+        manager: &WarmConnectionManager,
+        leader: &Pubkey,
+        state: WarmConnectionState,
+    ) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while manager.state(leader) != Some(state) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("state");
+    }
+
+    #[tokio::test]
+    async fn test_rotate_identity_should_only_apply_to_future_connections() {
+        let (server_addr, mut server_connections) = spawn_test_server(Duration::from_secs(10));
+        let leader1 = Pubkey::new_unique();
+        let leader2 = Pubkey::new_unique();
+        let contact_info = RpcTpuQuicContactInfo {
+            tpu_quic: Some(server_addr),
+            tpu_forwards_quic: Some(server_addr),
+        };
+        let tpu_info =
+            ContactInfoTable::from_iter([(leader1, contact_info.clone()), (leader2, contact_info)]);
+        let predictor = Arc::new(TestPredictor(Mutex::new(vec![leader1])));
+        let (slot_tx, slot_rx) = watch::channel(0);
+
+        let old_identity = Keypair::new();
+        let new_identity = Keypair::new();
+        let (manager, _jh) = WarmConnectionManager::builder(WarmConnectionManagerConfig::default())
+            .with_identity(old_identity.insecure_clone())
+            .spawn(slot_rx, Arc::clone(&predictor) as _, Arc::new(tpu_info))
+            .expect("spawn");
+        wait_for_state(&manager, &leader1, WarmConnectionState::Ready).await;
+        let old_server_conn = server_connections.recv().await.expect("server connection");
+        let endpoints_per_identity = manager.local_endpoints().len();
+
+        manager
+            .rotate_identity(new_identity.insecure_clone())
+            .expect("rotate");
+        assert_eq!(manager.identity(), new_identity.pubkey());
+        // The endpoints of the previous identity are in use until its connection closes.
+        assert_eq!(manager.local_endpoints().len(), 2 * endpoints_per_identity);
+        *predictor.0.lock().unwrap() = vec![leader1, leader2];
+        slot_tx.send(1).unwrap();
+        wait_for_state(&manager, &leader2, WarmConnectionState::Ready).await;
+        let new_server_conn = server_connections.recv().await.expect("server connection");
+
+        assert_eq!(
+            get_pubkey_from_tls_certificate(&peer_certificate(&old_server_conn)),
+            Some(old_identity.pubkey())
+        );
+        assert_eq!(
+            get_pubkey_from_tls_certificate(&peer_certificate(&new_server_conn)),
+            Some(new_identity.pubkey())
+        );
+
+        // The pre-existing connection still works
+        let mut stream = manager.open_uni(&leader1).await.expect("open uni");
+        stream.write_all(b"hello").await.expect("write");
+        stream.finish().expect("finish");
+        assert_eq!(manager.active_connection_count(), 2);
+        assert_eq!(manager.stats(&leader1).unwrap().stream_opens, 1);
+    }
+
+    #[tokio::test]
+    async fn test_connections_should_share_a_single_local_endpoint() {
+        let (server_addr, mut server_connections) = spawn_test_server(Duration::from_secs(10));
+        let leaders = (0..16).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let contact_info = RpcTpuQuicContactInfo {
+            tpu_quic: Some(server_addr),
+            tpu_forwards_quic: Some(server_addr),
+        };
+        let tpu_info = ContactInfoTable::from_iter(
+            leaders.iter().map(|leader| (*leader, contact_info.clone())),
+        );
+        let config = WarmConnectionManagerConfig {
+            lookahead: NonZeroUsize::new(leaders.len()).unwrap(),
+            address_family_preference: AddressFamily::V4Only,
+            ..Default::default()
+        };
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        let (manager, _jh) = spawn_warm_connection_manager(
+            config,
+            slot_rx,
+            Arc::new(TestPredictor(Mutex::new(leaders.clone()))),
+            Arc::new(tpu_info),
+        )
+        .expect("spawn");
+
+        for leader in &leaders {
+            wait_for_state(&manager, leader, WarmConnectionState::Ready).await;
+        }
+        assert_eq!(manager.active_connection_count(), leaders.len());
+        let local_endpoints = manager.local_endpoints();
+        assert_eq!(local_endpoints.len(), 1);
+        // Every connection comes from the same local socket.
+        for _ in &leaders {
+            let server_conn = server_connections.recv().await.expect("server connection");
+            assert_eq!(
+                server_conn.remote_address().port(),
+                local_endpoints[0].port()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_first_available_leader_should_skip_unreachable_leaders() {
+        let unreachable = Pubkey::new_unique();
+        let leader2 = Pubkey::new_unique();
+        let leader3 = Pubkey::new_unique();
+        // Nothing listens there, connection attempts fail without resetting circuit breakers.
+        let addr: SocketAddr = "127.0.0.1:1".parse().unwrap();
+        let contact_info = RpcTpuQuicContactInfo {
+            tpu_quic: Some(addr),
+            tpu_forwards_quic: Some(addr),
+        };
+        let tpu_info =
+            ContactInfoTable::from_iter([(leader2, contact_info.clone()), (leader3, contact_info)]);
+        let predictor = Arc::new(TestPredictor(Mutex::new(vec![
+            unreachable,
+            leader2,
+            leader3,
+        ])));
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        let (manager, _jh) = spawn_warm_connection_manager(
+            WarmConnectionManagerConfig::default(),
+            slot_rx,
+            predictor,
+            Arc::new(tpu_info),
+        )
+        .expect("spawn");
+
+        // The first leader has no known TPU address.
+        assert_eq!(manager.first_available_leader(3), Some((leader2, addr)));
+        assert_eq!(manager.first_available_leader(1), None);
+
+        {
+            let mut table = manager.table.write().unwrap();
+            let now = Instant::now();
+            table.refresh_window(&[unreachable, leader2, leader3], now);
+            for _ in 0..DEFAULT_WARM_CONNECTION_CIRCUIT_BREAKER_FAILURE_THRESHOLD {
+                table.connected(leader2, failed_connect(), now);
+            }
+        }
+        assert!(matches!(
+            manager.circuit_breaker_state(&leader2),
+            Some(CircuitBreakerState::Open { .. })
+        ));
+        assert_eq!(manager.first_available_leader(3), Some((leader3, addr)));
+    }
+
+    #[tokio::test]
+    async fn test_should_only_warm_the_first_reachable_leaders_of_the_predicted_window() {
+        const PREDICT_N: usize = 8;
+        const WARM_N: usize = 3;
+        let (server_addr, mut server_connections) = spawn_test_server(Duration::from_secs(10));
+        let leaders = (0..PREDICT_N)
+            .map(|_| Pubkey::new_unique())
+            .collect::<Vec<_>>();
+        let unreachable = leaders[1];
+        let contact_info = RpcTpuQuicContactInfo {
+            tpu_quic: Some(server_addr),
+            tpu_forwards_quic: Some(server_addr),
+        };
+        let tpu_info = ContactInfoTable::from_iter(
+            leaders
+                .iter()
+                .filter(|leader| **leader != unreachable)
+                .map(|leader| (*leader, contact_info.clone())),
+        );
+        let config = WarmConnectionManagerConfig {
+            lookahead: NonZeroUsize::new(WARM_N).unwrap(),
+            prediction_lookahead: NonZeroUsize::new(PREDICT_N),
+            ..Default::default()
+        };
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        let (manager, _jh) = spawn_warm_connection_manager(
+            config,
+            slot_rx,
+            Arc::new(TestPredictor(Mutex::new(leaders.clone()))),
+            Arc::new(tpu_info),
+        )
+        .expect("spawn");
+
+        let warmed = [leaders[0], leaders[2], leaders[3]];
+        for leader in &warmed {
+            wait_for_state(&manager, leader, WarmConnectionState::Ready).await;
+            server_connections.recv().await.expect("server connection");
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(manager.active_connection_count(), WARM_N);
+        assert!(server_connections.try_recv().is_err());
+        assert_eq!(manager.state(&unreachable), None);
+        for leader in &leaders[WARM_N + 1..] {
+            assert_eq!(manager.state(leader), None);
+        }
+        assert_eq!(manager.predicted_window(), leaders);
+    }
+
+    struct TestStakeInfo(HashMap<Pubkey, u64>);
+
+    impl ValidatorStakeInfoService for TestStakeInfo {
+        fn get_stake_info(&self, validator_pubkey: &Pubkey) -> Option<u64> {
+            self.0.get(validator_pubkey).copied()
+        }
+    }
+
+    #[test]
+    fn test_order_warm_window_should_follow_the_warming_order() {
+        let [previous, current, next1, next2] = std::array::from_fn(|_| Pubkey::new_unique());
+        let predicted_window = [previous, current, next1, next2]
+            .into_iter()
+            .zip((4..).step_by(4))
+            .map(|(leader, first_slot)| PredictedWindowEntry {
+                predicted: PredictedLeader {
+                    leader,
+                    slots: Some(first_slot..first_slot + 4),
+                },
+                addr: None,
+                warm: true,
+            })
+            .collect::<Vec<_>>();
+        let window = vec![previous, current, next1, next2];
+        let order = |order, stake_info: Option<&TestStakeInfo>, offset| {
+            order_warm_window(
+                order,
+                9,
+                &predicted_window,
+                window.clone(),
+                stake_info
+                    .map(|stake_info| stake_info as &(dyn ValidatorStakeInfoService + Send + Sync)),
+                offset,
+            )
+        };
+
+        assert_eq!(
+            order(WarmingOrder::CurrentLeaderFirst, None, 0),
+            vec![current, next1, next2, previous]
+        );
+        assert_eq!(
+            order(WarmingOrder::RoundRobin, None, 0),
+            vec![previous, current, next1, next2]
+        );
+        assert_eq!(
+            order(WarmingOrder::RoundRobin, None, 5),
+            vec![current, next1, next2, previous]
+        );
+        let stake_info = TestStakeInfo(HashMap::from([(next2, 100), (previous, 10)]));
+        assert_eq!(
+            order(WarmingOrder::StakeWeighted, Some(&stake_info), 0),
+            vec![next2, previous, current, next1]
+        );
+        assert_eq!(
+            order(WarmingOrder::StakeWeighted, None, 0),
+            order(WarmingOrder::CurrentLeaderFirst, None, 0)
+        );
+
+        // Without slots, the predicted order is kept.
+        let predicted_window = window
+            .iter()
+            .map(|leader| PredictedWindowEntry {
+                predicted: PredictedLeader {
+                    leader: *leader,
+                    slots: None,
+                },
+                addr: None,
+                warm: true,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order_warm_window(
+                WarmingOrder::CurrentLeaderFirst,
+                9,
+                &predicted_window,
+                window.clone(),
+                None,
+                0
+            ),
+            window
+        );
+    }
+
+    #[tokio::test]
+    async fn test_should_connect_the_current_leader_first_under_the_connection_cap() {
+        let (server_addr, mut server_connections) = spawn_test_server(Duration::from_secs(10));
+        let leaders = (0..4).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let contact_info = RpcTpuQuicContactInfo {
+            tpu_quic: Some(server_addr),
+            tpu_forwards_quic: Some(server_addr),
+        };
+        let tpu_info = ContactInfoTable::from_iter(
+            leaders.iter().map(|leader| (*leader, contact_info.clone())),
+        );
+        // Slot 9 is led by leaders[2], the window starts at the look-behind leaders[1].
+        let predictor = StaticLeaderPredictor::with_slots_per_leader(leaders.clone(), 9, 4);
+        let config = WarmConnectionManagerConfig {
+            lookahead: NonZeroUsize::new(3).unwrap(),
+            max_connections: 1,
+            ..Default::default()
+        };
+        let (_slot_tx, slot_rx) = watch::channel(9);
+        let (manager, _jh) =
+            spawn_warm_connection_manager(config, slot_rx, Arc::new(predictor), Arc::new(tpu_info))
+                .expect("spawn");
+
+        wait_for_state(&manager, &leaders[2], WarmConnectionState::Ready).await;
+        server_connections.recv().await.expect("server connection");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(manager.active_connection_count(), 1);
+        assert!(server_connections.try_recv().is_err());
+        for leader in [leaders[1], leaders[3]] {
+            assert_eq!(manager.state(&leader), Some(WarmConnectionState::Failed));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_warmup_lead_slots_should_start_warming_leaders_ahead_of_their_rotation() {
+        let (server_addr, _server_connections) = spawn_test_server(Duration::from_secs(10));
+        let leaders = (0..8).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let contact_info = RpcTpuQuicContactInfo {
+            tpu_quic: Some(server_addr),
+            tpu_forwards_quic: Some(server_addr),
+        };
+        let tpu_info = ContactInfoTable::from_iter(
+            leaders.iter().map(|leader| (*leader, contact_info.clone())),
+        );
+        let predictor = Arc::new(StaticLeaderPredictor::with_slots_per_leader(
+            leaders.clone(),
+            7,
+            NUM_CONSECUTIVE_LEADER_SLOTS,
+        ));
+        let config = WarmConnectionManagerConfig {
+            lookahead: NonZeroUsize::new(2).unwrap(),
+            warmup_lead_slots: 8,
+            ..Default::default()
+        };
+        // The lead spans two more rotations, plus the current one.
+        assert_eq!(config.effective_prediction_lookahead().get(), 5);
+        let (slot_tx, slot_rx) = watch::channel(7);
+        let (manager, _jh) = spawn_warm_connection_manager(
+            config,
+            slot_rx,
+            Arc::clone(&predictor) as _,
+            Arc::new(tpu_info),
+        )
+        .expect("spawn");
+
+        // At slot 7, the window is leaders[0] and the current leaders[1], and the lead covers the rotations
+        // starting up to slot 15: leaders[2] and leaders[3].
+        for leader in &leaders[..4] {
+            wait_for_state(&manager, leader, WarmConnectionState::Ready).await;
+        }
+        // leaders[4] rotation starts at slot 16, 9 slots out.
+        assert_eq!(manager.state(&leaders[4]), None);
+
+        // 8 slots ahead of its rotation, long before it enters the first `lookahead` leaders.
+        predictor.set_slot(8);
+        slot_tx.send(8).expect("send slot");
+        wait_for_state(&manager, &leaders[4], WarmConnectionState::Ready).await;
+        assert_eq!(manager.state(&leaders[5]), None);
+    }
+
+    #[tokio::test]
+    async fn test_debug_snapshot_should_reflect_the_predicted_and_connected_pipeline() {
+        let (server_addr, _server_connections) = spawn_test_server(Duration::from_secs(10));
+        let [a, b, c, d] = std::array::from_fn(|_| Pubkey::new_unique());
+        let contact_info = RpcTpuQuicContactInfo {
+            tpu_quic: Some(server_addr),
+            tpu_forwards_quic: Some(server_addr),
+        };
+        // `d` has no known TPU address
+        let tpu_info =
+            ContactInfoTable::from_iter([a, b, c].map(|leader| (leader, contact_info.clone())));
+        let config = WarmConnectionManagerConfig {
+            lookahead: NonZeroUsize::new(2).unwrap(),
+            prediction_lookahead: NonZeroUsize::new(4),
+            ..Default::default()
+        };
+        let slot = DEFAULT_SLOTS_PER_EPOCH + 9;
+        let (_slot_tx, slot_rx) = watch::channel(slot);
+        let (manager, _jh) = spawn_warm_connection_manager(
+            config,
+            slot_rx,
+            Arc::new(StaticLeaderPredictor::new(vec![a, b, c, d], slot)),
+            Arc::new(tpu_info),
+        )
+        .expect("spawn");
+
+        wait_for_state(&manager, &b, WarmConnectionState::Ready).await;
+        wait_for_state(&manager, &c, WarmConnectionState::Ready).await;
+        let snapshot = manager.debug_snapshot();
+
+        assert_eq!(snapshot.slot, Some(slot));
+        assert_eq!(snapshot.epoch, Some(1));
+        let first_slot = DEFAULT_SLOTS_PER_EPOCH + 4;
+        let expected_window = [
+            (b, Some(server_addr), true, Some(WarmConnectionState::Ready)),
+            (c, Some(server_addr), true, Some(WarmConnectionState::Ready)),
+            (d, None, false, None),
+            (a, Some(server_addr), false, None),
+        ]
+        .into_iter()
+        .zip((first_slot..).step_by(4))
+        .map(
+            |((leader, addr, warm, state), first_slot)| PredictedLeaderSnapshot {
+                leader,
+                slots: Some(first_slot..first_slot + 4),
+                addr,
+                warm,
+                state,
+            },
+        )
+        .collect::<Vec<_>>();
+        assert_eq!(snapshot.predicted_window, expected_window);
+
+        let mut expected_connections = vec![b, c];
+        expected_connections.sort_unstable();
+        assert_eq!(
+            snapshot
+                .connections
+                .iter()
+                .map(|connection| connection.leader)
+                .collect::<Vec<_>>(),
+            expected_connections
+        );
+        for connection in &snapshot.connections {
+            assert_eq!(connection.stats.state, WarmConnectionState::Ready);
+            assert!(connection.stats.rtt.is_some());
+        }
+
+        let json = serde_json::to_value(&snapshot).expect("serialize");
+        assert_eq!(json["epoch"], 1);
+        assert_eq!(json["predicted_window"][0]["leader"], b.to_string());
+        assert_eq!(json["predicted_window"][0]["state"], "ready");
+        assert_eq!(json["predicted_window"][2]["addr"], serde_json::Value::Null);
+        assert_eq!(json["connections"][0]["circuit_breaker"], "closed");
+        assert_eq!(json["connections"][0]["origin"], "predicted");
+    }
+
+    #[tokio::test]
+    async fn test_debug_snapshot_should_follow_the_slot_tracker_epoch_schedule() {
+        // Warmup epochs double from 32 slots, slot 100 is in epoch 2 (slots 96..224).
+        let slot = 100;
+        let slot_tracker = Arc::new(AtomicSlotTracker::new(slot));
+        assert!(slot_tracker.set_epoch_schedule(EpochSchedule::custom(432_000, 432_000, true)));
+        let (manager, _jh) = WarmConnectionManager::builder(Default::default())
+            .with_slot_tracker(Arc::clone(&slot_tracker))
+            .spawn(
+                slot_tracker.subscribe(),
+                Arc::new(StaticLeaderPredictor::new(vec![Pubkey::new_unique()], slot)),
+                Arc::new(ContactInfoTable::default()),
+            )
+            .expect("spawn");
+
+        let snapshot = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let snapshot = manager.debug_snapshot();
+                if snapshot.slot.is_some() {
+                    break snapshot;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("timeout");
+        assert_eq!(snapshot.slot, Some(slot));
+        assert_eq!(snapshot.epoch, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_with_resolver_should_connect_to_resolved_addresses() {
+        let (server_addr, mut server_connections) = spawn_test_server(Duration::from_secs(10));
+        let [resolved, unresolved] = std::array::from_fn(|_| Pubkey::new_unique());
+        let resolver = StaticAddressResolver::from_iter([(
+            resolved,
+            TpuAddrs {
+                tpu_quic: None,
+                tpu_forwards_quic: Some(server_addr),
+            },
+        )]);
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        let (manager, _jh) = WarmConnectionManager::builder(Default::default())
+            .spawn_with_resolver(
+                slot_rx,
+                Arc::new(TestPredictor(Mutex::new(vec![resolved, unresolved]))),
+                resolver,
+            )
+            .expect("spawn");
+
+        wait_for_state(&manager, &resolved, WarmConnectionState::Ready).await;
+        server_connections.recv().await.expect("server connection");
+        assert_eq!(manager.state(&unresolved), None);
+        assert_eq!(
+            manager.first_available_leader(2),
+            Some((resolved, server_addr))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pinned_leader_should_stay_connected_outside_the_predicted_window() {
+        let (server_addr, mut server_connections) = spawn_test_server(Duration::from_secs(10));
+        let [predicted, pinned] = std::array::from_fn(|_| Pubkey::new_unique());
+        let contact_info = RpcTpuQuicContactInfo {
+            tpu_quic: Some(server_addr),
+            tpu_forwards_quic: Some(server_addr),
+        };
+        let tpu_info = ContactInfoTable::from_iter(
+            [predicted, pinned].map(|leader| (leader, contact_info.clone())),
+        );
+        let pin_ttl = Duration::from_millis(500);
+        let config = WarmConnectionManagerConfig {
+            lookahead: NonZeroUsize::new(1).unwrap(),
+            grace_period: Duration::from_millis(10),
+            pin_ttl,
+            ..Default::default()
+        };
+        let (slot_tx, slot_rx) = watch::channel(0);
+        let (manager, _jh) = spawn_warm_connection_manager(
+            config,
+            slot_rx,
+            Arc::new(TestPredictor(Mutex::new(vec![predicted]))),
+            Arc::new(tpu_info),
+        )
+        .expect("spawn");
+        wait_for_state(&manager, &predicted, WarmConnectionState::Ready).await;
+        server_connections.recv().await.expect("server connection");
+        assert_eq!(manager.state(&pinned), None);
+
+        // Connected right away, without waiting for a slot change.
+        let pinned_at = Instant::now();
+        manager.warm(&[pinned]);
+        wait_for_state(&manager, &pinned, WarmConnectionState::Ready).await;
+        server_connections.recv().await.expect("server connection");
+        assert_eq!(manager.pinned_leaders(), vec![pinned]);
+        assert_eq!(manager.origin(&pinned), Some(WarmConnectionOrigin::Pinned));
+        assert_eq!(
+            manager.origin(&predicted),
+            Some(WarmConnectionOrigin::Predicted)
+        );
+
+        // Never predicted, yet kept past the grace period.
+        for slot in 1..=3 {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            slot_tx.send(slot).expect("send slot");
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(pinned_at.elapsed() < pin_ttl);
+        assert_eq!(manager.state(&pinned), Some(WarmConnectionState::Ready));
+        assert_eq!(manager.active_connection_count(), 2);
+        assert!(server_connections.try_recv().is_err());
+        let snapshot = manager.debug_snapshot();
+        let origins = snapshot
+            .connections
+            .iter()
+            .map(|connection| (connection.leader, connection.origin))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(origins[&pinned], WarmConnectionOrigin::Pinned);
+        assert_eq!(origins[&predicted], WarmConnectionOrigin::Predicted);
+
+        // Once the pin expired, the grace period applies.
+        tokio::time::sleep(pin_ttl.saturating_sub(pinned_at.elapsed())).await;
+        slot_tx.send(4).expect("send slot");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        slot_tx.send(5).expect("send slot");
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while manager.state(&pinned).is_some() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("pinned connection closed");
+        assert!(manager.pinned_leaders().is_empty());
+        assert_eq!(manager.state(&predicted), Some(WarmConnectionState::Ready));
+    }
+
+    #[tokio::test]
+    async fn test_local_leader_should_never_be_connected() {
+        let (server_addr, mut server_connections) = spawn_test_server(Duration::from_secs(10));
+        let local = Pubkey::new_unique();
+        let (previous, next) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let contact_info = RpcTpuQuicContactInfo {
+            tpu_quic: Some(server_addr),
+            tpu_forwards_quic: Some(server_addr),
+        };
+        let tpu_info = ContactInfoTable::from_iter(
+            [previous, local, next].map(|leader| (leader, contact_info.clone())),
+        );
+        // Slot 5 is held by the local node, between `previous` and `next`.
+        let predictor =
+            StaticLeaderPredictor::with_slots_per_leader(vec![previous, local, next], 5, 4);
+        let config = WarmConnectionManagerConfig {
+            lookahead: NonZeroUsize::new(3).unwrap(),
+            ..Default::default()
+        };
+        #[cfg(feature = "prometheus")]
+        let skipped_before = prom::warm_connection_local_leader_skip_cnt();
+        let (_slot_tx, slot_rx) = watch::channel(5);
+        let (manager, _jh) = WarmConnectionManager::builder(config)
+            .with_local_identity(local)
+            .spawn(slot_rx, Arc::new(predictor), Arc::new(tpu_info))
+            .expect("spawn");
+        assert_eq!(manager.local_identity(), Some(local));
+
+        wait_for_state(&manager, &previous, WarmConnectionState::Ready).await;
+        wait_for_state(&manager, &next, WarmConnectionState::Ready).await;
+        manager.warm(&[local]);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // The local node stays in the predicted window, but no connection is attempted.
+        assert_eq!(manager.predicted_window(), vec![previous, local, next]);
+        assert_eq!(manager.state(&local), None);
+        assert_eq!(manager.active_connection_count(), 2);
+        for _ in 0..2 {
+            server_connections.try_recv().expect("connection");
+        }
+        assert!(server_connections.try_recv().is_err());
+        assert!(matches!(
+            manager.open_uni(&local).await,
+            Err(OpenStreamError::LocalLeader(leader)) if leader == local
+        ));
+        assert!(matches!(
+            manager.send_batch(&[vec![1]], &local).await[0],
+            Err(SendError::OpenStream(OpenStreamError::LocalLeader(_)))
+        ));
+        #[cfg(feature = "prometheus")]
+        {
+            assert!(prom::warm_connection_local_leader_skip_cnt() - skipped_before >= 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_should_record_intended_targets_without_opening_sockets() {
+        let (server_addr, mut server_connections) = spawn_test_server(Duration::from_secs(10));
+        let leaders = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let contact_info = RpcTpuQuicContactInfo {
+            tpu_quic: Some(server_addr),
+            tpu_forwards_quic: Some(server_addr),
+        };
+        let tpu_info = Arc::new(ContactInfoTable::from_iter(
+            leaders.iter().map(|leader| (*leader, contact_info.clone())),
+        ));
+        // Binding the local endpoint fails on the only port allowed, dry-run must not even try.
+        let occupied = std::net::UdpSocket::bind((std::net::Ipv4Addr::UNSPECIFIED, 0)).unwrap();
+        let port = occupied.local_addr().unwrap().port();
+        let config = WarmConnectionManagerConfig {
+            address_family_preference: AddressFamily::V4Only,
+            endpoint_port_range: (port, port + 1),
+            max_local_port_binding_attempts: 1,
+            ..Default::default()
+        };
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        assert!(matches!(
+            spawn_warm_connection_manager(
+                config.clone(),
+                slot_rx.clone(),
+                Arc::new(TestPredictor(Mutex::new(leaders.to_vec()))),
+                tpu_info.clone(),
+            ),
+            Err(WarmConnectionManagerError::EndpointBind(_))
+        ));
+
+        #[cfg(feature = "prometheus")]
+        let intended_before = prom::warm_connection_intended_target_cnt();
+        let (manager, _jh) = spawn_warm_connection_manager(
+            WarmConnectionManagerConfig {
+                dry_run: true,
+                ..config
+            },
+            slot_rx,
+            Arc::new(TestPredictor(Mutex::new(leaders.to_vec()))),
+            tpu_info,
+        )
+        .expect("dry-run spawn");
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while manager.intended_target_count() < leaders.len() as u64 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("intended targets");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(manager.intended_target_count(), leaders.len() as u64);
+        #[cfg(feature = "prometheus")]
+        {
+            assert!(
+                prom::warm_connection_intended_target_cnt() - intended_before
+                    >= leaders.len() as u64
+            );
+        }
+        assert_eq!(manager.predicted_window(), leaders);
+        assert_eq!(manager.active_connection_count(), 0);
+        assert!(server_connections.try_recv().is_err());
+        for leader in &leaders {
+            assert_eq!(manager.state(leader), Some(WarmConnectionState::Connecting));
+            assert!(matches!(
+                manager.send_batch(&[vec![1]], leader).await[0],
+                Err(SendError::OpenStream(OpenStreamError::NotConnected(_)))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_spawn_should_reject_prediction_window_shorter_than_warm_window() {
+        let config = WarmConnectionManagerConfig {
+            lookahead: NonZeroUsize::new(3).unwrap(),
+            prediction_lookahead: NonZeroUsize::new(2),
+            ..Default::default()
+        };
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        assert!(matches!(
+            spawn_warm_connection_manager(
+                config,
+                slot_rx,
+                Arc::new(TestPredictor(Mutex::new(Vec::new()))),
+                Arc::new(ContactInfoTable::default()),
+            ),
+            Err(WarmConnectionManagerError::PredictionLookaheadTooShort { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_should_queue_txs_beyond_stream_limit() {
+        const SERVER_MAX_STREAMS: u32 = 2;
+        const BATCH_SIZE: usize = 10;
+        let mut transport_config = TransportConfig::default();
+        transport_config.max_concurrent_uni_streams(SERVER_MAX_STREAMS.into());
+        let (server_addr, mut server_connections) =
+            spawn_test_server_with_transport(transport_config);
+        let leader = Pubkey::new_unique();
+        let tpu_info = ContactInfoTable::from_iter([(
+            leader,
+            RpcTpuQuicContactInfo {
+                tpu_quic: Some(server_addr),
+                tpu_forwards_quic: Some(server_addr),
+            },
+        )]);
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        let config = WarmConnectionManagerConfig {
+            max_concurrent_streams: NonZeroUsize::new(4).unwrap(),
+            ..Default::default()
+        };
+        let (manager, _jh) = spawn_warm_connection_manager(
+            config,
+            slot_rx,
+            Arc::new(TestPredictor(Mutex::new(vec![leader]))),
+            Arc::new(tpu_info),
+        )
+        .expect("spawn");
+
+        let unknown_leader = Pubkey::new_unique();
+        let results = manager.send_batch(&[b"tx".to_vec()], &unknown_leader).await;
+        assert!(matches!(
+            results.as_slice(),
+            [Err(SendError::OpenStream(OpenStreamError::NotConnected(pubkey)))] if *pubkey == unknown_leader
+        ));
+
+        wait_for_state(&manager, &leader, WarmConnectionState::Ready).await;
+        let server_conn = server_connections.recv().await.expect("server connection");
+        let reader = tokio::spawn(async move {
+            let mut received = Vec::with_capacity(BATCH_SIZE);
+            while received.len() < BATCH_SIZE {
+                let mut stream = server_conn.accept_uni().await.expect("accept uni");
+                received.push(stream.read_to_end(1232).await.expect("read"));
+            }
+            received.sort();
+            received
+        });
+
+        let txs = (0..BATCH_SIZE as u8)
+            .map(|i| vec![i; 8])
+            .collect::<Vec<_>>();
+        let results =
+            tokio::time::timeout(Duration::from_secs(5), manager.send_batch(&txs, &leader))
+                .await
+                .expect("batch completes");
+        assert_eq!(results.len(), BATCH_SIZE);
+        assert!(results.iter().all(Result::is_ok), "{results:?}");
+
+        let received = tokio::time::timeout(Duration::from_secs(5), reader)
+            .await
+            .expect("server received the batch")
+            .expect("reader");
+        assert_eq!(received, txs);
+        assert_eq!(
+            manager.stats(&leader).unwrap().stream_opens,
+            BATCH_SIZE as u64
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pins_should_expire_with_the_clock() {
+        let clock = Arc::new(MockClock::new());
+        let pin_ttl = Duration::from_secs(60);
+        let config = WarmConnectionManagerConfig {
+            pin_ttl,
+            ..Default::default()
+        };
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        let (manager, _jh) = WarmConnectionManager::builder(config)
+            .with_clock(Arc::clone(&clock) as _)
+            .spawn(
+                slot_rx,
+                Arc::new(TestPredictor(Mutex::new(vec![]))),
+                Arc::new(ContactInfoTable::default()),
+            )
+            .expect("spawn");
+        let leader = Pubkey::new_unique();
+        manager.warm(&[leader]);
+        assert_eq!(manager.pinned_leaders(), vec![leader]);
+
+        clock.advance(pin_ttl - Duration::from_millis(1));
+        assert_eq!(manager.pinned_leaders(), vec![leader]);
+        // Renewed from the current instant of the clock.
+        manager.warm(&[leader]);
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(manager.pinned_leaders(), vec![leader]);
+        clock.advance(pin_ttl);
+        assert!(manager.pinned_leaders().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_connection_events_should_follow_the_connection_lifecycle() {
+        let (server_addr, mut server_connections) = spawn_test_server(Duration::from_secs(10));
+        let leader = Pubkey::new_unique();
+        let tpu_info = ContactInfoTable::from_iter([(
+            leader,
+            RpcTpuQuicContactInfo {
+                tpu_quic: Some(server_addr),
+                tpu_forwards_quic: Some(server_addr),
+            },
+        )]);
+        let config = WarmConnectionManagerConfig {
+            grace_period: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let predictor = Arc::new(TestPredictor(Mutex::new(vec![leader])));
+        let (slot_tx, slot_rx) = watch::channel(0);
+        let (manager, _jh) = spawn_warm_connection_manager(
+            config,
+            slot_rx,
+            Arc::clone(&predictor) as _,
+            Arc::new(tpu_info),
+        )
+        .expect("spawn");
+        let mut events = manager.subscribe_connection_events();
+
+        let event = next_event(&mut events).await;
+        assert!(
+            matches!(
+                event,
+                ConnectionEvent::Established { leader: pubkey, path } if pubkey == leader && path.remote_addr == server_addr
+            ),
+            "{event:?}"
+        );
+        let server_conn = server_connections.recv().await.expect("server connection");
+
+        // Closed by the leader, observed on the next slot change.
+        server_conn.close(0u32.into(), b"bye");
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while manager.stats(&leader).expect("stats").state != WarmConnectionState::Failed {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("connection lost");
+        slot_tx.send(1).expect("send slot");
+        let event = next_event(&mut events).await;
+        assert!(
+            matches!(
+                event,
+                ConnectionEvent::Lost {
+                    leader: pubkey,
+                    reason: ConnectionError::ApplicationClosed(_),
+                } if pubkey == leader
+            ),
+            "{event:?}"
+        );
+        // Still predicted, reconnected right away.
+        let event = next_event(&mut events).await;
+        assert!(
+            matches!(event, ConnectionEvent::Established { leader: pubkey, .. } if pubkey == leader),
+            "{event:?}"
+        );
+        server_connections.recv().await.expect("server connection");
+
+        // Out of the window past the grace period.
+        predictor.0.lock().unwrap().clear();
+        slot_tx.send(2).expect("send slot");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        slot_tx.send(3).expect("send slot");
+        assert_eq!(
+            next_event(&mut events).await,
+            ConnectionEvent::Closed {
+                leader,
+                cause: ConnectionCloseCause::OutOfWindow,
+            }
+        );
+        assert_eq!(manager.state(&leader), None);
+    }
+
+    #[tokio::test]
+    async fn test_sends_beyond_the_per_leader_limit_should_wait_for_a_free_slot() {
+        const MAX_STREAMS_PER_LEADER: usize = 2;
+        const MAX_QUEUED: usize = 3;
+        let (server_addr, mut server_connections) = spawn_test_server(Duration::from_secs(30));
+        let leader = Pubkey::new_unique();
+        let tpu_info = ContactInfoTable::from_iter([(
+            leader,
+            RpcTpuQuicContactInfo {
+                tpu_quic: Some(server_addr),
+                tpu_forwards_quic: Some(server_addr),
+            },
+        )]);
+        let (_slot_tx, slot_rx) = watch::channel(0);
+        let config = WarmConnectionManagerConfig {
+            max_streams_per_leader: NonZeroUsize::new(MAX_STREAMS_PER_LEADER),
+            max_queued_sends_per_leader: MAX_QUEUED,
+            ..Default::default()
+        };
+        let (manager, _jh) = spawn_warm_connection_manager(
+            config,
+            slot_rx,
+            Arc::new(TestPredictor(Mutex::new(vec![leader]))),
+            Arc::new(tpu_info),
+        )
+        .expect("spawn");
+        let manager = Arc::new(manager);
+        wait_for_state(&manager, &leader, WarmConnectionState::Ready).await;
+        let server_conn = server_connections.recv().await.expect("server connection");
+        let reader = tokio::spawn(async move {
+            let mut received = Vec::new();
+            while received.len() < MAX_QUEUED {
+                let mut stream = server_conn.accept_uni().await.expect("accept uni");
+                received.push(stream.read_to_end(1232).await.expect("read"));
+            }
+            received.sort();
+            received
+        });
+
+        // Hold every send slot, the sends must queue.
+        let mut permits = Vec::new();
+        for _ in 0..MAX_STREAMS_PER_LEADER {
+            permits.push(manager.acquire_send_permit(&leader).await.expect("permit"));
+        }
+        assert_eq!(manager.in_flight_sends(&leader), MAX_STREAMS_PER_LEADER);
+        let mut sends = (0..MAX_QUEUED as u8)
+            .map(|i| {
+                let manager = Arc::clone(&manager);
+                async move { manager.send(&leader, &[i; 8]).await }
+            })
+            .collect::<JoinSet<_>>();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(sends.try_join_next().is_none(), "sends did not wait");
+        assert_eq!(manager.in_flight_sends(&leader), MAX_STREAMS_PER_LEADER);
+
+        // The queue is full.
+        assert!(matches!(
+            manager.send(&leader, b"tx").await,
+            Err(SendError::LeaderBusy(pubkey)) if pubkey == leader
+        ));
+
+        // Releasing a slot lets the queued sends through, one at a time.
+        permits.pop();
+        let results = tokio::time::timeout(Duration::from_secs(5), sends.join_all())
+            .await
+            .expect("queued sends complete");
+        assert!(results.iter().all(Result::is_ok), "{results:?}");
+        let received = tokio::time::timeout(Duration::from_secs(5), reader)
+            .await
+            .expect("server received the sends")
+            .expect("reader");
+        assert_eq!(
+            received,
+            (0..MAX_QUEUED as u8)
+                .map(|i| vec![i; 8])
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(manager.in_flight_sends(&leader), 1);
+        permits.clear();
+        assert_eq!(manager.in_flight_sends(&leader), 0);
+    }
+}
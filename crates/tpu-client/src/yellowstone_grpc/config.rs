@@ -0,0 +1,424 @@
+//!
+//! File-based configuration of the leader predictor, warm connections and gRPC endpoints.
+//!
+//! [`Config`] gathers the operator tunables in a single deserializable struct, unknown fields are rejected to catch typos.
+//! Use [`Config::from_file`] to load and validate a YAML (or JSON) file, then [`Config::try_into_components`] to get
+//! the per-subsystem configuration objects. [`DEFAULT_CONFIG_YAML`] documents every field with its default value.
+//!
+use {
+    crate::{
+        rpc::schedule::ManagedLeaderSchedule,
+        slot::AtomicSlotTracker,
+        warm_connection::{
+            DEFAULT_WARM_CONNECTION_KEEP_ALIVE_INTERVAL, DEFAULT_WARM_CONNECTION_LOOKAHEAD,
+            DEFAULT_WARM_CONNECTION_MAX_CONNECTIONS, DEFAULT_WARM_CONNECTION_MAX_IDLE_TIMEOUT,
+            WarmConnectionManagerConfig,
+        },
+        yellowstone_grpc::{
+            failover::{GrpcEndpoint, GrpcEndpointSet},
+            schedule::{DEFAULT_LOOK_BEHIND, YellowstoneUpcomingLeaderBuilder},
+        },
+    },
+    serde::Deserialize,
+    solana_clock::{DEFAULT_SLOTS_PER_EPOCH, NUM_CONSECUTIVE_LEADER_SLOTS},
+    std::{io, num::NonZeroUsize, path::Path, sync::Arc, time::Duration},
+};
+
+///
+/// Default gRPC endpoint of a local Yellowstone plugin.
+///
+pub const DEFAULT_GRPC_ENDPOINT: &str = "http://127.0.0.1:10000";
+
+///
+/// Default configuration, equivalent to [`Config::default`].
+///
+pub const DEFAULT_CONFIG_YAML: &str = r#"
+# Number of consecutive slots assigned to a leader, must be in [1, slots per epoch].
+slots_per_leader: 4
+# Number of previous leaders included in the prediction window, must be lower than `lookahead`.
+look_behind: 1
+# Number of leaders kept warm at once (alias: `n`).
+lookahead: 5
+# Number of leaders predicted at once, must not be lower than `lookahead`, `null` predicts `lookahead` leaders.
+prediction_lookahead: null
+# Maximum number of warm connections open or being opened at once.
+max_connections: 64
+# Interval between QUIC keep-alive PING frames, must be shorter than `max_idle_timeout`.
+keep_alive_interval: 5s
+# Maximum idle timeout of warm QUIC connections.
+max_idle_timeout: 30s
+# Maximum time since the last slot update before predictions are refused, e.g. `10s`, `null` disables the check.
+max_slot_age: null
+grpc:
+  # Yellowstone gRPC endpoints in order of preference, at least one is required.
+  endpoints:
+    - url: http://127.0.0.1:10000
+      # Compression of the streamed messages: none, gzip or zstd.
+      compression: none
+  max_failures_before_failover: 3
+  primary_recheck_interval: 30s
+"#;
+
+///
+/// Leader predictor, warm connection and gRPC endpoints configuration.
+///
+/// See [`DEFAULT_CONFIG_YAML`] for the documented defaults.
+///
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    ///
+    /// Number of consecutive slots assigned to a leader, see [`crate::yellowstone_grpc::schedule::YellowstoneUpcomingLeader::slots_per_leader`].
+    ///
+    #[serde(default = "Config::default_slots_per_leader")]
+    pub slots_per_leader: u64,
+    ///
+    /// Number of previous leaders included in the prediction window.
+    ///
+    #[serde(default = "Config::default_look_behind")]
+    pub look_behind: usize,
+    ///
+    /// Number of leaders kept warm at once.
+    ///
+    #[serde(default = "Config::default_lookahead", alias = "n")]
+    pub lookahead: NonZeroUsize,
+    ///
+    /// Number of leaders predicted at once, see [`WarmConnectionManagerConfig::prediction_lookahead`].
+    ///
+    #[serde(default)]
+    pub prediction_lookahead: Option<NonZeroUsize>,
+    ///
+    /// Maximum number of warm connections open or being opened at once.
+    ///
+    #[serde(default = "Config::default_max_connections")]
+    pub max_connections: usize,
+    ///
+    /// Interval between QUIC keep-alive PING frames.
+    ///
+    #[serde(
+        default = "Config::default_keep_alive_interval",
+        with = "humantime_serde"
+    )]
+    pub keep_alive_interval: Duration,
+    ///
+    /// Maximum idle timeout of warm QUIC connections.
+    ///
+    #[serde(default = "Config::default_max_idle_timeout", with = "humantime_serde")]
+    pub max_idle_timeout: Duration,
+    ///
+    /// Maximum time since the last slot update before predictions are refused, `None` disables the check.
+    ///
+    #[serde(default = "Config::default_max_slot_age", with = "humantime_serde")]
+    pub max_slot_age: Option<Duration>,
+    ///
+    /// Yellowstone gRPC endpoints in order of preference.
+    ///
+    pub grpc: GrpcEndpointSet,
+}
+
+impl Config {
+    pub const fn default_slots_per_leader() -> u64 {
+        NUM_CONSECUTIVE_LEADER_SLOTS
+    }
+
+    pub const fn default_look_behind() -> usize {
+        DEFAULT_LOOK_BEHIND
+    }
+
+    pub const fn default_lookahead() -> NonZeroUsize {
+        DEFAULT_WARM_CONNECTION_LOOKAHEAD
+    }
+
+    pub const fn default_max_connections() -> usize {
+        DEFAULT_WARM_CONNECTION_MAX_CONNECTIONS
+    }
+
+    pub const fn default_keep_alive_interval() -> Duration {
+        DEFAULT_WARM_CONNECTION_KEEP_ALIVE_INTERVAL
+    }
+
+    pub const fn default_max_idle_timeout() -> Duration {
+        DEFAULT_WARM_CONNECTION_MAX_IDLE_TIMEOUT
+    }
+
+    pub const fn default_max_slot_age() -> Option<Duration> {
+        None
+    }
+
+    ///
+    /// Loads and validates a YAML configuration file, JSON files are accepted as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError`] if the file cannot be read, does not match [`Config`] or fails [`Config::validate`].
+    ///
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_yaml_str(&content)
+    }
+
+    ///
+    /// Parses and validates a YAML, or JSON, configuration.
+    ///
+    pub fn from_yaml_str(content: &str) -> Result<Self, ConfigError> {
+        let config: Self = serde_yaml::from_str(content)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    ///
+    /// Checks that the tunables are within their valid range.
+    ///
+    pub fn validate(&self) -> Result<(), InvalidConfigError> {
+        if !(1..=DEFAULT_SLOTS_PER_EPOCH).contains(&self.slots_per_leader) {
+            return Err(InvalidConfigError::SlotsPerLeaderOutOfRange(
+                self.slots_per_leader,
+            ));
+        }
+        if self.look_behind >= self.lookahead.get() {
+            return Err(InvalidConfigError::LookBehindTooLarge {
+                look_behind: self.look_behind,
+                lookahead: self.lookahead,
+            });
+        }
+        if let Some(prediction_lookahead) = self
+            .prediction_lookahead
+            .filter(|prediction_lookahead| *prediction_lookahead < self.lookahead)
+        {
+            return Err(InvalidConfigError::PredictionLookaheadTooShort {
+                prediction_lookahead,
+                lookahead: self.lookahead,
+            });
+        }
+        if self.max_connections == 0 {
+            return Err(InvalidConfigError::ZeroMaxConnections);
+        }
+        if self.keep_alive_interval >= self.max_idle_timeout {
+            return Err(InvalidConfigError::KeepAliveNotShorterThanIdleTimeout {
+                keep_alive_interval: self.keep_alive_interval,
+                max_idle_timeout: self.max_idle_timeout,
+            });
+        }
+        if self.grpc.endpoints.is_empty() {
+            return Err(InvalidConfigError::NoGrpcEndpoint);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Validates the configuration and splits it into the configuration of each subsystem.
+    ///
+    pub fn try_into_components(self) -> Result<ConfigComponents, InvalidConfigError> {
+        self.validate()?;
+        let warm_connection = WarmConnectionManagerConfig {
+            lookahead: self.lookahead,
+            prediction_lookahead: self.prediction_lookahead,
+            max_connections: self.max_connections,
+            keep_alive_interval: self.keep_alive_interval,
+            max_idle_timeout: self.max_idle_timeout,
+            ..Default::default()
+        };
+        Ok(ConfigComponents {
+            slots_per_leader: self.slots_per_leader,
+            look_behind: self.look_behind,
+            max_slot_age: self.max_slot_age,
+            warm_connection,
+            grpc: self.grpc,
+        })
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            slots_per_leader: Self::default_slots_per_leader(),
+            look_behind: Self::default_look_behind(),
+            lookahead: Self::default_lookahead(),
+            prediction_lookahead: None,
+            max_connections: Self::default_max_connections(),
+            keep_alive_interval: Self::default_keep_alive_interval(),
+            max_idle_timeout: Self::default_max_idle_timeout(),
+            max_slot_age: Self::default_max_slot_age(),
+            grpc: GrpcEndpointSet::new(vec![GrpcEndpoint {
+                url: DEFAULT_GRPC_ENDPOINT.to_string(),
+                x_token: None,
+                compression: Default::default(),
+            }]),
+        }
+    }
+}
+
+///
+/// Validated configuration of each subsystem, see [`Config::try_into_components`].
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigComponents {
+    pub slots_per_leader: u64,
+    pub look_behind: usize,
+    pub max_slot_age: Option<Duration>,
+    pub warm_connection: WarmConnectionManagerConfig,
+    pub grpc: GrpcEndpointSet,
+}
+
+impl ConfigComponents {
+    ///
+    /// Returns a [`YellowstoneUpcomingLeaderBuilder`] with the configured predictor options.
+    ///
+    pub fn upcoming_leader_builder(
+        &self,
+        slot_tracker: Arc<AtomicSlotTracker>,
+        managed_schedule: ManagedLeaderSchedule,
+    ) -> YellowstoneUpcomingLeaderBuilder {
+        YellowstoneUpcomingLeaderBuilder::new(slot_tracker, managed_schedule)
+            .slots_per_leader(self.slots_per_leader)
+            .look_behind(self.look_behind)
+            .max_slot_age(self.max_slot_age)
+    }
+}
+
+///
+/// Out-of-range tunable, see [`Config::validate`].
+///
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidConfigError {
+    #[error("slots_per_leader must be in [1, {DEFAULT_SLOTS_PER_EPOCH}], got {0}")]
+    SlotsPerLeaderOutOfRange(u64),
+    #[error("look_behind ({look_behind}) must be lower than lookahead ({lookahead})")]
+    LookBehindTooLarge {
+        look_behind: usize,
+        lookahead: NonZeroUsize,
+    },
+    #[error(
+        "prediction_lookahead ({prediction_lookahead}) must not be lower than lookahead ({lookahead})"
+    )]
+    PredictionLookaheadTooShort {
+        prediction_lookahead: NonZeroUsize,
+        lookahead: NonZeroUsize,
+    },
+    #[error("max_connections must be greater than zero")]
+    ZeroMaxConnections,
+    #[error(
+        "keep_alive_interval ({keep_alive_interval:?}) must be shorter than max_idle_timeout ({max_idle_timeout:?})"
+    )]
+    KeepAliveNotShorterThanIdleTimeout {
+        keep_alive_interval: Duration,
+        max_idle_timeout: Duration,
+    },
+    #[error("at least one gRPC endpoint is required")]
+    NoGrpcEndpoint,
+}
+
+///
+/// Error raised by [`Config::from_file`].
+///
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("failed to read config file: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to parse config: {0}")]
+    Parse(#[from] serde_yaml::Error),
+    #[error(transparent)]
+    Invalid(#[from] InvalidConfigError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_match_documented_default_config() {
+        let config = Config::from_yaml_str(DEFAULT_CONFIG_YAML).expect("default config");
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn it_should_load_yaml_and_json_config() {
+        let json = r#"{
+            "slots_per_leader": 2,
+            "n": 8,
+            "prediction_lookahead": 12,
+            "max_slot_age": "5s",
+            "grpc": {
+                "endpoints": [
+                    {"url": "https://primary.example.com", "x_token": "secret"},
+                    {"url": "https://secondary.example.com"}
+                ],
+                "primary_recheck_interval": "1m"
+            }
+        }"#;
+        let path =
+            std::env::temp_dir().join(format!("tpu-client-config-{}.json", std::process::id()));
+        std::fs::write(&path, json).expect("write config");
+        let from_json = Config::from_file(&path).expect("json config");
+        std::fs::remove_file(&path).expect("remove config");
+
+        let yaml = r#"
+        slots_per_leader: 2
+        lookahead: 8
+        prediction_lookahead: 12
+        max_slot_age: 5s
+        grpc:
+            endpoints:
+                - url: https://primary.example.com
+                  x_token: secret
+                - url: https://secondary.example.com
+            primary_recheck_interval: 1m
+        "#;
+        let from_yaml = Config::from_yaml_str(yaml).expect("yaml config");
+        assert_eq!(from_json, from_yaml);
+
+        let components = from_yaml.try_into_components().expect("components");
+        assert_eq!(components.slots_per_leader, 2);
+        assert_eq!(components.max_slot_age, Some(Duration::from_secs(5)));
+        assert_eq!(components.warm_connection.lookahead.get(), 8);
+        assert_eq!(
+            components.warm_connection.prediction_lookahead,
+            NonZeroUsize::new(12)
+        );
+        assert_eq!(components.grpc.endpoints.len(), 2);
+        assert_eq!(
+            components.grpc.primary_recheck_interval,
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn it_should_reject_out_of_range_slots_per_leader() {
+        for slots_per_leader in [0, DEFAULT_SLOTS_PER_EPOCH + 1] {
+            let config = Config {
+                slots_per_leader,
+                ..Default::default()
+            };
+            assert_eq!(
+                config.try_into_components(),
+                Err(InvalidConfigError::SlotsPerLeaderOutOfRange(
+                    slots_per_leader
+                ))
+            );
+        }
+        let yaml = r#"
+        slots_per_leader: 0
+        grpc:
+            endpoints: [{url: "http://127.0.0.1:10000"}]
+        "#;
+        assert!(matches!(
+            Config::from_yaml_str(yaml),
+            Err(ConfigError::Invalid(
+                InvalidConfigError::SlotsPerLeaderOutOfRange(0)
+            ))
+        ));
+    }
+
+    #[test]
+    fn it_should_reject_unknown_fields() {
+        let yaml = r#"
+        slot_per_leader: 4
+        grpc:
+            endpoints: [{url: "http://127.0.0.1:10000"}]
+        "#;
+        assert!(matches!(
+            Config::from_yaml_str(yaml),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,439 @@
+//!
+//! Yellowstone gRPC connection configuration.
+//!
+//! Hosted Yellowstone providers authenticate clients with an `x-token` header.
+//! [`connect_geyser_client`] attaches the configured token to every outgoing request through [`XTokenInterceptor`],
+//! and enables TLS for `https://` endpoints only.
+//!
+//! The token is redacted from the [`Debug`] output of every type holding it, so connection configs can be logged safely.
+//!
+//! Response compression is opt-in with [`GrpcConnectionConfig::compression`], see [`GrpcCompression`].
+//!
+use {
+    serde::Deserialize,
+    std::fmt,
+    tonic_health::pb::health_client::HealthClient,
+    yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcClient, Interceptor},
+    yellowstone_grpc_proto::{
+        geyser::{GetVersionRequest, geyser_client::GeyserClient},
+        tonic::{
+            Request, Status,
+            body::Body as TonicBody,
+            client::GrpcService,
+            codec::CompressionEncoding,
+            codegen::{Body, Bytes, StdError},
+            metadata::{AsciiMetadataValue, errors::InvalidMetadataValue},
+            transport::{self, Endpoint},
+        },
+    },
+};
+
+///
+/// Metadata key of the authentication token.
+///
+pub const X_TOKEN_HEADER: &str = "x-token";
+
+///
+/// Metadata key of the encoding a gRPC message is compressed with.
+///
+pub const GRPC_ENCODING_HEADER: &str = "grpc-encoding";
+
+const REDACTED: &str = "<redacted>";
+
+///
+/// Authentication token of a Yellowstone gRPC endpoint.
+///
+/// The token is redacted from the [`Debug`] output.
+///
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct XToken(String);
+
+impl XToken {
+    ///
+    /// Returns the token in clear.
+    ///
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for XToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("XToken").field(&REDACTED).finish()
+    }
+}
+
+impl From<String> for XToken {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for XToken {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+///
+/// Compression of the messages streamed by a Yellowstone gRPC endpoint.
+///
+/// The client advertises the encodings it accepts and the server picks one it supports, falling back to
+/// uncompressed messages. Requests are never compressed: they are small, and a server without the codec would
+/// reject them.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GrpcCompression {
+    ///
+    /// Uncompressed messages, the default.
+    ///
+    #[default]
+    None,
+    ///
+    /// Accept gzip compressed messages.
+    ///
+    Gzip,
+    ///
+    /// Accept zstd compressed messages, or gzip if the server does not support zstd.
+    ///
+    Zstd,
+}
+
+impl GrpcCompression {
+    ///
+    /// Encodings advertised to the server, in order of preference.
+    ///
+    pub const fn accepted_encodings(self) -> &'static [CompressionEncoding] {
+        match self {
+            Self::None => &[],
+            Self::Gzip => &[CompressionEncoding::Gzip],
+            Self::Zstd => &[CompressionEncoding::Zstd, CompressionEncoding::Gzip],
+        }
+    }
+
+    ///
+    /// Returns `true` if at least one compression encoding is accepted.
+    ///
+    pub const fn is_enabled(self) -> bool {
+        !matches!(self, Self::None)
+    }
+}
+
+///
+/// Configuration of a Yellowstone gRPC connection.
+///
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GrpcConnectionConfig {
+    ///
+    /// gRPC endpoint URL, TLS is enabled for `https://` endpoints.
+    ///
+    pub endpoint: String,
+    ///
+    /// Optional token sent in the `x-token` header of every request.
+    ///
+    #[serde(default)]
+    pub x_token: Option<XToken>,
+    ///
+    /// Compression of the streamed messages, disabled by default.
+    ///
+    #[serde(default)]
+    pub compression: GrpcCompression,
+}
+
+impl GrpcConnectionConfig {
+    pub fn new(endpoint: impl Into<String>, x_token: Option<impl Into<XToken>>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            x_token: x_token.map(Into::into),
+            compression: GrpcCompression::None,
+        }
+    }
+
+    ///
+    /// Sets [`GrpcConnectionConfig::compression`].
+    ///
+    pub const fn with_compression(mut self, compression: GrpcCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+}
+
+///
+/// Error raised when connecting to a Yellowstone gRPC endpoint.
+///
+#[derive(Debug, thiserror::Error)]
+pub enum GrpcConnectError {
+    ///
+    /// The endpoint URL could not be parsed.
+    ///
+    #[error("invalid gRPC endpoint: {0}")]
+    InvalidEndpoint(#[source] transport::Error),
+    ///
+    /// The token is not a valid ASCII metadata value.
+    ///
+    #[error("invalid x-token")]
+    InvalidXToken(#[from] InvalidMetadataValue),
+    ///
+    /// TLS could not be configured.
+    ///
+    #[error("failed to configure TLS: {0}")]
+    Tls(#[source] transport::Error),
+    ///
+    /// The connection to the endpoint failed.
+    ///
+    #[error(transparent)]
+    Transport(transport::Error),
+}
+
+///
+/// Interceptor attaching the `x-token` header to every outgoing request.
+///
+#[derive(Clone, Default)]
+pub struct XTokenInterceptor {
+    x_token: Option<AsciiMetadataValue>,
+}
+
+impl XTokenInterceptor {
+    pub fn new(x_token: Option<&XToken>) -> Result<Self, InvalidMetadataValue> {
+        let x_token = x_token
+            .map(|x_token| x_token.expose().parse())
+            .transpose()?;
+        Ok(Self { x_token })
+    }
+}
+
+impl fmt::Debug for XTokenInterceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XTokenInterceptor")
+            .field("x_token", &self.x_token.as_ref().map(|_| REDACTED))
+            .finish()
+    }
+}
+
+impl Interceptor for XTokenInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(x_token) = &self.x_token {
+            request
+                .metadata_mut()
+                .insert(X_TOKEN_HEADER, x_token.clone());
+        }
+        Ok(request)
+    }
+}
+
+///
+/// Returns the [`Endpoint`] for `config`, with TLS enabled for `https://` URLs.
+///
+fn grpc_endpoint(config: &GrpcConnectionConfig) -> Result<Endpoint, GrpcConnectError> {
+    let endpoint = Endpoint::from_shared(config.endpoint.clone())
+        .map_err(GrpcConnectError::InvalidEndpoint)?;
+    if endpoint.uri().scheme_str() == Some("https") {
+        endpoint
+            .tls_config(ClientTlsConfig::new().with_enabled_roots())
+            .map_err(GrpcConnectError::Tls)
+    } else {
+        Ok(endpoint)
+    }
+}
+
+fn geyser_client_from_channel(
+    channel: transport::Channel,
+    interceptor: XTokenInterceptor,
+    compression: GrpcCompression,
+) -> GeyserGrpcClient<XTokenInterceptor> {
+    GeyserGrpcClient::new(
+        HealthClient::with_interceptor(channel.clone(), interceptor.clone()),
+        accept_compressed(
+            GeyserClient::with_interceptor(channel, interceptor),
+            compression,
+        ),
+    )
+}
+
+///
+/// Advertises the encodings accepted with `compression` on every request of `client`.
+///
+fn accept_compressed<T>(
+    mut client: GeyserClient<T>,
+    compression: GrpcCompression,
+) -> GeyserClient<T>
+where
+    T: GrpcService<TonicBody>,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    for encoding in compression.accepted_encodings() {
+        client = client.accept_compressed(*encoding);
+    }
+    client
+}
+
+///
+/// Returns the encoding the server compresses its messages with, `None` if uncompressed.
+///
+async fn negotiated_encoding<T>(client: &mut GeyserClient<T>) -> Result<Option<String>, Status>
+where
+    T: GrpcService<TonicBody>,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    let response = client.get_version(GetVersionRequest {}).await?;
+    Ok(response
+        .metadata()
+        .get(GRPC_ENCODING_HEADER)
+        .and_then(|encoding| encoding.to_str().ok())
+        .map(str::to_string))
+}
+
+///
+/// Connects to the Yellowstone gRPC endpoint described by `config`.
+///
+pub async fn connect_geyser_client(
+    config: &GrpcConnectionConfig,
+) -> Result<GeyserGrpcClient<XTokenInterceptor>, GrpcConnectError> {
+    let interceptor = XTokenInterceptor::new(config.x_token.as_ref())?;
+    let endpoint = grpc_endpoint(config)?;
+    tracing::debug!("connecting to gRPC endpoint {config:?}");
+    let channel = endpoint
+        .connect()
+        .await
+        .map_err(GrpcConnectError::Transport)?;
+    let mut client = geyser_client_from_channel(channel, interceptor, config.compression);
+    if config.compression.is_enabled() {
+        match negotiated_encoding(&mut client.geyser).await {
+            Ok(encoding) => tracing::info!(
+                "gRPC endpoint {} negotiated {} compression",
+                config.endpoint,
+                encoding.as_deref().unwrap_or("no")
+            ),
+            Err(e) => tracing::warn!(
+                "failed to check the compression negotiated with gRPC endpoint {}: {e}",
+                config.endpoint
+            ),
+        }
+    }
+    Ok(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{
+            convert::Infallible,
+            sync::{Arc, Mutex},
+        },
+        yellowstone_grpc_proto::tonic::codegen::http,
+    };
+
+    #[tokio::test]
+    async fn it_should_inject_x_token_header() {
+        let config = GrpcConnectionConfig::new("http://127.0.0.1:10000", Some("my-secret"));
+        let interceptor = XTokenInterceptor::new(config.x_token.as_ref()).expect("interceptor");
+        let channel = grpc_endpoint(&config).expect("endpoint").connect_lazy();
+        let _client =
+            geyser_client_from_channel(channel, interceptor.clone(), GrpcCompression::None);
+
+        let request = interceptor
+            .clone()
+            .call(Request::new(()))
+            .expect("intercepted request");
+        assert_eq!(
+            request.metadata().get(X_TOKEN_HEADER).expect("x-token"),
+            "my-secret"
+        );
+
+        let request = XTokenInterceptor::default()
+            .call(Request::new(()))
+            .expect("intercepted request");
+        assert!(request.metadata().get(X_TOKEN_HEADER).is_none());
+    }
+
+    ///
+    /// Returns the headers of the first request sent through a mock service answering every request with an error.
+    ///
+    async fn captured_request_headers(compression: GrpcCompression) -> http::HeaderMap {
+        let captured = Arc::new(Mutex::new(None));
+        let service_captured = Arc::clone(&captured);
+        let service = tower::service_fn(move |request: http::Request<TonicBody>| {
+            service_captured
+                .lock()
+                .expect("captured lock")
+                .get_or_insert_with(|| request.headers().clone());
+            async move { Ok::<_, Infallible>(Status::unimplemented("mock").into_http::<TonicBody>()) }
+        });
+        let mut client = accept_compressed(GeyserClient::new(service), compression);
+        assert!(negotiated_encoding(&mut client).await.is_err());
+        captured
+            .lock()
+            .expect("captured lock")
+            .take()
+            .expect("request sent")
+    }
+
+    #[tokio::test]
+    async fn it_should_advertise_accepted_encodings() {
+        let headers = captured_request_headers(GrpcCompression::None).await;
+        assert!(headers.get("grpc-accept-encoding").is_none());
+
+        let headers = captured_request_headers(GrpcCompression::Gzip).await;
+        assert_eq!(
+            headers
+                .get("grpc-accept-encoding")
+                .expect("accept encoding"),
+            "gzip"
+        );
+        assert!(headers.get(GRPC_ENCODING_HEADER).is_none());
+
+        let headers = captured_request_headers(GrpcCompression::Zstd).await;
+        let accepted = headers
+            .get("grpc-accept-encoding")
+            .expect("accept encoding")
+            .to_str()
+            .expect("ascii");
+        assert!(
+            accepted.contains("zstd") && accepted.contains("gzip"),
+            "{accepted}"
+        );
+        assert!(headers.get(GRPC_ENCODING_HEADER).is_none());
+    }
+
+    #[test]
+    fn it_should_deserialize_compression() {
+        let config: GrpcConnectionConfig = serde_json::from_str(
+            r#"{"endpoint": "http://127.0.0.1:10000", "compression": "zstd"}"#,
+        )
+        .expect("config");
+        assert_eq!(config.compression, GrpcCompression::Zstd);
+
+        let config: GrpcConnectionConfig =
+            serde_json::from_str(r#"{"endpoint": "http://127.0.0.1:10000"}"#).expect("config");
+        assert_eq!(config.compression, GrpcCompression::None);
+        assert!(!config.compression.is_enabled());
+    }
+
+    #[test]
+    fn it_should_redact_x_token() {
+        let config = GrpcConnectionConfig::new("https://example.com", Some("my-secret"));
+        let interceptor = XTokenInterceptor::new(config.x_token.as_ref()).expect("interceptor");
+
+        for debug in [format!("{config:?}"), format!("{interceptor:?}")] {
+            assert!(!debug.contains("my-secret"), "{debug}");
+            assert!(debug.contains(REDACTED), "{debug}");
+        }
+    }
+
+    #[test]
+    fn it_should_reject_invalid_endpoint_or_token() {
+        let config = GrpcConnectionConfig::new("not a url", None::<XToken>);
+        assert!(matches!(
+            grpc_endpoint(&config),
+            Err(GrpcConnectError::InvalidEndpoint(_))
+        ));
+        assert!(XTokenInterceptor::new(Some(&XToken::from("bad\ntoken"))).is_err());
+    }
+}
@@ -0,0 +1,535 @@
+#[cfg(feature = "prometheus")]
+use crate::prom;
+use {
+    crate::yellowstone_grpc::{
+        connection::{GrpcCompression, GrpcConnectionConfig, XToken, connect_geyser_client},
+        slot_tracker::{GeyserSubscribeError, GeyserSubscriber, SlotUpdateStream},
+    },
+    futures::future::BoxFuture,
+    serde::Deserialize,
+    std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    },
+    yellowstone_grpc_proto::geyser::SubscribeRequest,
+};
+
+pub const DEFAULT_GRPC_MAX_FAILURES_BEFORE_FAILOVER: usize = 3;
+pub const DEFAULT_GRPC_PRIMARY_RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+///
+/// A Yellowstone gRPC endpoint.
+///
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct GrpcEndpoint {
+    ///
+    /// gRPC endpoint URL, TLS is enabled for `https://` endpoints.
+    ///
+    pub url: String,
+    ///
+    /// Optional X-Token for authentication.
+    ///
+    #[serde(default)]
+    pub x_token: Option<XToken>,
+    ///
+    /// Compression of the streamed messages, disabled by default.
+    ///
+    #[serde(default)]
+    pub compression: GrpcCompression,
+}
+
+///
+/// Ordered set of Yellowstone gRPC endpoints, the first one is the primary.
+///
+/// The subscription fails over to the next endpoint after [`GrpcEndpointSet::max_failures_before_failover`]
+/// consecutive connection errors, and switches back to the primary once it recovers.
+///
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct GrpcEndpointSet {
+    ///
+    /// Endpoints in order of preference.
+    ///
+    pub endpoints: Vec<GrpcEndpoint>,
+
+    ///
+    /// Number of consecutive connection errors before failing over to the next endpoint.
+    ///
+    #[serde(default = "GrpcEndpointSet::default_max_failures_before_failover")]
+    pub max_failures_before_failover: usize,
+
+    ///
+    /// How often the primary endpoint is probed while another endpoint is in use.
+    ///
+    #[serde(
+        default = "GrpcEndpointSet::default_primary_recheck_interval",
+        with = "humantime_serde"
+    )]
+    pub primary_recheck_interval: Duration,
+}
+
+impl GrpcEndpointSet {
+    pub const fn default_max_failures_before_failover() -> usize {
+        DEFAULT_GRPC_MAX_FAILURES_BEFORE_FAILOVER
+    }
+
+    pub const fn default_primary_recheck_interval() -> Duration {
+        DEFAULT_GRPC_PRIMARY_RECHECK_INTERVAL
+    }
+
+    ///
+    /// Creates a set from endpoints in order of preference, with default failover settings.
+    ///
+    pub fn new(endpoints: Vec<GrpcEndpoint>) -> Self {
+        Self {
+            endpoints,
+            max_failures_before_failover: Self::default_max_failures_before_failover(),
+            primary_recheck_interval: Self::default_primary_recheck_interval(),
+        }
+    }
+}
+
+///
+/// Reports which endpoint of a [`GrpcEndpointSet`] is currently in use.
+///
+#[derive(Debug, Clone)]
+pub struct ActiveGrpcEndpoint {
+    urls: Arc<[String]>,
+    index: Arc<AtomicUsize>,
+}
+
+impl ActiveGrpcEndpoint {
+    ///
+    /// Index of the active endpoint in [`GrpcEndpointSet::endpoints`], `0` is the primary.
+    ///
+    pub fn index(&self) -> usize {
+        self.index.load(Ordering::Relaxed)
+    }
+
+    ///
+    /// URL of the active endpoint.
+    ///
+    pub fn url(&self) -> &str {
+        &self.urls[self.index()]
+    }
+
+    ///
+    /// Returns `true` if the primary endpoint is in use.
+    ///
+    pub fn is_primary(&self) -> bool {
+        self.index() == 0
+    }
+}
+
+///
+/// Connects to a Yellowstone gRPC endpoint, abstracted so the failover logic can be tested without a server.
+///
+pub(crate) trait GeyserConnector: Send + Sync + 'static {
+    fn connect(
+        &self,
+        endpoint: GrpcEndpoint,
+    ) -> BoxFuture<'static, Result<Box<dyn GeyserSubscriber>, GeyserSubscribeError>>;
+}
+
+///
+/// Connects to Yellowstone gRPC endpoints over tonic.
+///
+pub(crate) struct TonicGeyserConnector;
+
+impl GeyserConnector for TonicGeyserConnector {
+    fn connect(
+        &self,
+        endpoint: GrpcEndpoint,
+    ) -> BoxFuture<'static, Result<Box<dyn GeyserSubscriber>, GeyserSubscribeError>> {
+        Box::pin(async move {
+            let config = GrpcConnectionConfig {
+                endpoint: endpoint.url,
+                x_token: endpoint.x_token,
+                compression: endpoint.compression,
+            };
+            let client = connect_geyser_client(&config).await?;
+            Ok(Box::new(client) as Box<dyn GeyserSubscriber>)
+        })
+    }
+}
+
+impl GeyserSubscriber for Box<dyn GeyserSubscriber> {
+    fn subscribe(
+        &mut self,
+        request: SubscribeRequest,
+    ) -> BoxFuture<'_, Result<SlotUpdateStream, GeyserSubscribeError>> {
+        (**self).subscribe(request)
+    }
+}
+
+///
+/// [`GeyserSubscriber`] over a [`GrpcEndpointSet`].
+///
+pub(crate) struct FailoverSubscriber<K> {
+    connector: K,
+    endpoints: GrpcEndpointSet,
+    clients: Vec<Option<Box<dyn GeyserSubscriber>>>,
+    active: Arc<AtomicUsize>,
+    consecutive_failures: usize,
+}
+
+impl<K: GeyserConnector> FailoverSubscriber<K> {
+    pub(crate) fn new(connector: K, endpoints: GrpcEndpointSet) -> Self {
+        assert!(
+            !endpoints.endpoints.is_empty(),
+            "GrpcEndpointSet must contain at least one endpoint"
+        );
+        let clients = endpoints.endpoints.iter().map(|_| None).collect();
+        Self {
+            connector,
+            endpoints,
+            clients,
+            active: Arc::new(AtomicUsize::new(0)),
+            consecutive_failures: 0,
+        }
+    }
+
+    pub(crate) fn active_endpoint(&self) -> ActiveGrpcEndpoint {
+        ActiveGrpcEndpoint {
+            urls: self
+                .endpoints
+                .endpoints
+                .iter()
+                .map(|endpoint| endpoint.url.clone())
+                .collect(),
+            index: Arc::clone(&self.active),
+        }
+    }
+
+    ///
+    /// Number of subscribe attempts needed to try every endpoint.
+    ///
+    pub(crate) fn initial_attempts(&self) -> usize {
+        self.endpoints.endpoints.len() * self.endpoints.max_failures_before_failover.max(1)
+    }
+
+    fn set_active(&mut self, index: usize) {
+        self.active.store(index, Ordering::Relaxed);
+        self.consecutive_failures = 0;
+        #[cfg(feature = "prometheus")]
+        {
+            prom::set_grpc_active_endpoint_index(index);
+        }
+    }
+
+    ///
+    /// Records a connection error on the active endpoint, failing over to the next one if needed.
+    ///
+    pub(crate) fn record_failure(&mut self) {
+        let active = self.active.load(Ordering::Relaxed);
+        self.clients[active] = None;
+        self.consecutive_failures += 1;
+        if self.consecutive_failures < self.endpoints.max_failures_before_failover {
+            return;
+        }
+        let next = (active + 1) % self.endpoints.endpoints.len();
+        if next != active {
+            tracing::warn!(
+                "Failing over from gRPC endpoint {} to {} after {} consecutive errors",
+                self.endpoints.endpoints[active].url,
+                self.endpoints.endpoints[next].url,
+                self.consecutive_failures
+            );
+            #[cfg(feature = "prometheus")]
+            {
+                prom::incr_grpc_endpoint_failover_cnt();
+            }
+        }
+        self.set_active(next);
+    }
+
+    async fn subscribe_to(
+        &mut self,
+        index: usize,
+        request: SubscribeRequest,
+    ) -> Result<SlotUpdateStream, GeyserSubscribeError> {
+        let client = match &mut self.clients[index] {
+            Some(client) => client,
+            slot @ None => {
+                let endpoint = self.endpoints.endpoints[index].clone();
+                slot.insert(self.connector.connect(endpoint).await?)
+            }
+        };
+        client.subscribe(request).await
+    }
+}
+
+impl<K: GeyserConnector> GeyserSubscriber for FailoverSubscriber<K> {
+    fn subscribe(
+        &mut self,
+        request: SubscribeRequest,
+    ) -> BoxFuture<'_, Result<SlotUpdateStream, GeyserSubscribeError>> {
+        Box::pin(async move {
+            let active = self.active.load(Ordering::Relaxed);
+            match self.subscribe_to(active, request).await {
+                Ok(stream) => {
+                    self.consecutive_failures = 0;
+                    Ok(stream)
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to subscribe to gRPC endpoint {}: {e:?}",
+                        self.endpoints.endpoints[active].url
+                    );
+                    self.record_failure();
+                    Err(e)
+                }
+            }
+        })
+    }
+
+    fn preferred_recheck_interval(&self) -> Option<Duration> {
+        (self.endpoints.endpoints.len() > 1).then_some(self.endpoints.primary_recheck_interval)
+    }
+
+    fn try_subscribe_preferred(
+        &mut self,
+        request: SubscribeRequest,
+    ) -> BoxFuture<'_, Option<SlotUpdateStream>> {
+        Box::pin(async move {
+            if self.active.load(Ordering::Relaxed) == 0 {
+                return None;
+            }
+            match self.subscribe_to(0, request).await {
+                Ok(stream) => {
+                    tracing::info!(
+                        "gRPC primary endpoint {} recovered",
+                        self.endpoints.endpoints[0].url
+                    );
+                    self.set_active(0);
+                    Some(stream)
+                }
+                Err(e) => {
+                    tracing::debug!("gRPC primary endpoint still unavailable: {e:?}");
+                    self.clients[0] = None;
+                    None
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            slot::Slot,
+            yellowstone_grpc::slot_tracker::{
+                SLOT_TRACKER_DM_FILTER_NAME, SlotTrackerReconnectPolicy,
+                start_failover_slot_tracker,
+            },
+        },
+        std::{
+            collections::{HashMap, HashSet},
+            sync::Mutex,
+        },
+        tokio_stream::wrappers::UnboundedReceiverStream,
+        yellowstone_grpc_client::GeyserGrpcClientError,
+        yellowstone_grpc_proto::{
+            geyser::{
+                SlotStatus, SubscribeUpdate, SubscribeUpdateSlot, subscribe_update::UpdateOneof,
+            },
+            tonic::Status,
+        },
+    };
+
+    type SlotUpdateSender = tokio::sync::mpsc::UnboundedSender<Result<SubscribeUpdate, Status>>;
+
+    #[derive(Default)]
+    struct MockServers {
+        down: HashSet<String>,
+        streams: HashMap<String, Vec<SlotUpdateStream>>,
+    }
+
+    impl MockServers {
+        fn add_stream(&mut self, url: &str) -> SlotUpdateSender {
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            self.streams
+                .entry(url.to_string())
+                .or_default()
+                .push(Box::pin(UnboundedReceiverStream::new(rx)));
+            tx
+        }
+    }
+
+    ///
+    /// Connector to in-memory servers, connecting to a server marked down fails.
+    ///
+    #[derive(Clone, Default)]
+    struct MockConnector {
+        servers: Arc<Mutex<MockServers>>,
+    }
+
+    struct MockClient {
+        url: String,
+        servers: Arc<Mutex<MockServers>>,
+    }
+
+    fn unavailable(msg: &str) -> GeyserSubscribeError {
+        GeyserGrpcClientError::TonicStatus(Status::unavailable(msg.to_string())).into()
+    }
+
+    impl GeyserConnector for MockConnector {
+        fn connect(
+            &self,
+            endpoint: GrpcEndpoint,
+        ) -> BoxFuture<'static, Result<Box<dyn GeyserSubscriber>, GeyserSubscribeError>> {
+            let result = if self.servers.lock().unwrap().down.contains(&endpoint.url) {
+                Err(unavailable("mock server down"))
+            } else {
+                Ok(Box::new(MockClient {
+                    url: endpoint.url,
+                    servers: Arc::clone(&self.servers),
+                }) as Box<dyn GeyserSubscriber>)
+            };
+            Box::pin(async move { result })
+        }
+    }
+
+    impl GeyserSubscriber for MockClient {
+        fn subscribe(
+            &mut self,
+            _request: SubscribeRequest,
+        ) -> BoxFuture<'_, Result<SlotUpdateStream, GeyserSubscribeError>> {
+            let mut servers = self.servers.lock().unwrap();
+            let result = if servers.down.contains(&self.url) {
+                Err(unavailable("mock server down"))
+            } else {
+                match servers.streams.get_mut(&self.url) {
+                    Some(streams) if !streams.is_empty() => Ok(streams.remove(0)),
+                    _ => Err(unavailable("no stream")),
+                }
+            };
+            Box::pin(async move { result })
+        }
+    }
+
+    fn slot_update(slot: Slot) -> Result<SubscribeUpdate, Status> {
+        Ok(SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Slot(SubscribeUpdateSlot {
+                slot,
+                dead_error: None,
+                parent: None,
+                status: SlotStatus::SlotProcessed as i32,
+            })),
+            filters: vec![SLOT_TRACKER_DM_FILTER_NAME.to_string()],
+            created_at: None,
+        })
+    }
+
+    fn endpoint_set(recheck_interval: Duration) -> GrpcEndpointSet {
+        GrpcEndpointSet {
+            endpoints: vec![
+                GrpcEndpoint {
+                    url: "http://primary".to_string(),
+                    x_token: None,
+                    compression: GrpcCompression::None,
+                },
+                GrpcEndpoint {
+                    url: "http://secondary".to_string(),
+                    x_token: None,
+                    compression: GrpcCompression::None,
+                },
+            ],
+            max_failures_before_failover: 2,
+            primary_recheck_interval: recheck_interval,
+        }
+    }
+
+    fn reconnect_policy() -> SlotTrackerReconnectPolicy {
+        SlotTrackerReconnectPolicy {
+            max_attempts: Some(3),
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_over_to_secondary_when_primary_is_down() {
+        let connector = MockConnector::default();
+        let secondary_tx = {
+            let mut servers = connector.servers.lock().unwrap();
+            servers.down.insert("http://primary".to_string());
+            servers.add_stream("http://secondary")
+        };
+        secondary_tx.send(slot_update(5)).unwrap();
+
+        let (slot_tracker, active_endpoint) = start_failover_slot_tracker(
+            connector.clone(),
+            endpoint_set(Duration::from_secs(60)),
+            reconnect_policy(),
+        )
+        .await
+        .expect("failover to secondary");
+
+        assert_eq!(active_endpoint.index(), 1);
+        assert_eq!(active_endpoint.url(), "http://secondary");
+        assert!(!active_endpoint.is_primary());
+        assert_eq!(slot_tracker.atomic_slot_tracker.load(), 5);
+
+        secondary_tx.send(slot_update(6)).unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(slot_tracker.atomic_slot_tracker.load(), 6);
+    }
+
+    #[tokio::test]
+    async fn it_should_switch_back_to_primary_once_recovered() {
+        let connector = MockConnector::default();
+        let secondary_tx = {
+            let mut servers = connector.servers.lock().unwrap();
+            servers.down.insert("http://primary".to_string());
+            servers.add_stream("http://secondary")
+        };
+        secondary_tx.send(slot_update(10)).unwrap();
+
+        let (slot_tracker, active_endpoint) = start_failover_slot_tracker(
+            connector.clone(),
+            endpoint_set(Duration::from_millis(50)),
+            reconnect_policy(),
+        )
+        .await
+        .expect("failover to secondary");
+        assert_eq!(active_endpoint.index(), 1);
+
+        let primary_tx = {
+            let mut servers = connector.servers.lock().unwrap();
+            servers.down.remove("http://primary");
+            servers.add_stream("http://primary")
+        };
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(active_endpoint.is_primary());
+        // The slot is not reset while switching endpoints
+        assert_eq!(slot_tracker.atomic_slot_tracker.load(), 10);
+
+        primary_tx.send(slot_update(11)).unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(slot_tracker.atomic_slot_tracker.load(), 11);
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_every_endpoint_is_down() {
+        let connector = MockConnector::default();
+        {
+            let mut servers = connector.servers.lock().unwrap();
+            servers.down.insert("http://primary".to_string());
+            servers.down.insert("http://secondary".to_string());
+        }
+        let result = start_failover_slot_tracker(
+            connector,
+            endpoint_set(Duration::from_secs(60)),
+            reconnect_policy(),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
@@ -1,6 +1,13 @@
+/// File-based configuration of the predictor, warm connections and gRPC endpoints.
+pub mod config;
+/// Yellowstone gRPC connection configuration and authentication.
+pub mod connection;
+/// Failover across multiple Yellowstone gRPC endpoints.
+pub mod failover;
 /// Yellowstone modules to predict upcoming leaders using gRPC+RPC services.
 pub mod schedule;
 /// Fully-features tpu sender using Yellowstone services.
+#[cfg(feature = "rpc")]
 pub mod sender;
 /// Yellowstone modules to track the current slot using gRPC+RPC services.
 pub mod slot_tracker;
@@ -13,20 +13,595 @@
 //!
 //! This module is designed to be thread-safe and can be shared across multiple tasks.
 //!
-//! # Poisoning
+//! # Epoch boundaries
 //!
-//! The slot tracker/managed schedule used in this implementation can be poisoned if the background task
-//! updating it panics or is dropped.
+//! A prediction window crossing into the next epoch is served from the prefetched next epoch schedule, up to
+//! [`YellowstoneUpcomingLeader::max_future_epochs`] epochs past the epoch of the current slot, epochs following the
+//! cluster epoch schedule installed with [`AtomicSlotTracker::set_epoch_schedule`]. Predictions stop cleanly
+//! at the horizon, or at the end of the loaded schedules if a future epoch is not loaded yet: the missing leaders are
+//! truncated rather than looked up, and the truncation is logged and counted. A truncation at the loaded schedules
+//! once the current slot reached the last loaded epoch, e.g. at an epoch rollover, triggers a prefetch of the missing
+//! epoch, see [`ManagedLeaderSchedule::trigger_prefetch`].
 //!
+//! # Genesis
+//!
+//! Near genesis, the current leader has fewer than [`YellowstoneUpcomingLeader::look_behind`] previous leaders: the
+//! window starts at slot 0 instead, never wrapping around, and is filled with future leaders so it still holds `n`
+//! distinct boundaries. E.g. at slots 0 to 3 with the default look-behind, the window starts at the current leader.
+//!
+//! # Recent predictions
+//!
+//! Beyond the debug logs, the last predictions can be kept in memory, see
+//! [`YellowstoneUpcomingLeaderBuilder::recent_predictions_capacity`]: [`YellowstoneUpcomingLeader::recent_predictions`]
+//! returns the slot, window and shortfall breakdown of each, e.g. for an admin endpoint or to correlate prediction
+//! gaps with landing failures. Disabled by default, recording costs a lock and a copy of the window per prediction.
+//!
+//! # Prediction cache
+//!
+//! Predictions stay the same for every slot of a leader rotation. The last prediction without deny-list is cached,
+//! keyed by the current leader boundary, the prediction options and the [`ManagedLeaderSchedule::generation`]:
+//! repeated predictions within the rotation return a copy of the cached window without locking the schedules, and
+//! the cache is invalidated once the boundary advances or the schedules are refreshed. The cache holds a single
+//! entry, callers alternating window sizes miss it.
+//!
+//! # Shutdown
+//!
+//! Reading the slot tracker never fails. Predictions fail with [`PredictError::SlotTrackerClosed`] once the task
+//! feeding the tracker has exited, rather than predicting from a frozen slot, see
+//! [closing](AtomicSlotTracker#closing). Leaders are not predicted past a managed schedule whose update task failed.
+//!
+#[cfg(feature = "metrics")]
+use crate::metrics;
 use {
     crate::{
-        core::UpcomingLeaderPredictor, rpc::schedule::ManagedLeaderSchedule,
+        core::{
+            LeaderTpuInfoService, PredictedLeader, UpcomingLeaderPredictor, collapse_leader_runs,
+        },
+        rpc::{
+            schedule::{LeaderLookup, ManagedLeaderSchedule, ScheduleError},
+            tpu_info::RpcTpuQuicContactInfo,
+        },
+        skip_rate::SkipRateTracker,
         slot::AtomicSlotTracker,
     },
+    futures::{Stream, stream},
+    solana_clock::{NUM_CONSECUTIVE_LEADER_SLOTS, Slot},
+    solana_commitment_config::CommitmentLevel,
     solana_pubkey::Pubkey,
-    std::sync::Arc,
+    std::{
+        collections::{HashSet, VecDeque},
+        net::SocketAddr,
+        ops::{ControlFlow, Range},
+        sync::{
+            Arc, Mutex, RwLock,
+            atomic::{AtomicU64, Ordering},
+        },
+        time::{Duration, SystemTime},
+    },
 };
 
+///
+/// Default number of previous leaders included in [`YellowstoneUpcomingLeader`] predictions.
+///
+pub const DEFAULT_LOOK_BEHIND: usize = 1;
+
+///
+/// How many leader boundaries per requested leader [`YellowstoneUpcomingLeader::try_predict_distinct_leaders`] may scan.
+///
+pub const MAX_DISTINCT_SCAN_FACTOR: usize = 4;
+
+///
+/// Number of missing leaders a prediction tolerates before logging a warning and counting a shortfall.
+///
+/// Predictions near genesis or right at an epoch boundary legitimately miss a few leaders.
+///
+pub const PREDICTION_SHORTFALL_WARN_THRESHOLD: usize = 2;
+
+///
+/// Suggested maximum age of the tracked slot before [`YellowstoneUpcomingLeader`] refuses to predict, see
+/// [`YellowstoneUpcomingLeaderBuilder::max_slot_age`]. The check is disabled by default.
+///
+pub const DEFAULT_MAX_SLOT_AGE: Duration = Duration::from_secs(10);
+
+///
+/// Default number of epochs past the current one [`YellowstoneUpcomingLeader`] predictions may span: the prefetched
+/// next epoch.
+///
+pub const DEFAULT_MAX_FUTURE_EPOCHS: u64 = 1;
+
+///
+/// Why a prediction stopped before filling its window, see [`PredictionShortfall::truncated`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PredictionTruncation {
+    ///
+    /// Stopped at this boundary, past the [`YellowstoneUpcomingLeader::max_future_epochs`] horizon.
+    ///
+    Horizon(Slot),
+    ///
+    /// Stopped at this boundary, past the loaded leader schedules.
+    ///
+    LoadedSchedules(Slot),
+}
+
+///
+/// Breakdown of the leaders missing from a [`PredictionRecord`].
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PredictionShortfall {
+    ///
+    /// Number of requested leaders missing from the window.
+    ///
+    pub missing: usize,
+    ///
+    /// Number of scanned boundaries without a leader in the loaded schedules.
+    ///
+    pub schedule_gaps: usize,
+    ///
+    /// Number of scanned boundaries outside the loaded schedules.
+    ///
+    pub outside_schedule: usize,
+    ///
+    /// Number of leaders skipped by the deny-list.
+    ///
+    pub denied: usize,
+    pub truncated: Option<PredictionTruncation>,
+}
+
+///
+/// Prediction retained by [`YellowstoneUpcomingLeader::recent_predictions`].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PredictionRecord {
+    pub predicted_at: SystemTime,
+    ///
+    /// Slot the prediction was computed from.
+    ///
+    pub slot: Slot,
+    ///
+    /// Number of requested leaders, or rotations for [`UpcomingLeaderPredictor::predict_until_slot`].
+    ///
+    pub requested: usize,
+    ///
+    /// Predicted leaders along with their slots.
+    ///
+    pub window: Vec<(Pubkey, Range<Slot>)>,
+    pub shortfall: PredictionShortfall,
+}
+
+///
+/// Bounded ring of the last [`PredictionRecord`], shared between the clones of a [`YellowstoneUpcomingLeader`].
+///
+#[derive(Debug)]
+struct RecentPredictions {
+    capacity: usize,
+    records: Mutex<VecDeque<PredictionRecord>>,
+}
+
+impl RecentPredictions {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    fn record(
+        &self,
+        slot: Slot,
+        requested: usize,
+        window: &[(Pubkey, Range<Slot>)],
+        shortfall: PredictionShortfall,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let record = PredictionRecord {
+            predicted_at: SystemTime::now(),
+            slot,
+            requested,
+            window: window.to_vec(),
+            shortfall,
+        };
+        let mut records = self.records.lock().expect("recent predictions lock");
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    fn recent(&self) -> Vec<PredictionRecord> {
+        self.records
+            .lock()
+            .expect("recent predictions lock")
+            .iter()
+            .rev()
+            .cloned()
+            .collect()
+    }
+}
+
+///
+/// Everything a prediction without deny-list depends on, besides the slot within the current leader rotation.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PredictionKey {
+    current_leader_boundary: Slot,
+    horizon_end: Slot,
+    n: usize,
+    distinct: bool,
+    look_behind: usize,
+    slots_per_leader: u64,
+    schedule_generation: u64,
+}
+
+#[derive(Debug)]
+struct CachedPrediction {
+    key: PredictionKey,
+    leaders: Vec<(Pubkey, Range<Slot>)>,
+    shortfall: PredictionShortfall,
+}
+
+///
+/// Last prediction, shared between the clones of a [`YellowstoneUpcomingLeader`], see the
+/// [module documentation](crate::yellowstone_grpc::schedule#prediction-cache).
+///
+#[derive(Debug, Default)]
+struct PredictionCache {
+    last: RwLock<Option<CachedPrediction>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl PredictionCache {
+    fn get(
+        &self,
+        key: &PredictionKey,
+    ) -> Option<(Vec<(Pubkey, Range<Slot>)>, PredictionShortfall)> {
+        let last = self.last.read().expect("prediction cache lock");
+        let hit = last
+            .as_ref()
+            .filter(|cached| cached.key == *key)
+            .map(|cached| (cached.leaders.clone(), cached.shortfall.clone()));
+        let counter = if hit.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        hit
+    }
+
+    fn store(
+        &self,
+        key: PredictionKey,
+        leaders: &[(Pubkey, Range<Slot>)],
+        shortfall: &PredictionShortfall,
+    ) {
+        *self.last.write().expect("prediction cache lock") = Some(CachedPrediction {
+            key,
+            leaders: leaders.to_vec(),
+            shortfall: shortfall.clone(),
+        });
+    }
+}
+
+///
+/// Error returned by [`YellowstoneUpcomingLeader::try_predict_next_n_leaders_checked`].
+///
+#[derive(Debug, thiserror::Error)]
+pub enum PredictError {
+    ///
+    /// The slot tracker is closed, the background task updating it has exited, see [`AtomicSlotTracker::is_closed`].
+    ///
+    #[error("slot tracker closed at slot {0}, the slot feed task has exited")]
+    SlotTrackerClosed(Slot),
+    ///
+    /// The slot tracker has not observed any slot yet, see [`AtomicSlotTracker::wait_until_ready`].
+    ///
+    #[error("slot tracker has not observed any slot yet")]
+    SlotTrackerNotReady,
+    ///
+    /// The slot tracker has not received a slot update for longer than [`YellowstoneUpcomingLeader::max_slot_age`].
+    ///
+    #[error("slot {slot} is stale, last updated {age:?} ago (max age {max_age:?})")]
+    StaleSlot {
+        slot: Slot,
+        age: Duration,
+        max_age: Duration,
+    },
+    ///
+    /// No slot has been observed yet at [`YellowstoneUpcomingLeader::prediction_commitment`].
+    ///
+    #[error("no slot observed yet at commitment {0:?}")]
+    NoSlotAtCommitment(CommitmentLevel),
+}
+
+///
+/// Error returned by [`YellowstoneUpcomingLeaderBuilder::build`] when the options are inconsistent.
+///
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BuildUpcomingLeaderError {
+    ///
+    /// `slots_per_leader` is `0`, no leader could ever be predicted.
+    ///
+    #[error("slots_per_leader must be greater than 0")]
+    ZeroSlotsPerLeader,
+    ///
+    /// `max_slot_age` is zero, every prediction would be refused as stale.
+    ///
+    #[error("max_slot_age must be greater than 0")]
+    ZeroMaxSlotAge,
+}
+
+///
+/// Set of leaders excluded by [`YellowstoneUpcomingLeader::try_predict_next_n_leaders_filtered`], updatable at runtime.
+///
+/// Updates swap the whole set at once: a prediction in flight keeps the set it started with.
+///
+/// # Safety
+///
+/// This struct is thread-safe. Share it using an atomic reference-counter.
+///
+#[derive(Debug, Default)]
+pub struct LeaderDenyList {
+    denied: RwLock<Arc<HashSet<Pubkey>>>,
+}
+
+impl LeaderDenyList {
+    pub fn new(denied: HashSet<Pubkey>) -> Self {
+        Self {
+            denied: RwLock::new(Arc::new(denied)),
+        }
+    }
+
+    ///
+    /// Returns the current set of denied leaders.
+    ///
+    pub fn load(&self) -> Arc<HashSet<Pubkey>> {
+        Arc::clone(&self.denied.read().expect("deny list read"))
+    }
+
+    ///
+    /// Replaces the set of denied leaders.
+    ///
+    pub fn store(&self, denied: HashSet<Pubkey>) {
+        *self.denied.write().expect("deny list write") = Arc::new(denied);
+    }
+
+    ///
+    /// Adds `leader` to the denied leaders, returns `false` if it was already denied.
+    ///
+    pub fn insert(&self, leader: Pubkey) -> bool {
+        let mut guard = self.denied.write().expect("deny list write");
+        if guard.contains(&leader) {
+            return false;
+        }
+        Arc::make_mut(&mut guard).insert(leader)
+    }
+
+    ///
+    /// Removes `leader` from the denied leaders, returns `false` if it was not denied.
+    ///
+    pub fn remove(&self, leader: &Pubkey) -> bool {
+        let mut guard = self.denied.write().expect("deny list write");
+        if !guard.contains(leader) {
+            return false;
+        }
+        Arc::make_mut(&mut guard).remove(leader)
+    }
+
+    pub fn contains(&self, leader: &Pubkey) -> bool {
+        self.denied.read().expect("deny list read").contains(leader)
+    }
+}
+
+///
+/// Builder for [`YellowstoneUpcomingLeader`].
+///
+/// Holds the default value of every option, see the setters for each default.
+///
+/// # Example
+///
+/// ```
+/// use {
+///     std::{sync::Arc, time::Duration},
+///     yellowstone_jet_tpu_client::{
+///         rpc::schedule::ManagedLeaderSchedule,
+///         slot::AtomicSlotTracker,
+///         yellowstone_grpc::schedule::YellowstoneUpcomingLeader,
+///     },
+/// };
+///
+/// fn upcoming_leader_predictor(
+///     slot_tracker: Arc<AtomicSlotTracker>,
+///     managed_schedule: ManagedLeaderSchedule,
+/// ) -> YellowstoneUpcomingLeader {
+///     YellowstoneUpcomingLeader::builder(slot_tracker, managed_schedule)
+///         .slots_per_leader(4)
+///         .look_behind(2)
+///         .dedup(true)
+///         .max_slot_age(Some(Duration::from_secs(5)))
+///         .use_estimated_slot(true)
+///         .build()
+///         .expect("valid predictor options")
+/// }
+/// ```
+///
+/// Invalid options are reported by [`YellowstoneUpcomingLeaderBuilder::build`]:
+///
+/// ```
+/// use {
+///     std::sync::Arc,
+///     yellowstone_jet_tpu_client::{
+///         rpc::schedule::ManagedLeaderSchedule,
+///         slot::AtomicSlotTracker,
+///         yellowstone_grpc::schedule::{BuildUpcomingLeaderError, YellowstoneUpcomingLeader},
+///     },
+/// };
+///
+/// fn check(slot_tracker: Arc<AtomicSlotTracker>, managed_schedule: ManagedLeaderSchedule) {
+///     let result = YellowstoneUpcomingLeader::builder(slot_tracker, managed_schedule)
+///         .slots_per_leader(0)
+///         .build();
+///     assert!(matches!(result, Err(BuildUpcomingLeaderError::ZeroSlotsPerLeader)));
+/// }
+/// ```
+///
+#[derive(Clone)]
+pub struct YellowstoneUpcomingLeaderBuilder {
+    slot_tracker: Arc<AtomicSlotTracker>,
+    managed_schedule: ManagedLeaderSchedule,
+    slots_per_leader: u64,
+    look_behind: usize,
+    dedup: bool,
+    max_slot_age: Option<Duration>,
+    use_estimated_slot: bool,
+    prediction_commitment: CommitmentLevel,
+    skip_rates: Option<Arc<SkipRateTracker>>,
+    deny_list: Arc<LeaderDenyList>,
+    max_future_epochs: u64,
+    recent_predictions_capacity: usize,
+}
+
+impl YellowstoneUpcomingLeaderBuilder {
+    pub fn new(
+        slot_tracker: Arc<AtomicSlotTracker>,
+        managed_schedule: ManagedLeaderSchedule,
+    ) -> Self {
+        Self {
+            slot_tracker,
+            managed_schedule,
+            slots_per_leader: NUM_CONSECUTIVE_LEADER_SLOTS,
+            look_behind: DEFAULT_LOOK_BEHIND,
+            dedup: false,
+            max_slot_age: None,
+            use_estimated_slot: false,
+            prediction_commitment: CommitmentLevel::Processed,
+            skip_rates: None,
+            deny_list: Default::default(),
+            max_future_epochs: DEFAULT_MAX_FUTURE_EPOCHS,
+            recent_predictions_capacity: 0,
+        }
+    }
+
+    ///
+    /// Sets [`YellowstoneUpcomingLeader::slots_per_leader`], defaults to [`NUM_CONSECUTIVE_LEADER_SLOTS`].
+    ///
+    pub fn slots_per_leader(mut self, slots_per_leader: u64) -> Self {
+        self.slots_per_leader = slots_per_leader;
+        self
+    }
+
+    ///
+    /// Sets [`YellowstoneUpcomingLeader::look_behind`], defaults to [`DEFAULT_LOOK_BEHIND`].
+    ///
+    pub fn look_behind(mut self, look_behind: usize) -> Self {
+        self.look_behind = look_behind;
+        self
+    }
+
+    ///
+    /// Sets [`YellowstoneUpcomingLeader::dedup`], defaults to `false`.
+    ///
+    pub fn dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    ///
+    /// Sets [`YellowstoneUpcomingLeader::max_slot_age`], defaults to `None`, e.g. `Some(DEFAULT_MAX_SLOT_AGE)`.
+    ///
+    pub fn max_slot_age(mut self, max_slot_age: Option<Duration>) -> Self {
+        self.max_slot_age = max_slot_age;
+        self
+    }
+
+    ///
+    /// Sets [`YellowstoneUpcomingLeader::use_estimated_slot`], defaults to `false`.
+    ///
+    pub fn use_estimated_slot(mut self, use_estimated_slot: bool) -> Self {
+        self.use_estimated_slot = use_estimated_slot;
+        self
+    }
+
+    ///
+    /// Sets [`YellowstoneUpcomingLeader::prediction_commitment`], defaults to [`CommitmentLevel::Processed`].
+    ///
+    pub fn prediction_commitment(mut self, prediction_commitment: CommitmentLevel) -> Self {
+        self.prediction_commitment = prediction_commitment;
+        self
+    }
+
+    ///
+    /// Sets [`YellowstoneUpcomingLeader::skip_rates`], defaults to `None`.
+    ///
+    pub fn skip_rates(mut self, skip_rates: Option<Arc<SkipRateTracker>>) -> Self {
+        self.skip_rates = skip_rates;
+        self
+    }
+
+    ///
+    /// Sets [`YellowstoneUpcomingLeader::deny_list`], defaults to an empty deny-list.
+    ///
+    pub fn deny_list(mut self, deny_list: Arc<LeaderDenyList>) -> Self {
+        self.deny_list = deny_list;
+        self
+    }
+
+    ///
+    /// Sets [`YellowstoneUpcomingLeader::max_future_epochs`], defaults to [`DEFAULT_MAX_FUTURE_EPOCHS`].
+    ///
+    pub fn max_future_epochs(mut self, max_future_epochs: u64) -> Self {
+        self.max_future_epochs = max_future_epochs;
+        self
+    }
+
+    ///
+    /// Sets the number of predictions retained by [`YellowstoneUpcomingLeader::recent_predictions`], defaults to `0`,
+    /// disabled.
+    ///
+    pub fn recent_predictions_capacity(mut self, capacity: usize) -> Self {
+        self.recent_predictions_capacity = capacity;
+        self
+    }
+
+    ///
+    /// Validates the options and builds the predictor.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BuildUpcomingLeaderError`] if `slots_per_leader` or `max_slot_age` is zero.
+    ///
+    pub fn build(self) -> Result<YellowstoneUpcomingLeader, BuildUpcomingLeaderError> {
+        if self.slots_per_leader == 0 {
+            return Err(BuildUpcomingLeaderError::ZeroSlotsPerLeader);
+        }
+        if self.max_slot_age.is_some_and(|max_age| max_age.is_zero()) {
+            return Err(BuildUpcomingLeaderError::ZeroMaxSlotAge);
+        }
+        Ok(self.build_unchecked())
+    }
+
+    fn build_unchecked(self) -> YellowstoneUpcomingLeader {
+        YellowstoneUpcomingLeader {
+            slot_tracker: self.slot_tracker,
+            managed_schedule: self.managed_schedule,
+            slots_per_leader: self.slots_per_leader,
+            look_behind: self.look_behind,
+            dedup: self.dedup,
+            max_slot_age: self.max_slot_age,
+            use_estimated_slot: self.use_estimated_slot,
+            prediction_commitment: self.prediction_commitment,
+            skip_rates: self.skip_rates,
+            deny_list: self.deny_list,
+            max_future_epochs: self.max_future_epochs,
+            recent_predictions: Arc::new(RecentPredictions::new(self.recent_predictions_capacity)),
+            prediction_cache: Default::default(),
+        }
+    }
+}
+
 ///
 /// A Yellowstone-specific implementation of UpcomingLeaderPredictor
 ///
@@ -43,81 +618,2121 @@ use {
 /// - Leader n (current)
 /// - Leader n+1, n+2, n+3 (next 3 leaders)
 ///
+/// # Look-behind
+///
+/// The number of previous leaders included in the prediction is controlled by [`YellowstoneUpcomingLeader::look_behind`] (defaults to 1).
+/// The total number of returned leaders stays `n`: the window starts `look_behind` leaders before the current one,
+/// so increasing `look_behind` reduces the number of future leaders predicted.
+///
+/// Example with n=5 and look_behind=2: n-2, n-1, n, n+1, n+2.
+///
 /// # Safety
 ///
 /// This struct is cheaply-cloneable and can be shared between threads.
 ///
+/// # Clone
+///
+/// Clones share a single view of the mutable state, behind an [`Arc`]:
+/// - [`YellowstoneUpcomingLeader::slot_tracker`], slot updates are visible through every clone,
+/// - [`YellowstoneUpcomingLeader::managed_schedule`], including its refreshes and poisoning,
+/// - [`YellowstoneUpcomingLeader::skip_rates`], recorded slot outcomes are visible through every clone,
+/// - [`YellowstoneUpcomingLeader::deny_list`], deny-list updates apply to every clone,
+/// - the [`YellowstoneUpcomingLeader::recent_predictions`] of every clone are retained together,
+/// - the last prediction is cached once for every clone, see the
+///   [module documentation](crate::yellowstone_grpc::schedule#prediction-cache).
+///
+/// The prediction options (`slots_per_leader`, `look_behind`, `dedup`, `max_slot_age`, `use_estimated_slot`,
+/// `prediction_commitment` and `max_future_epochs`) are plain values copied per clone: changing them on one clone does not affect the others.
+/// New mutable state, such as stats or caches, must be shared the same way.
+///
 #[derive(Clone)]
 pub struct YellowstoneUpcomingLeader {
     pub slot_tracker: Arc<AtomicSlotTracker>,
     pub managed_schedule: ManagedLeaderSchedule,
+    ///
+    /// Number of consecutive slots assigned to a leader, defaults to [`NUM_CONSECUTIVE_LEADER_SLOTS`].
+    ///
+    /// A value of `0` disables prediction.
+    ///
+    pub slots_per_leader: u64,
+    ///
+    /// Number of previous leaders to include in the prediction window, defaults to [`DEFAULT_LOOK_BEHIND`].
+    ///
+    pub look_behind: usize,
+    ///
+    /// If `true`, [`UpcomingLeaderPredictor::try_predict_next_n_leaders`] collapses runs of the same leader,
+    /// see [`YellowstoneUpcomingLeader::try_predict_distinct_leaders`]. Defaults to `false`.
+    ///
+    pub dedup: bool,
+    ///
+    /// Maximum time since the last slot update before predictions are refused, `None` (the default) disables the
+    /// check.
+    ///
+    /// A stalled slot feed would otherwise keep predicting the same, outdated, leaders, see [`DEFAULT_MAX_SLOT_AGE`].
+    ///
+    pub max_slot_age: Option<Duration>,
+    ///
+    /// If `true`, predictions are based on [`AtomicSlotTracker::estimated_slot`] instead of the last observed slot,
+    /// which keeps the prediction window ahead of leader rotation between slot updates. Defaults to `false`.
+    ///
+    pub use_estimated_slot: bool,
+    ///
+    /// Commitment level of the slot driving predictions, defaults to [`CommitmentLevel::Processed`].
+    ///
+    /// The processed slot is the freshest but may belong to a fork that gets rolled back, predictions then briefly
+    /// chase leaders of the abandoned fork. The confirmed slot lags the processed one by a couple of slots but is
+    /// rarely rolled back: predictions trade a little latency for stability. Finalized lags by ~32 slots, which
+    /// exceeds most prediction windows and is rarely useful.
+    ///
+    /// Predictions fail with [`PredictError::NoSlotAtCommitment`] until a slot is observed at that level.
+    ///
+    pub prediction_commitment: CommitmentLevel,
+    ///
+    /// Recent slot outcomes weighting [`YellowstoneUpcomingLeader::try_predict_weighted`], defaults to `None`.
+    ///
+    pub skip_rates: Option<Arc<SkipRateTracker>>,
+    ///
+    /// Leaders excluded by [`YellowstoneUpcomingLeader::try_predict_next_n_leaders_filtered`], empty by default.
+    ///
+    pub deny_list: Arc<LeaderDenyList>,
+    ///
+    /// Number of epochs past the epoch of the current slot predictions may span, defaults to
+    /// [`DEFAULT_MAX_FUTURE_EPOCHS`].
+    ///
+    /// `0` keeps predictions within the current epoch. Epochs past the loaded schedules truncate predictions
+    /// regardless, see the [module documentation](crate::yellowstone_grpc::schedule#epoch-boundaries).
+    ///
+    pub max_future_epochs: u64,
+    recent_predictions: Arc<RecentPredictions>,
+    prediction_cache: Arc<PredictionCache>,
 }
 
-impl UpcomingLeaderPredictor for YellowstoneUpcomingLeader {
-    fn try_predict_next_n_leaders(&self, n: usize) -> Vec<Pubkey> {
-        if n == 0 {
-            return Vec::new();
+impl YellowstoneUpcomingLeader {
+    ///
+    /// Creates a new predictor using Solana's default leader rotation width ([`NUM_CONSECUTIVE_LEADER_SLOTS`]).
+    ///
+    pub fn new(
+        slot_tracker: Arc<AtomicSlotTracker>,
+        managed_schedule: ManagedLeaderSchedule,
+    ) -> Self {
+        Self::with_slots_per_leader(slot_tracker, managed_schedule, NUM_CONSECUTIVE_LEADER_SLOTS)
+    }
+
+    ///
+    /// Creates a new predictor with a custom leader rotation width.
+    ///
+    /// Useful for test validators or forks where each leader holds a different number of consecutive slots.
+    ///
+    pub fn with_slots_per_leader(
+        slot_tracker: Arc<AtomicSlotTracker>,
+        managed_schedule: ManagedLeaderSchedule,
+        slots_per_leader: u64,
+    ) -> Self {
+        Self::builder(slot_tracker, managed_schedule)
+            .slots_per_leader(slots_per_leader)
+            .build_unchecked()
+    }
+
+    ///
+    /// Returns a [`YellowstoneUpcomingLeaderBuilder`] initialized with the default options.
+    ///
+    pub fn builder(
+        slot_tracker: Arc<AtomicSlotTracker>,
+        managed_schedule: ManagedLeaderSchedule,
+    ) -> YellowstoneUpcomingLeaderBuilder {
+        YellowstoneUpcomingLeaderBuilder::new(slot_tracker, managed_schedule)
+    }
+
+    ///
+    /// Returns the leader scheduled at the current slot.
+    ///
+    /// The loaded slot is snapped to its own leader boundary, without look-behind: unlike
+    /// `try_predict_next_n_leaders(1)[0]`, which is the *previous* leader with the default
+    /// [`YellowstoneUpcomingLeader::look_behind`], this is the leader producing blocks right now.
+    /// The slot is loaded like predictions do, honoring `prediction_commitment`, `max_slot_age` and
+    /// `use_estimated_slot`.
+    ///
+    /// Returns `None` if the slot tracker is closed or stale, or if the slot has no known leader.
+    ///
+    pub fn current_leader(&self) -> Option<Pubkey> {
+        match self.load_prediction_slot() {
+            Ok(slot) => self.predict_leader_at_slot(slot),
+            Err(e) => {
+                tracing::warn!("[YellowstoneUpcomingLeader] Failed to get current leader: {e}");
+                None
+            }
         }
+    }
+
+    ///
+    /// Returns the leader whose rotation immediately precedes the current one.
+    ///
+    /// A transaction sent right at a leader boundary may target a leader that just rotated out: the current
+    /// slot moves to the next rotation between the prediction and the send, or the slot feed lags the cluster by a
+    /// few hundred milliseconds. The previous leader can still include the transaction while it finishes its last
+    /// block, so a resend path can target it.
+    ///
+    /// Unlike the [`YellowstoneUpcomingLeader::look_behind`] window, this is always the single rotation right before
+    /// the current boundary, see [`YellowstoneUpcomingLeader::current_leader`]. It is the same leader as the current
+    /// one when a validator holds consecutive rotations.
+    ///
+    /// Returns `None` if the slot tracker is closed or stale, if the current rotation is the first one, or if
+    /// the previous slots have no known leader.
+    ///
+    pub fn previous_leader(&self) -> Option<Pubkey> {
+        if self.slots_per_leader == 0 {
+            return None;
+        }
+        match self.load_prediction_slot() {
+            Ok(slot) => {
+                let current_leader_boundary = slot - slot % self.slots_per_leader;
+                let previous_leader_boundary =
+                    current_leader_boundary.checked_sub(self.slots_per_leader)?;
+                self.get_leader_at_boundary(previous_leader_boundary)
+            }
+            Err(e) => {
+                tracing::warn!("[YellowstoneUpcomingLeader] Failed to get previous leader: {e}");
+                None
+            }
+        }
+    }
+
+    ///
+    /// Predicts the leader of a specific `slot`.
+    ///
+    /// The slot is snapped down to its leader boundary before looking up the [`ManagedLeaderSchedule`].
+    ///
+    /// # Returns
+    ///
+    /// `None` if the slot is outside the known schedules or if the schedule is poisoned.
+    ///
+    pub fn predict_leader_at_slot(&self, slot: Slot) -> Option<Pubkey> {
+        if self.slots_per_leader == 0 {
+            return None;
+        }
+        let leader_slot_boundary = slot - slot % self.slots_per_leader;
+        self.get_leader_at_boundary(leader_slot_boundary)
+    }
 
-        let slot = self.slot_tracker.load().expect("load");
-        let reminder = slot % 4;
+    fn get_leader_at_boundary(&self, leader_slot_boundary: Slot) -> Option<Pubkey> {
+        match self.managed_schedule.get_leader(leader_slot_boundary) {
+            Ok(Some(leader)) => {
+                Self::leader_from_lookup(leader_slot_boundary, LeaderLookup::Found(leader))
+            }
+            Ok(None) => Self::leader_from_lookup(leader_slot_boundary, LeaderLookup::Gap),
+            Err(ScheduleError::SlotBeforeLoadedEpoch(_)) => {
+                Self::leader_from_lookup(leader_slot_boundary, LeaderLookup::BeforeKnownSchedule)
+            }
+            // The background task prefetches the next epoch schedule ahead of the epoch boundary.
+            Err(ScheduleError::SlotAfterLoadedEpoch(_)) => {
+                Self::leader_from_lookup(leader_slot_boundary, LeaderLookup::PastKnownSchedule)
+            }
+            Err(ScheduleError::NotInitialized) => {
+                tracing::warn!(
+                    "[YellowstoneUpcomingLeader] Leader schedule not initialized at slot_boundary={}, triggering a refresh",
+                    leader_slot_boundary
+                );
+                self.managed_schedule.trigger_refresh();
+                None
+            }
+            Err(e) => {
+                #[cfg(feature = "metrics")]
+                {
+                    metrics::incr_leader_prediction_error("schedule_lookup");
+                }
+                tracing::warn!(
+                    "[YellowstoneUpcomingLeader] Failed to get leader for slot_boundary={}: {:?}",
+                    leader_slot_boundary,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    fn leader_from_lookup(leader_slot_boundary: Slot, lookup: LeaderLookup) -> Option<Pubkey> {
+        match lookup {
+            LeaderLookup::Found(leader) => {
+                tracing::trace!(
+                    "[YellowstoneUpcomingLeader] Predicted leader at slot_boundary={}: {}",
+                    leader_slot_boundary,
+                    leader
+                );
+                Some(leader)
+            }
+            LeaderLookup::PastKnownSchedule => {
+                tracing::debug!(
+                    "[YellowstoneUpcomingLeader] slot_boundary={} is past the known leader schedule",
+                    leader_slot_boundary
+                );
+                None
+            }
+            LeaderLookup::BeforeKnownSchedule => {
+                tracing::debug!(
+                    "[YellowstoneUpcomingLeader] slot_boundary={} is before the known leader schedule",
+                    leader_slot_boundary
+                );
+                None
+            }
+            LeaderLookup::Gap => {
+                tracing::debug!(
+                    "[YellowstoneUpcomingLeader] No leader found for slot_boundary={}",
+                    leader_slot_boundary
+                );
+                None
+            }
+        }
+    }
+
+    ///
+    /// Same as [`UpcomingLeaderPredictor::try_predict_next_n_leaders`], but surfaces slot tracker failures
+    /// instead of returning an empty prediction.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PredictError::SlotTrackerClosed`] if the slot tracker background task has exited,
+    /// or [`PredictError::StaleSlot`] if the slot has not been updated within [`YellowstoneUpcomingLeader::max_slot_age`].
+    ///
+    pub fn try_predict_next_n_leaders_checked(
+        &self,
+        n: usize,
+    ) -> Result<Vec<Pubkey>, PredictError> {
+        self.predict_leaders_with_slots(n, false, None)
+            .map(|leaders| leaders.into_iter().map(|(leader, _)| leader).collect())
+    }
+
+    ///
+    /// Lazily predicts up to `n` leaders, like [`UpcomingLeaderPredictor::try_predict_next_n_leaders`], looking up the
+    /// schedule boundary by boundary as the iterator advances.
+    ///
+    /// Callers that only need the first matching leader, e.g. the first reachable one, can stop early without
+    /// looking up the rest of the window.
+    /// The schedule lock is only held during each lookup, never across yields: a schedule swapped in between two
+    /// calls to `next` applies to the remaining leaders.
+    ///
+    /// Yields nothing if the slot tracker is closed or stale.
+    ///
+    pub fn predict_leaders_iter(&self, n: usize) -> impl Iterator<Item = Pubkey> + '_ {
+        let slots_per_leader = self.slots_per_leader;
+        let start_boundary = if n == 0 || slots_per_leader == 0 {
+            None
+        } else {
+            match self.load_prediction_slot() {
+                Ok(slot) => Some(self.window_start_boundary(slot - slot % slots_per_leader)),
+                Err(e) => {
+                    tracing::warn!("[YellowstoneUpcomingLeader] Failed to predict leaders: {e}");
+                    None
+                }
+            }
+        };
+        let distinct = self.dedup;
+        let max_scanned_boundaries = if distinct {
+            n.saturating_mul(MAX_DISTINCT_SCAN_FACTOR)
+        } else {
+            n
+        } as u64;
+        // Last yielded leader and the end of its slot range, to collapse consecutive rotations.
+        let mut last: Option<(Pubkey, Slot)> = None;
+        start_boundary
+            .into_iter()
+            .flat_map(move |start_boundary| {
+                (0..max_scanned_boundaries)
+                    .map(move |i| start_boundary.saturating_add(i * slots_per_leader))
+            })
+            .filter_map(move |leader_slot_boundary| {
+                let leader = self.get_leader_at_boundary(leader_slot_boundary)?;
+                let consecutive = last.is_some_and(|(last_leader, last_end)| {
+                    last_leader == leader && last_end == leader_slot_boundary
+                });
+                last = Some((leader, leader_slot_boundary + slots_per_leader));
+                (!distinct || !consecutive).then_some(leader)
+            })
+            .take(n)
+    }
+
+    ///
+    /// Predicts the next `n` leaders, each paired with the `[boundary, boundary + slots_per_leader)`
+    /// slot range during which it is scheduled.
+    ///
+    /// With the default look-behind, the first entry is the previous leader (n-1) and its range ends at
+    /// the current leader boundary, which lets callers detect that it is already expiring.
+    ///
+    /// Returns an empty vec if the slot tracker is closed or stale.
+    ///
+    pub fn try_predict_next_n_leaders_with_slots(&self, n: usize) -> Vec<(Pubkey, Range<Slot>)> {
+        match self.predict_leaders_with_slots(n, false, None) {
+            Ok(leaders) => leaders,
+            Err(e) => {
+                tracing::warn!("[YellowstoneUpcomingLeader] Failed to predict leaders: {e}");
+                Vec::new()
+            }
+        }
+    }
+
+    ///
+    /// Predicts up to `n` leaders, collapsing runs of the same leader holding consecutive rotations.
+    ///
+    /// Scans at most `n * MAX_DISTINCT_SCAN_FACTOR` leader boundaries to fill the `n` entries.
+    ///
+    /// Returns an empty vec if the slot tracker is closed or stale.
+    ///
+    pub fn try_predict_distinct_leaders(&self, n: usize) -> Vec<Pubkey> {
+        match self.predict_leaders_with_slots(n, true, None) {
+            Ok(leaders) => leaders.into_iter().map(|(leader, _)| leader).collect(),
+            Err(e) => {
+                tracing::warn!("[YellowstoneUpcomingLeader] Failed to predict leaders: {e}");
+                Vec::new()
+            }
+        }
+    }
+
+    ///
+    /// Predicts up to `n` leaders, like [`UpcomingLeaderPredictor::try_predict_next_n_leaders`], skipping the leaders
+    /// of [`YellowstoneUpcomingLeader::deny_list`].
+    ///
+    /// Scans at most `n * MAX_DISTINCT_SCAN_FACTOR` leader boundaries to replace the denied leaders, e.g. to avoid
+    /// known-bad validators during an incident.
+    ///
+    /// Returns an empty vec if the slot tracker is closed or stale.
+    ///
+    pub fn try_predict_next_n_leaders_filtered(&self, n: usize) -> Vec<Pubkey> {
+        let denied = self.deny_list.load();
+        let denied = (!denied.is_empty()).then_some(denied.as_ref());
+        match self.predict_leaders_with_slots(n, self.dedup, denied) {
+            Ok(leaders) => leaders.into_iter().map(|(leader, _)| leader).collect(),
+            Err(e) => {
+                tracing::warn!("[YellowstoneUpcomingLeader] Failed to predict leaders: {e}");
+                Vec::new()
+            }
+        }
+    }
+
+    ///
+    /// Predicts the next `n` leaders as if the current slot were `slot`, without reading or advancing the slot
+    /// tracker, e.g. for simulation, backtesting or what-if analysis.
+    ///
+    /// Computes the same window as [`UpcomingLeaderPredictor::try_predict_next_n_leaders`] does from the tracked
+    /// slot, collapsing consecutive rotations when `dedup` is enabled.
+    /// The slot age and estimation options don't apply since the slot is given.
+    ///
+    pub fn try_predict_next_n_leaders_from(&self, slot: Slot, n: usize) -> Vec<Pubkey> {
+        self.leaders_from_slot(slot, n, self.dedup, None)
+            .0
+            .into_iter()
+            .map(|(leader, _)| leader)
+            .collect()
+    }
+
+    ///
+    /// Returns the retained predictions, most recent first, see
+    /// [`YellowstoneUpcomingLeaderBuilder::recent_predictions_capacity`].
+    ///
+    /// Only the predictions computed from the tracked slot are retained, the what-if predictions of
+    /// [`YellowstoneUpcomingLeader::try_predict_next_n_leaders_from`] and the lazy
+    /// [`YellowstoneUpcomingLeader::predict_leaders_iter`] are not.
+    ///
+    pub fn recent_predictions(&self) -> Vec<PredictionRecord> {
+        self.recent_predictions.recent()
+    }
+
+    ///
+    /// Predicts the next `n` leaders, like [`UpcomingLeaderPredictor::try_predict_next_n_leaders`], along with the
+    /// confidence, between `0.0` and `1.0`, that each of them produces its blocks.
+    ///
+    /// The confidence derives from the recent skip rate of the leader over its slots of the current epoch, see
+    /// [`SkipRateTracker::confidence_of`]. Leaders without recorded slots, or every leader when
+    /// [`YellowstoneUpcomingLeader::skip_rates`] is `None`, get a neutral confidence.
+    /// Callers can deprioritize low-confidence leaders, e.g. when choosing which connection to warm first.
+    ///
+    pub fn try_predict_weighted(&self, n: usize) -> Vec<(Pubkey, f64)> {
+        let leaders = self.try_predict_next_n_leaders(n);
+        let Some(skip_rates) = &self.skip_rates else {
+            return leaders.into_iter().map(|leader| (leader, 1.0)).collect();
+        };
+        leaders
+            .into_iter()
+            .map(|leader| {
+                let slots = self
+                    .managed_schedule
+                    .slots_for_leader(&leader)
+                    .unwrap_or_default();
+                (leader, skip_rates.confidence_of(&slots))
+            })
+            .collect()
+    }
+
+    ///
+    /// Returns a stream of the next `n` predicted leaders, see [`UpcomingLeaderPredictor::try_predict_next_n_leaders`].
+    ///
+    /// The stream yields the current window first, then a new window each time the tracked slot crosses a
+    /// leader boundary. Slot updates within the same leader rotation are debounced and don't emit.
+    ///
+    /// The stream is driven by [`AtomicSlotTracker::subscribe`] and holds a clone of the predictor, it does not end
+    /// on its own: drop it to unsubscribe.
+    /// With `slots_per_leader == 0`, only the initial (empty) window is emitted.
+    ///
+    pub fn window_stream(&self, n: usize) -> impl Stream<Item = Vec<Pubkey>> + Send + 'static {
+        let predictor = self.clone();
+        let slot_rx = self.slot_tracker.subscribe();
+        stream::unfold(
+            (predictor, slot_rx, None),
+            move |(predictor, mut slot_rx, last_boundary)| async move {
+                let boundary = match last_boundary {
+                    None => predictor.leader_boundary_index(*slot_rx.borrow_and_update()),
+                    Some(last_boundary) => loop {
+                        slot_rx.changed().await.ok()?;
+                        let boundary =
+                            predictor.leader_boundary_index(*slot_rx.borrow_and_update());
+                        if boundary != last_boundary {
+                            break boundary;
+                        }
+                    },
+                };
+                let window = predictor.try_predict_next_n_leaders(n);
+                Some((window, (predictor, slot_rx, Some(boundary))))
+            },
+        )
+    }
+
+    fn leader_boundary_index(&self, slot: Slot) -> Slot {
+        slot.checked_div(self.slots_per_leader).unwrap_or_default()
+    }
+
+    ///
+    /// Predicts the next `n` leaders, like [`UpcomingLeaderPredictor::try_predict_next_n_leaders`], and resolves
+    /// their TPU QUIC socket address through `tpu_info` (e.g. a [`crate::rpc::contact::ContactInfoTable`]).
+    ///
+    /// Leaders whose address is unknown are dropped from the result.
+    ///
+    pub fn predict_next_n_tpu_addrs<T>(&self, n: usize, tpu_info: &T) -> Vec<(Pubkey, SocketAddr)>
+    where
+        T: LeaderTpuInfoService + ?Sized,
+    {
+        self.try_predict_next_n_leaders(n)
+            .into_iter()
+            .filter_map(|leader| match tpu_info.get_quic_tpu_socket_addr(&leader) {
+                Some(addr) => Some((leader, addr)),
+                None => {
+                    tracing::debug!(
+                        "[YellowstoneUpcomingLeader] Unknown TPU QUIC address for predicted leader {}",
+                        leader
+                    );
+                    None
+                }
+            })
+            .collect()
+    }
+
+    ///
+    /// Predicts the next `n` leaders and resolves both their TPU and TPU forwards QUIC socket addresses.
+    ///
+    /// Unlike [`YellowstoneUpcomingLeader::predict_next_n_tpu_addrs`], each port is kept separately so callers can
+    /// route each target to the right port. Leaders advertising neither port are dropped from the result.
+    ///
+    pub fn predict_next_n_tpu_contact_infos<T>(
+        &self,
+        n: usize,
+        tpu_info: &T,
+    ) -> Vec<(Pubkey, RpcTpuQuicContactInfo)>
+    where
+        T: LeaderTpuInfoService + ?Sized,
+    {
+        self.try_predict_next_n_leaders(n)
+            .into_iter()
+            .filter_map(|leader| {
+                let contact_info = RpcTpuQuicContactInfo {
+                    tpu_quic: tpu_info.get_quic_tpu_socket_addr(&leader),
+                    tpu_forwards_quic: tpu_info.get_quic_tpu_fwd_socket_addr(&leader),
+                };
+                if contact_info.tpu_quic.is_none() && contact_info.tpu_forwards_quic.is_none() {
+                    tracing::debug!(
+                        "[YellowstoneUpcomingLeader] Unknown TPU QUIC addresses for predicted leader {}",
+                        leader
+                    );
+                    return None;
+                }
+                Some((leader, contact_info))
+            })
+            .collect()
+    }
+
+    fn predict_leaders_with_slots(
+        &self,
+        n: usize,
+        distinct: bool,
+        denied: Option<&HashSet<Pubkey>>,
+    ) -> Result<Vec<(Pubkey, Range<Slot>)>, PredictError> {
+        let span = tracing::debug_span!(
+            "predict_leaders",
+            n,
+            distinct,
+            slot = tracing::field::Empty,
+            current_leader_boundary = tracing::field::Empty,
+            start_boundary = tracing::field::Empty,
+            look_behind = self.look_behind,
+        );
+        let result = span.in_scope(|| self.compute_leaders_with_slots(n, distinct, denied));
+        #[cfg(feature = "metrics")]
+        {
+            metrics::set_slot_tracker_age(self.slot_tracker.age());
+            match &result {
+                Ok(leaders) => metrics::incr_leader_prediction_served(leaders.len()),
+                Err(PredictError::SlotTrackerClosed(_)) => {
+                    metrics::incr_leader_prediction_error("slot_tracker_closed")
+                }
+                Err(PredictError::SlotTrackerNotReady) => {
+                    metrics::incr_leader_prediction_error("slot_tracker_not_ready")
+                }
+                Err(PredictError::StaleSlot { .. }) => {
+                    metrics::incr_leader_prediction_error("stale_slot")
+                }
+                Err(PredictError::NoSlotAtCommitment(_)) => {
+                    metrics::incr_leader_prediction_error("no_slot_at_commitment")
+                }
+            }
+        }
+        result
+    }
+
+    fn compute_leaders_with_slots(
+        &self,
+        n: usize,
+        distinct: bool,
+        denied: Option<&HashSet<Pubkey>>,
+    ) -> Result<Vec<(Pubkey, Range<Slot>)>, PredictError> {
+        if n == 0 || self.slots_per_leader == 0 {
+            return Ok(Vec::new());
+        }
+        let slot = self.load_prediction_slot()?;
+        if denied.is_some() {
+            return Ok(self.recorded_leaders_from_slot(slot, n, distinct, denied));
+        }
+        Ok(self.cached_leaders_from_slot(slot, n, distinct))
+    }
+
+    ///
+    /// Loads the slot driving predictions, honoring the commitment, slot age and estimation options.
+    ///
+    fn load_prediction_slot(&self) -> Result<Slot, PredictError> {
+        let slot = self.slot_tracker.load();
+        if self.slot_tracker.is_closed() {
+            return Err(PredictError::SlotTrackerClosed(slot));
+        }
+        if !self.slot_tracker.is_ready() {
+            return Err(PredictError::SlotTrackerNotReady);
+        }
+        let slot = match self.prediction_commitment {
+            CommitmentLevel::Processed => slot,
+            commitment => self
+                .slot_tracker
+                .load_commitment(commitment)
+                .ok_or(PredictError::NoSlotAtCommitment(commitment))?,
+        };
+        if let Some(max_age) = self.max_slot_age {
+            let age = self.slot_tracker.age();
+            if age > max_age {
+                return Err(PredictError::StaleSlot { slot, age, max_age });
+            }
+        }
+        let slot = if self.use_estimated_slot {
+            self.slot_tracker.estimate_from(slot)
+        } else {
+            slot
+        };
+        Ok(slot)
+    }
+
+    ///
+    /// First slot of the first epoch past the [`YellowstoneUpcomingLeader::max_future_epochs`] horizon of `slot`.
+    ///
+    /// Epochs follow the epoch schedule of the slot tracker, see [`AtomicSlotTracker::epoch_info_at`].
+    ///
+    fn horizon_end(&self, slot: Slot) -> Slot {
+        let epoch = self.slot_tracker.epoch_info_at(slot).epoch;
+        self.slot_tracker.first_slot_in_epoch(
+            epoch
+                .saturating_add(self.max_future_epochs)
+                .saturating_add(1),
+        )
+    }
+
+    ///
+    /// First slot of the prediction window of the leader starting at `current_leader_boundary`, the boundary
+    /// [`YellowstoneUpcomingLeader::look_behind`] leaders before it.
+    ///
+    /// Clamped to slot 0 near genesis, see the [module documentation](self): slot 0 is the first boundary of the
+    /// window then, and is scanned only once.
+    ///
+    fn window_start_boundary(&self, current_leader_boundary: Slot) -> Slot {
+        current_leader_boundary
+            .saturating_sub((self.look_behind as u64).saturating_mul(self.slots_per_leader))
+    }
+
+    ///
+    /// Predicts up to `n` leaders as if the current slot were `slot`, regardless of the slot tracker, along with the
+    /// breakdown of the missing leaders.
+    ///
+    /// Leaders in `denied` are skipped.
+    ///
+    fn leaders_from_slot(
+        &self,
+        slot: Slot,
+        n: usize,
+        distinct: bool,
+        denied: Option<&HashSet<Pubkey>>,
+    ) -> (Vec<(Pubkey, Range<Slot>)>, PredictionShortfall) {
+        if n == 0 || self.slots_per_leader == 0 {
+            return Default::default();
+        }
+
+        let slots_per_leader = self.slots_per_leader;
+        let reminder = slot % slots_per_leader;
 
         // Calculate the current leader's slot boundary
         let current_leader_boundary = slot.saturating_sub(reminder);
 
-        // Start from the PREVIOUS leader (n-1)
+        // Start from the PREVIOUS leader(s) (n-look_behind)
         // This ensures we have a connection even if the current leader is almost done
-        let start_boundary = current_leader_boundary.saturating_sub(4);
+        let start_boundary = self.window_start_boundary(current_leader_boundary);
 
+        let span = tracing::Span::current();
+        if !span.is_disabled() {
+            span.record("slot", slot);
+            span.record("current_leader_boundary", current_leader_boundary);
+            span.record("start_boundary", start_boundary);
+        }
         tracing::debug!(
-            "[YellowstoneUpcomingLeader] Predicting {} leaders starting from slot {} (current_slot={}, reminder={}/4, current_boundary={}, previous_boundary={})",
+            "[YellowstoneUpcomingLeader] Predicting {} leaders starting from slot {} (current_slot={}, reminder={}/{}, current_boundary={}, look_behind={})",
             n,
             start_boundary,
             slot,
             reminder,
+            slots_per_leader,
             current_leader_boundary,
-            start_boundary
-        );
-
-        // Generate n leaders starting from previous leader
-        // This gives us: n-1, n, n+1, ..., n+(n-2)
-        let leaders: Vec<Pubkey> = (0..n)
-            .map(|i| start_boundary + (i * 4) as u64)
-            .filter_map(|leader_slot_boundary| {
-                match self.managed_schedule.get_leader(leader_slot_boundary) {
-                    Ok(Some(leader)) => {
-                        tracing::trace!(
-                            "[YellowstoneUpcomingLeader] Predicted leader at slot_boundary={}: {}",
-                            leader_slot_boundary,
-                            leader
-                        );
-                        Some(leader)
-                    }
-                    Ok(None) => {
-                        tracing::debug!(
-                            "[YellowstoneUpcomingLeader] No leader found for slot_boundary={}",
-                            leader_slot_boundary
-                        );
-                        None
+            self.look_behind
+        );
+
+        // Generate n leaders starting from the first look-behind leader
+        // With the default look-behind, this gives us: n-1, n, n+1, ..., n+(n-2)
+        // When collapsing runs of the same leader, or skipping denied leaders, we need to scan further ahead to fill
+        // `n` entries.
+        let max_scanned_boundaries = if distinct || denied.is_some() {
+            n.saturating_mul(MAX_DISTINCT_SCAN_FACTOR)
+        } else {
+            n
+        };
+        let horizon_end = self.horizon_end(slot);
+        // Visit the whole window under a single schedule lock, without an intermediate lookup vec.
+        let mut leaders: Vec<(Pubkey, Range<Slot>)> = Vec::with_capacity(n);
+        let mut schedule_gaps = 0usize;
+        let mut outside_schedule = 0usize;
+        let mut denied_leaders = 0usize;
+        // Boundary at which the prediction stopped, past the horizon or the loaded schedules.
+        let mut truncated_at = None;
+        let visited = self.managed_schedule.visit_leaders_for_range(
+            start_boundary,
+            max_scanned_boundaries,
+            slots_per_leader,
+            |leader_slot_boundary, lookup| {
+                if leader_slot_boundary >= horizon_end
+                    || matches!(lookup, LeaderLookup::PastKnownSchedule)
+                {
+                    truncated_at = Some(leader_slot_boundary);
+                    return ControlFlow::Break(());
+                }
+                match lookup {
+                    LeaderLookup::Found(_) => {}
+                    LeaderLookup::Gap => schedule_gaps += 1,
+                    LeaderLookup::PastKnownSchedule | LeaderLookup::BeforeKnownSchedule => {
+                        outside_schedule += 1
                     }
-                    Err(e) => {
-                        tracing::warn!(
-                            "[YellowstoneUpcomingLeader] Failed to get leader for slot_boundary={}: {:?}",
-                            leader_slot_boundary,
-                            e
-                        );
-                        None
+                }
+                let Some(leader) = Self::leader_from_lookup(leader_slot_boundary, lookup) else {
+                    return ControlFlow::Continue(());
+                };
+                if denied.is_some_and(|denied| denied.contains(&leader)) {
+                    denied_leaders += 1;
+                    tracing::debug!(
+                        "[YellowstoneUpcomingLeader] Skipping deny-listed leader {} at slot_boundary={}",
+                        leader,
+                        leader_slot_boundary
+                    );
+                    return ControlFlow::Continue(());
+                }
+                let leader_slots = leader_slot_boundary..leader_slot_boundary + slots_per_leader;
+                let is_full = leaders.len() == n;
+                if distinct {
+                    match leaders.last_mut() {
+                        Some((last_leader, last_leader_slots))
+                            if *last_leader == leader
+                                && last_leader_slots.end == leader_slot_boundary =>
+                        {
+                            // Same leader holding consecutive rotations, extend its slot range.
+                            last_leader_slots.end = leader_slots.end;
+                            return ControlFlow::Continue(());
+                        }
+                        _ => {}
                     }
                 }
-            })
-            .collect();
-
-        tracing::debug!(
-            "[YellowstoneUpcomingLeader] Successfully predicted {}/{} leaders",
-            leaders.len(),
-            n
+                if is_full {
+                    return ControlFlow::Break(());
+                }
+                leaders.push((leader, leader_slots));
+                ControlFlow::Continue(())
+            },
         );
+        if let Err(e) = visited {
+            #[cfg(feature = "metrics")]
+            {
+                metrics::incr_leader_prediction_error("schedule_lookup");
+            }
+            tracing::warn!(
+                "[YellowstoneUpcomingLeader] Failed to get leaders from slot_boundary={}: {:?}",
+                start_boundary,
+                e
+            );
+            return (
+                Vec::new(),
+                PredictionShortfall {
+                    missing: n,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let missing = n.saturating_sub(leaders.len());
+        let truncated = truncated_at.filter(|_| missing > 0).map(|truncated_at| {
+            if truncated_at >= horizon_end {
+                PredictionTruncation::Horizon(truncated_at)
+            } else {
+                PredictionTruncation::LoadedSchedules(truncated_at)
+            }
+        });
+        if let Some(truncated) = truncated {
+            #[cfg(feature = "metrics")]
+            {
+                metrics::incr_leader_prediction_truncated(missing);
+            }
+            let (truncated_at, reason) = match truncated {
+                PredictionTruncation::Horizon(at) => (at, "the future epochs horizon"),
+                PredictionTruncation::LoadedSchedules(at) => (at, "the loaded leader schedules"),
+            };
+            tracing::debug!(
+                "[YellowstoneUpcomingLeader] Truncated {} of {} leaders at slot_boundary={}, past {}",
+                missing,
+                n,
+                truncated_at,
+                reason
+            );
+        } else if missing > PREDICTION_SHORTFALL_WARN_THRESHOLD {
+            #[cfg(feature = "metrics")]
+            {
+                metrics::incr_leader_prediction_shortfall();
+            }
+            tracing::warn!(
+                "[YellowstoneUpcomingLeader] Predicted only {}/{} leaders from slot_boundary={}: {} schedule gap(s), {} boundary(ies) outside the known schedule",
+                leaders.len(),
+                n,
+                start_boundary,
+                schedule_gaps,
+                outside_schedule
+            );
+        } else {
+            tracing::debug!(
+                "[YellowstoneUpcomingLeader] Successfully predicted {}/{} leaders",
+                leaders.len(),
+                n
+            );
+        }
 
+        let shortfall = PredictionShortfall {
+            missing,
+            schedule_gaps,
+            outside_schedule,
+            denied: denied_leaders,
+            truncated,
+        };
+        (leaders, shortfall)
+    }
+
+    ///
+    /// Predicts like [`YellowstoneUpcomingLeader::leaders_from_slot`] and retains the prediction in
+    /// [`YellowstoneUpcomingLeader::recent_predictions`].
+    ///
+    fn recorded_leaders_from_slot(
+        &self,
+        slot: Slot,
+        n: usize,
+        distinct: bool,
+        denied: Option<&HashSet<Pubkey>>,
+    ) -> Vec<(Pubkey, Range<Slot>)> {
+        let (leaders, shortfall) = self.leaders_from_slot(slot, n, distinct, denied);
+        self.prefetch_if_truncated(slot, &shortfall);
+        self.recent_predictions.record(slot, n, &leaders, shortfall);
+        leaders
+    }
+
+    ///
+    /// Asks the managed schedule to load the epoch past the loaded schedules if the prediction from the tracked
+    /// `slot` stopped at them, see [`ManagedLeaderSchedule::trigger_prefetch`].
+    ///
+    fn prefetch_if_truncated(&self, slot: Slot, shortfall: &PredictionShortfall) {
+        if matches!(
+            shortfall.truncated,
+            Some(PredictionTruncation::LoadedSchedules(_))
+        ) {
+            self.managed_schedule.trigger_prefetch(slot);
+        }
+    }
+
+    ///
+    /// Predicts like [`YellowstoneUpcomingLeader::recorded_leaders_from_slot`] without deny-list, served from the
+    /// prediction cache while the current leader boundary and the schedules stay the same.
+    ///
+    fn cached_leaders_from_slot(
+        &self,
+        slot: Slot,
+        n: usize,
+        distinct: bool,
+    ) -> Vec<(Pubkey, Range<Slot>)> {
+        let key = PredictionKey {
+            current_leader_boundary: slot - slot % self.slots_per_leader,
+            horizon_end: self.horizon_end(slot),
+            n,
+            distinct,
+            look_behind: self.look_behind,
+            slots_per_leader: self.slots_per_leader,
+            // Loaded before looking up the schedules: a concurrent refresh can only make the entry miss.
+            schedule_generation: self.managed_schedule.generation(),
+        };
+        let (leaders, shortfall) = match self.prediction_cache.get(&key) {
+            Some(cached) => {
+                #[cfg(feature = "metrics")]
+                {
+                    metrics::incr_leader_prediction_cache_hit();
+                }
+                cached
+            }
+            None => {
+                let (leaders, shortfall) = self.leaders_from_slot(slot, n, distinct, None);
+                self.prefetch_if_truncated(slot, &shortfall);
+                // Failed lookups, e.g. on a poisoned schedule, are retried by the next prediction.
+                if !leaders.is_empty() {
+                    self.prediction_cache.store(key, &leaders, &shortfall);
+                }
+                (leaders, shortfall)
+            }
+        };
+        self.recent_predictions.record(slot, n, &leaders, shortfall);
         leaders
     }
 }
+
+impl UpcomingLeaderPredictor for YellowstoneUpcomingLeader {
+    fn try_predict_next_n_leaders(&self, n: usize) -> Vec<Pubkey> {
+        if self.dedup {
+            return self.try_predict_distinct_leaders(n);
+        }
+        self.try_predict_next_n_leaders_with_slots(n)
+            .into_iter()
+            .map(|(leader, _)| leader)
+            .collect()
+    }
+
+    ///
+    /// Predicts a window `look_behind` leaders longer and drops the rotations ending before the current leader
+    /// boundary: fewer are dropped near genesis, see the [module documentation](self).
+    ///
+    fn try_predict_upcoming_leaders(&self, n: usize) -> Vec<Pubkey> {
+        if n == 0 || self.slots_per_leader == 0 {
+            return Vec::new();
+        }
+        let slot = match self.load_prediction_slot() {
+            Ok(slot) => slot,
+            Err(e) => {
+                tracing::warn!("[YellowstoneUpcomingLeader] Failed to predict leaders: {e}");
+                return Vec::new();
+            }
+        };
+        let current_leader_boundary = slot - slot % self.slots_per_leader;
+        self.cached_leaders_from_slot(slot, n.saturating_add(self.look_behind), self.dedup)
+            .into_iter()
+            .filter(|(_, slots)| slots.end > current_leader_boundary)
+            .take(n)
+            .map(|(leader, _)| leader)
+            .collect()
+    }
+
+    fn try_predict_leader_window(&self, n: usize) -> Vec<PredictedLeader> {
+        match self.predict_leaders_with_slots(n, self.dedup, None) {
+            Ok(leaders) => leaders
+                .into_iter()
+                .map(|(leader, slots)| PredictedLeader {
+                    leader,
+                    slots: Some(slots),
+                })
+                .collect(),
+            Err(e) => {
+                tracing::warn!("[YellowstoneUpcomingLeader] Failed to predict leaders: {e}");
+                Vec::new()
+            }
+        }
+    }
+
+    ///
+    /// Looks up exactly the rotations from the first predicted leader to `end_slot` under a single schedule lock,
+    /// bounded by [`YellowstoneUpcomingLeader::max_future_epochs`].
+    ///
+    fn predict_until_slot(&self, end_slot: Slot) -> Vec<(Pubkey, Range<Slot>)> {
+        let slots_per_leader = self.slots_per_leader;
+        if slots_per_leader == 0 {
+            return Vec::new();
+        }
+        let slot = match self.load_prediction_slot() {
+            Ok(slot) => slot,
+            Err(e) => {
+                tracing::warn!("[YellowstoneUpcomingLeader] Failed to predict leaders: {e}");
+                return Vec::new();
+            }
+        };
+        let start_boundary = self.window_start_boundary(slot - slot % slots_per_leader);
+        let end_slot = end_slot.min(self.horizon_end(slot).saturating_sub(1));
+        let Some(rotations) = end_slot
+            .checked_sub(start_boundary)
+            .map(|slots| slots / slots_per_leader + 1)
+        else {
+            return Vec::new();
+        };
+        let rotations = usize::try_from(rotations).unwrap_or(usize::MAX);
+        collapse_leader_runs(self.recorded_leaders_from_slot(slot, rotations, false, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            clock::{Clock, MockClock, SystemClock},
+            rpc::{contact::ContactInfoTable, schedule::CompactSortedSchedule},
+        },
+        futures::{FutureExt, StreamExt},
+        solana_clock::DEFAULT_SLOTS_PER_EPOCH,
+        solana_epoch_schedule::EpochSchedule,
+    };
+
+    ///
+    /// Builds a schedule where every 4-slot block of the epoch has its own unique leader.
+    ///
+    fn unique_leaders_schedule(first_slot: u64) -> (CompactSortedSchedule, Vec<Pubkey>) {
+        let leaders = (0..DEFAULT_SLOTS_PER_EPOCH / 4)
+            .map(|_| Pubkey::new_unique())
+            .collect::<Vec<_>>();
+        (
+            CompactSortedSchedule::new(first_slot, leaders.clone()),
+            leaders,
+        )
+    }
+
+    fn predictor_at_slot(
+        slot: u64,
+        slots_per_leader: u64,
+    ) -> (YellowstoneUpcomingLeader, Vec<Pubkey>) {
+        predictor_at_slot_with_clock(slot, slots_per_leader, Arc::new(SystemClock))
+    }
+
+    fn predictor_at_slot_with_clock(
+        slot: u64,
+        slots_per_leader: u64,
+        clock: Arc<dyn Clock + Send + Sync>,
+    ) -> (YellowstoneUpcomingLeader, Vec<Pubkey>) {
+        let (current, leaders) = unique_leaders_schedule(0);
+        let (next, _) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        let predictor = YellowstoneUpcomingLeader::with_slots_per_leader(
+            Arc::new(AtomicSlotTracker::new(slot).with_clock(clock)),
+            ManagedLeaderSchedule::from_schedules(current, next),
+            slots_per_leader,
+        );
+        (predictor, leaders)
+    }
+
+    #[test]
+    fn it_should_build_with_validated_options() {
+        let (current, _) = unique_leaders_schedule(0);
+        let (next, _) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        let builder = YellowstoneUpcomingLeader::builder(
+            Arc::new(AtomicSlotTracker::new(10)),
+            ManagedLeaderSchedule::from_schedules(current, next),
+        );
+
+        let predictor = builder.clone().build().expect("default options");
+        assert_eq!(predictor.slots_per_leader, NUM_CONSECUTIVE_LEADER_SLOTS);
+        assert_eq!(predictor.look_behind, DEFAULT_LOOK_BEHIND);
+        assert_eq!(predictor.max_slot_age, None);
+        assert!(!predictor.dedup);
+        assert!(!predictor.use_estimated_slot);
+        assert_eq!(predictor.prediction_commitment, CommitmentLevel::Processed);
+        assert_eq!(predictor.max_future_epochs, DEFAULT_MAX_FUTURE_EPOCHS);
+
+        let predictor = builder
+            .clone()
+            .slots_per_leader(2)
+            .look_behind(0)
+            .dedup(true)
+            .max_slot_age(Some(DEFAULT_MAX_SLOT_AGE))
+            .use_estimated_slot(true)
+            .max_future_epochs(0)
+            .build()
+            .expect("custom options");
+        assert_eq!(predictor.slots_per_leader, 2);
+        assert_eq!(predictor.look_behind, 0);
+        assert_eq!(predictor.max_slot_age, Some(DEFAULT_MAX_SLOT_AGE));
+        assert!(predictor.dedup);
+        assert!(predictor.use_estimated_slot);
+        assert_eq!(predictor.max_future_epochs, 0);
+
+        assert!(matches!(
+            builder.clone().slots_per_leader(0).build(),
+            Err(BuildUpcomingLeaderError::ZeroSlotsPerLeader)
+        ));
+        assert!(matches!(
+            builder.max_slot_age(Some(Duration::ZERO)).build(),
+            Err(BuildUpcomingLeaderError::ZeroMaxSlotAge)
+        ));
+    }
+
+    #[test]
+    fn it_should_default_to_num_consecutive_leader_slots() {
+        let (current, _) = unique_leaders_schedule(0);
+        let (next, _) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        let predictor = YellowstoneUpcomingLeader::new(
+            Arc::new(AtomicSlotTracker::new(10)),
+            ManagedLeaderSchedule::from_schedules(current, next),
+        );
+        assert_eq!(predictor.slots_per_leader, NUM_CONSECUTIVE_LEADER_SLOTS);
+    }
+
+    #[test]
+    fn it_should_predict_with_rotation_width_of_2() {
+        // slot 21 -> current boundary 20, previous boundary 18
+        let (predictor, leaders) = predictor_at_slot(21, 2);
+        let actual = predictor.try_predict_next_n_leaders(3);
+        // slots 18, 20, 22 fall into the 4-slot blocks 4, 5 and 5.
+        assert_eq!(actual, vec![leaders[4], leaders[5], leaders[5]]);
+    }
+
+    #[test]
+    fn it_should_predict_with_rotation_width_of_8() {
+        // slot 20 -> current boundary 16, previous boundary 8
+        let (predictor, leaders) = predictor_at_slot(20, 8);
+        let actual = predictor.try_predict_next_n_leaders(3);
+        // slots 8, 16, 24 fall into the 4-slot blocks 2, 4 and 6.
+        assert_eq!(actual, vec![leaders[2], leaders[4], leaders[6]]);
+    }
+
+    #[test]
+    fn it_should_predict_window_from_arbitrary_slot() {
+        // The tracked slot is far from the simulated ones and must not matter.
+        let (predictor, leaders) = predictor_at_slot(2_000, 4);
+        for (slot, current_block) in [(0, 0), (5, 1), (23, 5), (100, 25), (1_003, 250)] {
+            // Window n-1..n+(n-2) around the current leader of `slot`, saturating at genesis.
+            let first_block = current_block.saturating_sub(1);
+            let expected = leaders[first_block..first_block + 4].to_vec();
+            assert_eq!(
+                predictor.try_predict_next_n_leaders_from(slot, 4),
+                expected,
+                "slot {slot}"
+            );
+        }
+        assert_eq!(predictor.slot_tracker.load(), 2_000);
+
+        // The tracked prediction is the same window computed from the loaded slot.
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(4),
+            predictor.try_predict_next_n_leaders_from(2_000, 4)
+        );
+    }
+
+    #[test]
+    fn it_should_predict_from_arbitrary_slot_even_when_slot_tracker_is_closed() {
+        let (predictor, leaders) = predictor_at_slot(20, 4);
+        predictor.slot_tracker.close();
+        assert!(predictor.try_predict_next_n_leaders(3).is_empty());
+        assert_eq!(
+            predictor.try_predict_next_n_leaders_from(42, 3),
+            vec![leaders[9], leaders[10], leaders[11]]
+        );
+    }
+
+    #[test]
+    fn it_should_rank_weighted_predictions_by_skip_rate() {
+        // slot 21 -> blocks 4, 5, 6 and 7 with the default look-behind
+        let (mut predictor, leaders) = predictor_at_slot(21, 4);
+        let unweighted = predictor.try_predict_weighted(4);
+        assert_eq!(
+            unweighted,
+            leaders[4..8]
+                .iter()
+                .map(|leader| (*leader, 1.0))
+                .collect::<Vec<_>>()
+        );
+
+        let skip_rates = Arc::new(SkipRateTracker::default());
+        // Leader of block 4 produced every slot, 16 to 19.
+        for slot in 16..20 {
+            skip_rates.record_block(slot, slot - 1);
+        }
+        // Leader of block 5 skipped every slot, 20 to 23, then leader of block 6 produced slot 24.
+        skip_rates.record_block(24, 19);
+        predictor.skip_rates = Some(skip_rates);
+
+        let weighted = predictor.try_predict_weighted(4);
+        assert_eq!(
+            weighted
+                .iter()
+                .map(|(leader, _)| *leader)
+                .collect::<Vec<_>>(),
+            leaders[4..8].to_vec()
+        );
+        let confidence = weighted
+            .iter()
+            .map(|(_, confidence)| *confidence)
+            .collect::<Vec<_>>();
+        assert_eq!(confidence[0], 1.0);
+        assert_eq!(confidence[1], 0.0);
+        assert_eq!(confidence[2], 1.0);
+        // No slot of block 7 was observed, it gets the cluster skip rate: 4 skipped out of 9 slots.
+        assert_eq!(confidence[3], 1.0 - 4.0 / 9.0);
+        assert!(confidence[0] > confidence[3] && confidence[3] > confidence[1]);
+    }
+
+    #[test]
+    fn it_should_share_mutable_state_between_clones() {
+        let (mut predictor, leaders) = predictor_at_slot(21, 4);
+        predictor.skip_rates = Some(Arc::new(SkipRateTracker::default()));
+        let mut clone = predictor.clone();
+
+        predictor.slot_tracker.store(25);
+        assert_eq!(clone.slot_tracker.load(), 25);
+        assert_eq!(
+            clone.try_predict_next_n_leaders(2),
+            vec![leaders[5], leaders[6]]
+        );
+
+        // Leader of block 6 skipped slots 24 to 27.
+        clone
+            .skip_rates
+            .as_ref()
+            .expect("skip rates")
+            .record_block(28, 23);
+        assert_eq!(
+            predictor.try_predict_weighted(2),
+            vec![(leaders[5], 1.0 - 4.0 / 5.0), (leaders[6], 0.0)]
+        );
+
+        // Options stay per-clone.
+        clone.look_behind = 0;
+        assert_eq!(predictor.look_behind, DEFAULT_LOOK_BEHIND);
+
+        // Closing the shared slot tracker is visible through every clone.
+        clone.slot_tracker.close();
+        assert!(predictor.try_predict_next_n_leaders(2).is_empty());
+    }
+
+    #[test]
+    fn it_should_cache_the_prediction_within_a_leader_rotation() {
+        let (predictor, leaders) = predictor_at_slot(20, 4);
+        let cache = Arc::clone(&predictor.prediction_cache);
+        let counts = || {
+            (
+                cache.hits.load(Ordering::Relaxed),
+                cache.misses.load(Ordering::Relaxed),
+            )
+        };
+        let expected = vec![leaders[4], leaders[5], leaders[6]];
+        assert_eq!(predictor.try_predict_next_n_leaders(3), expected);
+        assert_eq!(counts(), (0, 1));
+
+        // Same leader boundary, from every clone.
+        predictor.slot_tracker.store(23);
+        assert_eq!(predictor.try_predict_next_n_leaders(3), expected);
+        assert_eq!(predictor.clone().try_predict_next_n_leaders(3), expected);
+        assert_eq!(counts(), (2, 1));
+
+        // Another window size or option is another prediction.
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(2),
+            expected[..2].to_vec()
+        );
+        assert_eq!(counts(), (2, 2));
+
+        // The boundary advanced.
+        predictor.slot_tracker.store(24);
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(2),
+            vec![leaders[5], leaders[6]]
+        );
+        assert_eq!(counts(), (2, 3));
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(2),
+            vec![leaders[5], leaders[6]]
+        );
+        assert_eq!(counts(), (3, 3));
+
+        // The schedules were refreshed.
+        let (current, new_leaders) = unique_leaders_schedule(0);
+        let (next, _) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        let generation = predictor.managed_schedule.generation();
+        predictor
+            .managed_schedule
+            .set_static_schedules(current, next);
+        assert!(predictor.managed_schedule.generation() > generation);
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(2),
+            vec![new_leaders[5], new_leaders[6]]
+        );
+        assert_eq!(counts(), (3, 4));
+
+        // Deny-listed predictions bypass the cache.
+        predictor.deny_list.insert(new_leaders[5]);
+        assert_eq!(
+            predictor.try_predict_next_n_leaders_filtered(2),
+            vec![new_leaders[6], new_leaders[7]]
+        );
+        assert_eq!(counts(), (3, 4));
+    }
+
+    #[test]
+    fn it_should_skip_deny_listed_leaders() {
+        // slot 41 -> current block 10, window starts at the previous block 9
+        let (predictor, leaders) = predictor_at_slot(41, 4);
+        assert_eq!(
+            predictor.try_predict_next_n_leaders_filtered(4),
+            leaders[9..13].to_vec()
+        );
+
+        // Deny-listing the current leader pulls the next leader into the window.
+        assert!(predictor.deny_list.insert(leaders[10]));
+        assert!(!predictor.deny_list.insert(leaders[10]));
+        assert_eq!(
+            predictor.try_predict_next_n_leaders_filtered(4),
+            vec![leaders[9], leaders[11], leaders[12], leaders[13]]
+        );
+        // The unfiltered prediction is unchanged.
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(4),
+            leaders[9..13].to_vec()
+        );
+
+        // Updates are visible to clones.
+        let clone = predictor.clone();
+        predictor
+            .deny_list
+            .store(HashSet::from([leaders[9], leaders[11]]));
+        assert_eq!(
+            clone.try_predict_next_n_leaders_filtered(4),
+            vec![leaders[10], leaders[12], leaders[13], leaders[14]]
+        );
+        assert!(predictor.deny_list.remove(&leaders[9]));
+        assert!(!predictor.deny_list.contains(&leaders[9]));
+        assert_eq!(
+            clone.try_predict_next_n_leaders_filtered(4),
+            vec![leaders[9], leaders[10], leaders[12], leaders[13]]
+        );
+    }
+
+    #[test]
+    fn it_should_retain_the_most_recent_predictions() {
+        let (current, leaders) = unique_leaders_schedule(0);
+        let (next, _) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        let slot_tracker = Arc::new(AtomicSlotTracker::new(5));
+        let predictor = YellowstoneUpcomingLeader::builder(
+            Arc::clone(&slot_tracker),
+            ManagedLeaderSchedule::from_schedules(current, next),
+        )
+        .max_future_epochs(0)
+        .recent_predictions_capacity(2)
+        .build()
+        .expect("valid options");
+        assert!(predictor.recent_predictions().is_empty());
+
+        predictor.try_predict_next_n_leaders(3);
+        predictor.deny_list.insert(leaders[1]);
+        assert_eq!(
+            predictor.try_predict_next_n_leaders_filtered(3),
+            vec![leaders[0], leaders[2], leaders[3]]
+        );
+        // slot E-3 -> the window starts at E-8 and stops at the end of the epoch.
+        slot_tracker.store(DEFAULT_SLOTS_PER_EPOCH - 3);
+        let last = leaders.len() - 1;
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(4),
+            vec![leaders[last - 1], leaders[last]]
+        );
+        // What-if predictions are not retained.
+        predictor.try_predict_next_n_leaders_from(5, 3);
+
+        let recent = predictor.recent_predictions();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].slot, DEFAULT_SLOTS_PER_EPOCH - 3);
+        assert_eq!(recent[0].requested, 4);
+        assert_eq!(
+            recent[0].window,
+            vec![
+                (
+                    leaders[last - 1],
+                    DEFAULT_SLOTS_PER_EPOCH - 8..DEFAULT_SLOTS_PER_EPOCH - 4
+                ),
+                (
+                    leaders[last],
+                    DEFAULT_SLOTS_PER_EPOCH - 4..DEFAULT_SLOTS_PER_EPOCH
+                ),
+            ]
+        );
+        assert_eq!(
+            recent[0].shortfall,
+            PredictionShortfall {
+                missing: 2,
+                truncated: Some(PredictionTruncation::Horizon(DEFAULT_SLOTS_PER_EPOCH)),
+                ..Default::default()
+            }
+        );
+        assert_eq!(recent[1].slot, 5);
+        assert_eq!(recent[1].window.len(), 3);
+        assert_eq!(
+            recent[1].shortfall,
+            PredictionShortfall {
+                denied: 1,
+                ..Default::default()
+            }
+        );
+        assert!(recent[1].predicted_at <= recent[0].predicted_at);
+
+        // Clones share the retained predictions.
+        predictor.clone().try_predict_next_n_leaders(1);
+        assert_eq!(predictor.recent_predictions()[0].requested, 1);
+    }
+
+    #[test]
+    fn it_should_return_empty_when_slots_per_leader_is_zero() {
+        let (predictor, _) = predictor_at_slot(20, 0);
+        assert!(predictor.try_predict_next_n_leaders(3).is_empty());
+    }
+
+    #[test]
+    fn it_should_return_error_when_slot_tracker_is_closed() {
+        let (predictor, _) = predictor_at_slot(20, 4);
+        predictor.slot_tracker.close();
+        // The slot stays readable, only predictions fail.
+        assert_eq!(predictor.slot_tracker.load(), 20);
+
+        let actual = predictor.try_predict_next_n_leaders_checked(3);
+        assert!(matches!(actual, Err(PredictError::SlotTrackerClosed(20))));
+        // The infallible API should degrade to an empty prediction instead of panicking.
+        assert!(predictor.try_predict_next_n_leaders(3).is_empty());
+    }
+
+    #[test]
+    fn it_should_refuse_to_predict_until_the_slot_tracker_is_ready() {
+        let (current, leaders) = unique_leaders_schedule(0);
+        let (next, _) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        let predictor = YellowstoneUpcomingLeader::new(
+            Arc::new(AtomicSlotTracker::pending()),
+            ManagedLeaderSchedule::from_schedules(current, next),
+        );
+        assert!(matches!(
+            predictor.try_predict_next_n_leaders_checked(3),
+            Err(PredictError::SlotTrackerNotReady)
+        ));
+
+        predictor.slot_tracker.store(5);
+        assert_eq!(
+            predictor
+                .try_predict_next_n_leaders_checked(3)
+                .expect("ready"),
+            leaders[..3].to_vec()
+        );
+    }
+
+    #[test]
+    fn it_should_refuse_to_predict_when_slot_is_stale() {
+        let clock = Arc::new(MockClock::new());
+        let (mut predictor, leaders) = predictor_at_slot_with_clock(21, 4, Arc::clone(&clock) as _);
+        predictor.max_slot_age = Some(Duration::from_millis(10));
+        clock.advance(Duration::from_millis(10));
+        assert!(predictor.try_predict_next_n_leaders_checked(3).is_ok());
+        clock.advance(Duration::from_millis(10));
+
+        let actual = predictor.try_predict_next_n_leaders_checked(3);
+        assert!(matches!(
+            actual,
+            Err(PredictError::StaleSlot { slot: 21, .. })
+        ));
+        assert!(predictor.try_predict_next_n_leaders(3).is_empty());
+
+        // A fresh slot update makes the predictor usable again.
+        predictor.slot_tracker.store(22);
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(3),
+            vec![leaders[4], leaders[5], leaders[6]]
+        );
+
+        // Disabling the check always predicts.
+        clock.advance(Duration::from_millis(20));
+        predictor.max_slot_age = None;
+        assert_eq!(predictor.try_predict_next_n_leaders(3).len(), 3);
+    }
+
+    #[test]
+    fn it_should_predict_from_estimated_slot() {
+        // slot 23 -> current boundary 20, previous boundary 16
+        let clock = Arc::new(MockClock::new());
+        let (mut predictor, leaders) = predictor_at_slot_with_clock(23, 4, Arc::clone(&clock) as _);
+        predictor
+            .slot_tracker
+            .set_slot_duration(Duration::from_millis(1));
+        clock.advance(Duration::from_millis(10));
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(2),
+            vec![leaders[4], leaders[5]]
+        );
+
+        // The estimated slot crossed into the next rotation, boundary 24.
+        predictor.use_estimated_slot = true;
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(2),
+            vec![leaders[5], leaders[6]]
+        );
+    }
+
+    #[test]
+    fn it_should_return_current_leader_without_look_behind() {
+        // slot 41 -> current block 10
+        let (predictor, leaders) = predictor_at_slot(41, 4);
+        assert_eq!(predictor.current_leader(), Some(leaders[10]));
+        // The single-leader prediction starts at the look-behind leader.
+        assert_eq!(predictor.try_predict_next_n_leaders(1), vec![leaders[9]]);
+        assert_eq!(predictor.try_predict_next_n_leaders(2)[1], leaders[10]);
+
+        predictor.slot_tracker.close();
+        assert_eq!(predictor.current_leader(), None);
+    }
+
+    #[test]
+    fn it_should_return_previous_leader_at_boundary() {
+        // slot 40 is the first slot of block 10, block 9 just rotated out
+        let (predictor, leaders) = predictor_at_slot(40, 4);
+        assert_eq!(predictor.current_leader(), Some(leaders[10]));
+        assert_eq!(predictor.previous_leader(), Some(leaders[9]));
+
+        // The last slot of block 9 is still led by block 9, its predecessor is block 8
+        let (predictor, leaders) = predictor_at_slot(39, 4);
+        assert_eq!(predictor.current_leader(), Some(leaders[9]));
+        assert_eq!(predictor.previous_leader(), Some(leaders[8]));
+
+        // Independent from the look-behind window
+        let (mut predictor, leaders) = predictor_at_slot(40, 4);
+        predictor.look_behind = 3;
+        assert_eq!(predictor.previous_leader(), Some(leaders[9]));
+
+        predictor.slot_tracker.close();
+        assert_eq!(predictor.previous_leader(), None);
+    }
+
+    #[test]
+    fn it_should_predict_leaders_lazily() {
+        let (mut predictor, leaders) = predictor_at_slot(40, 4);
+        let expected = predictor.try_predict_next_n_leaders(32);
+        assert_eq!(
+            predictor.predict_leaders_iter(32).collect::<Vec<_>>(),
+            expected
+        );
+
+        let mut window = predictor.predict_leaders_iter(32).take(2);
+        assert_eq!(window.next(), Some(leaders[9]));
+        // Nothing past the first leader was looked up yet: the swapped schedule applies to the second one,
+        // and the schedule lock is not held in between.
+        let (current, swapped_leaders) = unique_leaders_schedule(0);
+        let (next, _) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        predictor
+            .managed_schedule
+            .set_static_schedules(current, next);
+        assert_eq!(window.next(), Some(swapped_leaders[10]));
+        assert_eq!(window.next(), None);
+        drop(window);
+
+        predictor.dedup = true;
+        assert_eq!(
+            predictor.predict_leaders_iter(32).collect::<Vec<_>>(),
+            predictor.try_predict_next_n_leaders(32)
+        );
+        predictor.slot_tracker.close();
+        assert_eq!(predictor.predict_leaders_iter(32).next(), None);
+    }
+
+    #[tokio::test]
+    async fn it_should_predict_from_a_static_schedule() {
+        let (current, leaders) = unique_leaders_schedule(0);
+        let (next, next_leaders) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        let predictor = YellowstoneUpcomingLeader::with_slots_per_leader(
+            Arc::new(AtomicSlotTracker::new(40)),
+            ManagedLeaderSchedule::from_static_schedules(current, next),
+            4,
+        );
+        assert_eq!(predictor.predict_leaders_iter(32).next(), Some(leaders[9]));
+
+        // There is no backend: refreshes fail without marking the schedule stale, and misses are not loaded.
+        let schedule = &predictor.managed_schedule;
+        assert!(matches!(
+            schedule.refresh().await,
+            Err(ScheduleError::StaticSchedule)
+        ));
+        assert!(!schedule.is_stale());
+        let past_next_epoch = 2 * DEFAULT_SLOTS_PER_EPOCH;
+        assert!(matches!(
+            schedule.get_leader_async(past_next_epoch).await,
+            Err(ScheduleError::SlotAfterLoadedEpoch(slot)) if slot == past_next_epoch
+        ));
+
+        let (following, following_leaders) = unique_leaders_schedule(past_next_epoch);
+        schedule.set_static_schedules(
+            CompactSortedSchedule::new(DEFAULT_SLOTS_PER_EPOCH, next_leaders.clone()),
+            following,
+        );
+        assert_eq!(
+            schedule
+                .get_leader_async(past_next_epoch)
+                .await
+                .expect("leader"),
+            Some(following_leaders[0])
+        );
+        predictor.slot_tracker.store(DEFAULT_SLOTS_PER_EPOCH + 40);
+        assert_eq!(
+            predictor.predict_leaders_iter(32).next(),
+            Some(next_leaders[9])
+        );
+    }
+
+    #[test]
+    fn it_should_not_return_previous_leader_in_the_first_rotation() {
+        let (predictor, leaders) = predictor_at_slot(3, 4);
+        assert_eq!(predictor.current_leader(), Some(leaders[0]));
+        assert_eq!(predictor.previous_leader(), None);
+    }
+
+    #[test]
+    fn it_should_predict_from_selected_commitment_level() {
+        // processed slot 41 -> current block 10
+        let (mut predictor, leaders) = predictor_at_slot(41, 4);
+        predictor.prediction_commitment = CommitmentLevel::Confirmed;
+        assert!(matches!(
+            predictor.try_predict_next_n_leaders_checked(2),
+            Err(PredictError::NoSlotAtCommitment(CommitmentLevel::Confirmed))
+        ));
+
+        // confirmed slot 34 -> current block 8, finalized slot 9 -> current block 2
+        predictor
+            .slot_tracker
+            .store_commitment(CommitmentLevel::Confirmed, 34);
+        predictor
+            .slot_tracker
+            .store_commitment(CommitmentLevel::Finalized, 9);
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(2),
+            vec![leaders[7], leaders[8]]
+        );
+        predictor.prediction_commitment = CommitmentLevel::Finalized;
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(2),
+            vec![leaders[1], leaders[2]]
+        );
+        predictor.prediction_commitment = CommitmentLevel::Processed;
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(2),
+            vec![leaders[9], leaders[10]]
+        );
+
+        // Predictions follow the selected level as it advances.
+        predictor.prediction_commitment = CommitmentLevel::Confirmed;
+        predictor
+            .slot_tracker
+            .store_commitment(CommitmentLevel::Confirmed, 40);
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(2),
+            vec![leaders[9], leaders[10]]
+        );
+    }
+
+    #[test]
+    fn it_should_resolve_predicted_leaders_tpu_addrs() {
+        // slot 21 -> current boundary 20, previous boundary 16
+        let (predictor, leaders) = predictor_at_slot(21, 4);
+        let addr4: SocketAddr = "127.0.0.1:8004".parse().unwrap();
+        let addr6: SocketAddr = "127.0.0.1:8006".parse().unwrap();
+        let table = ContactInfoTable::from_iter([
+            (
+                leaders[4],
+                RpcTpuQuicContactInfo {
+                    tpu_quic: Some(addr4),
+                    tpu_forwards_quic: None,
+                },
+            ),
+            // Known node without a TPU QUIC address
+            (
+                leaders[5],
+                RpcTpuQuicContactInfo {
+                    tpu_quic: None,
+                    tpu_forwards_quic: None,
+                },
+            ),
+            (
+                leaders[6],
+                RpcTpuQuicContactInfo {
+                    tpu_quic: Some(addr6),
+                    tpu_forwards_quic: None,
+                },
+            ),
+        ]);
+
+        let actual = predictor.predict_next_n_tpu_addrs(4, &table);
+        assert_eq!(actual, vec![(leaders[4], addr4), (leaders[6], addr6)]);
+    }
+
+    #[test]
+    fn it_should_keep_tpu_and_tpu_forwards_addrs_separate() {
+        // slot 21 -> current boundary 20, previous boundary 16
+        let (predictor, leaders) = predictor_at_slot(21, 4);
+        let tpu_only = RpcTpuQuicContactInfo {
+            tpu_quic: Some("127.0.0.1:8004".parse().unwrap()),
+            tpu_forwards_quic: None,
+        };
+        let forwards_only = RpcTpuQuicContactInfo {
+            tpu_quic: None,
+            tpu_forwards_quic: Some("127.0.0.1:8105".parse().unwrap()),
+        };
+        let table = ContactInfoTable::from_iter([
+            (leaders[4], tpu_only.clone()),
+            (leaders[5], forwards_only.clone()),
+        ]);
+
+        let actual = predictor.predict_next_n_tpu_contact_infos(3, &table);
+        assert_eq!(
+            actual,
+            vec![(leaders[4], tpu_only), (leaders[5], forwards_only)]
+        );
+    }
+
+    #[test]
+    fn it_should_predict_leader_at_slot() {
+        let (predictor, leaders) = predictor_at_slot(0, 4);
+        // Any slot within a rotation maps to the same leader.
+        assert_eq!(predictor.predict_leader_at_slot(8), Some(leaders[2]));
+        assert_eq!(predictor.predict_leader_at_slot(11), Some(leaders[2]));
+        assert_eq!(predictor.predict_leader_at_slot(12), Some(leaders[3]));
+
+        // Past the known schedules
+        assert_eq!(
+            predictor.predict_leader_at_slot(DEFAULT_SLOTS_PER_EPOCH * 2),
+            None
+        );
+    }
+
+    #[test]
+    fn it_should_snap_predict_leader_at_slot_with_custom_rotation_width() {
+        let (predictor, leaders) = predictor_at_slot(0, 8);
+        // slot 15 snaps to boundary 8, which is the 3rd 4-slot block.
+        assert_eq!(predictor.predict_leader_at_slot(15), Some(leaders[2]));
+
+        let (predictor, _) = predictor_at_slot(0, 0);
+        assert_eq!(predictor.predict_leader_at_slot(15), None);
+    }
+
+    #[test]
+    fn it_should_return_slot_ranges_alongside_leaders() {
+        // slot 21 -> current boundary 20, previous boundary 16
+        let (predictor, leaders) = predictor_at_slot(21, 4);
+        let actual = predictor.try_predict_next_n_leaders_with_slots(3);
+        assert_eq!(
+            actual,
+            vec![
+                (leaders[4], 16..20),
+                (leaders[5], 20..24),
+                (leaders[6], 24..28),
+            ]
+        );
+        // The previous leader range ends exactly at the current leader boundary.
+        assert_eq!(actual[0].1.end, 20);
+
+        let without_ranges = predictor.try_predict_next_n_leaders(3);
+        assert_eq!(
+            without_ranges,
+            actual
+                .into_iter()
+                .map(|(leader, _)| leader)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_should_support_zero_look_behind() {
+        // slot 21 -> current boundary 20
+        let (mut predictor, leaders) = predictor_at_slot(21, 4);
+        predictor.look_behind = 0;
+        let actual = predictor.try_predict_next_n_leaders(3);
+        assert_eq!(actual, vec![leaders[5], leaders[6], leaders[7]]);
+    }
+
+    #[test]
+    fn it_should_keep_n_leaders_with_larger_look_behind() {
+        // slot 21 -> current boundary 20, two leaders behind is 12
+        let (mut predictor, leaders) = predictor_at_slot(21, 4);
+        predictor.look_behind = 2;
+        let actual = predictor.try_predict_next_n_leaders(4);
+        assert_eq!(actual, vec![leaders[3], leaders[4], leaders[5], leaders[6]]);
+    }
+
+    #[test]
+    fn it_should_predict_upcoming_leaders_from_the_current_leader() {
+        // slot 21 -> current boundary 20
+        let (mut predictor, leaders) = predictor_at_slot(21, 4);
+        for look_behind in [0, 1, 2] {
+            predictor.look_behind = look_behind;
+            assert_eq!(
+                predictor.try_predict_upcoming_leaders(3),
+                vec![leaders[5], leaders[6], leaders[7]],
+                "look-behind {look_behind}"
+            );
+        }
+        assert!(predictor.try_predict_upcoming_leaders(0).is_empty());
+
+        // No previous leader to drop at genesis.
+        let (predictor, leaders) = predictor_at_slot(1, 4);
+        assert_eq!(
+            predictor.try_predict_upcoming_leaders(2),
+            vec![leaders[0], leaders[1]]
+        );
+    }
+
+    #[test]
+    fn it_should_not_underflow_when_look_behind_exceeds_genesis() {
+        // slot 5 -> current boundary 4, looking 3 leaders behind would go below slot 0.
+        let (mut predictor, leaders) = predictor_at_slot(5, 4);
+        predictor.look_behind = 3;
+        let actual = predictor.try_predict_next_n_leaders_with_slots(3);
+        assert_eq!(
+            actual,
+            vec![(leaders[0], 0..4), (leaders[1], 4..8), (leaders[2], 8..12)]
+        );
+    }
+
+    #[test]
+    fn it_should_define_the_window_near_genesis() {
+        let (mut predictor, leaders) = predictor_at_slot(0, 4);
+        for look_behind in [0, 1, 2] {
+            predictor.look_behind = look_behind;
+            for slot in 0..8 {
+                let current_block = slot as usize / 4;
+                let first_block = current_block.saturating_sub(look_behind);
+                let expected = (first_block..first_block + 4)
+                    .map(|block| (leaders[block], block as u64 * 4..block as u64 * 4 + 4))
+                    .collect::<Vec<_>>();
+                let (actual, shortfall) = predictor.leaders_from_slot(slot, 4, false, None);
+                // Starts at slot 0 at most, each boundary once, still 4 leaders.
+                assert_eq!(actual, expected, "slot {slot}, look-behind {look_behind}");
+                assert_eq!(shortfall, PredictionShortfall::default());
+            }
+        }
+
+        // The tracked prediction and the slot ranges agree at genesis.
+        predictor.look_behind = DEFAULT_LOOK_BEHIND;
+        assert_eq!(
+            predictor.try_predict_next_n_leaders(3),
+            vec![leaders[0], leaders[1], leaders[2]]
+        );
+        assert_eq!(
+            predictor.predict_leaders_iter(3).collect::<Vec<_>>(),
+            vec![leaders[0], leaders[1], leaders[2]]
+        );
+        assert_eq!(
+            predictor.predict_until_slot(11),
+            vec![(leaders[0], 0..4), (leaders[1], 4..8), (leaders[2], 8..12)]
+        );
+    }
+
+    #[test]
+    fn it_should_collapse_consecutive_identical_leaders() {
+        let mut leaders = (0..DEFAULT_SLOTS_PER_EPOCH / 4)
+            .map(|_| Pubkey::new_unique())
+            .collect::<Vec<_>>();
+        // Leader #2 holds three consecutive rotations
+        leaders[3] = leaders[2];
+        leaders[4] = leaders[2];
+        let (next, _) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        let mut predictor = YellowstoneUpcomingLeader::new(
+            Arc::new(AtomicSlotTracker::new(5)),
+            ManagedLeaderSchedule::from_schedules(
+                CompactSortedSchedule::new(0, leaders.clone()),
+                next,
+            ),
+        );
+
+        // slot 5 -> previous boundary 0
+        // Non-distinct behavior is the default
+        let actual = predictor.try_predict_next_n_leaders(4);
+        assert_eq!(actual, vec![leaders[0], leaders[1], leaders[2], leaders[2]]);
+
+        let actual = predictor.try_predict_distinct_leaders(4);
+        assert_eq!(actual, vec![leaders[0], leaders[1], leaders[2], leaders[5]]);
+
+        predictor.dedup = true;
+        let actual = predictor.try_predict_next_n_leaders(4);
+        assert_eq!(actual, vec![leaders[0], leaders[1], leaders[2], leaders[5]]);
+    }
+
+    #[test]
+    fn it_should_predict_every_leader_until_slot() {
+        let mut leaders = (0..DEFAULT_SLOTS_PER_EPOCH / 4)
+            .map(|_| Pubkey::new_unique())
+            .collect::<Vec<_>>();
+        // Leader #2 holds three consecutive rotations
+        leaders[3] = leaders[2];
+        leaders[4] = leaders[2];
+        let (next, next_leaders) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        let predictor = YellowstoneUpcomingLeader::new(
+            Arc::new(AtomicSlotTracker::new(5)),
+            ManagedLeaderSchedule::from_schedules(
+                CompactSortedSchedule::new(0, leaders.clone()),
+                next,
+            ),
+        );
+
+        // slot 5 -> previous boundary 0, the run of leader #2 spans slots 8..20.
+        assert_eq!(
+            predictor.predict_until_slot(21),
+            vec![
+                (leaders[0], 0..4),
+                (leaders[1], 4..8),
+                (leaders[2], 8..20),
+                (leaders[5], 20..24),
+            ]
+        );
+        // The run is cut at the rotation holding the end slot.
+        assert_eq!(
+            predictor.predict_until_slot(8),
+            vec![(leaders[0], 0..4), (leaders[1], 4..8), (leaders[2], 8..12)]
+        );
+        assert_eq!(predictor.predict_until_slot(3), vec![(leaders[0], 0..4)]);
+
+        // Across the epoch boundary, up to the horizon.
+        let until_far_future = predictor.predict_until_slot(u64::MAX);
+        let last_slot = 2 * DEFAULT_SLOTS_PER_EPOCH;
+        assert_eq!(
+            until_far_future.last(),
+            Some(&(
+                next_leaders[next_leaders.len() - 1],
+                last_slot - 4..last_slot
+            ))
+        );
+        assert_eq!(
+            predictor.predict_until_slot(DEFAULT_SLOTS_PER_EPOCH).last(),
+            Some(&(
+                next_leaders[0],
+                DEFAULT_SLOTS_PER_EPOCH..DEFAULT_SLOTS_PER_EPOCH + 4
+            ))
+        );
+    }
+
+    #[test]
+    fn it_should_predict_leaders_across_epoch_boundary() {
+        let (current, current_leaders) = unique_leaders_schedule(0);
+        let (next, next_leaders) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        let last_rotation = current_leaders.len() - 1;
+        let slot_tracker = Arc::new(AtomicSlotTracker::new(DEFAULT_SLOTS_PER_EPOCH - 6));
+        let predictor = YellowstoneUpcomingLeader::new(
+            Arc::clone(&slot_tracker),
+            ManagedLeaderSchedule::from_schedules(current, next),
+        );
+
+        // Second to last rotation of the epoch, the window spills into the next epoch.
+        let actual = predictor.try_predict_next_n_leaders(4);
+        assert_eq!(
+            actual,
+            vec![
+                current_leaders[last_rotation - 2],
+                current_leaders[last_rotation - 1],
+                current_leaders[last_rotation],
+                next_leaders[0],
+            ]
+        );
+
+        // Walk the slot across the epoch boundary.
+        slot_tracker.slot.store(
+            DEFAULT_SLOTS_PER_EPOCH + 1,
+            std::sync::atomic::Ordering::Relaxed,
+        );
+        let actual = predictor.try_predict_next_n_leaders(4);
+        assert_eq!(
+            actual,
+            vec![
+                current_leaders[last_rotation],
+                next_leaders[0],
+                next_leaders[1],
+                next_leaders[2],
+            ]
+        );
+
+        // Past the last known schedule, prediction shrinks instead of returning bogus leaders.
+        slot_tracker.slot.store(
+            DEFAULT_SLOTS_PER_EPOCH * 2 - 1,
+            std::sync::atomic::Ordering::Relaxed,
+        );
+        let actual = predictor.try_predict_next_n_leaders(4);
+        assert_eq!(
+            actual,
+            vec![
+                next_leaders[next_leaders.len() - 2],
+                next_leaders[next_leaders.len() - 1],
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_predict_across_loaded_epochs_up_to_the_horizon() {
+        let (current, current_leaders) = unique_leaders_schedule(0);
+        let (next, next_leaders) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        let last_rotation = current_leaders.len() - 1;
+        // Last rotation of the epoch, the whole window but the first two leaders is in the next epoch.
+        let mut predictor = YellowstoneUpcomingLeader::with_slots_per_leader(
+            Arc::new(AtomicSlotTracker::new(DEFAULT_SLOTS_PER_EPOCH - 1)),
+            ManagedLeaderSchedule::from_schedules(current, next),
+            4,
+        );
+        assert_eq!(predictor.max_future_epochs, DEFAULT_MAX_FUTURE_EPOCHS);
+        let n = 64;
+        let expected = [
+            current_leaders[last_rotation - 1],
+            current_leaders[last_rotation],
+        ]
+        .into_iter()
+        .chain(next_leaders[..n - 2].iter().copied())
+        .collect::<Vec<_>>();
+        assert_eq!(predictor.try_predict_next_n_leaders(n), expected);
+
+        // Within the current epoch only.
+        predictor.max_future_epochs = 0;
+        assert_eq!(predictor.try_predict_next_n_leaders(n), expected[..2]);
+
+        // The epoch after the next one is not loaded, the prediction stops at its first boundary.
+        predictor.max_future_epochs = 2;
+        assert_eq!(
+            predictor.try_predict_next_n_leaders_from(2 * DEFAULT_SLOTS_PER_EPOCH - 5, n),
+            next_leaders[next_leaders.len() - 3..]
+        );
+    }
+
+    #[test]
+    fn it_should_place_the_horizon_on_the_tracker_epoch_schedule() {
+        let slot_tracker = Arc::new(AtomicSlotTracker::new(100));
+        let (current, _) = unique_leaders_schedule(0);
+        let (next, _) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        let mut predictor = YellowstoneUpcomingLeader::with_slots_per_leader(
+            Arc::clone(&slot_tracker),
+            ManagedLeaderSchedule::from_schedules(current, next),
+            4,
+        );
+        predictor.max_future_epochs = 1;
+        assert_eq!(predictor.horizon_end(100), 2 * DEFAULT_SLOTS_PER_EPOCH);
+
+        // Warmup epochs double from 32 slots: slot 100 is in epoch 2, `[96, 224)`, epoch 4 starts at slot 480.
+        assert!(slot_tracker.set_epoch_schedule(EpochSchedule::new(DEFAULT_SLOTS_PER_EPOCH)));
+        assert_eq!(predictor.horizon_end(100), 480);
+
+        predictor.max_future_epochs = u64::MAX;
+        assert_eq!(predictor.horizon_end(100), Slot::MAX);
+    }
+
+    #[tokio::test]
+    async fn it_should_emit_window_only_at_leader_boundary_crossings() {
+        let (predictor, leaders) = predictor_at_slot(4, 4);
+        let slot_tracker = Arc::clone(&predictor.slot_tracker);
+        let mut windows = std::pin::pin!(predictor.window_stream(3));
+
+        let initial = windows.next().await.expect("initial window");
+        assert_eq!(initial, leaders[0..3].to_vec());
+
+        for slot in 5..8 {
+            slot_tracker.store(slot);
+            assert!(
+                windows.next().now_or_never().is_none(),
+                "slot {slot} should not emit"
+            );
+        }
+
+        slot_tracker.store(8);
+        let window = windows.next().await.expect("window at boundary");
+        assert_eq!(window, leaders[1..4].to_vec());
+
+        // Jumping several boundaries at once emits a single window.
+        slot_tracker.store(17);
+        let window = windows.next().await.expect("window after jump");
+        assert_eq!(window, leaders[3..6].to_vec());
+        assert!(windows.next().now_or_never().is_none());
+    }
+
+    #[tokio::test]
+    async fn it_should_stream_windows_across_synthetic_slot_and_epoch_transitions() {
+        let (current, leaders) = unique_leaders_schedule(0);
+        let (next, next_leaders) = unique_leaders_schedule(DEFAULT_SLOTS_PER_EPOCH);
+        let (following, following_leaders) = unique_leaders_schedule(2 * DEFAULT_SLOTS_PER_EPOCH);
+        let last = leaders.len() - 1;
+        let predictor = YellowstoneUpcomingLeader::with_slots_per_leader(
+            Arc::new(AtomicSlotTracker::pending()),
+            ManagedLeaderSchedule::from_static_schedules(current, next),
+            4,
+        );
+        let slot_tracker = Arc::clone(&predictor.slot_tracker);
+        let mut windows = std::pin::pin!(predictor.window_stream(3));
+        // Not ready until the first synthetic slot.
+        assert!(windows.next().await.expect("initial window").is_empty());
+
+        // Last rotations of the current epoch, the window spans into the next one.
+        assert!(slot_tracker.push_slot(DEFAULT_SLOTS_PER_EPOCH - 8));
+        assert_eq!(
+            windows.next().await.expect("window"),
+            vec![leaders[last - 2], leaders[last - 1], leaders[last]]
+        );
+        assert!(!slot_tracker.push_slot(DEFAULT_SLOTS_PER_EPOCH - 8));
+        assert!(slot_tracker.push_slot(DEFAULT_SLOTS_PER_EPOCH - 5));
+        assert!(windows.next().now_or_never().is_none());
+        slot_tracker.push_slot(DEFAULT_SLOTS_PER_EPOCH - 4);
+        assert_eq!(
+            windows.next().await.expect("window"),
+            vec![leaders[last - 1], leaders[last], next_leaders[0]]
+        );
+        slot_tracker.push_slot(DEFAULT_SLOTS_PER_EPOCH);
+        assert_eq!(
+            windows.next().await.expect("window"),
+            vec![leaders[last], next_leaders[0], next_leaders[1]]
+        );
+
+        // The window is truncated at the loaded schedules until the following epoch is staged.
+        slot_tracker.push_slot(2 * DEFAULT_SLOTS_PER_EPOCH - 4);
+        assert_eq!(
+            windows.next().await.expect("window"),
+            vec![next_leaders[last - 1], next_leaders[last]]
+        );
+        // In the last loaded epoch, the truncation asks for the following one.
+        assert!(predictor.managed_schedule.take_refresh_request());
+        predictor.managed_schedule.stage_epoch(following);
+        assert_eq!(predictor.managed_schedule.loaded_epochs(), vec![1, 2]);
+        slot_tracker.push_slot(2 * DEFAULT_SLOTS_PER_EPOCH);
+        assert_eq!(
+            windows.next().await.expect("window"),
+            vec![
+                next_leaders[last],
+                following_leaders[0],
+                following_leaders[1]
+            ]
+        );
+    }
+}
@@ -1,23 +1,29 @@
 use {
     crate::{
+        blockhash::{RecentBlockhashes, recent_blockhash_of},
         config::TpuSenderConfig,
         core::{
             Nothing, StakeBasedEvictionStrategy, TpuSenderResponse, TpuSenderResponseCallback,
             TpuSenderTxn,
         },
+        dedup::{DEFAULT_DEDUP_TTL_SLOTS, DedupCache},
+        health::{DEFAULT_HEALTH_MAX_SLOT_AGE, HealthStatus, check_health},
         rpc::{
             schedule::{
-                ManagedLeaderSchedule, ManagedLeaderScheduleConfig, spawn_managed_leader_schedule,
+                CoverageError, ManagedLeaderSchedule, ManagedLeaderScheduleConfig, ScheduleError,
+                spawn_managed_leader_schedule_with_cancellation,
             },
             solana_rpc_utils::RetryRpcSender,
             stake::{RpcValidatorStakeInfoServiceConfig, rpc_validator_stake_info_service},
             tpu_info::{RpcClusterTpuQuicInfoServiceConfig, rpc_cluster_tpu_info_service},
         },
-        sender::{TpuSender, create_base_tpu_client},
-        slot::AtomicSlotTracker,
+        sender::{SubmitError, TpuSender, create_base_tpu_client},
+        slot::{AtomicSlotTracker, TimeoutError},
+        task::{DEPENDENCY_OVERSEER_TASK, spawn_named},
         yellowstone_grpc::{
+            connection::{GrpcConnectError, GrpcConnectionConfig, connect_geyser_client},
             schedule::YellowstoneUpcomingLeader,
-            slot_tracker::{self, YellowstoneSlotTrackerOk},
+            slot_tracker::{self, SlotTrackerReconnectPolicy, YellowstoneSlotTrackerOk},
         },
     },
     bytes::Bytes,
@@ -26,6 +32,7 @@ use {
     solana_client::{
         client_error::ClientError, nonblocking::rpc_client, rpc_client::RpcClientConfig,
     },
+    solana_clock::Slot,
     solana_commitment_config::CommitmentConfig,
     solana_keypair::{Keypair, Signature},
     solana_pubkey::Pubkey,
@@ -34,12 +41,36 @@ use {
         collections::{BTreeSet, HashSet},
         fmt,
         sync::Arc,
+        time::Duration,
     },
-    tokio::sync::mpsc::UnboundedSender,
-    yellowstone_grpc_client::{ClientTlsConfig, GeyserGrpcBuilder, GeyserGrpcClient},
+    tokio::{sync::mpsc::UnboundedSender, task::JoinHandle},
+    tokio_util::sync::CancellationToken,
+    yellowstone_grpc_client::GeyserGrpcClient,
 };
 
 pub const DEFAULT_TPU_SENDER_CHANNEL_CAPACITY: usize = 100_000;
+pub const DEFAULT_STARTUP_SCHEDULE_COVERAGE_SLOTS: u64 = 64;
+pub const DEFAULT_SLOT_TRACKER_INIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+///
+/// What [`YellowstoneTpuSender`]'s transaction sending API does when the channel to the TPU sender task is full.
+///
+/// The channel holds at most [`YellowstoneTpuSenderConfig::channel_capacity`] transactions, so a burst faster than
+/// the QUIC layer can forward never grows memory without bound: it either slows callers down or is rejected.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueFullPolicy {
+    ///
+    /// Waits until the TPU sender task frees a slot, applying backpressure to callers.
+    ///
+    #[default]
+    Wait,
+    ///
+    /// Fails immediately with [`SendErrorKind::QueueFull`], letting callers shed load or retry later.
+    ///
+    Reject,
+}
 
 ///
 /// Configuration object for [`YellowstoneTpuSender`].
@@ -66,6 +97,64 @@ pub struct YellowstoneTpuSenderConfig {
     /// Capacity of the internal channel used to send transactions to the TPU sender task.
     ///
     pub channel_capacity: usize,
+    ///
+    /// Behavior of the transaction sending API when the channel is full, see [`QueueFullPolicy`].
+    ///
+    #[serde(default)]
+    pub queue_full_policy: QueueFullPolicy,
+    ///
+    /// TTL, in slots, of the transaction dedup cache, see [`DedupCache`].
+    ///
+    /// Defaults to [`DEFAULT_DEDUP_TTL_SLOTS`], one blockhash lifetime. Disabled when `None` (`null`).
+    ///
+    #[serde(default = "YellowstoneTpuSenderConfig::default_dedup_ttl_slots")]
+    pub dedup_ttl_slots: Option<u64>,
+    ///
+    /// Reconnect policy of the internal slot tracker gRPC subscription.
+    ///
+    #[serde(default)]
+    pub slot_tracker_reconnect: SlotTrackerReconnectPolicy,
+    ///
+    /// Maximum age of the last slot update before [`YellowstoneTpuSender::health`] reports the slot feed unhealthy.
+    ///
+    #[serde(
+        default = "YellowstoneTpuSenderConfig::default_health_max_slot_age",
+        with = "humantime_serde"
+    )]
+    pub health_max_slot_age: Duration,
+    ///
+    /// Number of slots past the current slot the leader schedule must cover at startup, see
+    /// [`ManagedLeaderSchedule::validate_covers`].
+    ///
+    #[serde(default = "YellowstoneTpuSenderConfig::default_startup_schedule_coverage_slots")]
+    pub startup_schedule_coverage_slots: u64,
+    ///
+    /// How long startup waits for the slot tracker to observe its first slot, see
+    /// [`AtomicSlotTracker::wait_until_ready`].
+    ///
+    #[serde(
+        default = "YellowstoneTpuSenderConfig::default_slot_tracker_init_timeout",
+        with = "humantime_serde"
+    )]
+    pub slot_tracker_init_timeout: Duration,
+}
+
+impl YellowstoneTpuSenderConfig {
+    pub const fn default_dedup_ttl_slots() -> Option<u64> {
+        Some(DEFAULT_DEDUP_TTL_SLOTS)
+    }
+
+    pub const fn default_health_max_slot_age() -> Duration {
+        DEFAULT_HEALTH_MAX_SLOT_AGE
+    }
+
+    pub const fn default_startup_schedule_coverage_slots() -> u64 {
+        DEFAULT_STARTUP_SCHEDULE_COVERAGE_SLOTS
+    }
+
+    pub const fn default_slot_tracker_init_timeout() -> Duration {
+        DEFAULT_SLOT_TRACKER_INIT_TIMEOUT
+    }
 }
 
 impl Default for YellowstoneTpuSenderConfig {
@@ -76,6 +165,12 @@ impl Default for YellowstoneTpuSenderConfig {
             schedule: Default::default(),
             stake: Default::default(),
             channel_capacity: DEFAULT_TPU_SENDER_CHANNEL_CAPACITY,
+            queue_full_policy: QueueFullPolicy::default(),
+            dedup_ttl_slots: Self::default_dedup_ttl_slots(),
+            slot_tracker_reconnect: Default::default(),
+            health_max_slot_age: DEFAULT_HEALTH_MAX_SLOT_AGE,
+            startup_schedule_coverage_slots: DEFAULT_STARTUP_SCHEDULE_COVERAGE_SLOTS,
+            slot_tracker_init_timeout: DEFAULT_SLOT_TRACKER_INIT_TIMEOUT,
         }
     }
 }
@@ -96,10 +191,26 @@ pub enum CreateTpuSenderError {
     #[error(transparent)]
     YellowstoneGrpcError(#[from] yellowstone_grpc_client::GeyserGrpcClientError),
     ///
-    /// Raised when subscribing to a remote Yellowstone gRPC Subscription ended.
+    /// Raised when connecting to the Yellowstone gRPC endpoint failed.
+    ///
+    #[error(transparent)]
+    GrpcConnectError(#[from] GrpcConnectError),
+    ///
+    /// Raised when the slot tracker subscription ended before delivering a slot.
     ///
     #[error("geyser client returned empty slot tracker stream")]
     GeyserSubscriptionEnded,
+    ///
+    /// Raised when the slot tracker observed no slot within [`YellowstoneTpuSenderConfig::slot_tracker_init_timeout`].
+    ///
+    #[error("slot tracker initialization failed: {0}")]
+    SlotTrackerInitTimeout(#[from] TimeoutError),
+    ///
+    /// Raised when the leader schedule does not cover the current slot and the startup window, see
+    /// [`YellowstoneTpuSenderConfig::startup_schedule_coverage_slots`].
+    ///
+    #[error(transparent)]
+    ScheduleCoverage(#[from] CoverageError),
 }
 
 ///
@@ -284,6 +395,60 @@ pub struct YellowstoneTpuSender {
     pub base_tpu_sender: TpuSender,
     pub atomic_slot_tracker: Arc<AtomicSlotTracker>,
     pub leader_schedule: ManagedLeaderSchedule,
+    ///
+    /// When set, signatures already sent within the cache TTL are not sent again.
+    ///
+    pub dedup_cache: Option<DedupCache>,
+    ///
+    /// Recent blockhashes observed by the sender, used to reject expired transactions.
+    ///
+    /// Use [`RecentBlockhashes::latest`] to get a recent blockhash without an RPC round-trip.
+    ///
+    pub recent_blockhashes: Arc<RecentBlockhashes>,
+    ///
+    /// Maximum age of the last slot update accepted by [`YellowstoneTpuSender::health`].
+    ///
+    pub health_max_slot_age: Duration,
+    ///
+    /// Behavior of the transaction sending API when the channel to the TPU sender task is full.
+    ///
+    pub queue_full_policy: QueueFullPolicy,
+    ///
+    /// Stops the background slot tracker, leader schedule, stake and TPU info tasks, see [`YellowstoneTpuSender::shutdown`].
+    ///
+    pub shutdown_handle: ShutdownHandle,
+}
+
+///
+/// Handle to gracefully stop the background tasks of a [`YellowstoneTpuSender`].
+///
+/// # Clone
+///
+/// Cloned handles all control the same background tasks.
+///
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownHandle {
+    cancellation_token: CancellationToken,
+    finished: CancellationToken,
+}
+
+impl ShutdownHandle {
+    ///
+    /// Signals the background tasks to stop and waits until all of them exited.
+    ///
+    /// Calling it again, or after the tasks stopped on their own, returns once they all exited.
+    ///
+    pub async fn shutdown(&self) {
+        self.cancellation_token.cancel();
+        self.finished.cancelled().await;
+    }
+
+    ///
+    /// Returns `true` once every background task exited.
+    ///
+    pub fn is_finished(&self) -> bool {
+        self.finished.is_cancelled()
+    }
 }
 
 ///
@@ -324,9 +489,28 @@ pub enum SendErrorKind {
     #[display("managed leader schedule disconnected")]
     ManagedLeaderScheduleDisconnected,
     ///
+    /// The leader schedule does not cover the current slot yet: it is not loaded yet, e.g. at startup, or the slot is
+    /// past the loaded epochs, e.g. at an epoch rollover before the next epoch schedule is loaded.
+    ///
+    /// The missing schedules are requested from the background task, the send can be retried.
+    ///
+    #[display("leader schedule unavailable for the current slot")]
+    LeaderScheduleUnavailable,
+    ///
     /// No remote peers currently matched the user-provided `Blocklist`.
     #[display("destination(s) blocked")]
     RemotePeerBlocked,
+    ///
+    /// The transaction recent blockhash is older than [`crate::blockhash::BLOCKHASH_EXPIRY_SLOTS`], it can never land.
+    ///
+    #[display("blockhash expired")]
+    BlockhashExpired,
+    ///
+    /// The channel to the TPU sender task is full and [`YellowstoneTpuSender::queue_full_policy`] is
+    /// [`QueueFullPolicy::Reject`].
+    ///
+    #[display("tpu sender queue full")]
+    QueueFull,
 }
 
 ///
@@ -438,6 +622,39 @@ impl Blocklist for ShieldBlockList<'_> {
 }
 
 impl YellowstoneTpuSender {
+    ///
+    /// Summarizes the liveness of the sender subsystems, see [`check_health`].
+    ///
+    /// The sender is healthy when the gRPC slot feed is connected and fresh, the leader schedule of the current
+    /// epoch is loaded and at least one TPU connection is ready.
+    /// Every failed check is listed in [`HealthStatus::failures`].
+    ///
+    pub fn health(&self) -> HealthStatus {
+        check_health(
+            &self.atomic_slot_tracker,
+            &self.leader_schedule,
+            self.base_tpu_sender.active_connection_count(),
+            self.health_max_slot_age,
+        )
+    }
+
+    ///
+    /// Gracefully stops the background tasks backing this sender and waits until they exited.
+    ///
+    /// The slot tracker and the leader schedule are closed, see [`ShutdownHandle::shutdown`].
+    /// Sending transactions after shutdown fails with [`SendErrorKind::SlotTrackerDisconnected`] or
+    /// [`SendErrorKind::ManagedLeaderScheduleDisconnected`].
+    ///
+    pub async fn shutdown(&self) {
+        self.shutdown_handle.shutdown().await;
+    }
+
+    fn is_blockhash_expired(&self, wire_txn: &[u8]) -> bool {
+        let current_slot = self.atomic_slot_tracker.load();
+        recent_blockhash_of(wire_txn)
+            .is_some_and(|blockhash| self.recent_blockhashes.is_expired(&blockhash, current_slot))
+    }
+
     ///
     /// Sends a transaction to the specified destinations.
     ///
@@ -455,6 +672,15 @@ impl YellowstoneTpuSender {
     ///
     /// If `dests` is empty, the function returns `Ok(())` immediately
     ///
+    /// If [`YellowstoneTpuSender::dedup_cache`] is set and `sig` was already sent within the cache TTL,
+    /// the function returns `Ok(())` without sending. A send failing to queue is forgotten by the cache, so the
+    /// caller can retry it.
+    ///
+    /// Transactions whose recent blockhash expired are rejected with [`SendErrorKind::BlockhashExpired`].
+    ///
+    /// With [`QueueFullPolicy::Reject`], a full channel fails with [`SendErrorKind::QueueFull`]; destinations
+    /// before the failing one are already queued.
+    ///
     pub async fn send_txn_many_dest<T>(
         &mut self,
         sig: Signature,
@@ -475,6 +701,37 @@ impl YellowstoneTpuSender {
             return Ok(());
         }
 
+        if self.is_blockhash_expired(txn.as_ref()) {
+            tracing::debug!(
+                "[send_txn_many_dest] Rejecting transaction with expired blockhash (sig={})",
+                sig
+            );
+            #[cfg(feature = "prometheus")]
+            {
+                crate::prom::incr_blockhash_expired_rejected_cnt();
+            }
+            return Err(SendError {
+                kind: SendErrorKind::BlockhashExpired,
+                txn: Bytes::from_owner(txn),
+            });
+        }
+
+        // A closed slot tracker is reported by the slot-aware send APIs, don't fail here.
+        let dedup_cache = self
+            .dedup_cache
+            .clone()
+            .map(|dedup_cache| (dedup_cache, self.atomic_slot_tracker.load()));
+        let already_sent = dedup_cache
+            .as_ref()
+            .is_some_and(|(dedup_cache, slot)| dedup_cache.check_and_insert(sig, *slot));
+        if already_sent {
+            tracing::debug!(
+                "[send_txn_many_dest] Skipping already sent transaction (sig={})",
+                sig
+            );
+            return Ok(());
+        }
+
         let wire_txn = Bytes::from_owner(txn);
 
         for (idx, dest) in dests.iter().enumerate() {
@@ -492,15 +749,27 @@ impl YellowstoneTpuSender {
                 wire: wire_txn.clone(),
             };
 
-            if self.base_tpu_sender.send_txn(tpu_txn).await.is_err() {
-                tracing::error!(
-                    "[send_txn_many_dest] TPU sender channel closed while sending to {} (destination {}/{})",
-                    dest,
-                    idx + 1,
-                    dests.len()
-                );
+            if let Err(kind) = self.enqueue(tpu_txn).await {
+                match kind {
+                    SendErrorKind::QueueFull => tracing::debug!(
+                        "[send_txn_many_dest] TPU sender queue full while sending to {} (destination {}/{})",
+                        dest,
+                        idx + 1,
+                        dests.len()
+                    ),
+                    _ => tracing::error!(
+                        "[send_txn_many_dest] TPU sender channel closed while sending to {} (destination {}/{})",
+                        dest,
+                        idx + 1,
+                        dests.len()
+                    ),
+                }
+                // Not sent, a retry must not be skipped as a duplicate.
+                if let Some((dedup_cache, _)) = &dedup_cache {
+                    dedup_cache.remove(&sig);
+                }
                 return Err(SendError {
-                    kind: SendErrorKind::Closed,
+                    kind,
                     txn: wire_txn,
                 });
             }
@@ -561,19 +830,18 @@ impl YellowstoneTpuSender {
         }
 
         let wire_txn = Bytes::from_owner(txn);
-        let current_slot = match self.atomic_slot_tracker.load() {
-            Ok(slot) => {
-                tracing::debug!("[send_txn_to_n_leaders] Current slot loaded: {}", slot);
-                slot
-            }
-            Err(_) => {
-                tracing::error!("[send_txn_to_n_leaders] Slot tracker disconnected!");
-                return Err(SendError {
-                    kind: SendErrorKind::SlotTrackerDisconnected,
-                    txn: wire_txn,
-                });
-            }
-        };
+        if self.atomic_slot_tracker.is_closed() {
+            tracing::error!("[send_txn_to_n_leaders] Slot tracker disconnected!");
+            return Err(SendError {
+                kind: SendErrorKind::SlotTrackerDisconnected,
+                txn: wire_txn,
+            });
+        }
+        let current_slot = self.atomic_slot_tracker.load();
+        tracing::debug!(
+            "[send_txn_to_n_leaders] Current slot loaded: {}",
+            current_slot
+        );
 
         // Calculate the slot boundary for the current leader
         let reminder = current_slot % 4;
@@ -607,6 +875,19 @@ impl YellowstoneTpuSender {
                         leader_slot_boundary
                     );
                 }
+                Err(
+                    e @ (ScheduleError::NotInitialized
+                    | ScheduleError::SlotBeforeLoadedEpoch(_)
+                    | ScheduleError::SlotAfterLoadedEpoch(_)),
+                ) => {
+                    tracing::warn!(
+                        "[send_txn_to_n_leaders] Leader #{} UNKNOWN for slot_boundary={}, skipping: {}",
+                        i,
+                        leader_slot_boundary,
+                        e
+                    );
+                    self.request_missing_schedule(current_slot, &e);
+                }
                 Err(_) => {
                     tracing::error!(
                         "[send_txn_to_n_leaders] Leader schedule disconnected at slot_boundary={}",
@@ -683,6 +964,8 @@ impl YellowstoneTpuSender {
     /// # Note
     ///
     /// The fanout succeed if the sender can schedule at least one send to a leader.
+    /// Leaders not covered by the loaded schedule are skipped, the fanout fails with
+    /// [`SendErrorKind::LeaderScheduleUnavailable`] if none is.
     ///
     pub async fn send_txn_fanout_with_blocklist<T, B>(
         &mut self,
@@ -695,15 +978,13 @@ impl YellowstoneTpuSender {
         B: Blocklist,
     {
         let wire_txn = Bytes::from_owner(txn);
-        let current_slot = match self.atomic_slot_tracker.load() {
-            Ok(slot) => slot,
-            Err(_) => {
-                return Err(SendError {
-                    kind: SendErrorKind::SlotTrackerDisconnected,
-                    txn: wire_txn,
-                });
-            }
-        };
+        if self.atomic_slot_tracker.is_closed() {
+            return Err(SendError {
+                kind: SendErrorKind::SlotTrackerDisconnected,
+                txn: wire_txn,
+            });
+        }
+        let current_slot = self.atomic_slot_tracker.load();
         let reminder = current_slot % 4;
         let floor_leader_boundary = current_slot.saturating_sub(reminder);
 
@@ -712,13 +993,33 @@ impl YellowstoneTpuSender {
         let n = if reminder >= 2 { 2 } else { 1 };
 
         let mut blocked_cnt = 0;
+        let mut unavailable_cnt = 0;
         let result = (0..n)
             .map(|i| floor_leader_boundary + (i * 4) as u64)
-            .map(|leader_slot_boundary| self.leader_schedule.get_leader(leader_slot_boundary))
-            .filter_map(|res| match res {
+            .map(|leader_slot_boundary| {
+                (
+                    leader_slot_boundary,
+                    self.leader_schedule.get_leader(leader_slot_boundary),
+                )
+            })
+            .filter_map(|(leader_slot_boundary, res)| match res {
                 Ok(None) => {
                     panic!("unknown leader for slot boundary {floor_leader_boundary}");
                 }
+                Err(
+                    e @ (ScheduleError::NotInitialized
+                    | ScheduleError::SlotBeforeLoadedEpoch(_)
+                    | ScheduleError::SlotAfterLoadedEpoch(_)),
+                ) => {
+                    tracing::warn!(
+                        "[send_txn_fanout] Leader UNKNOWN for slot_boundary={}, skipping: {}",
+                        leader_slot_boundary,
+                        e
+                    );
+                    self.request_missing_schedule(current_slot, &e);
+                    unavailable_cnt += 1;
+                    None
+                }
                 Ok(Some(leader)) => {
                     if let Some(blocklist) = &blocklist {
                         if blocklist.is_blocked(&leader) {
@@ -742,6 +1043,11 @@ impl YellowstoneTpuSender {
                         kind: SendErrorKind::RemotePeerBlocked,
                         txn: wire_txn,
                     })
+                } else if leaders.is_empty() && unavailable_cnt > 0 {
+                    Err(SendError {
+                        kind: SendErrorKind::LeaderScheduleUnavailable,
+                        txn: wire_txn,
+                    })
                 } else {
                     self.send_txn_many_dest(sig, wire_txn, &leaders).await
                 }
@@ -843,6 +1149,38 @@ impl YellowstoneTpuSender {
     pub async fn update_identity(&mut self, new_identity: Keypair) {
         self.base_tpu_sender.update_identity(new_identity).await;
     }
+
+    ///
+    /// Requests the schedules missing for a lookup failing with `e` at `current_slot` from the background task.
+    ///
+    fn request_missing_schedule(&self, current_slot: Slot, e: &ScheduleError) {
+        match e {
+            ScheduleError::NotInitialized => self.leader_schedule.trigger_refresh(),
+            ScheduleError::SlotAfterLoadedEpoch(_) => {
+                self.leader_schedule.trigger_prefetch(current_slot);
+            }
+            _ => {}
+        }
+    }
+
+    ///
+    /// Queues `txn` to the TPU sender task according to [`YellowstoneTpuSender::queue_full_policy`].
+    ///
+    async fn enqueue(&mut self, txn: TpuSenderTxn) -> Result<(), SendErrorKind> {
+        match self.queue_full_policy {
+            QueueFullPolicy::Wait => self
+                .base_tpu_sender
+                .send_txn(txn)
+                .await
+                .map_err(|_| SendErrorKind::Closed),
+            QueueFullPolicy::Reject => {
+                self.base_tpu_sender.try_send_txn(txn).map_err(|e| match e {
+                    SubmitError::QueueFull(_) => SendErrorKind::QueueFull,
+                    SubmitError::Closed(_) => SendErrorKind::Closed,
+                })
+            }
+        }
+    }
 }
 
 ///
@@ -882,7 +1220,9 @@ pub async fn create_yellowstone_tpu_sender_with_clients<CB>(
     config: YellowstoneTpuSenderConfig,
     initial_identity: Keypair,
     rpc_client: Arc<rpc_client::RpcClient>,
-    grpc_client: GeyserGrpcClient<impl yellowstone_grpc_client::Interceptor + Clone + 'static>,
+    grpc_client: GeyserGrpcClient<
+        impl yellowstone_grpc_client::Interceptor + Clone + Send + 'static,
+    >,
     callback: Option<CB>,
 ) -> Result<NewYellowstoneTpuSender, CreateTpuSenderError>
 where
@@ -893,10 +1233,16 @@ where
 
     tracing::debug!("spawned tpu info service");
 
+    let shutdown_handle = ShutdownHandle::default();
+    let managed_leader_schedule_ct = shutdown_handle.cancellation_token.child_token();
     let (managed_leader_schedule, managed_leader_schedule_jh) =
-        spawn_managed_leader_schedule(Arc::clone(&rpc_client), config.schedule)
-            .await
-            .expect("spawn_managed_leader_schedule");
+        spawn_managed_leader_schedule_with_cancellation(
+            Arc::clone(&rpc_client),
+            config.schedule,
+            managed_leader_schedule_ct.clone(),
+        )
+        .await
+        .expect("spawn_managed_leader_schedule");
 
     tracing::debug!("spawned managed leader schedule");
 
@@ -907,22 +1253,61 @@ where
 
     let YellowstoneSlotTrackerOk {
         atomic_slot_tracker,
+        recent_blockhashes,
+        skip_rates,
+        schedule_mismatches,
         join_handle: slot_tracker_jh,
-    } = slot_tracker::atomic_slot_tracker(grpc_client)
-        .await?
-        .ok_or(CreateTpuSenderError::GeyserSubscriptionEnded)?;
+        cancellation_token: slot_tracker_ct,
+    } = slot_tracker::atomic_slot_tracker_with_reconnect_policy(
+        grpc_client,
+        config.slot_tracker_reconnect,
+    )
+    .await?;
 
     tracing::debug!("spawned slot tracker service");
 
+    // Predictions need the tip, a closed tracker never becomes ready.
+    if let Err(e) = atomic_slot_tracker
+        .wait_until_ready(config.slot_tracker_init_timeout)
+        .await
+    {
+        return Err(if atomic_slot_tracker.is_closed() {
+            CreateTpuSenderError::GeyserSubscriptionEnded
+        } else {
+            e.into()
+        });
+    }
+
+    tracing::debug!("slot tracker ready at slot {}", atomic_slot_tracker.load());
+
+    atomic_slot_tracker.set_epoch_schedule(rpc_client.get_epoch_schedule().await?);
+    schedule_mismatches.attach_schedule(managed_leader_schedule.clone());
+
+    // Fail fast rather than silently predicting empty windows.
+    if !atomic_slot_tracker.is_closed() {
+        let current_slot = atomic_slot_tracker.load();
+        managed_leader_schedule
+            .validate_covers(current_slot, config.startup_schedule_coverage_slots)
+            .inspect_err(|e| {
+                tracing::error!(
+                    "Leader schedule does not cover the {} slots past slot {current_slot}: {e}",
+                    config.startup_schedule_coverage_slots
+                );
+            })?;
+    }
+
     // TODO: make it configurable in another release
     let connection_eviction_strategy = StakeBasedEvictionStrategy {
         ..Default::default()
     };
 
-    let leader_predictor = YellowstoneUpcomingLeader {
-        slot_tracker: Arc::clone(&atomic_slot_tracker),
-        managed_schedule: managed_leader_schedule.clone(),
-    };
+    let leader_predictor = YellowstoneUpcomingLeader::builder(
+        Arc::clone(&atomic_slot_tracker),
+        managed_leader_schedule.clone(),
+    )
+    .skip_rates(Some(skip_rates))
+    .build()
+    .expect("default predictor options");
 
     let base_tpu_sender = create_base_tpu_client(
         config.tpu,
@@ -942,24 +1327,42 @@ where
         base_tpu_sender,
         atomic_slot_tracker,
         leader_schedule: managed_leader_schedule,
+        dedup_cache: config.dedup_ttl_slots.map(DedupCache::new),
+        recent_blockhashes,
+        health_max_slot_age: config.health_max_slot_age,
+        queue_full_policy: config.queue_full_policy,
+        shutdown_handle: shutdown_handle.clone(),
     };
 
-    let handles = vec![
-        tpu_info_service_jh,
-        managed_leader_schedule_jh,
-        stake_info_jh,
-        slot_tracker_jh,
-    ];
-    let handle_name_vec = vec![
-        "tpu-info-service",
-        "managed-leader-schedule",
-        "stake-info-service",
-        "slot-tracker",
+    let tasks = vec![
+        DependencyTask {
+            name: "tpu-info-service",
+            handle: tpu_info_service_jh,
+            cancellation_token: None,
+        },
+        DependencyTask {
+            name: "managed-leader-schedule",
+            handle: managed_leader_schedule_jh,
+            cancellation_token: Some(managed_leader_schedule_ct),
+        },
+        DependencyTask {
+            name: "stake-info-service",
+            handle: stake_info_jh,
+            cancellation_token: None,
+        },
+        DependencyTask {
+            name: "slot-tracker",
+            handle: slot_tracker_jh,
+            cancellation_token: Some(slot_tracker_ct),
+        },
     ];
 
     Ok(NewYellowstoneTpuSender {
         sender,
-        related_objects_jh: tokio::spawn(yellowstone_tpu_deps_overseer(handle_name_vec, handles)),
+        related_objects_jh: spawn_named(
+            DEPENDENCY_OVERSEER_TASK,
+            yellowstone_tpu_deps_overseer(tasks, shutdown_handle),
+        ),
     })
 }
 
@@ -975,6 +1378,19 @@ pub struct Endpoints {
     pub grpc_x_token: Option<String>,
 }
 
+impl fmt::Debug for Endpoints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Endpoints")
+            .field("rpc", &self.rpc)
+            .field("grpc", &self.grpc)
+            .field(
+                "grpc_x_token",
+                &self.grpc_x_token.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
 ///
 /// Connects to the specified RPC and gRPC endpoints to create a Yellowstone TPU sender.
 ///
@@ -1006,15 +1422,7 @@ where
         },
     ));
 
-    let grpc_client = GeyserGrpcBuilder::from_shared(grpc)
-        .expect("from_shared")
-        .x_token(grpc_x_token)
-        .expect("x-token")
-        .tls_config(ClientTlsConfig::default().with_enabled_roots())
-        .expect("tls_config")
-        .connect()
-        .await
-        .expect("connect");
+    let grpc_client = connect_geyser_client(&GrpcConnectionConfig::new(grpc, grpc_x_token)).await?;
 
     tracing::debug!("connected to rpc/grpc endpoints");
 
@@ -1036,27 +1444,65 @@ pub async fn create_yellowstone_tpu_sender(
     create_yellowstone_tpu_sender_with_callback(config, initial_identity, endpoints, Nothing).await
 }
 
+///
+/// Background task of a [`YellowstoneTpuSender`] supervised by [`yellowstone_tpu_deps_overseer`].
+///
+struct DependencyTask {
+    name: &'static str,
+    handle: JoinHandle<()>,
+    ///
+    /// Token stopping the task gracefully, tasks without one are aborted.
+    ///
+    cancellation_token: Option<CancellationToken>,
+}
+
 async fn yellowstone_tpu_deps_overseer(
-    handle_name_vec: Vec<&'static str>,
-    handles: Vec<tokio::task::JoinHandle<()>>,
+    mut tasks: Vec<DependencyTask>,
+    shutdown_handle: ShutdownHandle,
 ) {
-    // Wait for the first task to finish
+    let _finished = shutdown_handle.finished.drop_guard();
 
-    let (finished_handle, i, rest) = futures::future::select_all(handles).await;
-    if finished_handle.is_err() {
-        tracing::error!(
-            "Yellowstone TPU sender dependency task '{}' has failed with {finished_handle:?}",
-            handle_name_vec.get(i).unwrap_or(&"unknown")
-        );
-    } else {
-        tracing::warn!(
-            "Yellowstone TPU sender dependency task '{}' has finished",
-            handle_name_vec.get(i).unwrap_or(&"unknown")
-        );
+    // Wait for the first task to finish or for a shutdown request
+    let first_finished = tokio::select! {
+        (result, i, _) = futures::future::select_all(tasks.iter_mut().map(|task| &mut task.handle)) => {
+            Some((i, result))
+        }
+        _ = shutdown_handle.cancellation_token.cancelled() => None,
+    };
+    match first_finished {
+        Some((i, result)) => {
+            let finished_task = tasks.swap_remove(i);
+            if result.is_err() {
+                tracing::error!(
+                    "Yellowstone TPU sender dependency task '{}' has failed with {result:?}",
+                    finished_task.name
+                );
+            } else {
+                tracing::warn!(
+                    "Yellowstone TPU sender dependency task '{}' has finished",
+                    finished_task.name
+                );
+            }
+        }
+        None => tracing::info!("Yellowstone TPU sender shutdown requested"),
     }
 
-    // Abort the rest
-    rest.into_iter().for_each(|jh| jh.abort());
+    // Stop the rest and wait for them to exit
+    for task in &tasks {
+        match &task.cancellation_token {
+            Some(cancellation_token) => cancellation_token.cancel(),
+            None => task.handle.abort(),
+        }
+    }
+    for task in tasks {
+        match task.handle.await {
+            Err(e) if !e.is_cancelled() => tracing::error!(
+                "Yellowstone TPU sender dependency task '{}' has failed with {e:?}",
+                task.name
+            ),
+            _ => {}
+        }
+    }
 }
 
 impl TpuSenderResponseCallback for UnboundedSender<TpuSenderResponse> {
@@ -1064,3 +1510,163 @@ impl TpuSenderResponseCallback for UnboundedSender<TpuSenderResponse> {
         let _ = self.send(response);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, crate::rpc::schedule::CompactSortedSchedule,
+        solana_clock::DEFAULT_SLOTS_PER_EPOCH, tokio::sync::mpsc,
+    };
+
+    ///
+    /// Builds a sender queuing to a channel of `capacity` transactions, without background tasks.
+    ///
+    fn sender_with_channel(
+        capacity: usize,
+        queue_full_policy: QueueFullPolicy,
+    ) -> (YellowstoneTpuSender, mpsc::Receiver<TpuSenderTxn>) {
+        let (txn_tx, txn_rx) = mpsc::channel(capacity);
+        let schedule = |first_slot| {
+            CompactSortedSchedule::new(
+                first_slot,
+                vec![Pubkey::new_unique(); (DEFAULT_SLOTS_PER_EPOCH / 4) as usize],
+            )
+        };
+        let sender = YellowstoneTpuSender {
+            base_tpu_sender: TpuSender::from_channel(txn_tx),
+            atomic_slot_tracker: Arc::new(AtomicSlotTracker::new(10)),
+            leader_schedule: ManagedLeaderSchedule::from_static_schedules(
+                schedule(0),
+                schedule(DEFAULT_SLOTS_PER_EPOCH),
+            ),
+            dedup_cache: YellowstoneTpuSenderConfig::default()
+                .dedup_ttl_slots
+                .map(DedupCache::new),
+            recent_blockhashes: Default::default(),
+            health_max_slot_age: DEFAULT_HEALTH_MAX_SLOT_AGE,
+            queue_full_policy,
+            shutdown_handle: Default::default(),
+        };
+        (sender, txn_rx)
+    }
+
+    #[tokio::test]
+    async fn it_should_skip_the_leaders_past_the_loaded_schedules_in_the_fanout() {
+        let (mut sender, mut txn_rx) = sender_with_channel(10, QueueFullPolicy::Reject);
+        let leader = sender
+            .leader_schedule
+            .get_leader(0)
+            .expect("loaded")
+            .expect("leader");
+
+        // Near the end of the last loaded epoch, the next leader is in the following epoch, not loaded yet.
+        sender
+            .atomic_slot_tracker
+            .store(2 * DEFAULT_SLOTS_PER_EPOCH - 2);
+        sender
+            .send_txn_fanout_with_blocklist(
+                Signature::new_unique(),
+                b"txn".to_vec(),
+                None::<HashSet<Pubkey>>,
+            )
+            .await
+            .expect("sent to the current leader");
+        assert_eq!(txn_rx.recv().await.expect("txn").remote_peer, leader);
+        assert!(txn_rx.try_recv().is_err());
+        assert!(sender.leader_schedule.take_refresh_request());
+
+        // Past the loaded schedules, no leader is known: the send fails without panicking.
+        sender
+            .atomic_slot_tracker
+            .store(2 * DEFAULT_SLOTS_PER_EPOCH + 2);
+        let err = sender
+            .send_txn_fanout_with_blocklist(
+                Signature::new_unique(),
+                b"txn".to_vec(),
+                None::<HashSet<Pubkey>>,
+            )
+            .await
+            .expect_err("no leader");
+        assert!(matches!(err.kind, SendErrorKind::LeaderScheduleUnavailable));
+        assert!(txn_rx.try_recv().is_err());
+        // Already requested for the loaded schedules.
+        assert!(!sender.leader_schedule.take_refresh_request());
+    }
+
+    #[tokio::test]
+    async fn it_should_send_again_a_transaction_that_failed_to_queue() {
+        let (mut sender, mut txn_rx) = sender_with_channel(1, QueueFullPolicy::Reject);
+        let dedup_cache = sender
+            .dedup_cache
+            .clone()
+            .expect("dedup enabled by default");
+        let dest = Pubkey::new_unique();
+        sender
+            .send_txn_many_dest(Signature::new_unique(), b"first".to_vec(), &[dest])
+            .await
+            .expect("queued");
+
+        let sig = Signature::new_unique();
+        let err = sender
+            .send_txn_many_dest(sig, b"txn".to_vec(), &[dest])
+            .await
+            .expect_err("queue is full");
+        assert!(matches!(err.kind, SendErrorKind::QueueFull));
+        assert!(!dedup_cache.contains(&sig));
+
+        // The retry is queued rather than skipped as a duplicate.
+        txn_rx.recv().await.expect("first txn");
+        sender
+            .send_txn_many_dest(sig, b"txn".to_vec(), &[dest])
+            .await
+            .expect("queued");
+        assert_eq!(txn_rx.recv().await.expect("retried txn").tx_sig, sig);
+
+        // Once queued, it is deduplicated.
+        sender
+            .send_txn_many_dest(sig, b"txn".to_vec(), &[dest])
+            .await
+            .expect("skipped");
+        assert!(txn_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn it_should_stop_every_task_within_timeout_after_shutdown() {
+        let shutdown_handle = ShutdownHandle::default();
+        let graceful_ct = shutdown_handle.cancellation_token.child_token();
+        let loop_ct = graceful_ct.clone();
+        let graceful = tokio::spawn(async move {
+            loop_ct
+                .run_until_cancelled(std::future::pending::<()>())
+                .await;
+        });
+        let tasks = vec![
+            DependencyTask {
+                name: "graceful",
+                handle: graceful,
+                cancellation_token: Some(graceful_ct),
+            },
+            DependencyTask {
+                name: "abortable",
+                handle: tokio::spawn(std::future::pending()),
+                cancellation_token: None,
+            },
+        ];
+        let overseer = tokio::spawn(yellowstone_tpu_deps_overseer(
+            tasks,
+            shutdown_handle.clone(),
+        ));
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!shutdown_handle.is_finished());
+
+        tokio::time::timeout(Duration::from_secs(1), shutdown_handle.shutdown())
+            .await
+            .expect("background tasks did not exit after shutdown");
+        assert!(shutdown_handle.is_finished());
+        tokio::time::timeout(Duration::from_secs(1), overseer)
+            .await
+            .expect("overseer did not exit after shutdown")
+            .expect("overseer panicked");
+    }
+}
@@ -1,15 +1,45 @@
 use {
-    crate::slot::AtomicSlotTracker,
-    futures::Stream,
-    std::{collections::HashMap, panic, sync::Arc},
+    crate::{
+        blockhash::RecentBlockhashes,
+        forwarder::jittered_exponential_backoff,
+        schedule_mismatch::ScheduleMismatchTracker,
+        skip_rate::SkipRateTracker,
+        slot::AtomicSlotTracker,
+        task::{SLOT_TRACKER_TASK, TaskExitGuard, TaskExitReason, spawn_named},
+        yellowstone_grpc::{
+            connection::GrpcConnectError,
+            failover::{
+                ActiveGrpcEndpoint, FailoverSubscriber, GeyserConnector, GrpcEndpointSet,
+                TonicGeyserConnector,
+            },
+        },
+    },
+    futures::{FutureExt, Stream, future::BoxFuture, stream::BoxStream},
+    serde::Deserialize,
+    solana_clock::Slot,
+    solana_commitment_config::CommitmentLevel,
+    solana_hash::Hash,
+    solana_pubkey::Pubkey,
+    std::{
+        collections::HashMap,
+        future::Future,
+        panic::{self, AssertUnwindSafe},
+        str::FromStr,
+        sync::Arc,
+        time::Duration,
+    },
     tokio::task::JoinHandle,
     tokio_stream::StreamExt,
-    yellowstone_grpc_client::{GeyserGrpcClientResult, Interceptor},
+    tokio_util::sync::CancellationToken,
+    yellowstone_grpc_client::{
+        GeyserGrpcClient, GeyserGrpcClientError, GeyserGrpcClientResult, Interceptor,
+    },
     yellowstone_grpc_proto::{
         geyser::{
-            SubscribeRequest, SubscribeRequestFilterSlots, SubscribeUpdate,
-            subscribe_update::UpdateOneof,
+            SlotStatus, SubscribeRequest, SubscribeRequestFilterBlocksMeta,
+            SubscribeRequestFilterSlots, SubscribeUpdate, subscribe_update::UpdateOneof,
         },
+        solana::storage::confirmed_block::{RewardType, Rewards},
         tonic::Status,
     },
 };
@@ -18,7 +48,83 @@ pub(crate) const SLOT_TRACKER_DM_FILTER_NAME: &str = "jet-tpu-client";
 
 pub struct YellowstoneSlotTrackerOk {
     pub atomic_slot_tracker: Arc<AtomicSlotTracker>,
+    ///
+    /// Recent blockhashes observed on the block-meta stream.
+    ///
+    pub recent_blockhashes: Arc<RecentBlockhashes>,
+    ///
+    /// Outcomes of recent slots observed on the block-meta stream, used to weight predicted leaders.
+    ///
+    pub skip_rates: Arc<SkipRateTracker>,
+    ///
+    /// Compares the producer of each block observed on the block-meta stream against the scheduled leader.
+    ///
+    /// Attach the leader schedule with [`ScheduleMismatchTracker::attach_schedule`] to enable the comparison.
+    ///
+    pub schedule_mismatches: Arc<ScheduleMismatchTracker>,
     pub join_handle: JoinHandle<()>,
+    ///
+    /// Cancel to stop the background task, [`YellowstoneSlotTrackerOk::join_handle`] then completes without panicking
+    /// and the slot tracker is closed.
+    ///
+    pub cancellation_token: CancellationToken,
+}
+
+pub const DEFAULT_SLOT_TRACKER_RECONNECT_BASE_DELAY: Duration = Duration::from_millis(100);
+pub const DEFAULT_SLOT_TRACKER_RECONNECT_MAX_DELAY: Duration = Duration::from_secs(10);
+
+///
+/// Reconnect policy of the slot tracker gRPC subscription.
+///
+/// When the subscription stream ends or fails, the slot tracker re-subscribes with an exponential backoff and jitter.
+///
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct SlotTrackerReconnectPolicy {
+    ///
+    /// Maximum number of consecutive reconnect attempts before closing the slot tracker, `None` retries forever.
+    ///
+    /// `Some(0)` disables reconnection.
+    ///
+    #[serde(default)]
+    pub max_attempts: Option<usize>,
+
+    ///
+    /// Delay before the first reconnect attempt, doubled on each subsequent attempt.
+    ///
+    #[serde(
+        default = "SlotTrackerReconnectPolicy::default_base_delay",
+        with = "humantime_serde"
+    )]
+    pub base_delay: Duration,
+
+    ///
+    /// Upper bound of the delay between two reconnect attempts.
+    ///
+    #[serde(
+        default = "SlotTrackerReconnectPolicy::default_max_delay",
+        with = "humantime_serde"
+    )]
+    pub max_delay: Duration,
+}
+
+impl SlotTrackerReconnectPolicy {
+    pub const fn default_base_delay() -> Duration {
+        DEFAULT_SLOT_TRACKER_RECONNECT_BASE_DELAY
+    }
+
+    pub const fn default_max_delay() -> Duration {
+        DEFAULT_SLOT_TRACKER_RECONNECT_MAX_DELAY
+    }
+}
+
+impl Default for SlotTrackerReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: None,
+            base_delay: Self::default_base_delay(),
+            max_delay: Self::default_max_delay(),
+        }
+    }
 }
 
 pub(crate) fn get_yellowstone_slot_tracker_subscribe_request() -> SubscribeRequest {
@@ -30,81 +136,263 @@ pub(crate) fn get_yellowstone_slot_tracker_subscribe_request() -> SubscribeReque
                 ..Default::default()
             },
         )]),
+        blocks_meta: HashMap::from([(
+            SLOT_TRACKER_DM_FILTER_NAME.to_string(),
+            SubscribeRequestFilterBlocksMeta::default(),
+        )]),
         ..Default::default()
     }
 }
 
+///
+/// Consumers of the block-meta stream.
+///
+#[derive(Default)]
+struct BlockMetaRecorders {
+    recent_blockhashes: Arc<RecentBlockhashes>,
+    skip_rates: Arc<SkipRateTracker>,
+    schedule_mismatches: Arc<ScheduleMismatchTracker>,
+}
+
+///
+/// Returns the validator that produced a block, the recipient of its transaction fee reward.
+///
+fn block_producer(rewards: Option<&Rewards>) -> Option<Pubkey> {
+    rewards?
+        .rewards
+        .iter()
+        .find(|reward| reward.reward_type == RewardType::Fee as i32)
+        .and_then(|reward| Pubkey::from_str(&reward.pubkey).ok())
+}
+
 struct AutoCloseSlotTracker {
     slot_tracker: Arc<AtomicSlotTracker>,
 }
 
 impl Drop for AutoCloseSlotTracker {
     fn drop(&mut self) {
-        self.slot_tracker
-            .closed
-            .store(true, std::sync::atomic::Ordering::Release);
+        self.slot_tracker.close();
     }
 }
 
 ///
-/// Background task to update the AtomicSlotTracker from the Yellowstone Geyser slot stream
+/// Consumes the Yellowstone Geyser slot stream until it ends or fails, returns the stream error if any.
 ///
-async fn atomic_slot_tracker_loop<S>(mut dm_slot_stream: S, to_drop: AutoCloseSlotTracker)
+async fn process_slot_stream<S>(
+    dm_slot_stream: &mut S,
+    shared: &AtomicSlotTracker,
+    block_meta_recorders: &BlockMetaRecorders,
+) -> Option<Status>
 where
-    S: Stream<Item = Result<SubscribeUpdate, Status>> + Unpin + Send + 'static,
+    S: Stream<Item = Result<SubscribeUpdate, Status>> + Unpin,
 {
-    let shared = Arc::clone(&to_drop.slot_tracker);
-    let mut current_slot = shared.slot.load(std::sync::atomic::Ordering::Relaxed);
+    // A pending tracker accepts any first slot, genesis included.
+    let mut current_slot = shared.is_ready().then(|| shared.load());
     loop {
         let result = dm_slot_stream.next().await;
         if result.is_none() {
             tracing::warn!("Yellowstone slot tracker stream ended");
-            break;
+            return None;
         }
 
         let response = match result.unwrap() {
             Ok(response) => response,
             Err(err) => {
                 tracing::error!("Yellowstone slot tracker stream error: {:?}", err);
-                drop(to_drop);
-                panic::panic_any(err);
+                return Some(err);
             }
         };
         match response.update_oneof.expect("update_oneof") {
             UpdateOneof::Slot(subscribe_update_slot) => {
+                // The feed is live again
+                shared.set_stale(false);
                 let slot = subscribe_update_slot.slot;
-                if slot <= current_slot {
+                match SlotStatus::try_from(subscribe_update_slot.status) {
+                    Ok(SlotStatus::SlotConfirmed) => {
+                        shared.store_commitment(CommitmentLevel::Confirmed, slot);
+                    }
+                    Ok(SlotStatus::SlotFinalized) => {
+                        shared.store_commitment(CommitmentLevel::Finalized, slot);
+                    }
+                    _ => {}
+                }
+                // Any slot status advances the processed tip
+                if current_slot.is_some_and(|current_slot| slot <= current_slot) {
                     // Ignore out-of-order or duplicate slot updates
                     continue;
                 }
-                current_slot = slot;
+                current_slot = Some(slot);
                 tracing::trace!("Yellowstone slot tracker received slot update: {}", slot);
-                shared
-                    .slot
-                    .store(current_slot, std::sync::atomic::Ordering::Relaxed);
+                shared.store(slot);
+            }
+            UpdateOneof::BlockMeta(block_meta) => {
+                block_meta_recorders
+                    .skip_rates
+                    .record_block(block_meta.slot, block_meta.parent_slot);
+                if let Some(producer) = block_producer(block_meta.rewards.as_ref()) {
+                    block_meta_recorders
+                        .schedule_mismatches
+                        .observe(block_meta.slot, producer);
+                }
+                match Hash::from_str(&block_meta.blockhash) {
+                    Ok(blockhash) => block_meta_recorders
+                        .recent_blockhashes
+                        .record(blockhash, block_meta.slot),
+                    Err(e) => tracing::warn!(
+                        "Yellowstone slot tracker received invalid blockhash {} for slot {}: {e}",
+                        block_meta.blockhash,
+                        block_meta.slot
+                    ),
+                }
             }
             _ => {
                 // Ignore other updates
             }
         }
     }
+}
+
+///
+/// Background task to update the AtomicSlotTracker from the Yellowstone Geyser slot stream
+///
+/// The slot tracker is closed once the stream ends or fails.
+///
+async fn atomic_slot_tracker_loop<S>(
+    mut dm_slot_stream: S,
+    to_drop: AutoCloseSlotTracker,
+    block_meta_recorders: BlockMetaRecorders,
+) where
+    S: Stream<Item = Result<SubscribeUpdate, Status>> + Unpin + Send + 'static,
+{
+    let maybe_err = process_slot_stream(
+        &mut dm_slot_stream,
+        &to_drop.slot_tracker,
+        &block_meta_recorders,
+    )
+    .await;
     drop(to_drop);
+    if let Some(err) = maybe_err {
+        panic::panic_any(err);
+    }
 }
 
 ///
-/// Creates an [`AtomicSlotTracker`] that tracks the latest slot from Yellowstone Geyser.
+/// Runs a slot tracker loop until `cancellation_token` is cancelled, recording why it exited, see [`TaskExitGuard`].
 ///
-pub async fn atomic_slot_tracker<I>(
-    mut geyser_client: yellowstone_grpc_client::GeyserGrpcClient<I>,
-) -> GeyserGrpcClientResult<Option<YellowstoneSlotTrackerOk>>
+/// The loops end once their stream ends, or panic with the [`Status`] of the failed stream once the slot tracker is
+/// closed: both are recorded as [`TaskExitReason::StreamError`], any other panic as [`TaskExitReason::Panicked`].
+///
+async fn supervise_slot_tracker_loop<F>(cancellation_token: CancellationToken, slot_tracker_loop: F)
 where
-    I: Interceptor + 'static,
+    F: Future<Output = ()> + Send,
 {
-    let subscribe_request = get_yellowstone_slot_tracker_subscribe_request();
-    let mut stream = geyser_client.subscribe_once(subscribe_request).await?;
+    let exit = TaskExitGuard::new(SLOT_TRACKER_TASK);
+    let result = AssertUnwindSafe(cancellation_token.run_until_cancelled(slot_tracker_loop))
+        .catch_unwind()
+        .await;
+    match result {
+        Ok(None) => exit.set_reason(TaskExitReason::Shutdown),
+        Ok(Some(())) => exit.set_reason(TaskExitReason::StreamError),
+        Err(payload) => {
+            if payload.is::<Status>() {
+                exit.set_reason(TaskExitReason::StreamError);
+            }
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+///
+/// Error raised when subscribing to Yellowstone Geyser.
+///
+#[derive(Debug, thiserror::Error)]
+pub enum GeyserSubscribeError {
+    ///
+    /// Error caused by a [`GeyserGrpcClient`] API call.
+    ///
+    #[error(transparent)]
+    Client(#[from] GeyserGrpcClientError),
+    ///
+    /// Error caused by connecting to a gRPC endpoint.
+    ///
+    #[error(transparent)]
+    Connect(#[from] GrpcConnectError),
+    ///
+    /// Every endpoint failed to deliver a first slot update.
+    ///
+    #[error("no gRPC endpoint available")]
+    NoEndpointAvailable,
+}
+
+pub(crate) type SlotUpdateStream = BoxStream<'static, Result<SubscribeUpdate, Status>>;
+
+///
+/// Source of Yellowstone Geyser subscriptions, abstracted so the reconnect logic can be tested without a server.
+///
+pub(crate) trait GeyserSubscriber: Send + 'static {
+    fn subscribe(
+        &mut self,
+        request: SubscribeRequest,
+    ) -> BoxFuture<'_, Result<SlotUpdateStream, GeyserSubscribeError>>;
+
+    ///
+    /// Interval at which [`GeyserSubscriber::try_subscribe_preferred`] is polled, `None` if there is no preferred source.
+    ///
+    fn preferred_recheck_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    ///
+    /// Subscribes to the preferred source if the current subscription does not come from it and it is available again.
+    ///
+    fn try_subscribe_preferred(
+        &mut self,
+        _request: SubscribeRequest,
+    ) -> BoxFuture<'_, Option<SlotUpdateStream>> {
+        Box::pin(async { None })
+    }
+}
+
+impl<I> GeyserSubscriber for GeyserGrpcClient<I>
+where
+    I: Interceptor + Send + 'static,
+{
+    fn subscribe(
+        &mut self,
+        request: SubscribeRequest,
+    ) -> BoxFuture<'_, Result<SlotUpdateStream, GeyserSubscribeError>> {
+        Box::pin(async move { Ok(Box::pin(self.subscribe_once(request).await?) as _) })
+    }
+}
+
+///
+/// Resolves once `subscriber` could subscribe to its preferred source, never if it has none.
+///
+async fn wait_for_preferred<C: GeyserSubscriber>(
+    subscriber: &mut C,
+    subscribe_request: &SubscribeRequest,
+) -> SlotUpdateStream {
+    let Some(interval) = subscriber.preferred_recheck_interval() else {
+        return std::future::pending().await;
+    };
+    loop {
+        tokio::time::sleep(interval).await;
+        if let Some(stream) = subscriber
+            .try_subscribe_preferred(subscribe_request.clone())
+            .await
+        {
+            return stream;
+        }
+    }
+}
 
-    let initial_slot: u64;
-    // wait for the first slot update to establish the tip
+///
+/// Waits for the first slot update of `stream` to establish the tip, `None` if the stream ended before.
+///
+async fn wait_for_first_slot<S>(stream: &mut S) -> Result<Option<Slot>, Status>
+where
+    S: Stream<Item = Result<SubscribeUpdate, Status>> + Unpin,
+{
     loop {
         let Some(result) = stream.next().await else {
             return Ok(None);
@@ -114,34 +402,258 @@ where
             Ok(response) => response,
             Err(err) => {
                 tracing::error!("Yellowstone slot tracker stream error: {:?}", err);
-                return Err(yellowstone_grpc_client::GeyserGrpcClientError::TonicStatus(
-                    err,
-                ));
+                return Err(err);
             }
         };
 
-        match response.update_oneof.expect("update_oneof") {
-            UpdateOneof::Slot(subscribe_update_slot) => {
-                initial_slot = subscribe_update_slot.slot;
-                break;
-            }
-            _ => {
-                // Ignore other updates
+        if let UpdateOneof::Slot(subscribe_update_slot) =
+            response.update_oneof.expect("update_oneof")
+        {
+            return Ok(Some(subscribe_update_slot.slot));
+        }
+        // Ignore other updates
+    }
+}
+
+///
+/// Background task to update the AtomicSlotTracker from the Yellowstone Geyser slot stream,
+/// re-subscribing with the same filters whenever the stream ends or fails.
+///
+/// The slot tracker is marked stale until the new stream delivers a slot update,
+/// and closed once [`SlotTrackerReconnectPolicy::max_attempts`] consecutive reconnect attempts failed.
+///
+async fn reconnecting_slot_tracker_loop<C, S>(
+    mut subscriber: C,
+    subscribe_request: SubscribeRequest,
+    initial_stream: S,
+    to_drop: AutoCloseSlotTracker,
+    block_meta_recorders: BlockMetaRecorders,
+    policy: SlotTrackerReconnectPolicy,
+) where
+    C: GeyserSubscriber,
+    S: Stream<Item = Result<SubscribeUpdate, Status>> + Send + 'static,
+{
+    let shared = Arc::clone(&to_drop.slot_tracker);
+    let mut stream: SlotUpdateStream = Box::pin(initial_stream);
+    loop {
+        let preferred_stream = tokio::select! {
+            maybe_err = process_slot_stream(&mut stream, &shared, &block_meta_recorders) => Err(maybe_err),
+            preferred_stream = wait_for_preferred(&mut subscriber, &subscribe_request) => Ok(preferred_stream),
+        };
+        let maybe_err = match preferred_stream {
+            Ok(preferred_stream) => {
+                tracing::info!("Yellowstone slot tracker switched back to its preferred source");
+                stream = preferred_stream;
                 continue;
             }
-        }
+            Err(maybe_err) => maybe_err,
+        };
+        shared.set_stale(true);
+
+        let mut attempt = 0;
+        stream = loop {
+            attempt += 1;
+            if policy
+                .max_attempts
+                .is_some_and(|max_attempts| attempt > max_attempts)
+            {
+                tracing::error!(
+                    "Yellowstone slot tracker giving up after {} reconnect attempt(s)",
+                    attempt - 1
+                );
+                drop(to_drop);
+                match maybe_err {
+                    Some(err) => panic::panic_any(err),
+                    None => return,
+                }
+            }
+            let delay = jittered_exponential_backoff(policy.base_delay, policy.max_delay, attempt);
+            tracing::warn!(
+                "Yellowstone slot tracker reconnecting in {delay:?} (attempt {attempt})"
+            );
+            #[cfg(feature = "prometheus")]
+            {
+                crate::prom::incr_grpc_reconnect_attempt_cnt();
+            }
+            tokio::time::sleep(delay).await;
+            match subscriber.subscribe(subscribe_request.clone()).await {
+                Ok(stream) => {
+                    tracing::info!(
+                        "Yellowstone slot tracker re-subscribed after {attempt} attempt(s)"
+                    );
+                    break stream;
+                }
+                Err(e) => {
+                    tracing::warn!("Yellowstone slot tracker failed to re-subscribe: {e:?}");
+                }
+            }
+        };
     }
+}
+
+///
+/// Creates an [`AtomicSlotTracker`] that tracks the latest slot from Yellowstone Geyser.
+///
+/// The subscription reconnects with the default [`SlotTrackerReconnectPolicy`].
+///
+pub async fn atomic_slot_tracker<I>(
+    geyser_client: GeyserGrpcClient<I>,
+) -> GeyserGrpcClientResult<YellowstoneSlotTrackerOk>
+where
+    I: Interceptor + Send + 'static,
+{
+    atomic_slot_tracker_with_reconnect_policy(geyser_client, Default::default()).await
+}
 
-    let shared: Arc<AtomicSlotTracker> = Arc::new(AtomicSlotTracker::new(initial_slot));
+///
+/// Creates an [`AtomicSlotTracker`] that tracks the latest slot from Yellowstone Geyser,
+/// reconnecting the subscription according to `reconnect_policy`.
+///
+/// The feed is spawned as soon as the subscription is established: the returned tracker is pending until the first
+/// slot update, await [`AtomicSlotTracker::wait_until_ready`] before serving predictions.
+///
+pub async fn atomic_slot_tracker_with_reconnect_policy<I>(
+    mut geyser_client: GeyserGrpcClient<I>,
+    reconnect_policy: SlotTrackerReconnectPolicy,
+) -> GeyserGrpcClientResult<YellowstoneSlotTrackerOk>
+where
+    I: Interceptor + Send + 'static,
+{
+    let subscribe_request = get_yellowstone_slot_tracker_subscribe_request();
+    let stream = geyser_client
+        .subscribe_once(subscribe_request.clone())
+        .await?;
+
+    Ok(spawn_slot_tracker(
+        geyser_client,
+        subscribe_request,
+        stream,
+        None,
+        reconnect_policy,
+    ))
+}
+
+///
+/// Spawns the task feeding a new tracker from `stream`, the tracker starts at `initial_slot` or pending if `None`.
+///
+fn spawn_slot_tracker<C, S>(
+    subscriber: C,
+    subscribe_request: SubscribeRequest,
+    stream: S,
+    initial_slot: Option<Slot>,
+    reconnect_policy: SlotTrackerReconnectPolicy,
+) -> YellowstoneSlotTrackerOk
+where
+    C: GeyserSubscriber,
+    S: Stream<Item = Result<SubscribeUpdate, Status>> + Unpin + Send + 'static,
+{
+    let shared: Arc<AtomicSlotTracker> =
+        Arc::new(initial_slot.map_or_else(AtomicSlotTracker::pending, AtomicSlotTracker::new));
+    let recent_blockhashes = Arc::new(RecentBlockhashes::default());
+    let skip_rates = Arc::new(SkipRateTracker::default());
+    let schedule_mismatches = Arc::new(ScheduleMismatchTracker::default());
     let to_drop = AutoCloseSlotTracker {
         slot_tracker: Arc::clone(&shared),
     };
-    let jh = tokio::spawn(atomic_slot_tracker_loop(stream, to_drop));
+    let cancellation_token = CancellationToken::new();
+    let loop_ct = cancellation_token.clone();
+    let block_meta_recorders = BlockMetaRecorders {
+        recent_blockhashes: Arc::clone(&recent_blockhashes),
+        skip_rates: Arc::clone(&skip_rates),
+        schedule_mismatches: Arc::clone(&schedule_mismatches),
+    };
+    // Cancelling drops the loop future, closing the slot tracker through `to_drop`.
+    let jh = if reconnect_policy.max_attempts == Some(0) {
+        spawn_named(
+            SLOT_TRACKER_TASK,
+            supervise_slot_tracker_loop(
+                loop_ct,
+                atomic_slot_tracker_loop(stream, to_drop, block_meta_recorders),
+            ),
+        )
+    } else {
+        spawn_named(
+            SLOT_TRACKER_TASK,
+            supervise_slot_tracker_loop(
+                loop_ct,
+                reconnecting_slot_tracker_loop(
+                    subscriber,
+                    subscribe_request,
+                    stream,
+                    to_drop,
+                    block_meta_recorders,
+                    reconnect_policy,
+                ),
+            ),
+        )
+    };
 
-    Ok(Some(YellowstoneSlotTrackerOk {
+    YellowstoneSlotTrackerOk {
         atomic_slot_tracker: shared,
+        recent_blockhashes,
+        skip_rates,
+        schedule_mismatches,
         join_handle: jh,
-    }))
+        cancellation_token,
+    }
+}
+
+///
+/// Creates an [`AtomicSlotTracker`] that tracks the latest slot from a set of Yellowstone Geyser endpoints,
+/// failing over to the next endpoint on repeated connection errors, see [`GrpcEndpointSet`].
+///
+/// The slot tracker is not reset during failover, it keeps the last known slot until the new endpoint catches up.
+///
+/// Returns the slot tracker along with an [`ActiveGrpcEndpoint`] handle reporting the endpoint in use.
+///
+pub async fn atomic_slot_tracker_with_failover(
+    endpoints: GrpcEndpointSet,
+    reconnect_policy: SlotTrackerReconnectPolicy,
+) -> Result<(YellowstoneSlotTrackerOk, ActiveGrpcEndpoint), GeyserSubscribeError> {
+    start_failover_slot_tracker(TonicGeyserConnector, endpoints, reconnect_policy).await
+}
+
+pub(crate) async fn start_failover_slot_tracker<K>(
+    connector: K,
+    endpoints: GrpcEndpointSet,
+    reconnect_policy: SlotTrackerReconnectPolicy,
+) -> Result<(YellowstoneSlotTrackerOk, ActiveGrpcEndpoint), GeyserSubscribeError>
+where
+    K: GeyserConnector,
+{
+    let subscribe_request = get_yellowstone_slot_tracker_subscribe_request();
+    let mut subscriber = FailoverSubscriber::new(connector, endpoints);
+    let active_endpoint = subscriber.active_endpoint();
+
+    // Give every endpoint a chance to deliver the first slot
+    let mut last_error = GeyserSubscribeError::NoEndpointAvailable;
+    for _ in 0..subscriber.initial_attempts() {
+        let mut stream = match subscriber.subscribe(subscribe_request.clone()).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                last_error = e;
+                continue;
+            }
+        };
+        match wait_for_first_slot(&mut stream).await {
+            Ok(Some(initial_slot)) => {
+                let slot_tracker = spawn_slot_tracker(
+                    subscriber,
+                    subscribe_request,
+                    stream,
+                    Some(initial_slot),
+                    reconnect_policy,
+                );
+                return Ok((slot_tracker, active_endpoint));
+            }
+            Ok(None) => subscriber.record_failure(),
+            Err(status) => {
+                subscriber.record_failure();
+                last_error = GeyserGrpcClientError::TonicStatus(status).into();
+            }
+        }
+    }
+    Err(last_error)
 }
 
 #[cfg(test)]
@@ -149,14 +661,37 @@ mod tests {
 
     use {
         super::*,
-        std::time::Duration,
+        crate::{clock::MockClock, task::task_exit_count},
         tokio_stream::wrappers::UnboundedReceiverStream,
-        yellowstone_grpc_proto::geyser::{SlotStatus, SubscribeUpdateSlot},
+        yellowstone_grpc_proto::{
+            geyser::SubscribeUpdateSlot, solana::storage::confirmed_block::Reward,
+        },
     };
 
+    #[test]
+    fn test_block_producer_should_be_the_fee_recipient() {
+        let producer = Pubkey::new_unique();
+        let reward = |pubkey: Pubkey, reward_type: RewardType| Reward {
+            pubkey: pubkey.to_string(),
+            reward_type: reward_type as i32,
+            ..Default::default()
+        };
+        let rewards = Rewards {
+            rewards: vec![
+                reward(Pubkey::new_unique(), RewardType::Voting),
+                reward(producer, RewardType::Fee),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(block_producer(Some(&rewards)), Some(producer));
+        assert_eq!(block_producer(Some(&Rewards::default())), None);
+        assert_eq!(block_producer(None), None);
+    }
+
     #[tokio::test]
     async fn test_atomic_slot_tracker_loop() {
-        let slot_tracker = Arc::new(AtomicSlotTracker::new(0));
+        let clock = Arc::new(MockClock::new());
+        let slot_tracker = Arc::new(AtomicSlotTracker::new(0).with_clock(Arc::clone(&clock) as _));
         let to_drop = AutoCloseSlotTracker {
             slot_tracker: Arc::clone(&slot_tracker),
         };
@@ -196,14 +731,28 @@ mod tests {
         let expected_slot_views = [1, 2, 3];
         let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
         let stream = UnboundedReceiverStream::new(rx);
-        let handle = tokio::spawn(atomic_slot_tracker_loop(stream, to_drop));
+        let handle = tokio::spawn(atomic_slot_tracker_loop(
+            stream,
+            to_drop,
+            Default::default(),
+        ));
 
+        let mut slot_rx = slot_tracker.subscribe();
+        let mut last_updated_at = slot_tracker.last_updated_at();
         for (i, update) in updates.into_iter().enumerate() {
+            clock.advance(Duration::from_millis(400));
             tx.send(update).expect("send update");
-            tokio::time::sleep(Duration::from_millis(10)).await;
+            slot_rx.changed().await.expect("slot update");
             let expected_slot = expected_slot_views[i];
-            let current_slot = slot_tracker.load().expect("load");
+            let current_slot = slot_tracker.load();
             assert_eq!(current_slot, expected_slot);
+            // Every store should refresh the last update timestamp.
+            assert_eq!(
+                slot_tracker.last_updated_at(),
+                last_updated_at + Duration::from_millis(400)
+            );
+            assert_eq!(slot_tracker.age(), Duration::ZERO);
+            last_updated_at = slot_tracker.last_updated_at();
         }
 
         // Drop the handle to clean up
@@ -211,29 +760,383 @@ mod tests {
 
         // Sleep a bit to ensure the drop has taken effect
         tokio::time::sleep(Duration::from_millis(10)).await;
-        assert!(
-            slot_tracker
-                .closed
-                .load(std::sync::atomic::Ordering::Relaxed)
+        assert!(slot_tracker.is_closed());
+    }
+
+    fn slot_update(slot: Slot) -> Result<SubscribeUpdate, Status> {
+        slot_status_update(slot, SlotStatus::SlotProcessed)
+    }
+
+    fn slot_status_update(slot: Slot, status: SlotStatus) -> Result<SubscribeUpdate, Status> {
+        Ok(SubscribeUpdate {
+            update_oneof: Some(UpdateOneof::Slot(SubscribeUpdateSlot {
+                slot,
+                dead_error: None,
+                parent: None,
+                status: status as i32,
+            })),
+            filters: vec![SLOT_TRACKER_DM_FILTER_NAME.to_string()],
+            created_at: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_should_only_notify_higher_slots() {
+        let slot_tracker = Arc::new(AtomicSlotTracker::new(0));
+        let mut slot_rx = slot_tracker.subscribe();
+        let to_drop = AutoCloseSlotTracker {
+            slot_tracker: Arc::clone(&slot_tracker),
+        };
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let stream = UnboundedReceiverStream::new(rx);
+        let handle = tokio::spawn(atomic_slot_tracker_loop(
+            stream,
+            to_drop,
+            Default::default(),
+        ));
+
+        tx.send(slot_update(5)).expect("send update");
+        tokio::time::timeout(Duration::from_secs(1), slot_rx.changed())
+            .await
+            .expect("timeout")
+            .expect("changed");
+        assert_eq!(*slot_rx.borrow_and_update(), 5);
+
+        // Regressing and duplicate slots must not wake up subscribers.
+        tx.send(slot_update(3)).expect("send update");
+        tx.send(slot_update(5)).expect("send update");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!slot_rx.has_changed().expect("has_changed"));
+
+        tx.send(slot_update(6)).expect("send update");
+        tokio::time::timeout(Duration::from_secs(1), slot_rx.changed())
+            .await
+            .expect("timeout")
+            .expect("changed");
+        assert_eq!(*slot_rx.borrow_and_update(), 6);
+        assert_eq!(slot_tracker.load(), 6);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_commitment_levels_should_be_monotonic() {
+        let slot_tracker = Arc::new(AtomicSlotTracker::new(0));
+        let to_drop = AutoCloseSlotTracker {
+            slot_tracker: Arc::clone(&slot_tracker),
+        };
+        assert_eq!(
+            slot_tracker.load_commitment(CommitmentLevel::Confirmed),
+            None
+        );
+        assert_eq!(
+            slot_tracker.load_commitment(CommitmentLevel::Finalized),
+            None
+        );
+
+        // (update, expected processed, expected confirmed, expected finalized)
+        let updates = [
+            (
+                slot_status_update(10, SlotStatus::SlotProcessed),
+                10,
+                None,
+                None,
+            ),
+            (
+                slot_status_update(8, SlotStatus::SlotConfirmed),
+                10,
+                Some(8),
+                None,
+            ),
+            // Out-of-order confirmed slot is ignored
+            (
+                slot_status_update(7, SlotStatus::SlotConfirmed),
+                10,
+                Some(8),
+                None,
+            ),
+            (
+                slot_status_update(5, SlotStatus::SlotFinalized),
+                10,
+                Some(8),
+                Some(5),
+            ),
+            // Out-of-order finalized slot is ignored
+            (
+                slot_status_update(4, SlotStatus::SlotFinalized),
+                10,
+                Some(8),
+                Some(5),
+            ),
+            (
+                slot_status_update(12, SlotStatus::SlotFirstShredReceived),
+                12,
+                Some(8),
+                Some(5),
+            ),
+            // Late processed update for an older slot does not regress the tip
+            (
+                slot_status_update(11, SlotStatus::SlotProcessed),
+                12,
+                Some(8),
+                Some(5),
+            ),
+            // A finalized slot is also confirmed
+            (
+                slot_status_update(9, SlotStatus::SlotFinalized),
+                12,
+                Some(9),
+                Some(9),
+            ),
+        ];
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let stream = UnboundedReceiverStream::new(rx);
+        let handle = tokio::spawn(atomic_slot_tracker_loop(
+            stream,
+            to_drop,
+            Default::default(),
+        ));
+
+        for (update, processed, confirmed, finalized) in updates {
+            tx.send(update).expect("send update");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            assert_eq!(
+                slot_tracker.load_commitment(CommitmentLevel::Processed),
+                Some(processed)
+            );
+            assert_eq!(slot_tracker.load(), processed);
+            assert_eq!(
+                slot_tracker.load_commitment(CommitmentLevel::Confirmed),
+                confirmed
+            );
+            assert_eq!(
+                slot_tracker.load_commitment(CommitmentLevel::Finalized),
+                finalized
+            );
+        }
+
+        handle.abort();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        // A closed tracker keeps reporting the last observed slots
+        assert!(slot_tracker.is_closed());
+        assert_eq!(
+            slot_tracker.load_commitment(CommitmentLevel::Processed),
+            Some(12)
         );
     }
 
+    #[test]
+    fn test_store_should_ignore_non_increasing_slots() {
+        let slot_tracker = AtomicSlotTracker::new(10);
+        assert!(!slot_tracker.store(10));
+        assert!(!slot_tracker.store(9));
+        assert_eq!(slot_tracker.load(), 10);
+        assert!(slot_tracker.store(11));
+        assert_eq!(slot_tracker.load(), 11);
+    }
+
     #[tokio::test]
-    async fn test_it_should_poison_when_stream_empty() {
+    async fn test_it_should_close_when_stream_empty() {
         let slot_tracker = Arc::new(AtomicSlotTracker::new(0));
         let to_drop = AutoCloseSlotTracker {
             slot_tracker: Arc::clone(&slot_tracker),
         };
 
         let stream = tokio_stream::iter(vec![]);
-        let handle = tokio::spawn(atomic_slot_tracker_loop(stream, to_drop));
+        let handle = tokio::spawn(atomic_slot_tracker_loop(
+            stream,
+            to_drop,
+            Default::default(),
+        ));
 
         let _ = handle.await;
 
+        assert!(slot_tracker.is_closed());
+    }
+
+    ///
+    /// Subscriber replaying a queue of streams, one per subscription, failing once the queue is empty.
+    ///
+    struct MockSubscriber {
+        streams: Vec<SlotUpdateStream>,
+        subscribe_cnt: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl GeyserSubscriber for MockSubscriber {
+        fn subscribe(
+            &mut self,
+            _request: SubscribeRequest,
+        ) -> BoxFuture<'_, Result<SlotUpdateStream, GeyserSubscribeError>> {
+            self.subscribe_cnt
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            let next = if self.streams.is_empty() {
+                Err(
+                    GeyserGrpcClientError::TonicStatus(Status::unavailable("mock server down"))
+                        .into(),
+                )
+            } else {
+                Ok(self.streams.remove(0))
+            };
+            Box::pin(async move { next })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_it_should_exit_cleanly_once_cancelled() {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let subscriber = MockSubscriber {
+            streams: vec![],
+            subscribe_cnt: Default::default(),
+        };
+        let YellowstoneSlotTrackerOk {
+            atomic_slot_tracker,
+            join_handle,
+            cancellation_token,
+            ..
+        } = spawn_slot_tracker(
+            subscriber,
+            get_yellowstone_slot_tracker_subscribe_request(),
+            UnboundedReceiverStream::new(rx),
+            Some(1),
+            Default::default(),
+        );
+        tx.send(slot_update(2)).expect("send update");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(atomic_slot_tracker.load(), 2);
+
+        // The stream is still open, only the cancellation can stop the task
+        let shutdowns = task_exit_count(SLOT_TRACKER_TASK, TaskExitReason::Shutdown);
+        cancellation_token.cancel();
+        tokio::time::timeout(Duration::from_secs(1), join_handle)
+            .await
+            .expect("slot tracker task did not exit after cancellation")
+            .expect("slot tracker task panicked");
+        assert!(atomic_slot_tracker.is_closed());
+        assert!(task_exit_count(SLOT_TRACKER_TASK, TaskExitReason::Shutdown) > shutdowns);
+        drop(tx);
+    }
+
+    #[tokio::test]
+    async fn test_it_should_spawn_a_pending_tracker_ready_on_the_first_slot() {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let subscriber = MockSubscriber {
+            streams: vec![],
+            subscribe_cnt: Default::default(),
+        };
+        let YellowstoneSlotTrackerOk {
+            atomic_slot_tracker,
+            cancellation_token,
+            ..
+        } = spawn_slot_tracker(
+            subscriber,
+            get_yellowstone_slot_tracker_subscribe_request(),
+            UnboundedReceiverStream::new(rx),
+            None,
+            Default::default(),
+        );
+        assert!(!atomic_slot_tracker.is_ready());
         assert!(
-            slot_tracker
-                .closed
-                .load(std::sync::atomic::Ordering::Relaxed)
+            atomic_slot_tracker
+                .wait_until_ready(Duration::from_millis(10))
+                .await
+                .is_err()
+        );
+
+        tx.send(slot_update(0)).expect("send update");
+        assert_eq!(
+            atomic_slot_tracker
+                .wait_until_ready(Duration::from_secs(1))
+                .await
+                .expect("first slot"),
+            0
         );
+        assert!(atomic_slot_tracker.is_ready());
+        cancellation_token.cancel();
+    }
+
+    #[tokio::test]
+    async fn test_it_should_record_stream_errors_as_task_exit_reason() {
+        let subscriber = MockSubscriber {
+            streams: vec![],
+            subscribe_cnt: Default::default(),
+        };
+        let stream_errors = task_exit_count(SLOT_TRACKER_TASK, TaskExitReason::StreamError);
+        let YellowstoneSlotTrackerOk {
+            atomic_slot_tracker,
+            join_handle,
+            ..
+        } = spawn_slot_tracker(
+            subscriber,
+            get_yellowstone_slot_tracker_subscribe_request(),
+            tokio_stream::iter(vec![
+                slot_update(2),
+                Err(Status::unavailable("connection reset")),
+            ]),
+            Some(1),
+            SlotTrackerReconnectPolicy {
+                max_attempts: Some(0),
+                ..Default::default()
+            },
+        );
+        let err = join_handle.await.expect_err("closed slot tracker");
+        assert!(err.is_panic());
+        assert!(atomic_slot_tracker.is_closed());
+        assert!(task_exit_count(SLOT_TRACKER_TASK, TaskExitReason::StreamError) > stream_errors);
+    }
+
+    #[tokio::test]
+    async fn test_it_should_reconnect_when_stream_drops() {
+        let slot_tracker = Arc::new(AtomicSlotTracker::new(0));
+        let to_drop = AutoCloseSlotTracker {
+            slot_tracker: Arc::clone(&slot_tracker),
+        };
+        let policy = SlotTrackerReconnectPolicy {
+            max_attempts: Some(3),
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_millis(50),
+        };
+
+        // The first stream drops with an error right after slot 1
+        let initial_stream = tokio_stream::iter(vec![
+            slot_update(1),
+            Err(Status::unavailable("connection reset")),
+        ]);
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let subscribe_cnt = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let subscriber = MockSubscriber {
+            streams: vec![Box::pin(UnboundedReceiverStream::new(rx))],
+            subscribe_cnt: Arc::clone(&subscribe_cnt),
+        };
+        let handle = tokio::spawn(reconnecting_slot_tracker_loop(
+            subscriber,
+            get_yellowstone_slot_tracker_subscribe_request(),
+            initial_stream,
+            to_drop,
+            Default::default(),
+            policy,
+        ));
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(slot_tracker.load(), 1);
+        assert!(slot_tracker.is_stale());
+
+        // Wait for the reconnect backoff to elapse
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(subscribe_cnt.load(std::sync::atomic::Ordering::Relaxed), 1);
+        tx.send(slot_update(2)).expect("send update");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(slot_tracker.load(), 2);
+        assert!(!slot_tracker.is_stale());
+
+        // Once the reconnect attempts are exhausted, the slot tracker is closed
+        drop(tx);
+        let _ = tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("timeout");
+        assert!(slot_tracker.is_closed());
+        assert_eq!(slot_tracker.load(), 2);
+        assert_eq!(subscribe_cnt.load(std::sync::atomic::Ordering::Relaxed), 4);
     }
 }